@@ -37,10 +37,9 @@ fn bench_codegen(c: &mut Criterion) {
     let context = Context::create();
     let le_lexer = LELexer::new(buffer.as_str()).unwrap();
     let ast = Ast::from_lexer(le_lexer).unwrap();
-    let module = context.create_module("main");
     c.bench_function("llvm", |b| b.iter(|| {
-        let mut code_generator = CodeGenerator::create(&context);
-        code_generator.compile(&module, &ast).unwrap();
+        let mut code_generator = CodeGenerator::create(&context, "main");
+        code_generator.compile(&ast).unwrap();
     }));
 }
 
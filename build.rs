@@ -0,0 +1,6 @@
+fn main() {
+    //编译runtime目录下的运行时静态库，链接可执行文件时使用
+    let runtime = cmake::Config::new("runtime").build();
+    println!("cargo:rustc-env=LELANG_RUNTIME_DIR={}", runtime.join("lib").display());
+    println!("cargo:rerun-if-changed=runtime");
+}
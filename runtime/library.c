@@ -1,11 +1,131 @@
+#include <inttypes.h>
+#include <stdint.h>
 #include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <time.h>
 
-int print_int32(int32_t a) { return printf("%d\n", a); }
+#ifndef LELANG_RUNTIME_VERSION
+#define LELANG_RUNTIME_VERSION "unknown"
+#endif
 
-int print_int64(int64_t a) { return printf("%lld\n", a); }
+/* 运行时库导出的函数都带有`__lelang_`前缀，lelang程序通过编译器注入的声明(runtime.le)按不带前缀的名字调用，
+   程序和链接的C库中同名的函数(如alloc、panic)不会和运行时库冲突 */
 
-int print_bool(uint8_t a) { return printf("%d\n", a); }
+/* 运行时库的版本，和编译器注入的声明(runtime.le)一起更新 */
+const char *__lelang_runtime_version(void) { return LELANG_RUNTIME_VERSION; }
 
-int print_float32(float a) { return printf("%f\n", a); }
+/* 打印各种内建类型的值并换行，返回输出的字符数 */
+int __lelang_print_bool(uint8_t a) { return printf("%s\n", (a & 1) ? "true" : "false"); }
 
-int print_float64(double a) { return printf("%lf\n", a); }
+int __lelang_print_int8(int8_t a) { return printf("%" PRId8 "\n", a); }
+
+int __lelang_print_int16(int16_t a) { return printf("%" PRId16 "\n", a); }
+
+int __lelang_print_int32(int32_t a) { return printf("%" PRId32 "\n", a); }
+
+int __lelang_print_int64(int64_t a) { return printf("%" PRId64 "\n", a); }
+
+int __lelang_print_uint8(uint8_t a) { return printf("%" PRIu8 "\n", a); }
+
+int __lelang_print_uint16(uint16_t a) { return printf("%" PRIu16 "\n", a); }
+
+int __lelang_print_uint32(uint32_t a) { return printf("%" PRIu32 "\n", a); }
+
+int __lelang_print_uint64(uint64_t a) { return printf("%" PRIu64 "\n", a); }
+
+int __lelang_print_float32(float a) { return printf("%f\n", a); }
+
+int __lelang_print_float64(double a) { return printf("%lf\n", a); }
+
+int __lelang_print_str(const char *s) { return printf("%s\n", s); }
+
+/* print和println使用的输出函数，不换行，整数和浮点数由编译器扩展为64位后传入。
+   编译器直接按这些保留的名字调用，它们不会出现在lelang程序的名字空间中 */
+void __lelang_write_bool(uint8_t a) { fputs((a & 1) ? "true" : "false", stdout); }
+
+void __lelang_write_int64(int64_t a) { printf("%" PRId64, a); }
+
+void __lelang_write_uint64(uint64_t a) { printf("%" PRIu64, a); }
+
+void __lelang_write_float64(double a) { printf("%lf", a); }
+
+void __lelang_write_pointer(const void *p) { printf("%p", p); }
+
+void __lelang_write_str(const char *s) { fputs(s, stdout); }
+
+/* 从标准输入读取，读取失败时返回0 */
+int64_t __lelang_read_i64(void) {
+    int64_t value = 0;
+    if (scanf("%" SCNd64, &value) != 1) {
+        return 0;
+    }
+    return value;
+}
+
+double __lelang_read_f64(void) {
+    double value = 0;
+    if (scanf("%lf", &value) != 1) {
+        return 0;
+    }
+    return value;
+}
+
+void __lelang_panic(const char *message);
+
+/* 读取一行，不包含换行符，到达文件末尾时返回空字符串，返回的内存需要用dealloc释放 */
+char *__lelang_read_line(void) {
+    size_t capacity = 64;
+    size_t len = 0;
+    char *line = malloc(capacity);
+    if (line == NULL) {
+        __lelang_panic("out of memory");
+    }
+    int c;
+    while ((c = getchar()) != EOF && c != '\n') {
+        if (len + 1 == capacity) {
+            capacity *= 2;
+            char *grown = realloc(line, capacity);
+            if (grown == NULL) {
+                free(line);
+                __lelang_panic("out of memory");
+            }
+            line = grown;
+        }
+        line[len++] = (char) c;
+    }
+    line[len] = '\0';
+    return line;
+}
+
+/* 分配内存失败时直接终止程序，不会返回空指针 */
+uint8_t *__lelang_alloc(uint64_t size) {
+    uint8_t *memory = malloc(size == 0 ? 1 : size);
+    if (memory == NULL) {
+        __lelang_panic("out of memory");
+    }
+    return memory;
+}
+
+void __lelang_dealloc(uint8_t *memory) { free(memory); }
+
+/* 输出错误信息到标准错误并终止程序 */
+void __lelang_panic(const char *message) {
+    fflush(stdout);
+    fprintf(stderr, "panic: %s\n", message);
+    abort();
+}
+
+/* 以给定的状态码结束程序 */
+void __lelang_exit(int32_t code) { exit(code); }
+
+/* 从1970-01-01 00:00:00 UTC开始的秒数和毫秒数 */
+int64_t __lelang_time_seconds(void) { return (int64_t) time(NULL); }
+
+int64_t __lelang_time_millis(void) {
+    struct timespec ts;
+    if (timespec_get(&ts, TIME_UTC) == 0) {
+        return __lelang_time_seconds() * 1000;
+    }
+    return (int64_t) ts.tv_sec * 1000 + ts.tv_nsec / 1000000;
+}
@@ -3,24 +3,88 @@ use std::ops::RangeInclusive;
 use clap::ArgEnum;
 use clap::Parser;
 
+use crate::optimizer::{find_pass, Pass, PASSES};
+
 /// lelang programming language compiler, based on LLVM compiler infrastructure
 #[derive(Parser, Debug)]
 pub struct Args {
-    /// Set compiler optimize level
-    #[clap(short = 'O', default_value_t = 0, value_name = "OPTIMIZE_LEVEL", parse(try_from_str = port_in_range))]
-    pub optimization: usize,
+    /// Set compiler optimize level: 0-3, or s and z to optimize for size
+    #[clap(short = 'O', default_value = "0", value_name = "OPTIMIZE_LEVEL", parse(try_from_str = parse_optimize_level))]
+    pub optimization: OptimizeLevel,
+
+    /// Link all source files into one module and run link time optimization before emitting the output,
+    /// only `main` and `pub` functions stay external
+    #[clap(long)]
+    pub lto: bool,
+
+    /// Run the given comma separated LLVM passes in order instead of the -O presets, such as mem2reg,instcombine,gvn
+    #[clap(long, value_name = "PASSES", use_value_delimiter = true, parse(try_from_str = parse_pass))]
+    pub passes: Vec<Pass>,
+
+    /// Report the time spent in lexing, parsing, code generation, each optimization pass and emission
+    #[clap(long)]
+    pub time_passes: bool,
+
+    /// Write the LLVM IR after each optimization pass to files in the given directory
+    #[clap(long, parse(from_os_str), value_name = "DIR", value_hint = clap::ValueHint::DirPath)]
+    pub print_after_all: Option<std::path::PathBuf>,
+
+    /// Generate DWARF debug information
+    #[clap(short = 'g')]
+    pub debug_info: bool,
 
     /// Set compiler output format
     #[clap(short = 'S', default_value_t = OutputFormatEnum::OBJ, arg_enum)]
     pub output_format: OutputFormatEnum,
 
+    /// Also write a C header declaring the exported functions next to the output
+    #[clap(long)]
+    pub emit_header: bool,
+
     /// Set compiler output path
     #[clap(short = 'o', default_value = "./a.out", parse(from_os_str), value_name = "OUTPUT_FILE_PATH", value_hint = clap::ValueHint::DirPath)]
     pub output_path: std::path::PathBuf,
 
-    /// Set compiler source file path
-    #[clap(short = 'i', parse(from_os_str), value_name = "SOURCE_FILE_PATH", value_hint = clap::ValueHint::DirPath,)]
-    pub input_path: std::path::PathBuf,
+    /// Set compiler source file path, can be specified multiple times to compile and link several files,
+    /// LLVM IR files ending with .ll or .bc are linked into the program before optimization
+    #[clap(short = 'i', required = true, parse(from_os_str), value_name = "SOURCE_FILE_PATH", value_hint = clap::ValueHint::FilePath)]
+    pub input_paths: Vec<std::path::PathBuf>,
+
+    /// Add a directory to the module search path
+    #[clap(short = 'I', parse(from_os_str), value_name = "INCLUDE_PATH", value_hint = clap::ValueHint::DirPath)]
+    pub include_paths: Vec<std::path::PathBuf>,
+
+    /// Link with the given library when output format is EXE
+    #[clap(short = 'l', value_name = "LIBRARY")]
+    pub libraries: Vec<String>,
+
+    /// Add a directory to the library search path when output format is EXE
+    #[clap(short = 'L', parse(from_os_str), value_name = "LIBRARY_PATH", value_hint = clap::ValueHint::DirPath)]
+    pub library_paths: Vec<std::path::PathBuf>,
+
+    /// Use the given linker instead of searching cc, clang, gcc, ld.lld and ld in PATH
+    #[clap(long, parse(from_os_str), value_name = "LINKER", value_hint = clap::ValueHint::ExecutablePath)]
+    pub linker: Option<std::path::PathBuf>,
+
+    /// Set target triple for cross compilation, such as aarch64-unknown-linux-gnu, default is the host
+    #[clap(long, value_name = "TRIPLE")]
+    pub target: Option<String>,
+
+    /// Set target CPU, default is the host CPU, or the generic CPU of the target when cross compiling
+    #[clap(long, value_name = "CPU")]
+    pub cpu: Option<String>,
+
+    /// Set target features, such as +avx2,-sse4.1
+    #[clap(long, value_name = "FEATURES")]
+    pub features: Option<String>,
+
+    /// Set relocation model
+    #[clap(long, default_value_t = RelocationModelEnum::Default, arg_enum)]
+    pub relocation_model: RelocationModelEnum,
+
+    /// Set code model
+    #[clap(long, default_value_t = CodeModelEnum::Default, arg_enum)]
+    pub code_model: CodeModelEnum,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ArgEnum, Debug)]
@@ -28,25 +92,90 @@ pub enum OutputFormatEnum {
     TOKENS,
     AST,
     IR,
+    BC,
     ASM,
     OBJ,
     EXE,
+    SHARED,
+    STATIC,
+    HEADER,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ArgEnum, Debug)]
+pub enum RelocationModelEnum {
+    Default,
+    Pic,
+    Static,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ArgEnum, Debug)]
+pub enum CodeModelEnum {
+    Default,
+    Small,
+    Kernel,
+    Medium,
+    Large,
+}
+
+/// `-O`指定的优化级别，`Os`和`Oz`在`O2`的基础上优先减小代码体积
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum OptimizeLevel {
+    O0,
+    O1,
+    O2,
+    O3,
+    Os,
+    Oz,
 }
 
+impl OptimizeLevel {
+    /// 速度优化级别，`Os`和`Oz`为2
+    pub fn speed_level(&self) -> usize {
+        match self {
+            OptimizeLevel::O0 => 0,
+            OptimizeLevel::O1 => 1,
+            OptimizeLevel::O2 | OptimizeLevel::Os | OptimizeLevel::Oz => 2,
+            OptimizeLevel::O3 => 3,
+        }
+    }
+
+    /// 体积优化级别，`Os`为1，`Oz`为2，其余为0
+    pub fn size_level(&self) -> u32 {
+        match self {
+            OptimizeLevel::Os => 1,
+            OptimizeLevel::Oz => 2,
+            _ => 0,
+        }
+    }
+}
 
 const OPTIMIZE_LEVEL_RANGE: RangeInclusive<usize> = 0..=3;
 
-fn port_in_range(s: &str) -> Result<usize, String> {
-    let port: usize = s
+fn parse_optimize_level(s: &str) -> Result<OptimizeLevel, String> {
+    match s {
+        "s" => return Ok(OptimizeLevel::Os),
+        "z" => return Ok(OptimizeLevel::Oz),
+        _ => {}
+    }
+    let level: usize = s
         .parse()
-        .map_err(|_| format!("`{}` isn't a valid optimize level number", s))?;
-    if OPTIMIZE_LEVEL_RANGE.contains(&port) {
-        Ok(port)
-    } else {
-        Err(format!(
-            "Optimize level can only in range {}-{}",
+        .map_err(|_| format!("`{}` isn't a valid optimize level, expect a number or `s`, `z`", s))?;
+    match level {
+        0 => Ok(OptimizeLevel::O0),
+        1 => Ok(OptimizeLevel::O1),
+        2 => Ok(OptimizeLevel::O2),
+        3 => Ok(OptimizeLevel::O3),
+        _ => Err(format!(
+            "Optimize level can only in range {}-{}, or be `s`, `z`",
             OPTIMIZE_LEVEL_RANGE.start(),
             OPTIMIZE_LEVEL_RANGE.end()
-        ))
+        )),
     }
 }
+
+fn parse_pass(s: &str) -> Result<Pass, String> {
+    find_pass(s).ok_or_else(|| {
+        let names = PASSES.iter().map(|pass| pass.name).collect::<Vec<_>>();
+        format!("unknown pass `{}`, available passes: {}", s, names.join(", "))
+    })
+}
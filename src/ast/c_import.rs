@@ -0,0 +1,790 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::ast::Ast;
+use crate::ast::nodes::*;
+use crate::error::{CompileError, LEError, Result};
+use crate::lexer::{Number, Operator, Position};
+
+/// 把简单C头文件中的声明转换为lelang的定义：函数原型转换为`decl`声明的外部函数，
+/// 带成员定义的结构体转换为结构体，值在i32范围内的`#define`整数常量转换为全局变量。
+/// 没有完整的预处理器，条件编译指令会被忽略；函数指针、联合体、位域等
+/// 无法表示的声明会被跳过，超出u32的数组长度等错误会被报告
+pub fn parse_c_header(content: &str, file_id: usize) -> Result<Ast> {
+    let (tokens, constants) = tokenize(content);
+    let mut parser = CHeaderParser {
+        tokens,
+        index: 0,
+        file_id,
+        ast: Ast::new(),
+        aliases: HashMap::new(),
+        struct_tags: HashMap::new(),
+        constants: HashMap::new(),
+        error: None,
+    };
+    for (name, value, range) in constants {
+        parser.add_constant(name, value, range);
+    }
+    parser.parse();
+    match parser.error {
+        Some(error) => Err(error),
+        None => Ok(parser.ast),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum CToken {
+    Identifier(String),
+    Number(String),
+    StringLiteral,
+    Ellipsis,
+    Punct(char),
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    token: CToken,
+    range: Range<usize>,
+}
+
+impl Token {
+    fn is_punct(&self, c: char) -> bool {
+        self.token == CToken::Punct(c)
+    }
+
+    fn identifier(&self) -> Option<&str> {
+        match &self.token {
+            CToken::Identifier(name) => Some(name),
+            _ => None,
+        }
+    }
+}
+
+///类型修饰和存储类别，不影响转换结果
+const IGNORED_WORDS: [&str; 10] = ["const", "volatile", "restrict", "__restrict", "extern", "static", "inline", "__inline", "__extension__", "register"];
+
+///GCC、MSVC的扩展，后面是括号中的参数，如`__attribute__((visibility("default")))`、`__asm__("name")`
+const EXTENSION_WORDS: [&str; 6] = ["__attribute__", "__attribute", "__declspec", "__asm__", "__asm", "asm"];
+
+///C的基本类型关键字
+const BUILTIN_TYPE_WORDS: [&str; 11] = ["void", "char", "short", "int", "long", "float", "double", "signed", "unsigned", "_Bool", "bool"];
+
+///`<stdint.h>`、`<stddef.h>`中的类型对应的lelang类型，按LP64数据模型
+const FIXED_WIDTH_TYPES: [(&str, &str); 14] = [
+    ("int8_t", "i8"),
+    ("uint8_t", "u8"),
+    ("int16_t", "i16"),
+    ("uint16_t", "u16"),
+    ("int32_t", "i32"),
+    ("uint32_t", "u32"),
+    ("int64_t", "i64"),
+    ("uint64_t", "u64"),
+    ("size_t", "u64"),
+    ("ssize_t", "i64"),
+    ("intptr_t", "i64"),
+    ("uintptr_t", "u64"),
+    ("ptrdiff_t", "i64"),
+    ("off_t", "i64"),
+];
+
+/// 词法分析，同时去掉注释和预处理指令，收集`#define 名字 整数`形式的常量
+fn tokenize(content: &str) -> (Vec<Token>, Vec<(String, i128, Range<usize>)>) {
+    let bytes = content.as_bytes();
+    let mut tokens = vec![];
+    let mut constants = vec![];
+    let mut index = 0;
+    let mut line_start = true;
+    while index < bytes.len() {
+        //每次都从字符边界开始，非ASCII字符按整个字符前进
+        let c = content[index..].chars().next().unwrap();
+        let start = index;
+        if c == '\n' {
+            line_start = true;
+            index += 1;
+            continue;
+        }
+        //UTF-8的BOM按空白处理
+        if c.is_whitespace() || c == '\u{feff}' {
+            index += c.len_utf8();
+            continue;
+        }
+        if content[index..].starts_with("//") {
+            index = content[index..].find('\n').map(|end| index + end).unwrap_or(bytes.len());
+            continue;
+        }
+        if content[index..].starts_with("/*") {
+            index = content[index + 2..].find("*/").map(|end| index + 2 + end + 2).unwrap_or(bytes.len());
+            continue;
+        }
+        if c == '#' && line_start {
+            //预处理指令到行尾为止，行尾的`\`表示续行
+            let mut end = index;
+            while end < bytes.len() && !(bytes[end] == b'\n' && (end == 0 || bytes[end - 1] != b'\\')) {
+                end += 1;
+            }
+            if let Some(constant) = parse_define(&content[index + 1..end], index..end) {
+                constants.push(constant);
+            }
+            index = end;
+            continue;
+        }
+        line_start = false;
+        if c.is_ascii_alphabetic() || c == '_' {
+            while index < bytes.len() && (bytes[index].is_ascii_alphanumeric() || bytes[index] == b'_') {
+                index += 1;
+            }
+            tokens.push(Token { token: CToken::Identifier(content[start..index].to_string()), range: start..index });
+        } else if c.is_ascii_digit() {
+            while index < bytes.len() && (bytes[index].is_ascii_alphanumeric() || bytes[index] == b'.') {
+                index += 1;
+            }
+            tokens.push(Token { token: CToken::Number(content[start..index].to_string()), range: start..index });
+        } else if c == '"' || c == '\'' {
+            index += 1;
+            while index < bytes.len() && bytes[index] as char != c {
+                index += if bytes[index] == b'\\' { 2 } else { 1 };
+            }
+            index = (index + 1).min(bytes.len());
+            tokens.push(Token { token: CToken::StringLiteral, range: start..index });
+        } else if content[index..].starts_with("...") {
+            index += 3;
+            tokens.push(Token { token: CToken::Ellipsis, range: start..index });
+        } else {
+            index += c.len_utf8();
+            tokens.push(Token { token: CToken::Punct(c), range: start..index });
+        }
+    }
+    (tokens, constants)
+}
+
+/// `define NAME 42`、`define NAME (-0x10)`等形式的整数常量
+fn parse_define(directive: &str, range: Range<usize>) -> Option<(String, i128, Range<usize>)> {
+    let directive = directive.trim_start();
+    let rest = directive.strip_prefix("define")?;
+    if !rest.starts_with(|c: char| c.is_ascii_whitespace()) {
+        return None;
+    }
+    let rest = rest.trim_start();
+    let name_end = rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(rest.len());
+    let (name, value) = rest.split_at(name_end);
+    //带参数的宏不是常量
+    if name.is_empty() || value.starts_with('(') {
+        return None;
+    }
+    let value = value.split("//").next().unwrap_or_default();
+    let value = value.split("/*").next().unwrap_or_default();
+    parse_c_integer(value).map(|value| (name.to_string(), value, range))
+}
+
+/// 解析C的整数字面量，支持外层括号、负号、十六进制、八进制和`U`、`L`后缀
+fn parse_c_integer(text: &str) -> Option<i128> {
+    let mut text = text.trim();
+    while text.starts_with('(') && text.ends_with(')') {
+        text = text[1..text.len() - 1].trim();
+    }
+    if let Some(rest) = text.strip_prefix('-') {
+        return parse_c_integer(rest).map(|value| -value);
+    }
+    let text = text.trim_end_matches(|c| c == 'u' || c == 'U' || c == 'l' || c == 'L');
+    if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        i128::from_str_radix(hex, 16).ok()
+    } else if text.len() > 1 && text.starts_with('0') {
+        i128::from_str_radix(&text[1..], 8).ok()
+    } else {
+        text.parse().ok()
+    }
+}
+
+/// C类型去掉指针后的部分
+#[derive(Debug, Clone)]
+enum BaseType {
+    Void,
+    ///只有前向声明的结构体，只能通过指针使用
+    Opaque,
+    Type(TypeDeclarator),
+}
+
+struct CHeaderParser {
+    tokens: Vec<Token>,
+    index: usize,
+    file_id: usize,
+    ast: Ast,
+    ///`typedef`定义的别名到它代表的类型的token
+    aliases: HashMap<String, Vec<Token>>,
+    ///结构体标签到转换后的lelang结构体名字
+    struct_tags: HashMap<String, String>,
+    constants: HashMap<String, i128>,
+    ///第一个需要报告的错误，无法表示的声明只会被跳过，不是错误
+    error: Option<LEError>,
+}
+
+impl CHeaderParser {
+    fn pos(&self, range: Range<usize>) -> Position {
+        Position::new(range, self.file_id)
+    }
+
+    fn identifier(&self, name: &str, range: Range<usize>) -> Identifier {
+        Identifier { name: name.to_string(), pos: self.pos(range) }
+    }
+
+    fn type_identifier(&self, name: &str, range: Range<usize>) -> TypeDeclarator {
+        TypeDeclarator::TypeIdentifier(self.identifier(name, range))
+    }
+
+    /// 整数常量转换为全局变量，i32范围以外的常量无法用整数字面量表示，会被跳过
+    fn add_constant(&mut self, name: String, value: i128, range: Range<usize>) {
+        self.constants.insert(name.clone(), value);
+        if value < i32::MIN as i128 || value > i32::MAX as i128 {
+            return;
+        }
+        let pos = self.pos(range);
+        let literal = Expr::NumberLiteral(NumberLiteral { number: Number::Integer(value.unsigned_abs() as u64), pos: pos.clone() });
+        let value = if value < 0 {
+            Expr::UnaryOperator(UnaryOpExpression { op: Operator::Sub, expr: Box::new(literal), pos: pos.clone() })
+        } else {
+            literal
+        };
+        let identifier = Identifier { name, pos: pos.clone() };
+        self.ast.globals_variables.push(Variable {
+            is_public: false,
+            prototype: VariablePrototype { type_declarator: None, identifier, pos: pos.clone() },
+            value: Box::new(value),
+            pos,
+        });
+    }
+
+    fn parse(&mut self) {
+        //`extern "C" {`的花括号层数
+        let mut extern_blocks = 0;
+        while self.index < self.tokens.len() {
+            let token = self.tokens[self.index].clone();
+            if token.identifier() == Some("extern") && self.tokens.get(self.index + 1).map(|next| next.token == CToken::StringLiteral).unwrap_or(false) {
+                self.index += 2;
+                if self.tokens.get(self.index).map(|next| next.is_punct('{')).unwrap_or(false) {
+                    self.index += 1;
+                    extern_blocks += 1;
+                }
+                continue;
+            }
+            if token.is_punct('}') && extern_blocks > 0 {
+                self.index += 1;
+                extern_blocks -= 1;
+                continue;
+            }
+            if token.is_punct(';') {
+                self.index += 1;
+                continue;
+            }
+            if let Some(declaration) = self.next_declaration() {
+                self.parse_declaration(&declaration);
+            }
+        }
+    }
+
+    /// 取出下一个以`;`结尾的声明，带函数体的函数定义会被整体跳过，返回None
+    fn next_declaration(&mut self) -> Option<Vec<Token>> {
+        let mut declaration = vec![];
+        let mut depth = 0;
+        while self.index < self.tokens.len() {
+            let token = self.tokens[self.index].clone();
+            self.index += 1;
+            match token.token {
+                CToken::Punct(';') if depth == 0 => return Some(declaration),
+                CToken::Punct('{') if depth == 0 && declaration.last().map(|last: &Token| last.is_punct(')')).unwrap_or(false) => {
+                    self.skip_block();
+                    return None;
+                }
+                CToken::Punct('(') | CToken::Punct('{') | CToken::Punct('[') => depth += 1,
+                CToken::Punct(')') | CToken::Punct('}') | CToken::Punct(']') => depth -= 1,
+                _ => {}
+            }
+            declaration.push(token);
+        }
+        Some(declaration)
+    }
+
+    /// 跳过函数体，调用时已经越过了`{`
+    fn skip_block(&mut self) {
+        let mut depth = 1;
+        while self.index < self.tokens.len() && depth > 0 {
+            match self.tokens[self.index].token {
+                CToken::Punct('{') => depth += 1,
+                CToken::Punct('}') => depth -= 1,
+                _ => {}
+            }
+            self.index += 1;
+        }
+    }
+
+    fn parse_declaration(&mut self, tokens: &[Token]) {
+        let tokens = tokens
+            .iter()
+            .filter(|token| !token.identifier().map(|word| IGNORED_WORDS.contains(&word)).unwrap_or(false))
+            .cloned()
+            .collect::<Vec<_>>();
+        let (tokens, has_extensions) = strip_extensions(&tokens);
+        let has_body = tokens.iter().any(|token| token.is_punct('{'));
+        let is_function = !has_body && tokens.iter().any(|token| token.is_punct('('));
+        //`packed`、`aligned`等会改变类型的布局，带有扩展的声明只转换函数原型
+        if has_extensions && !is_function {
+            return;
+        }
+        match tokens.first().and_then(Token::identifier) {
+            Some("typedef") => {
+                let rest = &tokens[1..];
+                let keyword = rest.first().and_then(Token::identifier);
+                if has_body && keyword == Some("struct") {
+                    self.parse_struct(rest, true);
+                } else if has_body && keyword == Some("enum") {
+                    //枚举按i32处理
+                    if let Some(name) = rest.iter().rev().find(|token| token.identifier().is_some()) {
+                        self.aliases.insert(name.identifier().unwrap().to_string(), vec![rest[0].clone(), name.clone()]);
+                    }
+                } else if let Some(name_index) = declarator_name_index(rest) {
+                    let name = rest[name_index].identifier().unwrap().to_string();
+                    let mut aliased = rest[..name_index].to_vec();
+                    aliased.extend_from_slice(&rest[name_index + 1..]);
+                    self.aliases.insert(name, aliased);
+                }
+            }
+            Some("struct") if has_body => self.parse_struct(&tokens, false),
+            _ if is_function => self.parse_function(&tokens),
+            _ => {}
+        }
+    }
+
+    /// `struct 标签 { 成员 } 别名`，`typedef`时使用别名作为结构体的名字，否则使用标签
+    fn parse_struct(&mut self, tokens: &[Token], is_typedef: bool) {
+        let open = match tokens.iter().position(|token| token.is_punct('{')) {
+            Some(open) => open,
+            None => return,
+        };
+        let close = match tokens.iter().rposition(|token| token.is_punct('}')) {
+            Some(close) => close,
+            None => return,
+        };
+        let tag = tokens[1..open].iter().find_map(Token::identifier).map(str::to_string);
+        let alias = if is_typedef { tokens[close + 1..].iter().find_map(Token::identifier).map(str::to_string) } else { None };
+        let (name, name_range) = match (alias, &tag) {
+            (Some(alias), _) => (alias, tokens[close + 1..].iter().find(|token| token.identifier().is_some()).unwrap().range.clone()),
+            (None, Some(tag)) => (tag.clone(), tokens[1].range.clone()),
+            (None, None) => return,
+        };
+        if let Some(tag) = tag {
+            self.struct_tags.insert(tag, name.clone());
+        }
+        self.struct_tags.insert(name.clone(), name.clone());
+        let mut members = vec![];
+        for member in split_top_level(&tokens[open + 1..close], ';') {
+            if member.is_empty() {
+                continue;
+            }
+            match self.parse_member(member) {
+                Some(parsed) => members.extend(parsed),
+                //位域、嵌套的匿名结构体等无法表示的成员会使整个结构体被跳过
+                None => return,
+            }
+        }
+        let pos = self.pos(tokens[0].range.start..tokens[close].range.end);
+        self.ast.globals_structures.push(Structure {
+            is_public: false,
+            identifier: self.identifier(&name, name_range),
+            generic_params: vec![],
+            members,
+            pos,
+        });
+    }
+
+    /// 成员声明，如`double x, y;`、`int32_t data[4];`
+    fn parse_member(&mut self, tokens: &[Token]) -> Option<Vec<(String, TypeDeclarator)>> {
+        if tokens.iter().any(|token| token.is_punct(':') || token.is_punct('{') || token.is_punct('(')) {
+            return None;
+        }
+        let declarators = split_top_level(tokens, ',');
+        let first = declarators.first()?;
+        let first_name = declarator_name_index(first)?;
+        //后面的声明符共用第一个声明符的基本类型，但有各自的`*`
+        let base_words = first[..first_name].iter().filter(|token| !token.is_punct('*')).cloned().collect::<Vec<_>>();
+        let mut members = vec![];
+        for (index, declarator) in declarators.iter().enumerate() {
+            let (type_tokens, name_index) = if index == 0 {
+                (first[..first_name].to_vec(), first_name)
+            } else {
+                let name_index = declarator.iter().position(|token| token.identifier().is_some())?;
+                let mut type_tokens = base_words.clone();
+                type_tokens.extend_from_slice(&declarator[..name_index]);
+                (type_tokens, name_index)
+            };
+            let name = declarator[name_index].identifier()?.to_string();
+            let ty = self.resolve_type(&type_tokens)?;
+            let ty = self.apply_array_suffix(ty, &declarator[name_index + 1..], false)?;
+            members.push((name, ty));
+        }
+        Some(members)
+    }
+
+    /// 函数原型，如`double dot(const struct Vec3 *a, const struct Vec3 *b);`
+    fn parse_function(&mut self, tokens: &[Token]) {
+        let open = tokens.iter().position(|token| token.is_punct('(')).unwrap();
+        //`void (*callback)(int)`等函数指针无法表示
+        if open == 0 || tokens.get(open + 1).map(|token| token.is_punct('*')).unwrap_or(false) {
+            return;
+        }
+        let name_token = &tokens[open - 1];
+        let name = match name_token.identifier() {
+            Some(name) => name.to_string(),
+            None => return,
+        };
+        //参数列表在与`(`匹配的`)`处结束，之后可能还有`__attribute__((...))`
+        let mut depth = 0;
+        let close = tokens[open..].iter().position(|token| {
+            if token.is_punct('(') {
+                depth += 1;
+            } else if token.is_punct(')') {
+                depth -= 1;
+            }
+            depth == 0
+        });
+        let close = match close {
+            Some(close) => open + close,
+            None => return,
+        };
+        let return_type = match self.resolve_base_type(&tokens[..open - 1]) {
+            Some((BaseType::Void, 0)) => None,
+            Some((base, pointers)) => match self.to_declarator(base, pointers, &tokens[open - 1].range) {
+                Some(ty) => Some(ty),
+                None => return,
+            },
+            None => return,
+        };
+        let params = &tokens[open + 1..close];
+        let mut param_types = vec![];
+        let mut is_variadic = false;
+        let is_void = params.len() == 1 && params[0].identifier() == Some("void");
+        if !params.is_empty() && !is_void {
+            for param in split_top_level(params, ',') {
+                //`...`只能是最后一个参数
+                if param.iter().any(|token| token.token == CToken::Ellipsis) {
+                    is_variadic = true;
+                    break;
+                }
+                match self.parse_param(param) {
+                    Some(ty) => param_types.push(ty),
+                    None => return,
+                }
+            }
+        }
+        let pos = self.pos(tokens[0].range.start..tokens[close].range.end);
+        self.ast.extern_functions.push(FunctionPrototype {
+            is_public: false,
+            identifier: self.identifier(&name, name_token.range.clone()),
+            generic_params: vec![],
+            param_types,
+            is_variadic,
+            return_type,
+            attributes: vec![],
+            pos,
+        });
+    }
+
+    /// 参数可以没有名字，数组参数退化为指针
+    fn parse_param(&mut self, tokens: &[Token]) -> Option<TypeDeclarator> {
+        let bracket = tokens.iter().position(|token| token.is_punct('[')).unwrap_or(tokens.len());
+        let (type_tokens, suffix) = tokens.split_at(bracket);
+        let type_tokens = match declarator_name_index(type_tokens) {
+            Some(name_index) if name_index == type_tokens.len() - 1 => &type_tokens[..name_index],
+            _ => type_tokens,
+        };
+        let ty = self.resolve_type(type_tokens)?;
+        self.apply_array_suffix(ty, suffix, true)
+    }
+
+    /// 名字后面的`[N]`转换为数组类型，多维数组`[2][3]`转换为`[[T;3];2]`；参数中的数组退化为引用。
+    /// 长度为负数或超出u32时记录错误并返回None
+    fn apply_array_suffix(&mut self, ty: TypeDeclarator, suffix: &[Token], is_param: bool) -> Option<TypeDeclarator> {
+        let mut lens = vec![];
+        let mut rest = suffix;
+        while let Some(open) = rest.first() {
+            if !open.is_punct('[') {
+                return None;
+            }
+            let close = rest.iter().position(|token| token.is_punct(']'))?;
+            let len = match &rest[1..close] {
+                [] if is_param => 0,
+                [token] => match &token.token {
+                    CToken::Number(number) => parse_c_integer(number)?,
+                    CToken::Identifier(name) => *self.constants.get(name)?,
+                    _ => return None,
+                },
+                _ => return None,
+            };
+            let range = open.range.start..rest[close].range.end;
+            let len = match u32::try_from(len) {
+                Ok(len) => len,
+                Err(_) => {
+                    let error = CompileError::InvalidCArrayLength { len: len.to_string() }.to_leerror(self.pos(range));
+                    self.error.get_or_insert(error);
+                    return None;
+                }
+            };
+            lens.push((len, range));
+            rest = &rest[close + 1..];
+        }
+        let mut ty = ty;
+        for (index, (len, range)) in lens.iter().enumerate().rev() {
+            if is_param && index == 0 {
+                ty = TypeDeclarator::Reference(Box::new(ty));
+            } else {
+                ty = TypeDeclarator::Array(Box::new(ArrayDeclarator { element_type: ty, len: *len, pos: self.pos(range.clone()) }));
+            }
+        }
+        Some(ty)
+    }
+
+    /// 没有名字的完整类型，如`const char *`，`void`和前向声明的结构体只能通过指针使用
+    fn resolve_type(&self, tokens: &[Token]) -> Option<TypeDeclarator> {
+        let (base, pointers) = self.resolve_base_type(tokens)?;
+        let range = tokens.first()?.range.start..tokens.last()?.range.end;
+        self.to_declarator(base, pointers, &range)
+    }
+
+    fn to_declarator(&self, base: BaseType, pointers: usize, range: &Range<usize>) -> Option<TypeDeclarator> {
+        let mut ty = match (base, pointers) {
+            (BaseType::Type(ty), _) => ty,
+            //`void *`和不透明结构体的指针转换为`ref u8`
+            (BaseType::Void, 1..) | (BaseType::Opaque, 1..) => {
+                let ty = self.type_identifier("u8", range.clone());
+                return Some((1..pointers).fold(TypeDeclarator::Reference(Box::new(ty)), |ty, _| TypeDeclarator::Reference(Box::new(ty))));
+            }
+            _ => return None,
+        };
+        for _ in 0..pointers {
+            ty = TypeDeclarator::Reference(Box::new(ty));
+        }
+        Some(ty)
+    }
+
+    /// 类型去掉指针后的部分和指针的层数
+    fn resolve_base_type(&self, tokens: &[Token]) -> Option<(BaseType, usize)> {
+        let pointers = tokens.iter().filter(|token| token.is_punct('*')).count();
+        let words = tokens.iter().filter_map(Token::identifier).collect::<Vec<_>>();
+        if tokens.iter().any(|token| !token.is_punct('*') && token.identifier().is_none()) {
+            return None;
+        }
+        let range = tokens.first()?.range.clone();
+        let base = match words.as_slice() {
+            ["struct", tag] => match self.struct_tags.get(*tag) {
+                Some(name) => BaseType::Type(self.type_identifier(name, range)),
+                None => BaseType::Opaque,
+            },
+            ["union", _] => BaseType::Opaque,
+            ["enum", _] => BaseType::Type(self.type_identifier("i32", range)),
+            [word] if self.aliases.contains_key(*word) => {
+                let (base, alias_pointers) = self.resolve_base_type(&self.aliases[*word])?;
+                return Some((base, alias_pointers + pointers));
+            }
+            [word] if self.struct_tags.contains_key(*word) => BaseType::Type(self.type_identifier(&self.struct_tags[*word], range)),
+            [word] if is_fixed_width_type(word) => {
+                let name = FIXED_WIDTH_TYPES.iter().find(|(c_name, _)| c_name == word).unwrap().1;
+                BaseType::Type(self.type_identifier(name, range))
+            }
+            ["void"] => BaseType::Void,
+            words => BaseType::Type(self.type_identifier(builtin_type_name(words)?, range)),
+        };
+        Some((base, pointers))
+    }
+}
+
+fn is_fixed_width_type(word: &str) -> bool {
+    FIXED_WIDTH_TYPES.iter().any(|(c_name, _)| *c_name == word)
+}
+
+/// C基本类型关键字的组合对应的lelang类型，`char`按`u8`处理，`const char *`即lelang的`str`
+fn builtin_type_name(words: &[&str]) -> Option<&'static str> {
+    if words.is_empty() || words.iter().any(|word| !BUILTIN_TYPE_WORDS.contains(word)) {
+        return None;
+    }
+    let count = |name: &str| words.iter().filter(|word| **word == name).count();
+    let unsigned = count("unsigned") > 0;
+    let name = if count("_Bool") + count("bool") > 0 {
+        "bool"
+    } else if count("float") > 0 {
+        "f32"
+    } else if count("double") > 0 {
+        "f64"
+    } else if count("char") > 0 {
+        if count("signed") > 0 { "i8" } else { "u8" }
+    } else if count("short") > 0 {
+        if unsigned { "u16" } else { "i16" }
+    } else if count("long") > 0 {
+        if unsigned { "u64" } else { "i64" }
+    } else if unsigned {
+        "u32"
+    } else {
+        "i32"
+    };
+    Some(name)
+}
+
+/// 声明符中名字的位置：第一个`[`之前的最后一个标识符，`struct Point`的标签和单独的基本类型关键字不是名字
+fn declarator_name_index(tokens: &[Token]) -> Option<usize> {
+    let end = tokens.iter().position(|token| token.is_punct('[')).unwrap_or(tokens.len());
+    let index = tokens[..end].iter().rposition(|token| token.identifier().is_some())?;
+    let word = tokens[index].identifier().unwrap();
+    let after_struct = index > 0 && matches!(tokens[index - 1].identifier(), Some("struct") | Some("union") | Some("enum"));
+    if index == 0 || after_struct || BUILTIN_TYPE_WORDS.contains(&word) {
+        None
+    } else {
+        Some(index)
+    }
+}
+
+/// 去掉`__attribute__((...))`、`__declspec(...)`、`__asm__("name")`等扩展，返回剩余的token和是否有扩展
+fn strip_extensions(tokens: &[Token]) -> (Vec<Token>, bool) {
+    let mut stripped = vec![];
+    let mut has_extensions = false;
+    let mut index = 0;
+    while index < tokens.len() {
+        let is_extension = tokens[index].identifier().map(|word| EXTENSION_WORDS.contains(&word)).unwrap_or(false);
+        if !is_extension {
+            stripped.push(tokens[index].clone());
+            index += 1;
+            continue;
+        }
+        has_extensions = true;
+        index += 1;
+        //跳过与扩展后面的`(`匹配的`)`之前的所有token
+        if tokens.get(index).map(|token| token.is_punct('(')).unwrap_or(false) {
+            let mut depth = 0;
+            while index < tokens.len() {
+                if tokens[index].is_punct('(') {
+                    depth += 1;
+                } else if tokens[index].is_punct(')') {
+                    depth -= 1;
+                }
+                index += 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+        }
+    }
+    (stripped, has_extensions)
+}
+
+/// 按顶层（不在括号内）的分隔符切分token
+fn split_top_level(tokens: &[Token], separator: char) -> Vec<&[Token]> {
+    let mut parts = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (index, token) in tokens.iter().enumerate() {
+        match token.token {
+            CToken::Punct('(') | CToken::Punct('{') | CToken::Punct('[') => depth += 1,
+            CToken::Punct(')') | CToken::Punct('}') | CToken::Punct(']') => depth -= 1,
+            CToken::Punct(c) if c == separator && depth == 0 => {
+                parts.push(&tokens[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&tokens[start..]);
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 转换结果的lelang写法，C头文件只会产生类型名、引用和数组
+    fn type_text(ty: &TypeDeclarator) -> String {
+        match ty {
+            TypeDeclarator::TypeIdentifier(identifier) => identifier.name.clone(),
+            TypeDeclarator::Reference(ty) => format!("ref {}", type_text(ty)),
+            TypeDeclarator::Array(array) => format!("[{};{}]", type_text(&array.element_type), array.len),
+            _ => unreachable!("C headers only produce type names, references and arrays"),
+        }
+    }
+
+    fn function<'a>(ast: &'a Ast, name: &str) -> &'a FunctionPrototype {
+        ast.extern_functions.iter().find(|prototype| prototype.identifier.name == name).unwrap_or_else(|| panic!("`{}` is not imported", name))
+    }
+
+    fn param_texts(prototype: &FunctionPrototype) -> Vec<String> {
+        prototype.param_types.iter().map(type_text).collect()
+    }
+
+    fn function_names(ast: &Ast) -> Vec<&str> {
+        ast.extern_functions.iter().map(|prototype| prototype.identifier.name.as_str()).collect()
+    }
+
+    #[test]
+    fn pointers() {
+        let ast = parse_c_header("const char *name(void *p, int **pp, struct Unknown *handle);", 0).unwrap();
+        let name = function(&ast, "name");
+        assert_eq!(param_texts(name), ["ref u8", "ref ref i32", "ref u8"]);
+        assert_eq!(type_text(name.return_type.as_ref().unwrap()), "ref u8");
+    }
+
+    #[test]
+    fn arrays() {
+        let ast = parse_c_header("#define N 3\ntypedef struct { int32_t data[4]; double m[2][N]; } Grid;\nvoid fill(int32_t values[8]);", 0).unwrap();
+        let grid = &ast.globals_structures[0];
+        let members = grid.members.iter().map(|(name, ty)| (name.as_str(), type_text(ty))).collect::<Vec<_>>();
+        assert_eq!(members, [("data", "[i32;4]".to_string()), ("m", "[[f64;3];2]".to_string())]);
+        //数组形参就是指针
+        assert_eq!(param_texts(function(&ast, "fill")), ["ref i32"]);
+    }
+
+    #[test]
+    fn struct_typedef() {
+        let ast = parse_c_header("typedef struct Vec3 { double x, y, z; } Vec3;\ndouble dot(const struct Vec3 *a, Vec3 b);", 0).unwrap();
+        let vec3 = &ast.globals_structures[0];
+        assert_eq!(vec3.identifier.name, "Vec3");
+        assert_eq!(vec3.members.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>(), ["x", "y", "z"]);
+        assert!(vec3.members.iter().all(|(_, ty)| type_text(ty) == "f64"));
+        assert_eq!(param_texts(function(&ast, "dot")), ["ref Vec3", "Vec3"]);
+    }
+
+    #[test]
+    fn variadic_function() {
+        let ast = parse_c_header("int printf(const char *format, ...);\nint puts(const char *s);", 0).unwrap();
+        let printf = function(&ast, "printf");
+        assert!(printf.is_variadic);
+        assert_eq!(param_texts(printf), ["ref u8"]);
+        assert!(!function(&ast, "puts").is_variadic);
+    }
+
+    #[test]
+    fn function_pointers_are_skipped() {
+        let header = "typedef void (*callback)(int);\nvoid register_callback(callback cb);\nvoid (*handler)(int);\nint ok(int value);";
+        let ast = parse_c_header(header, 0).unwrap();
+        assert_eq!(function_names(&ast), ["ok"]);
+    }
+
+    #[test]
+    fn extensions_are_ignored() {
+        let header = "__attribute__((visibility(\"default\"))) int visible(int);\n\
+                      int renamed(int) __asm__(\"renamed64\");\n\
+                      __declspec(dllimport) void imported(void);\n\
+                      typedef struct __attribute__((packed)) { char a; int b; } Packed;";
+        let ast = parse_c_header(header, 0).unwrap();
+        assert_eq!(function_names(&ast), ["visible", "renamed", "imported"]);
+        //`packed`改变了布局，结构体被跳过
+        assert!(ast.globals_structures.is_empty());
+    }
+
+    #[test]
+    fn non_ascii_input() {
+        let header = "\u{feff}// 注释 ünïcode\nint32_t add(int32_t a, int32_t b); /* ü */\nconst char *greeting = \"héllo\";\nint é;\nint sub(int a, int b);";
+        let ast = parse_c_header(header, 0).unwrap();
+        assert_eq!(function_names(&ast), ["add", "sub"]);
+        assert_eq!(param_texts(function(&ast, "add")), ["i32", "i32"]);
+    }
+
+    #[test]
+    fn array_length_out_of_range_is_error() {
+        let result = parse_c_header("typedef struct { int data[4294967296]; } Huge;", 0);
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::InvalidCArrayLength { .. }, .. })));
+    }
+}
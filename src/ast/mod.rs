@@ -2,3 +2,5 @@ pub use nodes::Ast;
 
 pub mod parser;
 pub mod nodes;
+pub mod namespace;
+pub mod c_import;
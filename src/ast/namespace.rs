@@ -0,0 +1,288 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::ast::nodes::*;
+use crate::error::{CompileError, Result};
+use crate::lexer::Operator;
+
+/// 模块对外的接口：模块名、所有顶层定义的名字以及其中`pub`的部分
+#[derive(Debug, Clone)]
+pub struct ModuleInterface {
+    pub name: String,
+    items: HashSet<String>,
+    public_items: HashSet<String>,
+}
+
+impl ModuleInterface {
+    pub fn from_ast(name: String, ast: &Ast) -> Self {
+        let mut items = HashSet::new();
+        let mut public_items = HashSet::new();
+        let mut add = |identifier: &Identifier, is_public: bool| {
+            //运算符函数按参数类型查找，始终是全局的
+            if is_operator_function(&identifier.name) {
+                return;
+            }
+            items.insert(identifier.name.clone());
+            if is_public {
+                public_items.insert(identifier.name.clone());
+            }
+        };
+        for function in ast.function_definitions.iter() {
+            add(&function.prototype.identifier, function.prototype.is_public);
+        }
+        for structure in ast.globals_structures.iter() {
+            add(&structure.identifier, structure.is_public);
+        }
+        for trait_definition in ast.traits.iter() {
+            add(&trait_definition.identifier, trait_definition.is_public);
+        }
+        for variable in ast.globals_variables.iter() {
+            add(&variable.prototype.identifier, variable.is_public);
+        }
+        Self { name, items, public_items }
+    }
+
+    fn qualified_name(&self, name: &str) -> String {
+        format!("{}::{}", self.name, name)
+    }
+}
+
+fn is_operator_function(name: &str) -> bool {
+    name.starts_with("__")
+}
+
+/// 把模块中的顶层定义改名为`模块名::名字`，模块内部对这些定义的引用同样改名，
+/// 外部函数声明是全局的，不会改名；入口文件不是模块，`module`为None。
+/// 同时检查对其他模块的访问：路径`math::sqrt`中的模块必须已被当前文件导入，`sqrt`必须是`pub`的
+pub fn qualify_module(ast: &mut Ast, module: Option<&ModuleInterface>, imports: &HashMap<String, ModuleInterface>, all_modules: &HashSet<String>) -> Result<()> {
+    let mut qualifier = Qualifier { module, imports, all_modules, scopes: vec![], generic_params: vec![] };
+    if let Some(module) = module {
+        for function in ast.function_definitions.iter_mut() {
+            qualifier.qualify_definition(&mut function.prototype.identifier, module);
+        }
+        for structure in ast.globals_structures.iter_mut() {
+            qualifier.qualify_definition(&mut structure.identifier, module);
+        }
+        for trait_definition in ast.traits.iter_mut() {
+            qualifier.qualify_definition(&mut trait_definition.identifier, module);
+        }
+        for variable in ast.globals_variables.iter_mut() {
+            qualifier.qualify_definition(&mut variable.prototype.identifier, module);
+        }
+    }
+    for variable in ast.globals_variables.iter_mut() {
+        qualifier.qualify_variable(variable)?;
+    }
+    for prototype in ast.extern_functions.iter_mut() {
+        qualifier.qualify_prototype(prototype)?;
+    }
+    for structure in ast.globals_structures.iter_mut() {
+        qualifier.generic_params = structure.generic_params.iter().map(|param| param.identifier.name.clone()).collect();
+        qualifier.qualify_generic_params(&mut structure.generic_params)?;
+        for (_, ty) in structure.members.iter_mut() {
+            qualifier.qualify_type(ty)?;
+        }
+    }
+    qualifier.generic_params = vec!["Self".into()];
+    for trait_definition in ast.traits.iter_mut() {
+        for prototype in trait_definition.functions.iter_mut() {
+            qualifier.qualify_prototype(prototype)?;
+        }
+    }
+    for function in ast.function_definitions.iter_mut() {
+        qualifier.generic_params.clear();
+        qualifier.qualify_function(function)?;
+    }
+    for impl_block in ast.impls.iter_mut() {
+        qualifier.generic_params = vec!["Self".into()];
+        qualifier.qualify_path(&mut impl_block.target, false)?;
+        if let Some(trait_name) = &mut impl_block.trait_name {
+            qualifier.qualify_path(trait_name, false)?;
+        }
+        for function in impl_block.functions.iter_mut() {
+            qualifier.qualify_function(function)?;
+        }
+    }
+    Ok(())
+}
+
+struct Qualifier<'a> {
+    module: Option<&'a ModuleInterface>,
+    imports: &'a HashMap<String, ModuleInterface>,
+    all_modules: &'a HashSet<String>,
+    ///局部变量会遮蔽同名的全局变量
+    scopes: Vec<HashSet<String>>,
+    ///泛型参数会遮蔽同名的类型
+    generic_params: Vec<String>,
+}
+
+impl<'a> Qualifier<'a> {
+    fn qualify_definition(&self, identifier: &mut Identifier, module: &ModuleInterface) {
+        if !is_operator_function(&identifier.name) {
+            identifier.name = module.qualified_name(&identifier.name);
+        }
+    }
+
+    fn is_shadowed(&self, name: &str) -> bool {
+        self.scopes.iter().any(|scope| scope.contains(name))
+    }
+
+    fn insert_local(&mut self, name: &str) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string());
+        }
+    }
+
+    /// 处理对名字的引用：`模块名::名字`检查可见性，当前模块中的名字加上模块名前缀
+    fn qualify_path(&self, identifier: &mut Identifier, is_value: bool) -> Result<()> {
+        let first = identifier.name.split("::").next().unwrap_or_default().to_string();
+        if identifier.name.contains("::") {
+            if let Some(imported) = self.imports.get(&first) {
+                let item = identifier.name[first.len() + 2..].split("::").next().unwrap_or_default();
+                if !imported.public_items.contains(item) {
+                    return Err(CompileError::PrivateItem { module: first, item: item.into() }.to_leerror(identifier.pos()));
+                }
+                return Ok(());
+            }
+            if self.all_modules.contains(&first) && self.module.map(|module| module.name != first).unwrap_or(true) {
+                return Err(CompileError::UnknownModule { module: first }.to_leerror(identifier.pos()));
+            }
+        } else if (is_value && self.is_shadowed(&first)) || self.generic_params.contains(&first) {
+            return Ok(());
+        }
+        if let Some(module) = self.module {
+            //已经带有当前模块名的路径不再改名
+            if first != module.name && module.items.contains(&first) {
+                identifier.name = module.qualified_name(&identifier.name);
+            }
+        }
+        Ok(())
+    }
+
+    fn qualify_type(&self, ty: &mut TypeDeclarator) -> Result<()> {
+        match ty {
+            TypeDeclarator::TypeIdentifier(identifier) => self.qualify_path(identifier, false),
+            TypeDeclarator::Array(array) => self.qualify_type(&mut array.element_type),
+            TypeDeclarator::Slice(slice) => self.qualify_type(&mut slice.element_type),
+            TypeDeclarator::Vector(vector) => self.qualify_type(&mut vector.element_type),
+            TypeDeclarator::Reference(reference) => self.qualify_type(reference),
+            TypeDeclarator::Generic(generic) => {
+                self.qualify_path(&mut generic.identifier, false)?;
+                generic.type_arguments.iter_mut().try_for_each(|argument| self.qualify_type(argument))
+            }
+        }
+    }
+
+    fn qualify_generic_params(&self, generic_params: &mut [GenericParam]) -> Result<()> {
+        generic_params
+            .iter_mut()
+            .flat_map(|param| param.bounds.iter_mut())
+            .try_for_each(|bound| self.qualify_path(bound, false))
+    }
+
+    fn qualify_prototype(&mut self, prototype: &mut FunctionPrototype) -> Result<()> {
+        self.qualify_generic_params(&mut prototype.generic_params)?;
+        for ty in prototype.param_types.iter_mut() {
+            self.qualify_type(ty)?;
+        }
+        if let Some(ty) = &mut prototype.return_type {
+            self.qualify_type(ty)?;
+        }
+        Ok(())
+    }
+
+    fn qualify_function(&mut self, function: &mut FunctionDefinition) -> Result<()> {
+        let outer_generic_params = self.generic_params.clone();
+        self.generic_params.extend(function.prototype.generic_params.iter().map(|param| param.identifier.name.clone()));
+        self.qualify_prototype(&mut function.prototype)?;
+        self.scopes.push(function.param_names.iter().cloned().collect());
+        let result = self.qualify_code_block(&mut function.code_block);
+        self.scopes.pop();
+        self.generic_params = outer_generic_params;
+        result
+    }
+
+    fn qualify_variable(&mut self, variable: &mut Variable) -> Result<()> {
+        self.qualify_expression(&mut variable.value)?;
+        if let Some(ty) = &mut variable.prototype.type_declarator {
+            self.qualify_type(ty)?;
+        }
+        Ok(())
+    }
+
+    fn qualify_code_block(&mut self, code_block: &mut CodeBlock) -> Result<()> {
+        self.scopes.push(HashSet::new());
+        let result = code_block.statements.iter_mut().try_for_each(|statement| self.qualify_statement(statement));
+        self.scopes.pop();
+        result
+    }
+
+    fn qualify_statement(&mut self, statement: &mut Statement) -> Result<()> {
+        match statement {
+            Statement::Expressions(expr) | Statement::Return(expr) => self.qualify_expression(expr),
+            Statement::VariableDefinition(variable) => {
+                self.qualify_variable(variable)?;
+                let name = variable.prototype.identifier.name.clone();
+                self.insert_local(&name);
+                Ok(())
+            }
+            Statement::If(statement) => {
+                self.qualify_expression(&mut statement.cond)?;
+                self.qualify_code_block(&mut statement.then_block)?;
+                if let Some(else_block) = &mut statement.else_block {
+                    self.qualify_code_block(else_block)?;
+                }
+                Ok(())
+            }
+            Statement::ForLoop(for_loop) => {
+                self.scopes.push(HashSet::new());
+                let result = self.qualify_statement(&mut for_loop.init_statement)
+                    .and_then(|_| self.qualify_statement(&mut for_loop.condition))
+                    .and_then(|_| self.qualify_statement(&mut for_loop.iterate))
+                    .and_then(|_| self.qualify_code_block(&mut for_loop.code_block));
+                self.scopes.pop();
+                result
+            }
+            Statement::WhileLoop(while_loop) => {
+                self.qualify_expression(&mut while_loop.condition)?;
+                self.qualify_code_block(&mut while_loop.code_block)
+            }
+            Statement::Match(statement) => {
+                self.qualify_expression(&mut statement.scrutinee)?;
+                statement.arms.iter_mut().try_for_each(|arm| self.qualify_code_block(&mut arm.code_block))
+            }
+            Statement::Void(_) => Ok(())
+        }
+    }
+
+    fn qualify_expression(&mut self, expr: &mut Expr) -> Result<()> {
+        match expr {
+            Expr::BinaryOperator(binary) => {
+                self.qualify_expression(&mut binary.left)?;
+                match (&binary.op, binary.right.as_mut()) {
+                    //成员和方法的名字不属于任何模块
+                    (Operator::Dot, Expr::CallExpression(method_call)) => {
+                        method_call.type_arguments.iter_mut().try_for_each(|argument| self.qualify_type(argument))?;
+                        method_call.params.iter_mut().try_for_each(|param| self.qualify_expression(param))
+                    }
+                    (Operator::Dot, Expr::Identifier(_)) => Ok(()),
+                    (Operator::Cast, Expr::Identifier(type_identifier)) => self.qualify_path(type_identifier, false),
+                    (_, right) => self.qualify_expression(right),
+                }
+            }
+            Expr::UnaryOperator(unary) => self.qualify_expression(&mut unary.expr),
+            Expr::CallExpression(call) => {
+                self.qualify_path(&mut call.function_name, false)?;
+                call.type_arguments.iter_mut().try_for_each(|argument| self.qualify_type(argument))?;
+                call.params.iter_mut().try_for_each(|param| self.qualify_expression(param))
+            }
+            Expr::ArrayInitializer(array) => array.elements.iter_mut().try_for_each(|element| self.qualify_expression(element)),
+            Expr::StructureInitializer(structure) => {
+                self.qualify_path(&mut structure.structure_name, false)?;
+                structure.type_arguments.iter_mut().try_for_each(|argument| self.qualify_type(argument))?;
+                structure.member_initial_values.iter_mut().try_for_each(|(_, value)| self.qualify_expression(value))
+            }
+            Expr::Identifier(identifier) => self.qualify_path(identifier, true),
+            Expr::NumberLiteral(_) | Expr::StringLiteral(_) => Ok(())
+        }
+    }
+}
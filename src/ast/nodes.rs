@@ -7,7 +7,8 @@ use std::io::Write;
 use ptree::{Style, TreeBuilder, TreeItem};
 
 use crate::ast::parser::{
-    parse_extern_function_prototype, parse_function, parse_structure, parse_variable_declaration,
+    parse_attributes, parse_extern_function_prototype, parse_function, parse_impl_block, parse_structure,
+    parse_trait_definition, parse_variable_declaration,
 };
 use crate::error::{LEError, Result, SyntaxError, TokenType};
 use crate::lexer::{KeyWord, LELexer, LEToken, Position};
@@ -37,6 +38,7 @@ pub struct BinaryOpExpression {
 #[derive(Debug, Clone)]
 pub struct FunctionCall {
     pub function_name: Identifier,
+    pub type_arguments: Vec<TypeDeclarator>,
     pub params: Vec<Expr>,
     pub pos: Position,
 }
@@ -59,9 +61,22 @@ pub struct WhileLoop {
 
 #[derive(Debug, Clone)]
 pub struct FunctionPrototype {
+    pub is_public: bool,
     pub identifier: Identifier,
+    pub generic_params: Vec<GenericParam>,
     pub param_types: Vec<TypeDeclarator>,
+    ///只有`decl`声明的外部函数可以是可变参数函数
+    pub is_variadic: bool,
     pub return_type: Option<TypeDeclarator>,
+    pub attributes: Vec<FunctionAttribute>,
+    pub pos: Position,
+}
+
+/// 函数定义或声明前的属性，如`#[inline]`、`#[link_name = "foo"]`
+#[derive(Debug, Clone)]
+pub struct FunctionAttribute {
+    pub name: Identifier,
+    pub value: Option<StringLiteral>,
     pub pos: Position,
 }
 
@@ -87,6 +102,29 @@ pub struct IfStatement {
     pub pos: Position,
 }
 
+#[derive(Debug, Clone)]
+pub struct MatchStatement {
+    pub scrutinee: Box<Expr>,
+    pub arms: Vec<MatchArm>,
+    pub pos: Position,
+}
+
+#[derive(Debug, Clone)]
+pub struct MatchArm {
+    pub pattern: MatchPattern,
+    pub code_block: CodeBlock,
+    pub pos: Position,
+}
+
+///整数模式使用i128，可以同时表示u64和i64的全部取值
+#[derive(Debug, Clone)]
+pub enum MatchPattern {
+    Integer { value: i128, pos: Position },
+    Bool { value: bool, pos: Position },
+    Range { start: i128, end: i128, inclusive: bool, pos: Position },
+    Wildcard { pos: Position },
+}
+
 #[derive(Debug, Clone)]
 pub struct NumberLiteral {
     pub number: Number,
@@ -102,6 +140,7 @@ pub struct UnaryOpExpression {
 
 #[derive(Debug, Clone)]
 pub struct Variable {
+    pub is_public: bool,
     pub prototype: VariablePrototype,
     pub value: Box<Expr>,
     pub pos: Position,
@@ -133,16 +172,70 @@ pub struct ArrayDeclarator {
     pub pos: Position,
 }
 
+///切片类型`[T]`，由指向第一个元素的引用`data`和元素个数`len`组成
+#[derive(Debug, Clone)]
+pub struct SliceDeclarator {
+    pub element_type: TypeDeclarator,
+    pub pos: Position,
+}
+
+#[derive(Debug, Clone)]
+pub struct VectorDeclarator {
+    pub element_type: TypeDeclarator,
+    pub len: u32,
+    pub pos: Position,
+}
+
 #[derive(Debug, Clone)]
 pub struct Structure {
+    pub is_public: bool,
     pub identifier: Identifier,
+    pub generic_params: Vec<GenericParam>,
     pub members: Vec<(String, TypeDeclarator)>,
     pub pos: Position,
 }
 
+#[derive(Debug, Clone)]
+pub struct ImplBlock {
+    pub target: Identifier,
+    pub trait_name: Option<Identifier>,
+    pub functions: Vec<FunctionDefinition>,
+    pub pos: Position,
+}
+
+#[derive(Debug, Clone)]
+pub struct TraitDefinition {
+    pub is_public: bool,
+    pub identifier: Identifier,
+    pub functions: Vec<FunctionPrototype>,
+    pub pos: Position,
+}
+
+///`import math;`，导入与当前文件同目录或搜索路径下的`math.le`
+#[derive(Debug, Clone)]
+pub struct Import {
+    pub module: Identifier,
+    pub pos: Position,
+}
+
+///`decl c "header.h";`导入的C头文件，其中的函数原型、结构体和整数常量在加载时转换为lelang的定义
+#[derive(Debug, Clone)]
+pub struct CHeaderImport {
+    pub path: StringLiteral,
+    pub pos: Position,
+}
+
+#[derive(Debug, Clone)]
+pub struct GenericParam {
+    pub identifier: Identifier,
+    pub bounds: Vec<Identifier>,
+    pub pos: Position,
+}
+
 #[derive(Debug, Clone)]
 pub struct StructureInitializer {
     pub structure_name: Identifier,
+    pub type_arguments: Vec<TypeDeclarator>,
     pub member_initial_values: Vec<(String, Box<Expr>)>,
     pub pos: Position,
 }
@@ -153,11 +246,21 @@ pub struct StringLiteral {
     pub pos: Position,
 }
 
+#[derive(Debug, Clone)]
+pub struct GenericDeclarator {
+    pub identifier: Identifier,
+    pub type_arguments: Vec<TypeDeclarator>,
+    pub pos: Position,
+}
+
 #[derive(Debug, Clone)]
 pub enum TypeDeclarator {
     TypeIdentifier(Identifier),
     Array(Box<ArrayDeclarator>),
+    Slice(Box<SliceDeclarator>),
+    Vector(Box<VectorDeclarator>),
     Reference(Box<TypeDeclarator>),
+    Generic(Box<GenericDeclarator>),
 }
 
 #[derive(Debug, Clone)]
@@ -180,6 +283,7 @@ pub enum Statement {
     If(IfStatement),
     ForLoop(ForLoop),
     WhileLoop(WhileLoop),
+    Match(MatchStatement),
     Void(Position),
 }
 
@@ -278,6 +382,14 @@ impl ASTNode for Structure {
         self.identifier.build_tree_format(builder);
         builder.end_child();
 
+        if !self.generic_params.is_empty() {
+            builder.begin_child("generic_params".to_string());
+            for param in &self.generic_params {
+                param.build_tree_format(builder);
+            }
+            builder.end_child();
+        }
+
         builder.begin_child("members".to_string());
         for (member_name, member_type) in &self.members {
             builder.begin_child(member_name.clone());
@@ -288,6 +400,88 @@ impl ASTNode for Structure {
     }
 }
 
+impl ASTNode for ImplBlock {
+    fn pos(&self) -> Position {
+        self.pos.clone()
+    }
+
+    fn build_tree_format(&self, builder: &mut TreeBuilder) {
+        builder.begin_child("target".to_string());
+        self.target.build_tree_format(builder);
+        builder.end_child();
+
+        if let Some(trait_name) = &self.trait_name {
+            builder.begin_child("trait".to_string());
+            trait_name.build_tree_format(builder);
+            builder.end_child();
+        }
+
+        builder.begin_child("functions".to_string());
+        for (index, f) in self.functions.iter().enumerate() {
+            builder.begin_child(index.to_string());
+            f.build_tree_format(builder);
+            builder.end_child();
+        }
+        builder.end_child();
+    }
+}
+
+impl ASTNode for TraitDefinition {
+    fn pos(&self) -> Position {
+        self.pos.clone()
+    }
+
+    fn build_tree_format(&self, builder: &mut TreeBuilder) {
+        builder.begin_child("identifier".to_string());
+        self.identifier.build_tree_format(builder);
+        builder.end_child();
+
+        builder.begin_child("functions".to_string());
+        for (index, f) in self.functions.iter().enumerate() {
+            builder.begin_child(index.to_string());
+            f.build_tree_format(builder);
+            builder.end_child();
+        }
+        builder.end_child();
+    }
+}
+
+impl ASTNode for GenericParam {
+    fn pos(&self) -> Position {
+        self.pos.clone()
+    }
+
+    fn build_tree_format(&self, builder: &mut TreeBuilder) {
+        if self.bounds.is_empty() {
+            self.identifier.build_tree_format(builder);
+        } else {
+            builder.begin_child(self.identifier.name.clone());
+            for bound in &self.bounds {
+                bound.build_tree_format(builder);
+            }
+            builder.end_child();
+        }
+    }
+}
+
+impl ASTNode for GenericDeclarator {
+    fn pos(&self) -> Position {
+        self.pos.clone()
+    }
+
+    fn build_tree_format(&self, builder: &mut TreeBuilder) {
+        builder.begin_child("identifier".to_string());
+        self.identifier.build_tree_format(builder);
+        builder.end_child();
+
+        builder.begin_child("type_arguments".to_string());
+        for ty in &self.type_arguments {
+            ty.build_tree_format(builder);
+        }
+        builder.end_child();
+    }
+}
+
 impl ASTNode for ArrayDeclarator {
     fn pos(&self) -> Position {
         self.pos.clone()
@@ -304,6 +498,69 @@ impl ASTNode for ArrayDeclarator {
     }
 }
 
+impl ASTNode for SliceDeclarator {
+    fn pos(&self) -> Position {
+        self.pos.clone()
+    }
+
+    fn build_tree_format(&self, builder: &mut TreeBuilder) {
+        builder.begin_child("element_type".to_string());
+        self.element_type.build_tree_format(builder);
+        builder.end_child();
+    }
+}
+
+impl ASTNode for Import {
+    fn pos(&self) -> Position {
+        self.pos.clone()
+    }
+
+    fn build_tree_format(&self, builder: &mut TreeBuilder) {
+        builder.begin_child("module".to_string());
+        self.module.build_tree_format(builder);
+        builder.end_child();
+    }
+}
+
+impl ASTNode for FunctionAttribute {
+    fn pos(&self) -> Position {
+        self.pos.clone()
+    }
+
+    fn build_tree_format(&self, builder: &mut TreeBuilder) {
+        match &self.value {
+            Some(value) => builder.add_empty_child(format!("#[{} = \"{}\"]", self.name.name, value.content)),
+            None => builder.add_empty_child(format!("#[{}]", self.name.name)),
+        }
+    }
+}
+
+impl ASTNode for CHeaderImport {
+    fn pos(&self) -> Position {
+        self.pos.clone()
+    }
+
+    fn build_tree_format(&self, builder: &mut TreeBuilder) {
+        builder.add_empty_child(format!("header: {}", self.path.content));
+    }
+}
+
+impl ASTNode for VectorDeclarator {
+    fn pos(&self) -> Position {
+        self.pos.clone()
+    }
+
+    fn build_tree_format(&self, builder: &mut TreeBuilder) {
+        builder.begin_child("element_type".to_string());
+        self.element_type.build_tree_format(builder);
+        builder.end_child();
+
+        builder.begin_child("lanes".to_string());
+        builder.add_empty_child(self.len.to_string());
+        builder.end_child();
+    }
+}
+
 impl ASTNode for Identifier {
     fn pos(&self) -> Position {
         self.pos.clone()
@@ -384,6 +641,64 @@ impl ASTNode for IfStatement {
     }
 }
 
+impl ASTNode for MatchPattern {
+    fn pos(&self) -> Position {
+        match self {
+            MatchPattern::Integer { pos, .. } => pos.clone(),
+            MatchPattern::Bool { pos, .. } => pos.clone(),
+            MatchPattern::Range { pos, .. } => pos.clone(),
+            MatchPattern::Wildcard { pos } => pos.clone(),
+        }
+    }
+
+    fn build_tree_format(&self, builder: &mut TreeBuilder) {
+        builder.add_empty_child(match self {
+            MatchPattern::Integer { value, .. } => format!("`{}`", value),
+            MatchPattern::Bool { value, .. } => format!("`{}`", value),
+            MatchPattern::Range { start, end, inclusive, .. } => {
+                format!("`{}{}{}`", start, if *inclusive { "..=" } else { ".." }, end)
+            }
+            MatchPattern::Wildcard { .. } => "`_`".to_string(),
+        });
+    }
+}
+
+impl ASTNode for MatchArm {
+    fn pos(&self) -> Position {
+        self.pos.clone()
+    }
+
+    fn build_tree_format(&self, builder: &mut TreeBuilder) {
+        builder.begin_child("pattern".to_string());
+        self.pattern.build_tree_format(builder);
+        builder.end_child();
+
+        builder.begin_child("code_block".to_string());
+        self.code_block.build_tree_format(builder);
+        builder.end_child();
+    }
+}
+
+impl ASTNode for MatchStatement {
+    fn pos(&self) -> Position {
+        self.pos.clone()
+    }
+
+    fn build_tree_format(&self, builder: &mut TreeBuilder) {
+        builder.begin_child("scrutinee".to_string());
+        self.scrutinee.build_tree_format(builder);
+        builder.end_child();
+
+        builder.begin_child("arms".to_string());
+        for arm in &self.arms {
+            builder.begin_child("arm".to_string());
+            arm.build_tree_format(builder);
+            builder.end_child();
+        }
+        builder.end_child();
+    }
+}
+
 impl ASTNode for CodeBlock {
     fn pos(&self) -> Position {
         self.pos.clone()
@@ -430,12 +745,32 @@ impl ASTNode for FunctionPrototype {
         self.identifier.build_tree_format(builder);
         builder.end_child();
 
+        if !self.generic_params.is_empty() {
+            builder.begin_child("generic_params".to_string());
+            for param in &self.generic_params {
+                param.build_tree_format(builder);
+            }
+            builder.end_child();
+        }
+
         builder.begin_child("param_types".to_string());
         for ty in &self.param_types {
             ty.build_tree_format(builder);
         }
         builder.end_child();
 
+        if !self.attributes.is_empty() {
+            builder.begin_child("attributes".to_string());
+            for attribute in &self.attributes {
+                attribute.build_tree_format(builder);
+            }
+            builder.end_child();
+        }
+
+        if self.is_variadic {
+            builder.add_empty_child("variadic".to_string());
+        }
+
         builder.begin_child("return_type".to_string());
         if let Some(ret) = &self.return_type {
             ret.build_tree_format(builder);
@@ -505,7 +840,10 @@ impl ASTNode for TypeDeclarator {
         match self {
             TypeDeclarator::TypeIdentifier(e) => e.pos(),
             TypeDeclarator::Array(e) => e.pos(),
+            TypeDeclarator::Slice(e) => e.pos(),
+            TypeDeclarator::Vector(e) => e.pos(),
             TypeDeclarator::Reference(e) => e.pos(),
+            TypeDeclarator::Generic(e) => e.pos(),
         }
     }
 
@@ -521,11 +859,26 @@ impl ASTNode for TypeDeclarator {
                 t.build_tree_format(builder);
                 builder.end_child();
             }
+            TypeDeclarator::Slice(t) => {
+                builder.begin_child("slice_type".to_string());
+                t.build_tree_format(builder);
+                builder.end_child();
+            }
+            TypeDeclarator::Vector(t) => {
+                builder.begin_child("vector_type".to_string());
+                t.build_tree_format(builder);
+                builder.end_child();
+            }
             TypeDeclarator::Reference(t) => {
                 builder.begin_child("reference_type".to_string());
                 t.build_tree_format(builder);
                 builder.end_child();
             }
+            TypeDeclarator::Generic(t) => {
+                builder.begin_child("generic_type".to_string());
+                t.build_tree_format(builder);
+                builder.end_child();
+            }
         };
     }
 }
@@ -599,6 +952,7 @@ impl ASTNode for Statement {
             Statement::If(e) => e.pos(),
             Statement::ForLoop(e) => e.pos(),
             Statement::WhileLoop(e) => e.pos(),
+            Statement::Match(e) => e.pos(),
             Statement::Void(p) => p.clone(),
         }
     }
@@ -635,6 +989,11 @@ impl ASTNode for Statement {
                 s.build_tree_format(builder);
                 builder.end_child();
             }
+            Statement::Match(s) => {
+                builder.begin_child("match_statement".to_string());
+                s.build_tree_format(builder);
+                builder.end_child();
+            }
             Statement::Void(_) => {
                 builder.add_empty_child("void statement".to_string());
             }
@@ -644,81 +1003,186 @@ impl ASTNode for Statement {
 
 #[derive(Debug, Clone)]
 pub struct Ast {
+    pub imports: Vec<Import>,
+    pub c_headers: Vec<CHeaderImport>,
     pub globals_variables: Vec<Variable>,
     pub globals_structures: Vec<Structure>,
     pub function_definitions: Vec<FunctionDefinition>,
     pub extern_functions: Vec<FunctionPrototype>,
+    pub impls: Vec<ImplBlock>,
+    pub traits: Vec<TraitDefinition>,
 }
 
 impl Ast {
-    pub fn from_lexer(tokens: LELexer) -> Result<Self> {
-        let mut ast = Self {
+    pub fn new() -> Self {
+        Self {
+            imports: vec![],
+            c_headers: vec![],
             globals_variables: vec![],
             globals_structures: vec![],
             function_definitions: vec![],
             extern_functions: vec![],
-        };
+            impls: vec![],
+            traits: vec![],
+        }
+    }
+
+    pub fn from_lexer(tokens: LELexer) -> Result<Self> {
+        let mut ast = Self::new();
         ast.parse(tokens)?;
         Ok(ast)
     }
 
+    ///合并另一个文件的语法树，外部函数声明是全局的，同名的声明只保留第一个
+    pub fn merge(&mut self, other: Ast) {
+        self.imports.extend(other.imports);
+        self.c_headers.extend(other.c_headers);
+        self.globals_variables.extend(other.globals_variables);
+        self.globals_structures.extend(other.globals_structures);
+        self.function_definitions.extend(other.function_definitions);
+        for prototype in other.extern_functions {
+            if !self.extern_functions.iter().any(|declared| declared.identifier.name == prototype.identifier.name) {
+                self.extern_functions.push(prototype);
+            }
+        }
+        self.impls.extend(other.impls);
+        self.traits.extend(other.traits);
+    }
+
     fn parse(&mut self, mut lexer: LELexer) -> Result<()> {
-        loop {
-            let next_token = lexer.current();
-            match next_token {
-                None => {
-                    break;
+        while let Some(token) = lexer.current() {
+            match token {
+                LEToken::KeyWord(KeyWord::Import) => {
+                    let start_pos = lexer.pos();
+                    lexer.consume_keyword()?;
+                    let module_pos = lexer.pos();
+                    let module = Identifier { name: lexer.consume_identifier()?, pos: module_pos };
+                    lexer.consume_semicolon()?;
+                    self.imports.push(Import { module, pos: start_pos.sum(&lexer.last_pos()) });
                 }
-                Some(token) => {
-                    if let LEToken::KeyWord(keyword) = token {
-                        match keyword {
-                            KeyWord::Declare => {
-                                lexer.consume_keyword()?;
-                                let function_prototype =
-                                    parse_extern_function_prototype(&mut lexer)?;
-                                lexer.consume_semicolon()?;
-                                self.extern_functions.push(function_prototype);
-                            }
-                            KeyWord::FunctionDefine => {
-                                let function = parse_function(&mut lexer)?;
-                                self.function_definitions.push(function);
-                            }
-                            KeyWord::VariableDeclare => {
-                                let variable = parse_variable_declaration(&mut lexer)?;
-                                lexer.consume_semicolon()?;
-                                self.globals_variables.push(variable);
-                            }
-                            KeyWord::StructureDeclare => {
-                                let structure = parse_structure(&mut lexer)?;
-                                self.globals_structures.push(structure);
-                            }
-                            _ => {
-                                return Err(LEError::new_syntax_error(
-                                    SyntaxError::unexpect_token(
-                                        vec![TokenType::FunctionDefine, TokenType::FunctionDeclare],
-                                        LEToken::KeyWord(keyword),
-                                    ),
-                                    lexer.pos(),
-                                ));
-                            }
-                        }
-                    } else {
-                        return Err(SyntaxError::unexpect_token(
-                            vec![TokenType::FunctionDefine, TokenType::FunctionDeclare],
-                            token.clone(),
-                        )
-                            .to_leerror(lexer.pos()));
+                //`#[...]`修饰其后的函数定义或`decl`声明，属性写在`pub`之前
+                LEToken::Sharp => {
+                    let attributes = parse_attributes(&mut lexer)?;
+                    let is_public = matches!(lexer.current(), Some(LEToken::KeyWord(KeyWord::Pub)));
+                    if is_public {
+                        lexer.consume_keyword()?;
                     }
+                    self.parse_item(&mut lexer, is_public, attributes)?;
+                }
+                //`pub`修饰的定义可以在导入它所在模块的文件中访问
+                LEToken::KeyWord(KeyWord::Pub) => {
+                    lexer.consume_keyword()?;
+                    self.parse_item(&mut lexer, true, vec![])?;
+                }
+                _ => {
+                    self.parse_item(&mut lexer, false, vec![])?;
                 }
             }
         }
         Ok(())
     }
 
+    fn parse_item(&mut self, lexer: &mut LELexer, is_public: bool, attributes: Vec<FunctionAttribute>) -> Result<()> {
+        let token = lexer.current().ok_or_else(|| LEError::new_syntax_error(
+            SyntaxError::missing_token(vec![TokenType::FunctionDefine, TokenType::FunctionDeclare]),
+            lexer.pos(),
+        ))?;
+        if let LEToken::KeyWord(keyword) = token {
+            //属性只能修饰函数定义和`decl`声明
+            if !attributes.is_empty() && !matches!(keyword, KeyWord::Declare | KeyWord::FunctionDefine) {
+                return Err(LEError::new_syntax_error(
+                    SyntaxError::unexpect_token(vec![TokenType::FunctionDefine, TokenType::FunctionDeclare], LEToken::KeyWord(keyword)),
+                    lexer.pos(),
+                ));
+            }
+            match keyword {
+                KeyWord::Declare => {
+                    let start_pos = lexer.pos();
+                    lexer.consume_keyword()?;
+                    //`decl c "header.h";`导入C头文件中的声明
+                    if let Some(LEToken::Identifier(language)) = lexer.current() {
+                        if language == "c" && attributes.is_empty() {
+                            lexer.consume_identifier()?;
+                            let path_pos = lexer.pos();
+                            let content = lexer.consume_string_literal()?;
+                            lexer.consume_semicolon()?;
+                            let path = StringLiteral { content, pos: path_pos };
+                            self.c_headers.push(CHeaderImport { path, pos: start_pos.sum(&lexer.last_pos()) });
+                            return Ok(());
+                        }
+                    }
+                    let mut function_prototype = parse_extern_function_prototype(lexer)?;
+                    lexer.consume_semicolon()?;
+                    function_prototype.is_public = is_public;
+                    function_prototype.attributes = attributes;
+                    self.extern_functions.push(function_prototype);
+                }
+                KeyWord::FunctionDefine => {
+                    let mut function = parse_function(lexer)?;
+                    function.prototype.is_public = is_public;
+                    function.prototype.attributes = attributes;
+                    self.function_definitions.push(function);
+                }
+                KeyWord::VariableDeclare => {
+                    let mut variable = parse_variable_declaration(lexer)?;
+                    lexer.consume_semicolon()?;
+                    variable.is_public = is_public;
+                    self.globals_variables.push(variable);
+                }
+                KeyWord::StructureDeclare => {
+                    let mut structure = parse_structure(lexer)?;
+                    structure.is_public = is_public;
+                    self.globals_structures.push(structure);
+                }
+                KeyWord::Impl if !is_public => {
+                    let impl_block = parse_impl_block(lexer)?;
+                    self.impls.push(impl_block);
+                }
+                KeyWord::Trait => {
+                    let mut trait_definition = parse_trait_definition(lexer)?;
+                    trait_definition.is_public = is_public;
+                    self.traits.push(trait_definition);
+                }
+                _ => {
+                    return Err(LEError::new_syntax_error(
+                        SyntaxError::unexpect_token(
+                            vec![TokenType::FunctionDefine, TokenType::FunctionDeclare],
+                            LEToken::KeyWord(keyword),
+                        ),
+                        lexer.pos(),
+                    ));
+                }
+            }
+            Ok(())
+        } else {
+            Err(SyntaxError::unexpect_token(
+                vec![TokenType::FunctionDefine, TokenType::FunctionDeclare],
+                token,
+            )
+                .to_leerror(lexer.pos()))
+        }
+    }
+
     pub fn print_with_root_name<W: io::Write>(&self, w: W, root: String) -> std::io::Result<()> {
         let mut builder = TreeBuilder::new(root);
         let builder_ref = &mut builder;
 
+        builder_ref.begin_child("imports".to_string());
+        for (index, import) in self.imports.iter().enumerate() {
+            builder_ref.begin_child(index.to_string());
+            import.build_tree_format(builder_ref);
+            builder_ref.end_child();
+        }
+        builder_ref.end_child();
+
+        builder_ref.begin_child("c headers".to_string());
+        for (index, header) in self.c_headers.iter().enumerate() {
+            builder_ref.begin_child(index.to_string());
+            header.build_tree_format(builder_ref);
+            builder_ref.end_child();
+        }
+        builder_ref.end_child();
+
         builder_ref.begin_child("external functions".to_string());
         for (index, f) in self.extern_functions.iter().enumerate() {
             builder_ref.begin_child(index.to_string());
@@ -743,6 +1207,22 @@ impl Ast {
         }
         builder_ref.end_child();
 
+        builder_ref.begin_child("impls".to_string());
+        for (index, f) in self.impls.iter().enumerate() {
+            builder_ref.begin_child(index.to_string());
+            f.build_tree_format(builder_ref);
+            builder_ref.end_child();
+        }
+        builder_ref.end_child();
+
+        builder_ref.begin_child("traits".to_string());
+        for (index, f) in self.traits.iter().enumerate() {
+            builder_ref.begin_child(index.to_string());
+            f.build_tree_format(builder_ref);
+            builder_ref.end_child();
+        }
+        builder_ref.end_child();
+
         builder_ref.begin_child("globals_variables".to_string());
         for (index, f) in self.globals_variables.iter().enumerate() {
             builder_ref.begin_child(index.to_string());
@@ -1,4 +1,4 @@
-use crate::ast::nodes::{ArrayDeclarator, ArrayInitializer, Expr, FunctionCall};
+use crate::ast::nodes::{ArrayDeclarator, ArrayInitializer, Expr, FunctionCall, SliceDeclarator, TypeDeclarator};
 use crate::ast::parser::{parse_call_expression, parse_expression};
 use crate::ast::parser::type_declarator::parse_type_declarator;
 use crate::error::{LEError, Result};
@@ -47,20 +47,28 @@ pub fn parse_array_initializer(lexer: &mut LELexer) -> Result<Box<Expr>> {
 }
 
 
-pub fn parse_array_declarator(lexer: &mut LELexer) -> Result<ArrayDeclarator> {
+/// 解析数组类型`[T;N]`或切片类型`[T]`
+pub fn parse_array_declarator(lexer: &mut LELexer) -> Result<TypeDeclarator> {
     let start_pos = lexer.pos();
     lexer.consume_left_bracket()?;
     let element_type = parse_type_declarator(lexer)?;
+    if let Some(LEToken::RightBracket) = lexer.current() {
+        lexer.consume_right_bracket()?;
+        return Ok(TypeDeclarator::Slice(Box::new(SliceDeclarator {
+            element_type,
+            pos: start_pos.sum(&lexer.last_pos()),
+        })));
+    }
     lexer.consume_semicolon()?;
     let len = lexer.consume_number_literal()?;
     if let Number::Integer(len) = len {
         lexer.consume_right_bracket()?;
-        Ok(ArrayDeclarator {
+        Ok(TypeDeclarator::Array(Box::new(ArrayDeclarator {
             element_type,
             len: len as u32,
             pos: start_pos.sum(&lexer.pos()),
-        })
+        })))
     } else {
         Err(LEError::new_syntax_error(SyntaxError::unexpect_token(vec![TokenType::NumberLiteral, Identifier], LEToken::NumberLiteral(len)), lexer.pos()))
     }
-}
\ No newline at end of file
+}
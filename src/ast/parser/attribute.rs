@@ -0,0 +1,25 @@
+use crate::ast::nodes::{FunctionAttribute, Identifier, StringLiteral};
+use crate::error::Result;
+use crate::lexer::{LELexer, LEToken, Operator};
+
+///解析函数前的属性列表`#[inline] #[link_name = "foo"]`，没有属性时返回空列表
+pub fn parse_attributes(lexer: &mut LELexer) -> Result<Vec<FunctionAttribute>> {
+    let mut attributes = vec![];
+    while let Some(LEToken::Sharp) = lexer.current() {
+        let start_pos = lexer.pos();
+        lexer.consume();
+        lexer.consume_left_bracket()?;
+        let name_pos = lexer.pos();
+        let name = Identifier { name: lexer.consume_identifier()?, pos: name_pos };
+        let value = if let Some(LEToken::Operator(Operator::Assign)) = lexer.current() {
+            lexer.consume();
+            let value_pos = lexer.pos();
+            Some(StringLiteral { content: lexer.consume_string_literal()?, pos: value_pos })
+        } else {
+            None
+        };
+        lexer.consume_right_bracket()?;
+        attributes.push(FunctionAttribute { name, value, pos: start_pos.sum(&lexer.last_pos()) });
+    }
+    Ok(attributes)
+}
@@ -1,6 +1,7 @@
-use crate::ast::nodes::{ASTNode, BinaryOpExpression, CodeBlock, Expr, FunctionCall, FunctionDefinition, Identifier, NumberLiteral, Structure, StructureInitializer, TypeDeclarator, UnaryOpExpression};
+use crate::ast::nodes::{ASTNode, BinaryOpExpression, CodeBlock, Expr, FunctionCall, FunctionDefinition, Identifier, NumberLiteral, StringLiteral, Structure, StructureInitializer, TypeDeclarator, UnaryOpExpression};
 use crate::ast::parser::{parse_anonymous_function, parse_structure_initializer};
 use crate::ast::parser::array::parse_array_initializer;
+use crate::ast::parser::generic::parse_type_arguments;
 use crate::ast::parser::statement::parse_statement;
 use crate::ast::parser::type_declarator::parse_type_declarator;
 use crate::error::{LEError, SyntaxError, TokenType};
@@ -39,7 +40,7 @@ pub fn parse_annotation(lexer: &mut LELexer) -> Result<(String, TypeDeclarator)>
     Ok((identifier, type_declarator))
 }
 
-pub fn parse_call_expression(lexer: &mut LELexer, function_name: Identifier) -> Result<Box<Expr>> {
+pub fn parse_call_expression(lexer: &mut LELexer, function_name: Identifier, type_arguments: Vec<TypeDeclarator>) -> Result<Box<Expr>> {
     let start_pos = lexer.pos();
     lexer.consume();
     let mut params = vec![];
@@ -51,6 +52,7 @@ pub fn parse_call_expression(lexer: &mut LELexer, function_name: Identifier) ->
                 lexer.consume();
                 return Ok(Box::new(Expr::CallExpression(FunctionCall {
                     function_name,
+                    type_arguments,
                     params,
                     pos: start_pos.sum(&lexer.pos()),
                 })));
@@ -95,18 +97,33 @@ pub fn parse_binary_ops(lexer: &mut LELexer, mut lhs: Box<Expr>, expression_prec
 
 pub fn parse_identifier_expression(lexer: &mut LELexer) -> Result<Box<Expr>> {
     let start_pos = lexer.pos();
-    let identifier = Identifier { name: lexer.consume_identifier()?, pos: start_pos.clone() };
+    let mut name = lexer.consume_identifier()?;
+    let mut identifier_pos = start_pos.clone();
+    let mut type_arguments = vec![];
+    //路径形式的标识符，如`MyStruct::new`，`::<...>`指定泛型的类型实参，如`max::<i32>`
+    while let Some(LEToken::DoubleColon) = lexer.current() {
+        lexer.consume();
+        if let Some(LEToken::Operator(Operator::LessThan)) = lexer.current() {
+            type_arguments = parse_type_arguments(lexer)?;
+            break;
+        }
+        identifier_pos = identifier_pos.sum(&lexer.pos());
+        name.push_str("::");
+        name.push_str(&lexer.consume_identifier()?);
+    }
+    let identifier = Identifier { name, pos: identifier_pos };
     let current_token = lexer.current().ok_or(
         LEError::new_syntax_error(SyntaxError::missing_token(vec![TokenType::LeftPar, TokenType::LeftBrace]), lexer.pos())
     )?;
     match current_token {
         LEToken::LeftPar => {
-            Ok(parse_call_expression(lexer, identifier)?)
+            Ok(parse_call_expression(lexer, identifier, type_arguments)?)
         }
         LEToken::LeftBrace => {
             let (initializer, pos) = parse_structure_initializer(lexer)?;
             Ok(Box::new(Expr::StructureInitializer(StructureInitializer {
                 structure_name: identifier,
+                type_arguments,
                 member_initial_values: initializer,
                 pos: start_pos.sum(&pos),
             })))
@@ -123,6 +140,12 @@ pub fn parse_number_expression(lexer: &mut LELexer) -> Result<Box<Expr>> {
     Ok(Box::new(Expr::NumberLiteral(NumberLiteral { number, pos: start_pos })))
 }
 
+pub fn parse_string_expression(lexer: &mut LELexer) -> Result<Box<Expr>> {
+    let start_pos = lexer.pos();
+    let content = lexer.consume_string_literal()?;
+    Ok(Box::new(Expr::StringLiteral(StringLiteral { content, pos: start_pos })))
+}
+
 pub fn parse_little_par_expression(lexer: &mut LELexer) -> Result<Box<Expr>> {
     lexer.consume_left_par()?;
     let expression = parse_expression(lexer)?;
@@ -151,6 +174,9 @@ pub fn parse_primary_expression(lexer: &mut LELexer) -> Result<Box<Expr>> {
         LEToken::NumberLiteral(_) => {
             parse_number_expression(lexer)
         }
+        LEToken::StringLiteral(_) => {
+            parse_string_expression(lexer)
+        }
         LEToken::Identifier(_) => {
             parse_identifier_expression(lexer)
         }
@@ -161,7 +187,7 @@ pub fn parse_primary_expression(lexer: &mut LELexer) -> Result<Box<Expr>> {
         // LEToken::KeyWord(KeyWord::FunctionDefine)=>{ parse_anonymous_function(lexer) }
         _ => {
             Err(LEError::new_syntax_error(SyntaxError::unexpect_token(
-                vec![TokenType::Operator, TokenType::NumberLiteral, TokenType::Identifier, TokenType::LeftBracket, TokenType::LeftPar],
+                vec![TokenType::Operator, TokenType::NumberLiteral, TokenType::StringLiteral, TokenType::Identifier, TokenType::LeftBracket, TokenType::LeftPar],
                 current_token.clone()), lexer.pos()))
         }
     }
@@ -1,6 +1,7 @@
 use crate::ast::nodes::{FunctionDefinition, FunctionPrototype, Identifier, TypeDeclarator};
 use crate::ast::parser::array::parse_array_declarator;
 use crate::ast::parser::common::parse_code_block;
+use crate::ast::parser::generic::parse_generic_params;
 use crate::ast::parser::parse_annotation;
 use crate::ast::parser::type_declarator::parse_type_declarator;
 use crate::error::{LEError, Result};
@@ -29,7 +30,8 @@ pub fn parse_function_params(lexer: &mut LELexer) -> Result<Vec<(String, TypeDec
     }
 }
 
-pub fn parse_type_list(lexer: &mut LELexer) -> Result<Vec<TypeDeclarator>> {
+/// `decl`声明的参数类型列表，以`...`结尾时为可变参数函数，`...`只能出现在最后
+pub fn parse_extern_param_types(lexer: &mut LELexer) -> Result<(Vec<TypeDeclarator>, bool)> {
     lexer.consume_left_par()?;
     let mut params = vec![];
     loop {
@@ -39,11 +41,23 @@ pub fn parse_type_list(lexer: &mut LELexer) -> Result<Vec<TypeDeclarator>> {
         match current_token {
             LEToken::RightPar => {
                 lexer.consume();
-                return Ok(params);
+                return Ok((params, false));
             }
             LEToken::Comma => {
                 lexer.consume();
             }
+            LEToken::Ellipsis => {
+                lexer.consume();
+                let pos = lexer.pos();
+                return match lexer.current() {
+                    Some(LEToken::RightPar) => {
+                        lexer.consume();
+                        Ok((params, true))
+                    }
+                    Some(token) => Err(LEError::new_syntax_error(SyntaxError::unexpect_token(vec![TokenType::RightPar], token), pos)),
+                    None => Err(LEError::new_syntax_error(SyntaxError::missing_token(vec![TokenType::RightPar]), pos)),
+                };
+            }
             _ => {
                 params.push(parse_type_declarator(lexer)?);
             }
@@ -57,12 +71,16 @@ pub fn parse_extern_function_prototype(lexer: &mut LELexer) -> Result<FunctionPr
     lexer.consume_keyword()?;
     let identifier_pos = lexer.pos();
     let identifier = Identifier { name: lexer.consume_identifier()?, pos: identifier_pos };
-    let param_types = parse_type_list(lexer)?;
+    let (param_types, is_variadic) = parse_extern_param_types(lexer)?;
     let return_type = parse_function_return_type(lexer)?;
     Ok(FunctionPrototype {
+        is_public: false,
         identifier,
+        generic_params: vec![],
         param_types,
+        is_variadic,
         return_type,
+        attributes: vec![],
         pos: start_pos.sum(&lexer.pos()),
     })
 }
@@ -86,6 +104,7 @@ pub fn parse_function(lexer: &mut LELexer) -> Result<FunctionDefinition> {
     lexer.consume_keyword()?;
     let identifier_pos = lexer.pos();
     let identifier = Identifier { name: lexer.consume_identifier()?, pos: identifier_pos };
+    let generic_params = parse_generic_params(lexer)?;
     let params = parse_function_params(lexer)?;
     let return_type = parse_function_return_type(lexer)?;
     let proto_type_pos = start_pos.sum(&lexer.pos());
@@ -99,9 +118,13 @@ pub fn parse_function(lexer: &mut LELexer) -> Result<FunctionDefinition> {
     });
     let function = FunctionDefinition {
         prototype: FunctionPrototype {
+            is_public: false,
             identifier,
+            generic_params,
             param_types,
+            is_variadic: false,
             return_type,
+            attributes: vec![],
             pos: proto_type_pos,
         },
         param_names,
@@ -0,0 +1,81 @@
+use crate::ast::nodes::{GenericParam, Identifier, TypeDeclarator};
+use crate::ast::parser::type_declarator::parse_type_declarator;
+use crate::error::{LEError, Result};
+use crate::error::{SyntaxError, TokenType};
+use crate::lexer::{LELexer, LEToken, Operator};
+
+///解析定义处的泛型参数列表`<T: Show, U>`，没有泛型参数时返回空列表
+pub fn parse_generic_params(lexer: &mut LELexer) -> Result<Vec<GenericParam>> {
+    let mut params = vec![];
+    if let Some(LEToken::Operator(Operator::LessThan)) = lexer.current() {
+        lexer.consume();
+        loop {
+            let current_token = lexer.current()
+                .ok_or_else(|| LEError::new_syntax_error(
+                    SyntaxError::missing_token(vec![TokenType::Identifier, TokenType::Comma, TokenType::Operator]),
+                    lexer.pos()))?;
+            match current_token {
+                LEToken::Operator(Operator::GreaterThan) => {
+                    lexer.consume();
+                    break;
+                }
+                LEToken::Comma => {
+                    lexer.consume();
+                }
+                _ => {
+                    params.push(parse_generic_param(lexer)?);
+                }
+            }
+        }
+    }
+    Ok(params)
+}
+
+///解析单个泛型参数和它的约束`T: Show + Eq`
+fn parse_generic_param(lexer: &mut LELexer) -> Result<GenericParam> {
+    let start_pos = lexer.pos();
+    let identifier = Identifier { name: lexer.consume_identifier()?, pos: start_pos.clone() };
+    let mut bounds = vec![];
+    if let Some(LEToken::Colon) = lexer.current() {
+        lexer.consume();
+        loop {
+            let pos = lexer.pos();
+            bounds.push(Identifier { name: lexer.consume_identifier()?, pos });
+            if let Some(LEToken::Operator(Operator::Plus)) = lexer.current() {
+                lexer.consume();
+            } else {
+                break;
+            }
+        }
+    }
+    Ok(GenericParam {
+        identifier,
+        bounds,
+        pos: start_pos.sum(&lexer.last_pos()),
+    })
+}
+
+///解析使用处的类型实参列表`<i32, f64>`，调用前当前token必须是`<`
+pub fn parse_type_arguments(lexer: &mut LELexer) -> Result<Vec<TypeDeclarator>> {
+    let mut arguments = vec![];
+    lexer.consume_operator()?;
+    loop {
+        let current_token = lexer.current()
+            .ok_or_else(|| LEError::new_syntax_error(
+                SyntaxError::missing_token(vec![TokenType::Identifier, TokenType::Comma, TokenType::Operator]),
+                lexer.pos()))?;
+        match current_token {
+            LEToken::Operator(Operator::GreaterThan) => {
+                lexer.consume();
+                break;
+            }
+            LEToken::Comma => {
+                lexer.consume();
+            }
+            _ => {
+                arguments.push(parse_type_declarator(lexer)?);
+            }
+        }
+    }
+    Ok(arguments)
+}
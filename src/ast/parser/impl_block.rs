@@ -0,0 +1,50 @@
+use crate::ast::nodes::{Identifier, ImplBlock};
+use crate::ast::parser::attribute::parse_attributes;
+use crate::ast::parser::function_parser::parse_function;
+use crate::error::{LEError, Result};
+use crate::error::{SyntaxError, TokenType};
+use crate::lexer::{KeyWord, LELexer, LEToken};
+
+pub fn parse_impl_block(lexer: &mut LELexer) -> Result<ImplBlock> {
+    let start_pos = lexer.pos();
+    lexer.consume_keyword()?;
+    let target_pos = lexer.pos();
+    let first = Identifier { name: lexer.consume_identifier()?, pos: target_pos };
+    //`impl Trait for Type`形式的实现块
+    let (target, trait_name) = if let Some(LEToken::KeyWord(KeyWord::For)) = lexer.current() {
+        lexer.consume();
+        let target_pos = lexer.pos();
+        (Identifier { name: lexer.consume_identifier()?, pos: target_pos }, Some(first))
+    } else {
+        (first, None)
+    };
+    lexer.consume_left_brace()?;
+    let mut functions = vec![];
+    loop {
+        let current_token = lexer.current()
+            .ok_or_else(|| LEError::new_syntax_error(
+                SyntaxError::missing_token(vec![TokenType::RightBrace, TokenType::FunctionDefine]),
+                lexer.pos()))?;
+        match current_token {
+            LEToken::RightBrace => {
+                lexer.consume();
+                break;
+            }
+            LEToken::KeyWord(KeyWord::FunctionDefine) => {
+                functions.push(parse_function(lexer)?);
+            }
+            LEToken::Sharp => {
+                let attributes = parse_attributes(lexer)?;
+                let mut function = parse_function(lexer)?;
+                function.prototype.attributes = attributes;
+                functions.push(function);
+            }
+            _ => {
+                return Err(LEError::new_syntax_error(
+                    SyntaxError::unexpect_token(vec![TokenType::RightBrace, TokenType::FunctionDefine], current_token),
+                    lexer.pos()));
+            }
+        }
+    }
+    Ok(ImplBlock { target, trait_name, functions, pos: start_pos.sum(&lexer.pos()) })
+}
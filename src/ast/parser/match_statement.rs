@@ -0,0 +1,175 @@
+use crate::ast::nodes::{CodeBlock, Expr, Identifier, MatchArm, MatchPattern, MatchStatement};
+use crate::ast::parser::common::{parse_binary_ops, parse_call_expression, parse_code_block, parse_expression};
+use crate::ast::parser::statement::parse_statement;
+use crate::error::{LEError, Result, SyntaxError, TokenType};
+use crate::lexer::{LELexer, LEToken, Number, Operator};
+
+pub fn parse_match_statement(lexer: &mut LELexer) -> Result<MatchStatement> {
+    let start_pos = lexer.pos();
+    lexer.consume_keyword()?;
+    let scrutinee = parse_match_scrutinee(lexer)?;
+    lexer.consume_left_brace()?;
+    let mut arms = vec![];
+    loop {
+        let current = lexer.current()
+            .ok_or_else(|| LEError::new_syntax_error(SyntaxError::missing_token(vec![TokenType::RightBrace]), lexer.pos()))?;
+        match current {
+            LEToken::RightBrace => {
+                break;
+            }
+            LEToken::Comma => {
+                lexer.consume();
+            }
+            _ => {
+                arms.push(parse_match_arm(lexer)?);
+            }
+        }
+    }
+    lexer.consume_right_brace()?;
+    Ok(MatchStatement {
+        scrutinee,
+        arms,
+        pos: start_pos.sum(&lexer.pos()),
+    })
+}
+
+///解析被匹配的表达式，`match x {` 中的 `x {` 不能被当作结构体初始化
+fn parse_match_scrutinee(lexer: &mut LELexer) -> Result<Box<Expr>> {
+    if let Some(LEToken::Identifier(_)) = lexer.current() {
+        let start_pos = lexer.pos();
+        let identifier = Identifier { name: lexer.consume_identifier()?, pos: start_pos };
+        let primary = if let Some(LEToken::LeftPar) = lexer.current() {
+            parse_call_expression(lexer, identifier, vec![])?
+        } else {
+            Box::new(Expr::Identifier(identifier))
+        };
+        parse_binary_ops(lexer, primary, 0)
+    } else {
+        parse_expression(lexer)
+    }
+}
+
+fn parse_match_arm(lexer: &mut LELexer) -> Result<MatchArm> {
+    let start_pos = lexer.pos();
+    let pattern = parse_match_pattern(lexer)?;
+    lexer.consume_double_arrow()?;
+    let code_block = if let Some(LEToken::LeftBrace) = lexer.current() {
+        parse_code_block(lexer)?
+    } else {
+        //单条语句的分支
+        let statement_pos = lexer.pos();
+        let statement = parse_statement(lexer)?;
+        CodeBlock {
+            statements: vec![statement],
+            pos: statement_pos.sum(&lexer.pos()),
+        }
+    };
+    Ok(MatchArm {
+        pattern,
+        code_block,
+        pos: start_pos.sum(&lexer.pos()),
+    })
+}
+
+fn parse_match_pattern(lexer: &mut LELexer) -> Result<MatchPattern> {
+    let start_pos = lexer.pos();
+    let current = lexer.current()
+        .ok_or_else(|| LEError::new_syntax_error(SyntaxError::missing_token(vec![TokenType::NumberLiteral, TokenType::Identifier]), lexer.pos()))?;
+    match current {
+        LEToken::Identifier(identifier) => {
+            lexer.consume();
+            match identifier.as_str() {
+                "_" => Ok(MatchPattern::Wildcard { pos: start_pos }),
+                "true" => Ok(MatchPattern::Bool { value: true, pos: start_pos }),
+                "false" => Ok(MatchPattern::Bool { value: false, pos: start_pos }),
+                _ => Err(SyntaxError::InvalidMatchPattern.to_leerror(start_pos)),
+            }
+        }
+        LEToken::NumberLiteral(_) | LEToken::Operator(Operator::Sub) => {
+            let start = parse_pattern_integer(lexer)?;
+            let inclusive = match lexer.current() {
+                Some(LEToken::DoubleDot) => false,
+                Some(LEToken::DoubleDotEqual) => true,
+                _ => return Ok(MatchPattern::Integer { value: start, pos: start_pos }),
+            };
+            lexer.consume();
+            let end = parse_pattern_integer(lexer)?;
+            let pos = start_pos.sum(&lexer.last_pos());
+            //不允许空范围
+            if (inclusive && start > end) || (!inclusive && start >= end) {
+                return Err(SyntaxError::InvalidMatchPattern.to_leerror(pos));
+            }
+            Ok(MatchPattern::Range { start, end, inclusive, pos })
+        }
+        _ => {
+            Err(SyntaxError::InvalidMatchPattern.to_leerror(start_pos))
+        }
+    }
+}
+
+///解析模式中的整数，允许带负号，u64的最大值和i64的最小值都可以表示
+fn parse_pattern_integer(lexer: &mut LELexer) -> Result<i128> {
+    let start_pos = lexer.pos();
+    let negative = if let Some(LEToken::Operator(Operator::Sub)) = lexer.current() {
+        lexer.consume();
+        true
+    } else {
+        false
+    };
+    let number_pos = lexer.pos();
+    match lexer.consume_number_literal()? {
+        Number::Integer(value) => {
+            let value = value as i128;
+            Ok(if negative { -value } else { value })
+        }
+        Number::Float(_) => {
+            Err(SyntaxError::InvalidMatchPattern.to_leerror(start_pos.sum(&number_pos)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> Result<MatchStatement> {
+        parse_match_statement(&mut LELexer::new(source).unwrap())
+    }
+
+    fn patterns(source: &str) -> Vec<MatchPattern> {
+        parse(source).unwrap().arms.into_iter().map(|arm| arm.pattern).collect()
+    }
+
+    #[test]
+    fn literal_range_and_wildcard_patterns() {
+        let patterns = patterns("match x { 0 => ret 0; 1..=9 => ret 1; 10..100 => { ret 2; } -3..=-1 => ret 3; true => ret 4; _ => ret 5; }");
+        assert!(matches!(patterns[0], MatchPattern::Integer { value: 0, .. }));
+        assert!(matches!(patterns[1], MatchPattern::Range { start: 1, end: 9, inclusive: true, .. }));
+        assert!(matches!(patterns[2], MatchPattern::Range { start: 10, end: 100, inclusive: false, .. }));
+        assert!(matches!(patterns[3], MatchPattern::Range { start: -3, end: -1, inclusive: true, .. }));
+        assert!(matches!(patterns[4], MatchPattern::Bool { value: true, .. }));
+        assert!(matches!(patterns[5], MatchPattern::Wildcard { .. }));
+    }
+
+    #[test]
+    fn integers_beyond_i64() {
+        let patterns = patterns("match x { 18446744073709551615 => ret 0; 9223372036854775808..=18446744073709551614 => ret 1; -9223372036854775808 => ret 2; }");
+        assert!(matches!(patterns[0], MatchPattern::Integer { value, .. } if value == u64::MAX as i128));
+        assert!(matches!(patterns[1], MatchPattern::Range { start, end, .. } if start == 1i128 << 63 && end == u64::MAX as i128 - 1));
+        assert!(matches!(patterns[2], MatchPattern::Integer { value, .. } if value == i64::MIN as i128));
+    }
+
+    #[test]
+    fn empty_range_is_error() {
+        assert!(parse("match x { 5..5 => ret 0; }").is_err());
+        assert!(parse("match x { 9..=1 => ret 0; }").is_err());
+        assert!(parse("match x { 5..=5 => ret 0; }").is_ok());
+    }
+
+    #[test]
+    fn invalid_patterns() {
+        assert!(parse("match x { 1.5 => ret 0; }").is_err());
+        assert!(parse("match x { y => ret 0; }").is_err());
+        assert!(parse("match x { 1 => ret 0;").is_err());
+    }
+}
@@ -1,13 +1,19 @@
 pub use anonymous_function::*;
 pub use array::*;
+pub use attribute::*;
 pub use common::*;
 pub use for_loop::*;
 pub use function_parser::*;
+pub use generic::*;
 pub use if_statement::*;
+pub use impl_block::*;
+pub use match_statement::*;
 pub use statement::*;
 pub use structure::*;
+pub use trait_definition::*;
 pub use type_declarator::*;
 pub use variable_parser::*;
+pub use vector::*;
 pub use while_loop::*;
 
 mod common;
@@ -17,7 +23,13 @@ mod statement;
 mod if_statement;
 mod for_loop;
 mod while_loop;
+mod match_statement;
 mod structure;
+mod impl_block;
+mod trait_definition;
 mod array;
+mod vector;
 mod type_declarator;
-mod anonymous_function;
\ No newline at end of file
+mod generic;
+mod anonymous_function;
+mod attribute;
\ No newline at end of file
@@ -2,6 +2,7 @@ use crate::ast::nodes::Statement;
 use crate::ast::parser::common::parse_expression;
 use crate::ast::parser::for_loop::parse_for_loop;
 use crate::ast::parser::if_statement::parse_if_statement;
+use crate::ast::parser::match_statement::parse_match_statement;
 use crate::ast::parser::variable_parser::parse_variable_declaration;
 use crate::ast::parser::while_loop::parse_while_loop;
 use crate::error::{LEError, Result};
@@ -11,7 +12,7 @@ use crate::lexer::{KeyWord, LELexer, LEToken};
 pub fn parse_statement(lexer: &mut LELexer) -> Result<Statement> {
     let next_token = lexer.current()
         .ok_or_else(|| LEError::new_syntax_error(
-            SyntaxError::missing_token(vec![TokenType::Return, TokenType::VariableDeclare, TokenType::If, TokenType::For, TokenType::While]),
+            SyntaxError::missing_token(vec![TokenType::Return, TokenType::VariableDeclare, TokenType::If, TokenType::For, TokenType::While, TokenType::Match]),
             lexer.pos()))?;
     match next_token {
         LEToken::KeyWord(ref keyword) => {
@@ -30,10 +31,11 @@ pub fn parse_statement(lexer: &mut LELexer) -> Result<Statement> {
                 KeyWord::If => Ok(Statement::If(parse_if_statement(lexer)?)),
                 KeyWord::For => Ok(Statement::ForLoop(parse_for_loop(lexer)?)),
                 KeyWord::While => Ok(Statement::WhileLoop(parse_while_loop(lexer)?)),
+                KeyWord::Match => Ok(Statement::Match(parse_match_statement(lexer)?)),
                 _ => {
                     Err(LEError::new_syntax_error(
                         SyntaxError::unexpect_token(
-                            vec![TokenType::Return, TokenType::VariableDeclare, TokenType::If, TokenType::For, TokenType::While], next_token.clone())
+                            vec![TokenType::Return, TokenType::VariableDeclare, TokenType::If, TokenType::For, TokenType::While, TokenType::Match], next_token.clone())
                         , lexer.pos()))
                 }
             }
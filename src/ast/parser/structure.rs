@@ -1,5 +1,5 @@
 use crate::ast::nodes::{Expr, Identifier, Structure, StructureInitializer};
-use crate::ast::parser::{parse_annotation, parse_expression, parse_function_params, parse_type_declarator};
+use crate::ast::parser::{parse_annotation, parse_expression, parse_function_params, parse_generic_params, parse_type_declarator};
 use crate::error::{LEError, Result};
 use crate::error::{SyntaxError, TokenType};
 use crate::lexer::{LELexer, LEToken, Position};
@@ -9,6 +9,7 @@ pub fn parse_structure(lexer: &mut LELexer) -> Result<Structure> {
     lexer.consume_keyword()?;
     let structure_pos = lexer.pos();
     let structure_name = lexer.consume_identifier()?;
+    let generic_params = parse_generic_params(lexer)?;
     lexer.consume_left_brace()?;
     let mut members = vec![];
     loop {
@@ -34,7 +35,7 @@ pub fn parse_structure(lexer: &mut LELexer) -> Result<Structure> {
             }
         }
     }
-    Ok(Structure { identifier: Identifier { name: structure_name, pos: structure_pos }, members, pos: start_pos.sum(&lexer.pos()) })
+    Ok(Structure { is_public: false, identifier: Identifier { name: structure_name, pos: structure_pos }, generic_params, members, pos: start_pos.sum(&lexer.pos()) })
 }
 
 
@@ -0,0 +1,57 @@
+use crate::ast::nodes::{FunctionPrototype, Identifier, TraitDefinition};
+use crate::ast::parser::function_parser::{parse_function_params, parse_function_return_type};
+use crate::error::{LEError, Result};
+use crate::error::{SyntaxError, TokenType};
+use crate::lexer::{KeyWord, LELexer, LEToken};
+
+pub fn parse_trait_definition(lexer: &mut LELexer) -> Result<TraitDefinition> {
+    let start_pos = lexer.pos();
+    lexer.consume_keyword()?;
+    let identifier_pos = lexer.pos();
+    let identifier = Identifier { name: lexer.consume_identifier()?, pos: identifier_pos };
+    lexer.consume_left_brace()?;
+    let mut functions = vec![];
+    loop {
+        let current_token = lexer.current()
+            .ok_or_else(|| LEError::new_syntax_error(
+                SyntaxError::missing_token(vec![TokenType::RightBrace, TokenType::FunctionDefine]),
+                lexer.pos()))?;
+        match current_token {
+            LEToken::RightBrace => {
+                lexer.consume();
+                break;
+            }
+            LEToken::KeyWord(KeyWord::FunctionDefine) => {
+                functions.push(parse_trait_function(lexer)?);
+            }
+            _ => {
+                return Err(LEError::new_syntax_error(
+                    SyntaxError::unexpect_token(vec![TokenType::RightBrace, TokenType::FunctionDefine], current_token),
+                    lexer.pos()));
+            }
+        }
+    }
+    Ok(TraitDefinition { is_public: false, identifier, functions, pos: start_pos.sum(&lexer.pos()) })
+}
+
+///解析trait中的方法声明`le show(self: ref Self);`，只保留参数类型
+fn parse_trait_function(lexer: &mut LELexer) -> Result<FunctionPrototype> {
+    let start_pos = lexer.pos();
+    lexer.consume_keyword()?;
+    let identifier_pos = lexer.pos();
+    let identifier = Identifier { name: lexer.consume_identifier()?, pos: identifier_pos };
+    let params = parse_function_params(lexer)?;
+    let return_type = parse_function_return_type(lexer)?;
+    let pos = start_pos.sum(&lexer.pos());
+    lexer.consume_semicolon()?;
+    Ok(FunctionPrototype {
+        is_public: false,
+        identifier,
+        generic_params: vec![],
+        param_types: params.into_iter().map(|(_, ty)| ty).collect(),
+        is_variadic: false,
+        return_type,
+        attributes: vec![],
+        pos,
+    })
+}
@@ -1,8 +1,10 @@
-use crate::ast::nodes::{Identifier, TypeDeclarator};
+use crate::ast::nodes::{GenericDeclarator, Identifier, TypeDeclarator};
 use crate::ast::parser::array::parse_array_declarator;
+use crate::ast::parser::generic::parse_type_arguments;
+use crate::ast::parser::vector::parse_vector_declarator;
 use crate::error::{LEError, Result};
 use crate::error::{SyntaxError, TokenType};
-use crate::lexer::{KeyWord, LELexer, LEToken, Position};
+use crate::lexer::{KeyWord, LELexer, LEToken, Operator, Position};
 
 pub fn parse_type_declarator(lexer: &mut LELexer) -> Result<TypeDeclarator> {
     let current_token = lexer.current().ok_or_else(|| LEError::new_syntax_error(
@@ -12,17 +14,29 @@ pub fn parse_type_declarator(lexer: &mut LELexer) -> Result<TypeDeclarator> {
     match current_token {
         LEToken::Identifier(identifier) => {
             let pos = lexer.pos();
-            let identifier = lexer.consume_identifier()?;
-            Ok(TypeDeclarator::TypeIdentifier(Identifier { name: identifier, pos }))
+            let identifier = Identifier { name: lexer.consume_identifier()?, pos: pos.clone() };
+            //SIMD向量类型，如`vec<f32;4>`
+            if let (Some(LEToken::Operator(Operator::LessThan)), "vec") = (lexer.current(), identifier.name.as_str()) {
+                return Ok(TypeDeclarator::Vector(Box::new(parse_vector_declarator(lexer, pos)?)));
+            }
+            //泛型结构体的实例，如`Pair<i32,f64>`
+            if let Some(LEToken::Operator(Operator::LessThan)) = lexer.current() {
+                let type_arguments = parse_type_arguments(lexer)?;
+                Ok(TypeDeclarator::Generic(Box::new(GenericDeclarator {
+                    identifier,
+                    type_arguments,
+                    pos: pos.sum(&lexer.last_pos()),
+                })))
+            } else {
+                Ok(TypeDeclarator::TypeIdentifier(identifier))
+            }
         }
         LEToken::KeyWord(KeyWord::Ref) => {
             lexer.consume_keyword()?;
             let ref_type = parse_type_declarator(lexer)?;
             Ok(TypeDeclarator::Reference(Box::new(ref_type)))
         }
-        LEToken::LeftBracket => {
-            Ok(TypeDeclarator::Array(Box::new(parse_array_declarator(lexer)?)))
-        }
+        LEToken::LeftBracket => parse_array_declarator(lexer),
         _ => {
             Err(LEError::new_syntax_error(
                 SyntaxError::unexpect_token(vec![TokenType::Identifier, TokenType::LeftBracket], current_token),
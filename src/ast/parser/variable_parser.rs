@@ -31,6 +31,7 @@ pub fn parse_variable_declaration(lexer: &mut LELexer) -> Result<Variable> {
     if Operator::Assign == equal_op {
         let initial_value = parse_expression(lexer)?;
         Ok(Variable {
+            is_public: false,
             prototype: VariablePrototype {
                 type_declarator,
                 identifier,
@@ -0,0 +1,34 @@
+use crate::ast::nodes::VectorDeclarator;
+use crate::ast::parser::type_declarator::parse_type_declarator;
+use crate::error::{LEError, Result};
+use crate::error::{SyntaxError, TokenType};
+use crate::lexer::{LELexer, LEToken, Number, Operator, Position};
+
+///解析SIMD向量类型`vec<f32;4>`，通道数在1到`u32::MAX`之间，调用前`vec`已经被消耗，当前token必须是`<`
+pub fn parse_vector_declarator(lexer: &mut LELexer, start_pos: Position) -> Result<VectorDeclarator> {
+    lexer.consume_operator()?;
+    let element_type = parse_type_declarator(lexer)?;
+    lexer.consume_semicolon()?;
+    let len_pos = lexer.pos();
+    let len = lexer.consume_number_literal()?;
+    let len = if let Number::Integer(len) = len {
+        //不允许0个通道，也不允许超出u32的通道数被截断
+        match u32::try_from(len) {
+            Ok(lanes) if lanes > 0 => lanes,
+            _ => return Err(SyntaxError::InvalidVectorLength { len }.to_leerror(len_pos)),
+        }
+    } else {
+        return Err(LEError::new_syntax_error(SyntaxError::unexpect_token(vec![TokenType::NumberLiteral], LEToken::NumberLiteral(len)), len_pos));
+    };
+    let close_pos = lexer.pos();
+    match lexer.consume_operator()? {
+        Operator::GreaterThan => {
+            Ok(VectorDeclarator {
+                element_type,
+                len,
+                pos: start_pos.sum(&lexer.last_pos()),
+            })
+        }
+        op => Err(LEError::new_syntax_error(SyntaxError::unexpect_token(vec![TokenType::Operator], LEToken::Operator(op)), close_pos))
+    }
+}
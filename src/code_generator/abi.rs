@@ -0,0 +1,325 @@
+use inkwell::attributes::{Attribute, AttributeLoc};
+use inkwell::context::Context;
+use inkwell::targets::TargetData;
+use inkwell::types::{AnyType, BasicMetadataTypeEnum, BasicType, BasicTypeEnum, FunctionType, StructType};
+use inkwell::values::FunctionValue;
+
+///结构体和数组按值传递时，参数或返回值最多占用的字节数，超过时通过内存传递
+const MAX_REGISTER_AGGREGATE_SIZE: u64 = 16;
+
+///System V x86_64传递参数的通用寄存器(rdi、rsi、rdx、rcx、r8、r9)和SSE寄存器(xmm0-xmm7)的个数
+const SYSV64_INTEGER_REGISTERS: u32 = 6;
+const SYSV64_SSE_REGISTERS: u32 = 8;
+
+/// 支持按C ABI传递结构体和数组的调用约定
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CAbi {
+    ///x86_64 Linux上的System V ABI
+    SysV64,
+    ///aarch64 Linux上的AAPCS64
+    AArch64,
+    ///wasm32上clang使用的C ABI
+    Wasm32,
+}
+
+impl CAbi {
+    /// 目标三元组对应的调用约定，其他目标按LLVM的默认方式传递结构体
+    pub fn from_triple(triple: &str) -> Option<Self> {
+        let arch = triple.split('-').next().unwrap_or_default();
+        match arch {
+            "x86_64" if !triple.contains("windows") => Some(CAbi::SysV64),
+            "aarch64" | "arm64" => Some(CAbi::AArch64),
+            "wasm32" => Some(CAbi::Wasm32),
+            _ => None,
+        }
+    }
+}
+
+/// 一个参数或返回值按C ABI传递的方式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PassMode<'ctx> {
+    ///标量，直接传递
+    Direct,
+    ///按位拷贝为若干个标量，每个标量占用一个寄存器；aarch64上是一个`[N x i64]`或`[N x float]`数组，
+    ///由后端整体分配寄存器，不会一半在寄存器一半在栈上
+    Cast(Vec<BasicTypeEnum<'ctx>>),
+    ///传递指向副本的指针，返回值由调用者提供存放结果的内存(sret)；
+    ///x86_64和wasm32上结构体的副本位于调用者的栈上(byval)，数组形参与C相同，传递的是普通指针
+    Indirect,
+}
+
+/// 函数按C ABI降级后的签名
+#[derive(Debug, Clone)]
+pub struct FunctionAbi<'ctx> {
+    pub params: Vec<PassMode<'ctx>>,
+    pub return_mode: Option<PassMode<'ctx>>,
+    pub llvm_type: FunctionType<'ctx>,
+    param_types: Vec<BasicTypeEnum<'ctx>>,
+    return_type: Option<BasicTypeEnum<'ctx>>,
+    abi: CAbi,
+}
+
+impl<'ctx> FunctionAbi<'ctx> {
+    /// 降级前的参数类型
+    pub fn param_types(&self) -> &[BasicTypeEnum<'ctx>] {
+        &self.param_types
+    }
+
+    /// 降级前的返回类型
+    pub fn return_type(&self) -> Option<BasicTypeEnum<'ctx>> {
+        self.return_type
+    }
+
+    /// 按多个标量传递时，这些标量组成的结构体，用于在内存中按位转换
+    pub fn cast_struct_type(context: &'ctx Context, types: &[BasicTypeEnum<'ctx>]) -> StructType<'ctx> {
+        context.struct_type(types, false)
+    }
+
+    /// 为降级后的函数加上sret和byval属性
+    pub fn add_attributes(&self, context: &'ctx Context, function: FunctionValue<'ctx>) {
+        let mut index = 0;
+        if let (Some(PassMode::Indirect), Some(return_type)) = (&self.return_mode, self.return_type) {
+            let sret = context.create_type_attribute(Attribute::get_named_enum_kind_id("sret"), return_type.as_any_type_enum());
+            function.add_attribute(AttributeLoc::Param(0), sret);
+            index = 1;
+        }
+        for (mode, ty) in self.params.iter().zip(self.param_types.iter()) {
+            match mode {
+                PassMode::Direct => index += 1,
+                PassMode::Cast(types) => index += types.len() as u32,
+                PassMode::Indirect => {
+                    //x86_64和wasm32上结构体的副本位于调用者的栈上，数组形参和aarch64上传递的是普通指针
+                    if self.abi != CAbi::AArch64 && !ty.is_array_type() {
+                        let byval = context.create_type_attribute(Attribute::get_named_enum_kind_id("byval"), ty.as_any_type_enum());
+                        function.add_attribute(AttributeLoc::Param(index), byval);
+                    }
+                    index += 1;
+                }
+            }
+        }
+    }
+}
+
+/// 按目标的调用约定降级函数签名
+pub struct CAbiLowering {
+    abi: CAbi,
+    target_data: TargetData,
+}
+
+impl CAbiLowering {
+    pub fn new(abi: CAbi, target_data: TargetData) -> Self {
+        Self { abi, target_data }
+    }
+
+    pub fn target_data(&self) -> &TargetData {
+        &self.target_data
+    }
+
+    /// 参数和返回值中有结构体或数组时返回降级后的签名，只有标量时不需要降级，返回None
+    pub fn function_abi<'ctx>(&self, context: &'ctx Context, param_types: &[BasicTypeEnum<'ctx>], return_type: Option<BasicTypeEnum<'ctx>>) -> Option<FunctionAbi<'ctx>> {
+        let has_aggregate = param_types.iter().chain(return_type.iter()).any(|ty| is_aggregate(*ty));
+        if !has_aggregate {
+            return None;
+        }
+        let return_mode = return_type.map(|ty| self.classify(context, ty));
+        let mut registers = FreeRegisters { integer: SYSV64_INTEGER_REGISTERS, sse: SYSV64_SSE_REGISTERS };
+        //x86_64上sret的指针占用一个通用寄存器，aarch64上使用单独的x8
+        if return_mode == Some(PassMode::Indirect) {
+            registers.take(1, 0);
+        }
+        let params = param_types.iter().map(|ty| self.classify_param(context, *ty, &mut registers)).collect::<Vec<_>>();
+        let mut llvm_params: Vec<BasicMetadataTypeEnum> = vec![];
+        if let (Some(PassMode::Indirect), Some(return_type)) = (&return_mode, return_type) {
+            llvm_params.push(return_type.ptr_type(0.into()).into());
+        }
+        for (mode, ty) in params.iter().zip(param_types.iter()) {
+            match mode {
+                PassMode::Direct => llvm_params.push((*ty).into()),
+                PassMode::Cast(types) => llvm_params.extend(types.iter().map(|ty| BasicMetadataTypeEnum::from(*ty))),
+                PassMode::Indirect => llvm_params.push(ty.ptr_type(0.into()).into()),
+            }
+        }
+        let llvm_type = match (&return_mode, return_type) {
+            (Some(PassMode::Direct), Some(return_type)) => return_type.fn_type(&llvm_params, false),
+            (Some(PassMode::Cast(types)), _) if types.len() == 1 => types[0].fn_type(&llvm_params, false),
+            (Some(PassMode::Cast(types)), _) => FunctionAbi::cast_struct_type(context, types).fn_type(&llvm_params, false),
+            _ => context.void_type().fn_type(&llvm_params, false),
+        };
+        Some(FunctionAbi {
+            params,
+            return_mode,
+            llvm_type,
+            param_types: param_types.to_vec(),
+            return_type,
+            abi: self.abi,
+        })
+    }
+
+    /// 参数的传递方式。数组形参在C中就是指针，与生成的头文件一致，传递指向副本的指针。
+    /// x86_64上和clang一样按顺序统计剩余的寄存器，结构体需要的寄存器不够时整体通过栈传递，
+    /// 否则LLVM会把一半放在最后的寄存器中、一半放在栈上
+    fn classify_param<'ctx>(&self, context: &'ctx Context, ty: BasicTypeEnum<'ctx>, registers: &mut FreeRegisters) -> PassMode<'ctx> {
+        if ty.is_array_type() {
+            registers.take(1, 0);
+            return PassMode::Indirect;
+        }
+        let mode = self.classify(context, ty);
+        if self.abi != CAbi::SysV64 {
+            return mode;
+        }
+        let (integer, sse) = match &mode {
+            PassMode::Direct => scalar_registers(ty),
+            PassMode::Cast(types) => types.iter().fold((0, 0), |(integer, sse), ty| {
+                let (scalar_integer, scalar_sse) = scalar_registers(*ty);
+                (integer + scalar_integer, sse + scalar_sse)
+            }),
+            PassMode::Indirect => (0, 0),
+        };
+        //标量放不进寄存器时由LLVM放到栈上，结构体需要整体通过栈传递
+        if !registers.take(integer, sse) && is_aggregate(ty) {
+            PassMode::Indirect
+        } else {
+            mode
+        }
+    }
+
+    fn classify<'ctx>(&self, context: &'ctx Context, ty: BasicTypeEnum<'ctx>) -> PassMode<'ctx> {
+        if !is_aggregate(ty) {
+            return PassMode::Direct;
+        }
+        let size = self.target_data.get_abi_size(&ty);
+        let mut fields = vec![];
+        self.flatten(ty, 0, &mut fields);
+        match self.abi {
+            CAbi::SysV64 => self.classify_sysv64(context, size, &fields),
+            CAbi::AArch64 => self.classify_aarch64(context, size, &fields),
+            CAbi::Wasm32 => self.classify_wasm32(size, &fields),
+        }
+    }
+
+    /// System V：大于16字节或有未对齐成员的通过内存传递，否则每8字节为一组，
+    /// 组内全是浮点数或8字节的向量时使用SSE寄存器，否则使用通用寄存器；
+    /// 16字节的向量占满两组(SSE和SSEUP)，和clang一样按向量本身使用一个SSE寄存器，不超过4字节的向量按整数传递
+    fn classify_sysv64<'ctx>(&self, context: &'ctx Context, size: u64, fields: &[(u64, BasicTypeEnum<'ctx>)]) -> PassMode<'ctx> {
+        let unaligned = fields.iter().any(|(offset, ty)| offset % self.target_data.get_abi_alignment(ty) as u64 != 0);
+        if size > MAX_REGISTER_AGGREGATE_SIZE || unaligned {
+            return PassMode::Indirect;
+        }
+        if let Some((_, vector)) = fields.iter().find(|(_, ty)| ty.is_vector_type() && self.target_data.get_abi_size(ty) == 16) {
+            return PassMode::Cast(vec![*vector]);
+        }
+        let is_sse = |ty: &BasicTypeEnum| ty.is_float_type() || (ty.is_vector_type() && self.target_data.get_abi_size(ty) == 8);
+        let mut types = vec![];
+        for start in (0..size).step_by(8) {
+            let len = (size - start).min(8);
+            let group = fields.iter().filter(|(offset, _)| *offset >= start && *offset < start + 8).map(|(_, ty)| *ty).collect::<Vec<_>>();
+            let all_sse = !group.is_empty() && group.iter().all(is_sse);
+            let ty: BasicTypeEnum = if !all_sse {
+                context.custom_width_int_type((len * 8) as u32).into()
+            } else if let [vector @ BasicTypeEnum::VectorType(_)] = group.as_slice() {
+                *vector
+            } else if group.iter().all(|ty| ty.is_float_type() && self.target_data.get_abi_size(ty) == 4) {
+                match group.len() {
+                    1 => context.f32_type().into(),
+                    _ => context.f32_type().vec_type(2).into(),
+                }
+            } else {
+                context.f64_type().into()
+            };
+            types.push(ty);
+        }
+        PassMode::Cast(types)
+    }
+
+    /// AAPCS64：1到4个相同浮点类型的成员组成的同构浮点聚合，以及1到4个8字节或16字节的向量组成的同构短向量聚合使用浮点寄存器，
+    /// 其余不超过16字节的按8字节一组使用通用寄存器，更大的传递指向副本的指针。
+    /// 和clang一样降级为`[N x float]`、`[N x <4 x float>]`或`[N x i64]`数组，寄存器不够时后端把整个数组放到栈上
+    fn classify_aarch64<'ctx>(&self, context: &'ctx Context, size: u64, fields: &[(u64, BasicTypeEnum<'ctx>)]) -> PassMode<'ctx> {
+        let first = fields.first().map(|(_, ty)| *ty);
+        let homogeneous = match first {
+            Some(first) if fields.len() <= 4 => {
+                if first.is_float_type() {
+                    fields.iter().all(|(_, ty)| *ty == first)
+                } else if first.is_vector_type() {
+                    //同样大小的短向量是相同的基本类型，不区分通道的类型
+                    let vector_size = self.target_data.get_abi_size(&first);
+                    matches!(vector_size, 8 | 16) && fields.iter().all(|(_, ty)| ty.is_vector_type() && self.target_data.get_abi_size(ty) == vector_size)
+                } else {
+                    false
+                }
+            }
+            _ => false,
+        };
+        let (element, count): (BasicTypeEnum<'ctx>, u64) = if homogeneous {
+            (first.unwrap(), fields.len() as u64)
+        } else if size > MAX_REGISTER_AGGREGATE_SIZE {
+            return PassMode::Indirect;
+        } else {
+            (context.i64_type().into(), (size + 7) / 8)
+        };
+        if count == 1 {
+            PassMode::Cast(vec![element])
+        } else {
+            PassMode::Cast(vec![element.array_type(count as u32).into()])
+        }
+    }
+
+    /// wasm32：只有一个标量成员的结构体和数组按这个标量传递，其余的参数传递指向副本的指针，返回值通过sret返回
+    fn classify_wasm32<'ctx>(&self, size: u64, fields: &[(u64, BasicTypeEnum<'ctx>)]) -> PassMode<'ctx> {
+        match fields {
+            [(_, ty)] if self.target_data.get_abi_size(ty) == size => PassMode::Cast(vec![*ty]),
+            _ => PassMode::Indirect,
+        }
+    }
+
+    /// 把结构体和数组展开为标量成员及其字节偏移，向量作为一个成员
+    fn flatten<'ctx>(&self, ty: BasicTypeEnum<'ctx>, offset: u64, fields: &mut Vec<(u64, BasicTypeEnum<'ctx>)>) {
+        match ty {
+            BasicTypeEnum::StructType(struct_type) => {
+                for (index, field) in struct_type.get_field_types().into_iter().enumerate() {
+                    let field_offset = self.target_data.offset_of_element(&struct_type, index as u32).unwrap_or(0);
+                    self.flatten(field, offset + field_offset, fields);
+                }
+            }
+            BasicTypeEnum::ArrayType(array_type) => {
+                let element = array_type.get_element_type();
+                let stride = self.target_data.get_abi_size(&element);
+                for index in 0..array_type.len() as u64 {
+                    self.flatten(element, offset + index * stride, fields);
+                }
+            }
+            _ => fields.push((offset, ty)),
+        }
+    }
+}
+
+fn is_aggregate(ty: BasicTypeEnum) -> bool {
+    ty.is_struct_type() || ty.is_array_type()
+}
+
+/// x86_64上还没有分配的参数寄存器
+struct FreeRegisters {
+    integer: u32,
+    sse: u32,
+}
+
+impl FreeRegisters {
+    /// 剩余的寄存器足够时占用它们并返回true，否则不占用
+    fn take(&mut self, integer: u32, sse: u32) -> bool {
+        if integer > self.integer || sse > self.sse {
+            return false;
+        }
+        self.integer -= integer;
+        self.sse -= sse;
+        true
+    }
+}
+
+/// 标量占用的通用寄存器和SSE寄存器个数
+fn scalar_registers(ty: BasicTypeEnum) -> (u32, u32) {
+    match ty {
+        BasicTypeEnum::FloatType(_) | BasicTypeEnum::VectorType(_) => (0, 1),
+        BasicTypeEnum::IntType(int_type) if int_type.get_bit_width() > 64 => (2, 0),
+        _ => (1, 0),
+    }
+}
@@ -1,28 +1,152 @@
 use inkwell::builder::Builder;
+use inkwell::IntPredicate;
+use inkwell::values::PointerValue;
 
-use crate::code_generator::builder::{LEArrayValue, LEBoolValue, LEPointerValue};
+use crate::code_generator::builder::{build_entry_alloca, LEArrayValue, LEBasicValue, LEBasicValueEnum, LEBoolValue, LEType, LEValue};
 use crate::code_generator::builder::binary_operator_builder::traits::{BasicMathOperateValue, CompareBinaryOperator};
 use crate::code_generator::context::LEContext;
-use crate::code_generator::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ElementOperator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl<'ctx> LEArrayValue<'ctx> {
+    /// 逐个元素做算术运算，生成遍历所有元素的循环，调用前需要保证元素类型支持算术运算
+    fn build_element_wise(self, le_context: &LEContext<'ctx>, llvm_builder: &Builder<'ctx>, rhs: Self, op: ElementOperator) -> Self {
+        let array_type = self.ty.get_llvm_type();
+        if self.ty.len() == 0 {
+            return LEArrayValue { ty: self.ty.clone(), llvm_value: array_type.const_zero() };
+        }
+        let element_type = self.ty.get_element_type();
+        let index_type = le_context.llvm_context.i64_type();
+        //两个操作数存入临时变量，按下标读取元素，结果逐个写入结果的临时变量
+        let lhs_pointer = build_entry_alloca(llvm_builder, array_type);
+        let rhs_pointer = build_entry_alloca(llvm_builder, array_type);
+        let result_pointer = build_entry_alloca(llvm_builder, array_type);
+        llvm_builder.build_store(lhs_pointer, self.llvm_value);
+        llvm_builder.build_store(rhs_pointer, rhs.llvm_value);
+        let preheader = llvm_builder.get_insert_block().unwrap();
+        let function = preheader.get_parent().unwrap();
+        let loop_block = le_context.llvm_context.append_basic_block(function, "element_wise");
+        let end_block = le_context.llvm_context.append_basic_block(function, "element_wise_end");
+        llvm_builder.build_unconditional_branch(loop_block);
+
+        llvm_builder.position_at_end(loop_block);
+        let index = llvm_builder.build_phi(index_type, "");
+        let indices = [index_type.const_zero(), index.as_basic_value().into_int_value()];
+        let element_pointer = |pointer: PointerValue<'ctx>| unsafe { llvm_builder.build_in_bounds_gep(pointer, &indices, "") };
+        let left = LEBasicValueEnum::from_type_and_llvm_value(element_type.clone(), llvm_builder.build_load(element_pointer(lhs_pointer), "")).unwrap();
+        let right = LEBasicValueEnum::from_type_and_llvm_value(element_type, llvm_builder.build_load(element_pointer(rhs_pointer), "")).unwrap();
+        let element = build_element_operate(le_context, llvm_builder, left, right, op);
+        llvm_builder.build_store(element_pointer(result_pointer), element.to_llvm_basic_value_enum());
+        //元素为数组时内层循环会新建基本块，回边从当前插入的基本块出发
+        let next = llvm_builder.build_int_add(indices[1], index_type.const_int(1, false), "");
+        let latch = llvm_builder.get_insert_block().unwrap();
+        index.add_incoming(&[(&index_type.const_zero(), preheader), (&next, latch)]);
+        let finished = llvm_builder.build_int_compare(IntPredicate::EQ, next, index_type.const_int(self.ty.len() as u64, false), "");
+        llvm_builder.build_conditional_branch(finished, end_block, loop_block);
+
+        llvm_builder.position_at_end(end_block);
+        let result = llvm_builder.build_load(result_pointer, "").into_array_value();
+        LEArrayValue { ty: self.ty.clone(), llvm_value: result }
+    }
+}
+
+fn build_element_operate<'ctx>(le_context: &LEContext<'ctx>, llvm_builder: &Builder<'ctx>, lhs: LEBasicValueEnum<'ctx>, rhs: LEBasicValueEnum<'ctx>, op: ElementOperator) -> LEBasicValueEnum<'ctx> {
+    match (lhs, rhs) {
+        (LEBasicValueEnum::Integer(left), LEBasicValueEnum::Integer(right)) => {
+            match op {
+                ElementOperator::Add => left.build_add_unchecked(le_context, llvm_builder, right),
+                ElementOperator::Sub => left.build_sub_unchecked(le_context, llvm_builder, right),
+                ElementOperator::Mul => left.build_mul_unchecked(le_context, llvm_builder, right),
+                ElementOperator::Div => left.build_div_unchecked(le_context, llvm_builder, right),
+            }.to_le_value_enum()
+        }
+        (LEBasicValueEnum::Float(left), LEBasicValueEnum::Float(right)) => {
+            match op {
+                ElementOperator::Add => left.build_add_unchecked(le_context, llvm_builder, right),
+                ElementOperator::Sub => left.build_sub_unchecked(le_context, llvm_builder, right),
+                ElementOperator::Mul => left.build_mul_unchecked(le_context, llvm_builder, right),
+                ElementOperator::Div => left.build_div_unchecked(le_context, llvm_builder, right),
+            }.to_le_value_enum()
+        }
+        (LEBasicValueEnum::Array(left), LEBasicValueEnum::Array(right)) => {
+            left.build_element_wise(le_context, llvm_builder, right, op).to_le_value_enum()
+        }
+        _ => unreachable!("element type must be checked before element-wise operation")
+    }
+}
+
+/// 判断两个值是否相等，数组、结构体和向量逐个元素比较，调用前需要保证类型支持相等比较
+pub(super) fn build_equal_unchecked<'ctx>(le_context: &LEContext<'ctx>, llvm_builder: &Builder<'ctx>, lhs: LEBasicValueEnum<'ctx>, rhs: LEBasicValueEnum<'ctx>) -> LEBoolValue<'ctx> {
+    match (lhs, rhs) {
+        (LEBasicValueEnum::Integer(left), LEBasicValueEnum::Integer(right)) => {
+            left.build_cmp_unchecked(le_context, llvm_builder, CompareBinaryOperator::Equal, right)
+        }
+        (LEBasicValueEnum::Float(left), LEBasicValueEnum::Float(right)) => {
+            left.build_cmp_unchecked(le_context, llvm_builder, CompareBinaryOperator::Equal, right)
+        }
+        (LEBasicValueEnum::Bool(left), LEBasicValueEnum::Bool(right)) => {
+            LEBoolValue { ty: le_context.bool_type(), llvm_value: llvm_builder.build_int_compare(IntPredicate::EQ, left.llvm_value, right.llvm_value, "") }
+        }
+        (LEBasicValueEnum::Array(left), LEBasicValueEnum::Array(right)) => {
+            left.build_cmp_unchecked(le_context, llvm_builder, CompareBinaryOperator::Equal, right)
+        }
+        (LEBasicValueEnum::Struct(left), LEBasicValueEnum::Struct(right)) => {
+            left.build_cmp_unchecked(le_context, llvm_builder, CompareBinaryOperator::Equal, right)
+        }
+        (LEBasicValueEnum::Vector(left), LEBasicValueEnum::Vector(right)) => {
+            left.build_cmp_unchecked(le_context, llvm_builder, CompareBinaryOperator::Equal, right)
+        }
+        _ => unreachable!("type must be checked before equality comparison")
+    }
+}
+
+/// 把逐个元素比较的结果合并，`!=`取合并结果的反
+pub(super) fn build_all_equal<'ctx>(le_context: &LEContext<'ctx>, llvm_builder: &Builder<'ctx>, element_results: Vec<LEBoolValue<'ctx>>, op: CompareBinaryOperator) -> LEBoolValue<'ctx> {
+    let bool_type = le_context.bool_type();
+    let all_equal = element_results
+        .into_iter()
+        .fold(bool_type.const_true_value().llvm_value, |all, element| llvm_builder.build_and(all, element.llvm_value, ""));
+    let result = if let CompareBinaryOperator::NotEqual = op {
+        llvm_builder.build_not(all_equal, "")
+    } else {
+        all_equal
+    };
+    LEBoolValue { ty: bool_type, llvm_value: result }
+}
 
 impl<'ctx> BasicMathOperateValue<'ctx> for LEArrayValue<'ctx> {
     fn build_add_unchecked(self, le_context: &LEContext<'ctx>, llvm_builder: &Builder<'ctx>, rhs: Self) -> Self {
-        todo!()
+        self.build_element_wise(le_context, llvm_builder, rhs, ElementOperator::Add)
     }
 
     fn build_sub_unchecked(self, le_context: &LEContext<'ctx>, llvm_builder: &Builder<'ctx>, rhs: Self) -> Self {
-        todo!()
+        self.build_element_wise(le_context, llvm_builder, rhs, ElementOperator::Sub)
     }
 
     fn build_mul_unchecked(self, le_context: &LEContext<'ctx>, llvm_builder: &Builder<'ctx>, rhs: Self) -> Self {
-        todo!()
+        self.build_element_wise(le_context, llvm_builder, rhs, ElementOperator::Mul)
     }
 
     fn build_div_unchecked(self, le_context: &LEContext<'ctx>, llvm_builder: &Builder<'ctx>, rhs: Self) -> Self {
-        todo!()
+        self.build_element_wise(le_context, llvm_builder, rhs, ElementOperator::Div)
     }
 
+    /// 数组只支持`==`和`!=`
     fn build_cmp_unchecked(self, le_context: &LEContext<'ctx>, llvm_builder: &Builder<'ctx>, op: CompareBinaryOperator, rhs: Self) -> LEBoolValue<'ctx> {
-        todo!()
+        let element_type = self.ty.get_element_type();
+        let element_results = (0..self.ty.len()).map(|index| {
+            let left = llvm_builder.build_extract_value(self.llvm_value, index, "").unwrap();
+            let right = llvm_builder.build_extract_value(rhs.llvm_value, index, "").unwrap();
+            let left = LEBasicValueEnum::from_type_and_llvm_value(element_type.clone(), left).unwrap();
+            let right = LEBasicValueEnum::from_type_and_llvm_value(element_type.clone(), right).unwrap();
+            build_equal_unchecked(le_context, llvm_builder, left, right)
+        }).collect();
+        build_all_equal(le_context, llvm_builder, element_results, op)
     }
-}
\ No newline at end of file
+}
@@ -2,6 +2,7 @@ use inkwell::builder::Builder;
 
 use crate::code_generator::builder::{LEBasicType, LEBasicTypeEnum, LEBasicValue, LEBasicValueEnum, LEBoolValue, LEFloatType, LEFloatValue, LEIntegerType, LEIntegerValue, LEType, LEValue};
 use crate::code_generator::builder::binary_operator_builder::{LogicBinaryOperator, ModOperateValue};
+use crate::code_generator::builder::binary_operator_builder::array_builder::{build_all_equal, build_equal_unchecked};
 use crate::code_generator::builder::binary_operator_builder::traits::{BasicMathOperateValue, CompareBinaryOperator};
 use crate::code_generator::context::LEContext;
 use crate::code_generator::Result;
@@ -68,6 +69,12 @@ impl<'a, 'ctx> MathOperateBuilder<'a, 'ctx> {
                 (LEBasicValueEnum::Float(left), LEBasicValueEnum::Float(right)) => {
                     Ok(left.build_add_unchecked(le_context, self.llvm_builder, right).to_le_value_enum())
                 }
+                (LEBasicValueEnum::Array(left), LEBasicValueEnum::Array(right)) if is_arithmetic_type(&left_type) => {
+                    Ok(left.build_add_unchecked(le_context, self.llvm_builder, right).to_le_value_enum())
+                }
+                (LEBasicValueEnum::Vector(left), LEBasicValueEnum::Vector(right)) if is_arithmetic_type(&left_type) => {
+                    Ok(left.build_add_unchecked(le_context, self.llvm_builder, right).to_le_value_enum())
+                }
                 _ => {
                     Err(CompileError::NoSuitableBinaryOperator {
                         op: Operator::Plus,
@@ -96,6 +103,12 @@ impl<'a, 'ctx> MathOperateBuilder<'a, 'ctx> {
                 (LEBasicValueEnum::Float(left), LEBasicValueEnum::Float(right)) => {
                     Ok(left.build_sub_unchecked(le_context, self.llvm_builder, right).to_le_value_enum())
                 }
+                (LEBasicValueEnum::Array(left), LEBasicValueEnum::Array(right)) if is_arithmetic_type(&left_type) => {
+                    Ok(left.build_sub_unchecked(le_context, self.llvm_builder, right).to_le_value_enum())
+                }
+                (LEBasicValueEnum::Vector(left), LEBasicValueEnum::Vector(right)) if is_arithmetic_type(&left_type) => {
+                    Ok(left.build_sub_unchecked(le_context, self.llvm_builder, right).to_le_value_enum())
+                }
                 _ => {
                     Err(CompileError::NoSuitableBinaryOperator {
                         op: Operator::Plus,
@@ -123,6 +136,12 @@ impl<'a, 'ctx> MathOperateBuilder<'a, 'ctx> {
                 (LEBasicValueEnum::Float(left), LEBasicValueEnum::Float(right)) => {
                     Ok(left.build_mul_unchecked(le_context, self.llvm_builder, right).to_le_value_enum())
                 }
+                (LEBasicValueEnum::Array(left), LEBasicValueEnum::Array(right)) if is_arithmetic_type(&left_type) => {
+                    Ok(left.build_mul_unchecked(le_context, self.llvm_builder, right).to_le_value_enum())
+                }
+                (LEBasicValueEnum::Vector(left), LEBasicValueEnum::Vector(right)) if is_arithmetic_type(&left_type) => {
+                    Ok(left.build_mul_unchecked(le_context, self.llvm_builder, right).to_le_value_enum())
+                }
                 _ => {
                     Err(CompileError::NoSuitableBinaryOperator {
                         op: Operator::Plus,
@@ -150,6 +169,12 @@ impl<'a, 'ctx> MathOperateBuilder<'a, 'ctx> {
                 (LEBasicValueEnum::Float(left), LEBasicValueEnum::Float(right)) => {
                     Ok(left.build_div_unchecked(le_context, self.llvm_builder, right).to_le_value_enum())
                 }
+                (LEBasicValueEnum::Array(left), LEBasicValueEnum::Array(right)) if is_arithmetic_type(&left_type) => {
+                    Ok(left.build_div_unchecked(le_context, self.llvm_builder, right).to_le_value_enum())
+                }
+                (LEBasicValueEnum::Vector(left), LEBasicValueEnum::Vector(right)) if is_arithmetic_type(&left_type) => {
+                    Ok(left.build_div_unchecked(le_context, self.llvm_builder, right).to_le_value_enum())
+                }
                 _ => {
                     Err(CompileError::NoSuitableBinaryOperator {
                         op: Operator::Plus,
@@ -177,6 +202,11 @@ impl<'a, 'ctx> MathOperateBuilder<'a, 'ctx> {
                 (LEBasicValueEnum::Float(left), LEBasicValueEnum::Float(right)) => {
                     Ok(left.build_cmp_unchecked(le_context, self.llvm_builder, op, right))
                 }
+                //布尔值、数组和结构体只能比较是否相等
+                (left, right) if is_equality_operator(op) && is_equality_comparable(&left_type) => {
+                    let equal = build_equal_unchecked(le_context, self.llvm_builder, left, right);
+                    Ok(build_all_equal(le_context, self.llvm_builder, vec![equal], op))
+                }
                 _ => {
                     Err(CompileError::NoSuitableBinaryOperator {
                         op: Operator::Plus,
@@ -243,4 +273,29 @@ impl<'a, 'ctx> MathOperateBuilder<'a, 'ctx> {
         };
         LEBoolValue { ty: lhs.ty.clone(), llvm_value: result }
     }
-}
\ No newline at end of file
+}
+
+///整数、浮点数以及元素支持算术运算的数组和向量可以做`+ - * /`
+fn is_arithmetic_type(ty: &LEBasicTypeEnum) -> bool {
+    match ty {
+        LEBasicTypeEnum::Integer(_) | LEBasicTypeEnum::Float(_) => true,
+        LEBasicTypeEnum::Array(array) => is_arithmetic_type(&array.get_element_type()),
+        LEBasicTypeEnum::Vector(vector) => is_arithmetic_type(&vector.get_element_type()),
+        _ => false
+    }
+}
+
+///数组逐个元素、结构体逐个成员、向量逐个通道比较，指针不支持
+fn is_equality_comparable(ty: &LEBasicTypeEnum) -> bool {
+    match ty {
+        LEBasicTypeEnum::Integer(_) | LEBasicTypeEnum::Float(_) | LEBasicTypeEnum::Bool(_) => true,
+        LEBasicTypeEnum::Vector(vector) => is_equality_comparable(&vector.get_element_type()),
+        LEBasicTypeEnum::Array(array) => is_equality_comparable(&array.get_element_type()),
+        LEBasicTypeEnum::Struct(structure) => structure.get_member_types().iter().all(is_equality_comparable),
+        _ => false
+    }
+}
+
+fn is_equality_operator(op: CompareBinaryOperator) -> bool {
+    matches!(op, CompareBinaryOperator::Equal | CompareBinaryOperator::NotEqual)
+}
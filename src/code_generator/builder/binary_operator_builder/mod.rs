@@ -5,5 +5,6 @@ mod number_builder;
 mod generic_builder;
 mod struct_builder;
 mod array_builder;
+mod vector_builder;
 mod traits;
 mod pointer_builder;
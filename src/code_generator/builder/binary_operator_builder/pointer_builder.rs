@@ -9,6 +9,14 @@ use crate::error::CompileError;
 impl<'ctx> MemberAccessOperateValue<'ctx> for LEPointerValue<'ctx> {
     fn build_dot_unchecked(&self, le_context: &LEContext<'ctx>, llvm_builder: &Builder<'ctx>, member_name: &str) -> Result<LEPointerValue<'ctx>> {
         let pointed_type = self.ty.get_point_type();
+        //对引用类型的变量自动解引用一次
+        if let LEBasicTypeEnum::Pointer(reference_type) = &pointed_type {
+            if reference_type.get_point_type().is_struct_type() {
+                let reference = llvm_builder.build_load(self.llvm_value, "").into_pointer_value();
+                let reference_value = LEPointerValue { ty: reference_type.clone(), llvm_value: reference };
+                return reference_value.build_dot_unchecked(le_context, llvm_builder, member_name);
+            }
+        }
         if let LEBasicTypeEnum::Struct(struct_type) = pointed_type {
             let (offset, member_type) = struct_type.get_member_offset_and_type(member_name)
                 .ok_or_else(|| CompileError::NoSuchMember { member_name: member_name.into() })?;
@@ -1,29 +1,39 @@
 use inkwell::builder::Builder;
 
-use crate::code_generator::builder::{LEBoolValue, LEPointerValue, LEStructValue};
+use crate::code_generator::builder::{LEBasicValueEnum, LEBoolValue, LEPointerValue, LEStructValue, LEValue};
+use crate::code_generator::builder::binary_operator_builder::array_builder::{build_all_equal, build_equal_unchecked};
 use crate::code_generator::builder::binary_operator_builder::MemberAccessOperateValue;
 use crate::code_generator::builder::binary_operator_builder::traits::{BasicMathOperateValue, CompareBinaryOperator};
 use crate::code_generator::context::LEContext;
 use crate::code_generator::Result;
 
 impl<'ctx> BasicMathOperateValue<'ctx> for LEStructValue<'ctx> {
+    //结构体的算术运算由用户定义的运算符函数实现，不会走到这里
     fn build_add_unchecked(self, le_context: &LEContext<'ctx>, llvm_builder: &Builder<'ctx>, rhs: Self) -> Self {
-        todo!()
+        unreachable!("struct arithmetic is lowered to operator functions")
     }
 
     fn build_sub_unchecked(self, le_context: &LEContext<'ctx>, llvm_builder: &Builder<'ctx>, rhs: Self) -> Self {
-        todo!()
+        unreachable!("struct arithmetic is lowered to operator functions")
     }
 
     fn build_mul_unchecked(self, le_context: &LEContext<'ctx>, llvm_builder: &Builder<'ctx>, rhs: Self) -> Self {
-        todo!()
+        unreachable!("struct arithmetic is lowered to operator functions")
     }
 
     fn build_div_unchecked(self, le_context: &LEContext<'ctx>, llvm_builder: &Builder<'ctx>, rhs: Self) -> Self {
-        todo!()
+        unreachable!("struct arithmetic is lowered to operator functions")
     }
 
+    /// 结构体只支持`==`和`!=`，按成员逐个比较
     fn build_cmp_unchecked(self, le_context: &LEContext<'ctx>, llvm_builder: &Builder<'ctx>, op: CompareBinaryOperator, rhs: Self) -> LEBoolValue<'ctx> {
-        todo!()
+        let element_results = self.ty.get_member_types().into_iter().enumerate().map(|(index, member_type)| {
+            let left = llvm_builder.build_extract_value(self.llvm_value, index as u32, "").unwrap();
+            let right = llvm_builder.build_extract_value(rhs.llvm_value, index as u32, "").unwrap();
+            let left = LEBasicValueEnum::from_type_and_llvm_value(member_type.clone(), left).unwrap();
+            let right = LEBasicValueEnum::from_type_and_llvm_value(member_type, right).unwrap();
+            build_equal_unchecked(le_context, llvm_builder, left, right)
+        }).collect();
+        build_all_equal(le_context, llvm_builder, element_results, op)
     }
 }
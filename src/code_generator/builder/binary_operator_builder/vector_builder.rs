@@ -0,0 +1,74 @@
+use inkwell::builder::Builder;
+use inkwell::{FloatPredicate, IntPredicate};
+
+use crate::code_generator::builder::{LEBasicTypeEnum, LEBoolValue, LEType, LEVectorValue};
+use crate::code_generator::builder::binary_operator_builder::array_builder::build_all_equal;
+use crate::code_generator::builder::binary_operator_builder::traits::{BasicMathOperateValue, CompareBinaryOperator};
+use crate::code_generator::context::LEContext;
+
+impl<'ctx> LEVectorValue<'ctx> {
+    fn is_signed_integer(&self) -> bool {
+        matches!(self.ty.get_element_type(), LEBasicTypeEnum::Integer(t) if t.signed())
+    }
+
+    fn is_float(&self) -> bool {
+        matches!(self.ty.get_element_type(), LEBasicTypeEnum::Float(_))
+    }
+}
+
+/// 向量的算术运算直接生成对应的LLVM向量指令，每个通道独立计算，调用前需要保证元素类型支持算术运算
+impl<'ctx> BasicMathOperateValue<'ctx> for LEVectorValue<'ctx> {
+    fn build_add_unchecked(self, le_context: &LEContext<'ctx>, llvm_builder: &Builder<'ctx>, rhs: Self) -> Self {
+        let llvm_value = if self.is_float() {
+            llvm_builder.build_float_add(self.llvm_value, rhs.llvm_value, "")
+        } else {
+            llvm_builder.build_int_add(self.llvm_value, rhs.llvm_value, "")
+        };
+        LEVectorValue { ty: self.ty.clone(), llvm_value }
+    }
+
+    fn build_sub_unchecked(self, le_context: &LEContext<'ctx>, llvm_builder: &Builder<'ctx>, rhs: Self) -> Self {
+        let llvm_value = if self.is_float() {
+            llvm_builder.build_float_sub(self.llvm_value, rhs.llvm_value, "")
+        } else {
+            llvm_builder.build_int_sub(self.llvm_value, rhs.llvm_value, "")
+        };
+        LEVectorValue { ty: self.ty.clone(), llvm_value }
+    }
+
+    fn build_mul_unchecked(self, le_context: &LEContext<'ctx>, llvm_builder: &Builder<'ctx>, rhs: Self) -> Self {
+        let llvm_value = if self.is_float() {
+            llvm_builder.build_float_mul(self.llvm_value, rhs.llvm_value, "")
+        } else {
+            llvm_builder.build_int_mul(self.llvm_value, rhs.llvm_value, "")
+        };
+        LEVectorValue { ty: self.ty.clone(), llvm_value }
+    }
+
+    fn build_div_unchecked(self, le_context: &LEContext<'ctx>, llvm_builder: &Builder<'ctx>, rhs: Self) -> Self {
+        let llvm_value = if self.is_float() {
+            llvm_builder.build_float_div(self.llvm_value, rhs.llvm_value, "")
+        } else if self.is_signed_integer() {
+            llvm_builder.build_int_signed_div(self.llvm_value, rhs.llvm_value, "")
+        } else {
+            llvm_builder.build_int_unsigned_div(self.llvm_value, rhs.llvm_value, "")
+        };
+        LEVectorValue { ty: self.ty.clone(), llvm_value }
+    }
+
+    /// 向量只支持`==`和`!=`，所有通道都相等时才相等
+    fn build_cmp_unchecked(self, le_context: &LEContext<'ctx>, llvm_builder: &Builder<'ctx>, op: CompareBinaryOperator, rhs: Self) -> LEBoolValue<'ctx> {
+        let lane_results = if self.is_float() {
+            llvm_builder.build_float_compare(FloatPredicate::OEQ, self.llvm_value, rhs.llvm_value, "")
+        } else {
+            llvm_builder.build_int_compare(IntPredicate::EQ, self.llvm_value, rhs.llvm_value, "")
+        };
+        let bool_type = le_context.bool_type();
+        let element_results = (0..self.ty.len()).map(|index| {
+            let index = le_context.i32_type().get_llvm_type().const_int(index as u64, false);
+            let lane = llvm_builder.build_extract_element(lane_results, index, "").into_int_value();
+            LEBoolValue { ty: bool_type.clone(), llvm_value: lane }
+        }).collect();
+        build_all_equal(le_context, llvm_builder, element_results, op)
+    }
+}
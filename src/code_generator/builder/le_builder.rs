@@ -1,14 +1,15 @@
 use inkwell::AddressSpace;
 use inkwell::builder::Builder;
 use inkwell::context::Context;
-use inkwell::module::Module;
-use inkwell::values::{BasicMetadataValueEnum, BasicValueEnum, FunctionValue, InstructionValue};
+use inkwell::module::{Linkage, Module};
+use inkwell::types::{BasicMetadataTypeEnum, BasicType, VectorType};
+use inkwell::values::{BasicMetadataValueEnum, BasicValueEnum, FunctionValue, InstructionValue, IntValue, PointerValue};
 
 use crate::ast::nodes::TypeDeclarator;
-use crate::code_generator::builder::{LEBasicType, LEBasicTypeEnum, LEBasicValue, LEBasicValueEnum, LEBoolType, LEBoolValue, LEFloatType, LEFloatValue, LEFunctionValue, LEIntegerType, LEIntegerValue, LEPointerType, LEPointerValue, LEType, LEValue};
+use crate::code_generator::builder::{LEArrayValue, LEBasicType, LEBasicTypeEnum, LEBasicValue, LEBasicValueEnum, LEBoolType, LEBoolValue, LEFloatType, LEFloatValue, LEFunctionValue, LEIntegerType, LEIntegerValue, LEPointerType, LEPointerValue, LEType, LEValue, LEVectorValue};
 use crate::code_generator::builder::binary_operator_builder::{CompareBinaryOperator, LogicBinaryOperator, MathOperateBuilder, MemberAccessOperateValue, ModOperateValue};
 use crate::code_generator::builder::expression::Expression;
-use crate::code_generator::context::LEContext;
+use crate::code_generator::context::{get_vector_type, LEContext};
 use crate::error::CompileError;
 use crate::lexer::{Operator, Position};
 
@@ -130,6 +131,12 @@ impl<'ctx> LEBuilder<'ctx> {
             }
             args.push(BasicMetadataValueEnum::from(argument_value.to_llvm_basic_value_enum()));
         }
+        if function.ty.is_variadic() {
+            for argument in params.iter().skip(function.ty.param_types().len()) {
+                let argument_value = self.read_expression(le_context, argument.clone())?;
+                args.push(BasicMetadataValueEnum::from(self.build_default_argument_promotion(le_context, argument_value)?));
+            }
+        }
         let site_value = self.llvm_builder.build_call(function.llvm_value, &args, "");
         if let Some(v) = site_value.try_as_basic_value().left() {
             Ok(Expression::Right(LEBasicValueEnum::from_type_and_llvm_value(function.ty.return_type().unwrap(), v)?))
@@ -138,6 +145,31 @@ impl<'ctx> LEBuilder<'ctx> {
         }
     }
 
+    /// 可变参数部分的实参按C的默认实参提升传递：`f32`提升为`f64`，不足32位的整数按自身的符号扩展为32位，
+    /// `bool`零扩展为32位；结构体、数组和向量没有对应的C可变参数传递方式
+    fn build_default_argument_promotion(&self, le_context: &LEContext<'ctx>, value: LEBasicValueEnum<'ctx>) -> Result<BasicValueEnum<'ctx>> {
+        let i32_type = le_context.llvm_context.i32_type();
+        match value {
+            LEBasicValueEnum::Float(float) if !float.ty.is_double() => {
+                Ok(self.llvm_builder.build_float_ext(float.llvm_value, le_context.llvm_context.f64_type(), "").into())
+            }
+            LEBasicValueEnum::Integer(integer) if integer.ty.get_llvm_type().get_bit_width() < 32 => {
+                if integer.ty.signed() {
+                    Ok(self.llvm_builder.build_int_s_extend(integer.llvm_value, i32_type, "").into())
+                } else {
+                    Ok(self.llvm_builder.build_int_z_extend(integer.llvm_value, i32_type, "").into())
+                }
+            }
+            LEBasicValueEnum::Bool(boolean) => {
+                Ok(self.llvm_builder.build_int_z_extend(boolean.llvm_value, i32_type, "").into())
+            }
+            LEBasicValueEnum::Struct(_) | LEBasicValueEnum::Array(_) | LEBasicValueEnum::Vector(_) => {
+                Err(CompileError::TypeMismatched { expect: "scalar or pointer".into(), found: LEBasicValue::get_le_type(&value).to_string() })
+            }
+            _ => Ok(value.to_llvm_basic_value_enum()),
+        }
+    }
+
     pub fn build_alloca(&self, le_context: &LEContext<'ctx>, ty: LEBasicTypeEnum<'ctx>) -> LEPointerValue<'ctx> {
         let llvm_pointer_value = self.llvm_builder.build_alloca(ty.get_llvm_type(), "");
         let pointer_type = LEPointerType::new(le_context, ty);
@@ -165,19 +197,13 @@ impl<'ctx> LEBuilder<'ctx> {
         LEPointerValue::from_type_and_llvm_value(ty, BasicValueEnum::PointerValue(global_ptr)).unwrap()
     }
 
-    pub fn build_global_alloca_with_initial_value(&self, value: LEBasicValueEnum<'ctx>, module: &Module<'ctx>, address_space: Option<AddressSpace>) -> LEPointerValue<'ctx> {
+    /// 定义全局变量，初始值必须是常量
+    pub fn build_global_alloca_with_initial_value(&self, value: LEBasicValueEnum<'ctx>, module: &Module<'ctx>, name: &str, linkage: Linkage) -> LEPointerValue<'ctx> {
         let target_type = LEBasicValue::get_le_type(&value);
-        let global_ptr = match target_type {
-            LEBasicTypeEnum::Integer(ref t) => { module.add_global(t.get_llvm_type(), address_space, "") }
-            LEBasicTypeEnum::Bool(ref t) => { module.add_global(t.get_llvm_type(), address_space, "") }
-            LEBasicTypeEnum::Float(ref t) => { module.add_global(t.get_llvm_type(), address_space, "") }
-            LEBasicTypeEnum::Pointer(ref t) => { module.add_global(t.get_llvm_type(), address_space, "") }
-            LEBasicTypeEnum::Array(ref t) => { module.add_global(t.get_llvm_type(), address_space, "") }
-            LEBasicTypeEnum::Struct(ref t) => { module.add_global(t.get_llvm_type(), address_space, "") }
-            LEBasicTypeEnum::Vector(ref t) => { module.add_global(t.get_llvm_type(), address_space, "") }
-        }.as_pointer_value();
-        self.llvm_builder.build_store(global_ptr, value.get_llvm_value());
-        LEPointerValue::from_type_and_llvm_value(target_type, BasicValueEnum::PointerValue(global_ptr)).unwrap()
+        let global = module.add_global(target_type.get_llvm_basic_type(), None, name);
+        global.set_initializer(&value.to_llvm_basic_value_enum());
+        global.set_linkage(linkage);
+        LEPointerValue::from_type_and_llvm_value(target_type, BasicValueEnum::PointerValue(global.as_pointer_value())).unwrap()
     }
 
 
@@ -212,7 +238,199 @@ impl<'ctx> LEBuilder<'ctx> {
                 let result = self.llvm_builder.build_float_neg(f.llvm_value, "");
                 Ok(LEFloatValue { ty: f.ty, llvm_value: result }.to_le_value_enum())
             }
+            LEBasicValueEnum::Vector(v) => {
+                let result = match v.ty.get_element_type() {
+                    LEBasicTypeEnum::Integer(_) => self.llvm_builder.build_int_neg(v.llvm_value, ""),
+                    LEBasicTypeEnum::Float(_) => self.llvm_builder.build_float_neg(v.llvm_value, ""),
+                    _ => return Err(CompileError::NoSuitableUnaryOperator { op: Operator::Sub, target_type: v.ty.to_string() }),
+                };
+                Ok(LEVectorValue { ty: v.ty, llvm_value: result }.to_le_value_enum())
+            }
             _ => { unimplemented!() }
         }
     }
+
+    fn lane_index(&self, le_context: &LEContext<'ctx>, index: u32) -> IntValue<'ctx> {
+        le_context.i32_type().get_llvm_type().const_int(index as u64, false)
+    }
+
+    /// 用数组的元素依次填充向量的每个通道
+    pub fn build_vector_from_array(&self, le_context: &LEContext<'ctx>, array: LEArrayValue<'ctx>) -> Result<LEVectorValue<'ctx>> {
+        let vector_type = get_vector_type(array.ty.get_element_type(), array.ty.len())?.into_vector_type().unwrap();
+        let mut result = vector_type.get_llvm_type().get_undef();
+        for index in 0..array.ty.len() {
+            let element = self.llvm_builder.build_extract_value(array.llvm_value, index, "").unwrap();
+            result = self.llvm_builder.build_insert_element(result, element, self.lane_index(le_context, index), "");
+        }
+        Ok(LEVectorValue { ty: vector_type, llvm_value: result })
+    }
+
+    /// 把同一个值广播到向量的所有通道
+    pub fn build_vector_splat(&self, le_context: &LEContext<'ctx>, value: LEBasicValueEnum<'ctx>, lanes: u32) -> Result<LEVectorValue<'ctx>> {
+        let vector_type = get_vector_type(LEBasicValue::get_le_type(&value), lanes)?.into_vector_type().unwrap();
+        let single = self.llvm_builder.build_insert_element(vector_type.get_llvm_type().get_undef(), value.to_llvm_basic_value_enum(), self.lane_index(le_context, 0), "");
+        let mask = VectorType::const_vector(&vec![self.lane_index(le_context, 0); lanes as usize]);
+        let llvm_value = self.llvm_builder.build_shuffle_vector(single, vector_type.get_llvm_type().get_undef(), mask, "");
+        Ok(LEVectorValue { ty: vector_type, llvm_value })
+    }
+
+    /// 读取一个通道，通道序号可以是运行时的值
+    pub fn build_vector_extract(&self, le_context: &LEContext<'ctx>, vector: LEVectorValue<'ctx>, index: LEIntegerValue<'ctx>) -> Result<LEBasicValueEnum<'ctx>> {
+        let element = self.llvm_builder.build_extract_element(vector.llvm_value, index.llvm_value, "");
+        LEBasicValueEnum::from_type_and_llvm_value(vector.ty.get_element_type(), element)
+    }
+
+    /// 替换一个通道，返回新的向量
+    pub fn build_vector_insert(&self, le_context: &LEContext<'ctx>, vector: LEVectorValue<'ctx>, index: LEIntegerValue<'ctx>, value: LEBasicValueEnum<'ctx>) -> Result<LEVectorValue<'ctx>> {
+        let element_type = vector.ty.get_element_type();
+        let value_type = LEBasicValue::get_le_type(&value);
+        if element_type != value_type {
+            return Err(CompileError::TypeMismatched { expect: element_type.to_string(), found: value_type.to_string() });
+        }
+        let llvm_value = self.llvm_builder.build_insert_element(vector.llvm_value, value.to_llvm_basic_value_enum(), index.llvm_value, "");
+        Ok(LEVectorValue { ty: vector.ty, llvm_value })
+    }
+
+    /// 从两个向量中按掩码挑选通道组成新向量，掩码中小于通道数的序号取自左边，其余取自右边
+    pub fn build_vector_shuffle(&self, le_context: &LEContext<'ctx>, left: LEVectorValue<'ctx>, right: LEVectorValue<'ctx>, mask: &[u32]) -> Result<LEVectorValue<'ctx>> {
+        if left.ty != right.ty {
+            return Err(CompileError::TypeMismatched { expect: left.ty.to_string(), found: right.ty.to_string() });
+        }
+        let lanes = left.ty.len().saturating_mul(2);
+        if let Some(index) = mask.iter().find(|index| **index >= lanes) {
+            return Err(CompileError::VectorLaneOutOfRange { index: *index as u64, lanes });
+        }
+        let vector_type = get_vector_type(left.ty.get_element_type(), mask.len() as u32)?.into_vector_type().unwrap();
+        let mask = mask.iter().map(|index| self.lane_index(le_context, *index)).collect::<Vec<_>>();
+        let llvm_value = self.llvm_builder.build_shuffle_vector(left.llvm_value, right.llvm_value, VectorType::const_vector(&mask), "");
+        Ok(LEVectorValue { ty: vector_type, llvm_value })
+    }
+
+    /// 把向量的所有通道归约为一个值，使用LLVM的`llvm.vector.reduce.*`内建函数
+    pub fn build_vector_reduce(&self, le_context: &LEContext<'ctx>, module: &Module<'ctx>, vector: LEVectorValue<'ctx>, op: VectorReduceOperator) -> Result<LEBasicValueEnum<'ctx>> {
+        let element_type = vector.ty.get_element_type();
+        let (intrinsic, suffix, start) = match &element_type {
+            LEBasicTypeEnum::Integer(t) => {
+                let intrinsic = match op {
+                    VectorReduceOperator::Add => "add",
+                    VectorReduceOperator::Mul => "mul",
+                    VectorReduceOperator::Min => if t.signed() { "smin" } else { "umin" },
+                    VectorReduceOperator::Max => if t.signed() { "smax" } else { "umax" },
+                };
+                (intrinsic, format!("i{}", t.get_llvm_type().get_bit_width()), None)
+            }
+            LEBasicTypeEnum::Float(t) => {
+                //浮点数的加法和乘法归约需要一个初始值
+                let (intrinsic, start) = match op {
+                    VectorReduceOperator::Add => ("fadd", Some(t.get_llvm_type().const_float(0.0))),
+                    VectorReduceOperator::Mul => ("fmul", Some(t.get_llvm_type().const_float(1.0))),
+                    VectorReduceOperator::Min => ("fmin", None),
+                    VectorReduceOperator::Max => ("fmax", None),
+                };
+                (intrinsic, t.to_string(), start)
+            }
+            _ => {
+                return Err(CompileError::NoSuitableUnaryOperator { op: Operator::Plus, target_type: vector.ty.to_string() });
+            }
+        };
+        let name = format!("llvm.vector.reduce.{}.v{}{}", intrinsic, vector.ty.len(), suffix);
+        let element_llvm_type = element_type.get_llvm_basic_type();
+        let function = module.get_function(&name).unwrap_or_else(|| {
+            let param_types: Vec<BasicMetadataTypeEnum> = match start {
+                Some(_) => vec![element_llvm_type.into(), vector.ty.get_llvm_basic_type().into()],
+                None => vec![vector.ty.get_llvm_basic_type().into()],
+            };
+            module.add_function(&name, element_llvm_type.fn_type(&param_types, false), None)
+        });
+        let args: Vec<BasicMetadataValueEnum> = match start {
+            Some(start) => vec![start.into(), vector.llvm_value.into()],
+            None => vec![vector.llvm_value.into()],
+        };
+        let result = self.llvm_builder.build_call(function, &args, "").try_as_basic_value().left().unwrap();
+        LEBasicValueEnum::from_type_and_llvm_value(element_type, result)
+    }
+
+    /// 指针向后偏移`offset`个元素，偏移量按自身的符号扩展为64位
+    pub fn build_pointer_offset(&self, le_context: &LEContext<'ctx>, pointer: LEPointerValue<'ctx>, offset: LEIntegerValue<'ctx>) -> LEPointerValue<'ctx> {
+        let i64_type = le_context.llvm_context.i64_type();
+        let offset_value = if offset.ty.get_llvm_type().get_bit_width() >= 64 {
+            offset.llvm_value
+        } else if offset.ty.signed() {
+            self.llvm_builder.build_int_s_extend(offset.llvm_value, i64_type, "")
+        } else {
+            self.llvm_builder.build_int_z_extend(offset.llvm_value, i64_type, "")
+        };
+        let llvm_value = unsafe { self.llvm_builder.build_gep(pointer.llvm_value, &[offset_value], "") };
+        LEPointerValue { ty: pointer.ty, llvm_value }
+    }
+
+    /// 把指针转换为指向另一种类型的指针
+    pub fn build_pointer_cast(&self, le_context: &LEContext<'ctx>, pointer: LEPointerValue<'ctx>, point_type: LEBasicTypeEnum<'ctx>) -> LEPointerValue<'ctx> {
+        let pointer_type = LEPointerType::new(le_context, point_type);
+        let llvm_value = self.llvm_builder.build_pointer_cast(pointer.llvm_value, pointer_type.get_llvm_type(), "");
+        LEPointerValue { ty: pointer_type, llvm_value }
+    }
+
+    /// 调用LLVM内建函数，`overloaded_types`是参与名字修饰的类型，如`llvm.sqrt.f64`中的f64
+    pub fn build_intrinsic_call(
+        &self,
+        module: &Module<'ctx>,
+        intrinsic: &str,
+        overloaded_types: &[LEBasicTypeEnum<'ctx>],
+        args: &[LEBasicValueEnum<'ctx>],
+        return_type: Option<LEBasicTypeEnum<'ctx>>,
+    ) -> Result<Option<LEBasicValueEnum<'ctx>>> {
+        let suffixes = overloaded_types
+            .iter()
+            .map(|ty| intrinsic_type_suffix(ty).ok_or_else(|| CompileError::InvalidIntrinsicArgument { intrinsic: format!("llvm.{}", intrinsic), ty: ty.to_string() }))
+            .collect::<Result<Vec<_>>>()?;
+        let name = std::iter::once(format!("llvm.{}", intrinsic)).chain(suffixes.into_iter()).collect::<Vec<_>>().join(".");
+        let function = module.get_function(&name).unwrap_or_else(|| {
+            let param_types = args.iter().map(|arg| BasicMetadataTypeEnum::from(LEBasicValue::get_le_type(arg).get_llvm_basic_type())).collect::<Vec<_>>();
+            let function_type = match &return_type {
+                Some(ty) => ty.get_llvm_basic_type().fn_type(&param_types, false),
+                None => module.get_context().void_type().fn_type(&param_types, false),
+            };
+            module.add_function(&name, function_type, None)
+        });
+        let args = args.iter().map(|arg| BasicMetadataValueEnum::from(arg.to_llvm_basic_value_enum())).collect::<Vec<_>>();
+        let result = self.llvm_builder.build_call(function, &args, "").try_as_basic_value().left();
+        match (return_type, result) {
+            (Some(ty), Some(result)) => Ok(Some(LEBasicValueEnum::from_type_and_llvm_value(ty, result)?)),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// LLVM内建函数名中类型的写法，只支持数值、向量和数值的指针
+fn intrinsic_type_suffix(ty: &LEBasicTypeEnum) -> Option<String> {
+    match ty {
+        LEBasicTypeEnum::Integer(t) => Some(format!("i{}", t.get_llvm_type().get_bit_width())),
+        LEBasicTypeEnum::Float(t) => Some(t.to_string()),
+        LEBasicTypeEnum::Vector(t) => Some(format!("v{}{}", t.len(), intrinsic_type_suffix(&t.get_element_type())?)),
+        LEBasicTypeEnum::Pointer(t) => Some(format!("p0{}", intrinsic_type_suffix(&t.get_point_type())?)),
+        _ => None
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VectorReduceOperator {
+    Add,
+    Mul,
+    Min,
+    Max,
+}
+
+/// 在函数入口块的开头申请临时变量，运算位于循环中时不会重复申请栈空间
+pub fn build_entry_alloca<'ctx, T: BasicType<'ctx>>(llvm_builder: &Builder<'ctx>, ty: T) -> PointerValue<'ctx> {
+    let current_insert_block = llvm_builder.get_insert_block().unwrap();
+    let entry_block = current_insert_block.get_parent().and_then(|function| function.get_first_basic_block()).unwrap();
+    if let Some(first_instruction) = entry_block.get_first_instruction() {
+        llvm_builder.position_at(entry_block, &first_instruction);
+    } else {
+        llvm_builder.position_at_end(entry_block);
+    }
+    let pointer = llvm_builder.build_alloca(ty, "");
+    llvm_builder.position_at_end(current_insert_block);
+    pointer
 }
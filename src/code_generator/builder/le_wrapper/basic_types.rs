@@ -9,6 +9,7 @@ use inkwell::types::{ArrayType, BasicTypeEnum, FloatType, IntType, PointerType,
 use crate::code_generator::builder::{LEArrayValue, LEBoolValue, LEFloatValue, LEIntegerValue, LEPointerValue, LEStructValue, LEType, LEVectorValue};
 use crate::code_generator::builder::le_wrapper::LEBasicType;
 use crate::code_generator::context::LEContext;
+use crate::code_generator::mangle;
 
 #[derive(Debug, Clone, PartialEq, Eq, Copy)]
 struct LEIntegerTypeInner<'ctx> {
@@ -57,6 +58,7 @@ impl<'ctx> LEBasicType<'ctx> for LEIntegerType<'ctx> {
             inner: Rc::new(LEPointerTypeInner {
                 point_type: self.to_le_type_enum(),
                 llvm_type: pointer_type,
+                is_str: false,
             })
         }
     }
@@ -130,6 +132,7 @@ impl<'ctx> LEBasicType<'ctx> for LEFloatType<'ctx> {
             inner: Rc::new(LEPointerTypeInner {
                 point_type: self.to_le_type_enum(),
                 llvm_type: pointer_type,
+                is_str: false,
             })
         }
     }
@@ -202,6 +205,7 @@ impl<'ctx> LEBasicType<'ctx> for LEBoolType<'ctx> {
             inner: Rc::new(LEPointerTypeInner {
                 point_type: self.to_le_type_enum(),
                 llvm_type: pointer_type,
+                is_str: false,
             })
         }
     }
@@ -234,12 +238,22 @@ impl<'ctx> Display for LEBoolType<'ctx> {
 }
 
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 struct LEPointerTypeInner<'ctx> {
     pub point_type: LEBasicTypeEnum<'ctx>,
     pub llvm_type: PointerType<'ctx>,
+    ///是否为str，str和ref u8可以互相赋值，只在输出和显示时区分，不参与类型比较
+    pub is_str: bool,
 }
 
+impl<'ctx> PartialEq for LEPointerTypeInner<'ctx> {
+    fn eq(&self, other: &Self) -> bool {
+        self.point_type == other.point_type && self.llvm_type == other.llvm_type
+    }
+}
+
+impl<'ctx> Eq for LEPointerTypeInner<'ctx> {}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct LEPointerType<'ctx> {
     inner: Rc<LEPointerTypeInner<'ctx>>,
@@ -282,6 +296,7 @@ impl<'ctx> LEBasicType<'ctx> for LEPointerType<'ctx> {
             inner: Rc::new(LEPointerTypeInner {
                 point_type: self.to_le_type_enum(),
                 llvm_type: pointer_type,
+                is_str: false,
             })
         }
     }
@@ -305,11 +320,21 @@ impl<'ctx> LEPointerType<'ctx> {
             inner: Rc::new(LEPointerTypeInner {
                 point_type,
                 llvm_type,
+                is_str: false,
             })
         }
     }
     pub fn from_llvm_type(llvm_type: PointerType<'ctx>, point_type: LEBasicTypeEnum<'ctx>) -> Self {
-        Self { inner: Rc::new(LEPointerTypeInner { llvm_type, point_type }) }
+        Self { inner: Rc::new(LEPointerTypeInner { llvm_type, point_type, is_str: false }) }
+    }
+
+    /// 内建的str类型，和ref u8的LLVM类型相同
+    pub fn str_type(llvm_type: PointerType<'ctx>, u8_type: LEBasicTypeEnum<'ctx>) -> Self {
+        Self { inner: Rc::new(LEPointerTypeInner { llvm_type, point_type: u8_type, is_str: true }) }
+    }
+
+    pub fn is_str(&self) -> bool {
+        self.inner.is_str
     }
 
     pub fn const_array(&self, values: &[LEPointerValue<'ctx>]) -> LEArrayValue<'ctx> {
@@ -321,6 +346,9 @@ impl<'ctx> LEPointerType<'ctx> {
 
 impl<'ctx> Display for LEPointerType<'ctx> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_str() {
+            return write!(f, "str");
+        }
         let mut pointed_type = self.get_point_type();
         let mut point_counter = 1;
         while let LEBasicTypeEnum::Pointer(pointer) = pointed_type {
@@ -381,6 +409,7 @@ impl<'ctx> LEBasicType<'ctx> for LEArrayType<'ctx> {
             inner: Rc::new(LEPointerTypeInner {
                 point_type: self.to_le_type_enum(),
                 llvm_type: pointer_type,
+                is_str: false,
             })
         }
     }
@@ -397,6 +426,14 @@ impl<'ctx> LEArrayType<'ctx> {
         }
     }
 
+    pub fn get_element_type(&self) -> LEBasicTypeEnum<'ctx> {
+        self.inner.element_type.clone()
+    }
+
+    pub fn len(&self) -> u32 {
+        self.inner.llvm_type.len()
+    }
+
     pub fn const_array(&self, values: &[LEArrayValue<'ctx>]) -> LEArrayValue<'ctx> {
         let llvm_values = values.iter().map(|v| v.llvm_value).collect::<Vec<_>>();
         let array_value = self.get_llvm_type().const_array(&llvm_values);
@@ -412,7 +449,11 @@ impl<'ctx> Display for LEArrayType<'ctx> {
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct LEStructTypeInner<'ctx> {
+    pub name: String,
+    pub mangled_name: String,
     pub llvm_type: StructType<'ctx>,
+    ///按在结构体中的顺序排列的成员名和类型
+    pub members: Vec<(String, LEBasicTypeEnum<'ctx>)>,
     pub member_offset: HashMap<String, (u32, LEBasicTypeEnum<'ctx>)>,
 }
 
@@ -458,6 +499,7 @@ impl<'ctx> LEBasicType<'ctx> for LEStructType<'ctx> {
             inner: Rc::new(LEPointerTypeInner {
                 point_type: self.to_le_type_enum(),
                 llvm_type: pointer_type,
+                is_str: false,
             })
         }
     }
@@ -465,24 +507,47 @@ impl<'ctx> LEBasicType<'ctx> for LEStructType<'ctx> {
 
 impl<'ctx> Display for LEStructType<'ctx> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self.name())
+        f.write_str(&self.inner.name)
     }
 }
 
 
 impl<'ctx> LEStructType<'ctx> {
-    pub fn from_llvm_type(context: &LEContext<'ctx>, names: &[&str], member_types: &[LEBasicTypeEnum<'ctx>]) -> Self {
-        let mut offset = HashMap::default();
-        for (index, (name, ty)) in names.iter().zip(member_types.iter()).enumerate() {
-            offset.entry(name.to_string()).or_insert((index as u32, ty.clone()));
-        }
-        let struct_type = context.llvm_context.opaque_struct_type("struct");
+    pub fn from_llvm_type(context: &LEContext<'ctx>, struct_name: &str, names: &[&str], member_types: &[LEBasicTypeEnum<'ctx>]) -> Self {
+        Self::with_mangled_name(context, struct_name, &mangle::mangle_type_name(struct_name), names, member_types)
+    }
+
+    ///泛型实例和切片的名字不是合法的符号，由调用者给出它在修饰名中的编码
+    pub fn with_mangled_name(context: &LEContext<'ctx>, struct_name: &str, mangled_name: &str, names: &[&str], member_types: &[LEBasicTypeEnum<'ctx>]) -> Self {
+        //成员名在结构体定义处检查过不会重复
+        let members = names.iter().map(|name| name.to_string()).zip(member_types.iter().cloned()).collect::<Vec<_>>();
+        let offset = members.iter().enumerate().map(|(index, (name, ty))| (name.clone(), (index as u32, ty.clone()))).collect();
+        //成员按自然对齐排列，与C结构体的内存布局相同
+        let struct_type = context.llvm_context.opaque_struct_type(struct_name);
         struct_type.set_body(&member_types
             .iter()
             .map(|x| x.get_llvm_basic_type())
-            .collect::<Vec<_>>(), true,
+            .collect::<Vec<_>>(), false,
         );
-        Self { inner: Rc::new(LEStructTypeInner { llvm_type: struct_type, member_offset: offset }) }
+        Self {
+            inner: Rc::new(LEStructTypeInner {
+                name: struct_name.to_string(),
+                mangled_name: mangled_name.to_string(),
+                llvm_type: struct_type,
+                members,
+                member_offset: offset,
+            })
+        }
+    }
+
+    ///结构体的名字，用于查找方法
+    pub fn struct_name(&self) -> &str {
+        &self.inner.name
+    }
+
+    ///结构体类型在修饰名中的编码
+    pub fn mangled_name(&self) -> &str {
+        &self.inner.mangled_name
     }
     pub fn get_member_offset(&self, name: &str) -> Option<u32> {
         let offset = self.inner.member_offset.get(name)?;
@@ -499,6 +564,16 @@ impl<'ctx> LEStructType<'ctx> {
         Some(offset.1.clone())
     }
 
+    ///按成员在结构体中的顺序返回所有成员的类型
+    pub fn get_member_types(&self) -> Vec<LEBasicTypeEnum<'ctx>> {
+        self.inner.members.iter().map(|(_, ty)| ty.clone()).collect()
+    }
+
+    ///按成员在结构体中的顺序返回所有成员的名字
+    pub fn get_member_names(&self) -> Vec<String> {
+        self.inner.members.iter().map(|(name, _)| name.clone()).collect()
+    }
+
     pub fn const_array(&self, values: &[LEStructValue<'ctx>]) -> LEArrayValue<'ctx> {
         let llvm_values = values.iter().map(|v| v.llvm_value).collect::<Vec<_>>();
         let array_value = self.get_llvm_type().const_array(&llvm_values);
@@ -507,8 +582,9 @@ impl<'ctx> LEStructType<'ctx> {
 }
 
 
-#[derive(Debug, Clone, PartialEq, Eq, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct LEVectorTypeInner<'ctx> {
+    pub element_type: LEBasicTypeEnum<'ctx>,
     pub llvm_type: VectorType<'ctx>,
 }
 
@@ -518,6 +594,25 @@ pub struct LEVectorType<'ctx> {
 }
 
 impl<'ctx> LEVectorType<'ctx> {
+    ///向量的元素只能是整数、浮点数或布尔类型
+    pub fn new(element_type: LEBasicTypeEnum<'ctx>, len: u32) -> Option<Self> {
+        let llvm_type = match &element_type {
+            LEBasicTypeEnum::Integer(t) => t.get_llvm_type().vec_type(len),
+            LEBasicTypeEnum::Float(t) => t.get_llvm_type().vec_type(len),
+            LEBasicTypeEnum::Bool(t) => t.get_llvm_type().vec_type(len),
+            _ => return None,
+        };
+        Some(Self { inner: Rc::new(LEVectorTypeInner { element_type, llvm_type }) })
+    }
+
+    pub fn get_element_type(&self) -> LEBasicTypeEnum<'ctx> {
+        self.inner.element_type.clone()
+    }
+
+    pub fn len(&self) -> u32 {
+        self.inner.llvm_type.get_size()
+    }
+
     pub fn const_array(&self, values: &[LEVectorValue<'ctx>]) -> LEArrayValue<'ctx> {
         let llvm_values = values.iter().map(|v| v.llvm_value).collect::<Vec<_>>();
         let array_value = self.get_llvm_type().const_array(&llvm_values);
@@ -561,6 +656,7 @@ impl<'ctx> LEBasicType<'ctx> for LEVectorType<'ctx> {
             inner: Rc::new(LEPointerTypeInner {
                 point_type: self.to_le_type_enum(),
                 llvm_type: pointer_type,
+                is_str: false,
             })
         }
     }
@@ -568,7 +664,7 @@ impl<'ctx> LEBasicType<'ctx> for LEVectorType<'ctx> {
 
 impl<'ctx> Display for LEVectorType<'ctx> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+        write!(f, "vec<{};{}>", self.inner.element_type, self.len())
     }
 }
 
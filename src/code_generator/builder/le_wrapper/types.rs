@@ -23,6 +23,10 @@ impl<'ctx> LEFunctionType<'ctx> {
     pub fn param_types(&self) -> &[LEBasicTypeEnum<'ctx>] {
         &self.inner.param_types
     }
+    ///`decl`声明的C可变参数函数，实参可以多于`param_types`
+    pub fn is_variadic(&self) -> bool {
+        self.inner.llvm_type.is_var_arg()
+    }
     pub fn new(llvm_type: FunctionType<'ctx>, return_type: Option<LEBasicTypeEnum<'ctx>>, param_types: Vec<LEBasicTypeEnum<'ctx>>) -> Self {
         Self {
             inner: Rc::new(LEFunctionTypeInner {
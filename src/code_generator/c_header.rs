@@ -0,0 +1,187 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use crate::code_generator::builder::{LEBasicTypeEnum, LEFunctionType, LEStructType, LEType};
+
+/// 导出到C的函数：源代码中的名字、参数名和函数类型
+#[derive(Debug, Clone)]
+pub struct ExportedFunction<'ctx> {
+    pub name: String,
+    pub param_names: Vec<String>,
+    pub ty: LEFunctionType<'ctx>,
+}
+
+/// 为导出的函数生成C头文件，`guard`是头文件保护宏的名字，
+/// 函数用到的结构体按依赖顺序在函数声明之前定义
+pub fn generate_c_header(functions: &[ExportedFunction], guard: &str) -> String {
+    //函数名和结构体、向量的typedef在C中位于同一个命名空间，先占用导出的函数名
+    let used_names = functions.iter().map(|function| function.name.clone()).collect();
+    let mut writer = CHeaderWriter { structs: String::new(), names: HashMap::new(), used_names };
+    let mut declarations = String::new();
+    for function in functions.iter() {
+        match writer.function_declaration(function) {
+            Some(declaration) => writeln!(declarations, "{};", declaration).unwrap(),
+            None => writeln!(declarations, "/* `{}` returns an array, which can not be returned by value in C */", function.name).unwrap(),
+        }
+    }
+    let mut header = String::new();
+    writeln!(header, "/* generated by lelang, do not edit */").unwrap();
+    writeln!(header, "#ifndef {}", guard).unwrap();
+    writeln!(header, "#define {}\n", guard).unwrap();
+    writeln!(header, "#include <stdint.h>\n").unwrap();
+    writeln!(header, "#ifdef __cplusplus\nextern \"C\" {{\n#endif\n").unwrap();
+    header.push_str(&writer.structs);
+    header.push_str(&declarations);
+    writeln!(header, "\n#ifdef __cplusplus\n}}\n#endif\n").unwrap();
+    writeln!(header, "#endif /* {} */", guard).unwrap();
+    header
+}
+
+/// 头文件保护宏的名字，如`vector_math.h`对应`VECTOR_MATH_H`
+pub fn header_guard(file_name: &str) -> String {
+    c_identifier(file_name).to_uppercase()
+}
+
+/// 把`math::Point`、`Pair<i32,f64>`等名字中不能出现在C标识符中的字符替换为下划线
+fn c_identifier(name: &str) -> String {
+    let identifier = name.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect::<String>();
+    let identifier = identifier.trim_matches('_').to_string();
+    if identifier.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", identifier)
+    } else {
+        identifier
+    }
+}
+
+/// 导出函数签名中C无法表示的类型，返回这个类型和原因：布尔向量和通道数不是2的幂的向量没有对应的GCC向量扩展类型。
+/// 检查指针指向的类型、数组元素和结构体成员
+pub fn unsupported_c_type<'ctx>(ty: &LEBasicTypeEnum<'ctx>) -> Option<(LEBasicTypeEnum<'ctx>, String)> {
+    find_unsupported_c_type(ty, &mut HashSet::new())
+}
+
+fn find_unsupported_c_type<'ctx>(ty: &LEBasicTypeEnum<'ctx>, visited_structs: &mut HashSet<String>) -> Option<(LEBasicTypeEnum<'ctx>, String)> {
+    match ty {
+        LEBasicTypeEnum::Vector(vector) => {
+            if let LEBasicTypeEnum::Bool(_) = vector.get_element_type() {
+                Some((ty.clone(), "has boolean lanes, which have no C vector type".into()))
+            } else if !vector.len().is_power_of_two() {
+                Some((ty.clone(), format!("has {} lanes, C vector types need a power of two lane count", vector.len())))
+            } else {
+                None
+            }
+        }
+        LEBasicTypeEnum::Pointer(pointer) => find_unsupported_c_type(&pointer.get_point_type(), visited_structs),
+        LEBasicTypeEnum::Array(array) => find_unsupported_c_type(&array.get_element_type(), visited_structs),
+        //结构体可以通过指针引用自身
+        LEBasicTypeEnum::Struct(structure) => {
+            if !visited_structs.insert(structure.struct_name().to_string()) {
+                return None;
+            }
+            structure.get_member_types().iter().find_map(|member_type| find_unsupported_c_type(member_type, visited_structs))
+        }
+        _ => None,
+    }
+}
+
+struct CHeaderWriter {
+    ///按依赖顺序生成的结构体定义
+    structs: String,
+    ///结构体和向量类型对应的C名字
+    names: HashMap<String, String>,
+    ///已经使用的C名字，`a::b`和`a_b`替换字符后相同，后出现的加上数字后缀
+    used_names: HashSet<String>,
+}
+
+impl CHeaderWriter {
+    /// 函数声明，返回数组的函数无法用C表示，返回None
+    fn function_declaration(&mut self, function: &ExportedFunction) -> Option<String> {
+        let params = function.param_names
+            .iter()
+            .zip(function.ty.param_types())
+            .map(|(name, ty)| self.declarator(ty, name))
+            .collect::<Vec<_>>();
+        let params = if params.is_empty() { "void".to_string() } else { params.join(", ") };
+        let signature = format!("{}({})", function.name, params);
+        match function.ty.return_type() {
+            None => Some(format!("void {}", signature)),
+            Some(LEBasicTypeEnum::Array(_)) => None,
+            Some(return_type) => Some(self.declarator(&return_type, &signature)),
+        }
+    }
+
+    /// 类型为`ty`、名字为`name`的C声明，数组和数组的指针需要把名字放在类型中间，
+    /// 如`[i32;4]`为`int32_t name[4]`，`ref [i32;4]`为`int32_t (*name)[4]`。
+    /// 数组形参在C中就是指针，`abi`模块同样把数组参数降级为指向副本的指针
+    fn declarator(&mut self, ty: &LEBasicTypeEnum, name: &str) -> String {
+        match ty {
+            LEBasicTypeEnum::Pointer(pointer) => {
+                let point_type = pointer.get_point_type();
+                if point_type.is_array_type() {
+                    self.declarator(&point_type, &format!("(*{})", name))
+                } else {
+                    self.declarator(&point_type, &format!("*{}", name))
+                }
+            }
+            LEBasicTypeEnum::Array(array) => self.declarator(&array.get_element_type(), &format!("{}[{}]", name, array.len())),
+            _ => format!("{} {}", self.type_name(ty), name),
+        }
+    }
+
+    /// 非数组、非指针类型在C中的名字
+    fn type_name(&mut self, ty: &LEBasicTypeEnum) -> String {
+        match ty {
+            LEBasicTypeEnum::Integer(integer) => {
+                let bits = integer.get_llvm_type().get_bit_width();
+                if integer.signed() { format!("int{}_t", bits) } else { format!("uint{}_t", bits) }
+            }
+            LEBasicTypeEnum::Float(float) => if float.is_double() { "double".into() } else { "float".into() },
+            LEBasicTypeEnum::Bool(_) => "uint8_t".into(),
+            //使用`struct 名字`，成员中可以引用正在定义的结构体
+            LEBasicTypeEnum::Struct(structure) => format!("struct {}", self.define_struct(structure)),
+            LEBasicTypeEnum::Vector(vector) => {
+                //向量使用GCC和Clang的向量扩展
+                let element = self.type_name(&vector.get_element_type());
+                let (name, is_new) = self.assign_name(&format!("vec<{};{}>", element, vector.len()), &c_identifier(&format!("{}x{}", element, vector.len())));
+                if is_new {
+                    let element_size = match vector.get_element_type() {
+                        LEBasicTypeEnum::Integer(integer) => integer.get_llvm_type().get_bit_width() / 8,
+                        LEBasicTypeEnum::Float(float) => if float.is_double() { 8 } else { 4 },
+                        _ => 1,
+                    };
+                    let size = element_size * vector.len();
+                    writeln!(self.structs, "typedef {} {} __attribute__((vector_size({})));\n", element, name, size).unwrap();
+                }
+                name
+            }
+            LEBasicTypeEnum::Pointer(_) | LEBasicTypeEnum::Array(_) => unreachable!("pointer and array types are written by declarator"),
+        }
+    }
+
+    /// 生成结构体定义，成员顺序与`LEStructType`相同，两者都按自然对齐排列
+    fn define_struct(&mut self, structure: &LEStructType) -> String {
+        let (name, is_new) = self.assign_name(structure.struct_name(), &c_identifier(structure.struct_name()));
+        if is_new {
+            let members = structure.get_member_names()
+                .into_iter()
+                .zip(structure.get_member_types())
+                .map(|(member_name, member_type)| format!("    {};\n", self.declarator(&member_type, &member_name)))
+                .collect::<String>();
+            writeln!(self.structs, "typedef struct {} {{\n{}}} {};\n", name, members, name).unwrap();
+        }
+        name
+    }
+    /// `key`对应的C名字，第一次出现时从`identifier`开始分配一个没有用过的名字，并返回true
+    fn assign_name(&mut self, key: &str, identifier: &str) -> (String, bool) {
+        if let Some(name) = self.names.get(key) {
+            return (name.clone(), false);
+        }
+        let mut name = identifier.to_string();
+        let mut suffix = 2;
+        while !self.used_names.insert(name.clone()) {
+            name = format!("{}_{}", identifier, suffix);
+            suffix += 1;
+        }
+        self.names.insert(key.to_string(), name.clone());
+        (name, true)
+    }
+}
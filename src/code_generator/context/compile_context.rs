@@ -1,11 +1,14 @@
+use std::collections::{HashMap, HashSet};
+
 use inkwell::basic_block::BasicBlock;
 use inkwell::context::Context;
 use inkwell::values::FunctionValue;
 
-use crate::ast::nodes::TypeDeclarator;
+use crate::ast::nodes::{FunctionDefinition, GenericParam, Structure, TraitDefinition, TypeDeclarator};
 use crate::code_generator::builder;
 use crate::code_generator::builder::{LEBasicTypeEnum, LEFunctionValue, LEPointerValue};
 use crate::code_generator::context::symbol_table::{Symbol, SymbolTable};
+use crate::error::CompileError;
 use crate::lexer::Position;
 
 use super::super::Result;
@@ -16,6 +19,19 @@ pub struct CompilerContext<'ctx> {
     pub current_function: Option<FunctionValue<'ctx>>,
     pub return_variable: Option<LEPointerValue<'ctx>>,
     pub return_block: Option<BasicBlock<'ctx>>,
+    ///泛型函数和泛型结构体的定义，实例化时使用
+    pub generic_functions: HashMap<String, FunctionDefinition>,
+    pub generic_structures: HashMap<String, Structure>,
+    ///按类型实参缓存的实例，key为`max<i32>`形式的实例名
+    pub function_instances: HashMap<String, LEFunctionValue<'ctx>>,
+    pub structure_instances: HashMap<String, LEBasicTypeEnum<'ctx>>,
+    ///结构体实例名到泛型名和类型实参的映射，用于推导嵌套的泛型参数
+    pub structure_instance_arguments: HashMap<String, (String, Vec<LEBasicTypeEnum<'ctx>>)>,
+    ///当前正在实例化的泛型参数绑定
+    pub generic_bindings: HashMap<String, LEBasicTypeEnum<'ctx>>,
+    ///所有trait的定义，以及已实现的`(类型名, trait名)`
+    pub traits: HashMap<String, TraitDefinition>,
+    pub trait_impls: HashSet<(String, String)>,
 }
 
 
@@ -26,6 +42,14 @@ impl<'ctx> CompilerContext<'ctx> {
             current_function: None,
             return_variable: None,
             return_block: None,
+            generic_functions: HashMap::default(),
+            generic_structures: HashMap::default(),
+            function_instances: HashMap::default(),
+            structure_instances: HashMap::default(),
+            structure_instance_arguments: HashMap::default(),
+            generic_bindings: HashMap::default(),
+            traits: HashMap::default(),
+            trait_impls: HashSet::default(),
         }
     }
 
@@ -88,4 +112,32 @@ impl<'ctx> CompilerContext<'ctx> {
     pub fn get_symbol(&self, identifier: &str) -> Option<Symbol<'ctx>> {
         self.symbols.get_symbol(identifier)
     }
+
+    pub fn get_generic_binding(&self, identifier: &str) -> Option<LEBasicTypeEnum<'ctx>> {
+        self.generic_bindings.get(identifier).cloned()
+    }
+
+    pub fn is_generic_function(&self, identifier: &str) -> bool {
+        self.generic_functions.contains_key(identifier)
+    }
+
+    pub fn is_generic_structure(&self, identifier: &str) -> bool {
+        self.generic_structures.contains_key(identifier)
+    }
+
+    pub fn implements_trait(&self, ty: &LEBasicTypeEnum<'ctx>, trait_name: &str) -> bool {
+        self.trait_impls.contains(&(ty.to_string(), trait_name.to_string()))
+    }
+
+    /// 检查类型实参是否满足泛型参数的trait约束
+    pub fn check_trait_bounds(&self, generic_params: &[GenericParam], type_arguments: &[LEBasicTypeEnum<'ctx>]) -> Result<()> {
+        for (param, argument) in generic_params.iter().zip(type_arguments.iter()) {
+            for bound in param.bounds.iter() {
+                if !self.implements_trait(argument, &bound.name) {
+                    return Err(CompileError::TraitBoundNotSatisfied { ty: argument.to_string(), trait_name: bound.name.clone() });
+                }
+            }
+        }
+        Ok(())
+    }
 }
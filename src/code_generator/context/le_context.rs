@@ -1,9 +1,14 @@
+use std::collections::HashMap;
+
 use inkwell::context::Context;
 
-use crate::ast::nodes::{Identifier, TypeDeclarator};
-use crate::code_generator::builder::{LEBasicTypeEnum, LEBoolType, LEFloatType, LEFunctionValue, LEIntegerType, LEPointerValue};
+use crate::ast::nodes::{GenericParam, Identifier, TypeDeclarator};
+use crate::code_generator::builder::{LEBasicType, LEBasicTypeEnum, LEBoolType, LEFloatType, LEFunctionValue, LEIntegerType, LEPointerType, LEPointerValue, LEStructType};
 use crate::code_generator::context::compile_context::CompilerContext;
+use crate::code_generator::context::get_vector_type;
+use crate::code_generator::mangle;
 use crate::code_generator::Result;
+use crate::error::CompileError;
 use crate::lexer::Position;
 
 pub struct LEContext<'ctx> {
@@ -51,13 +56,148 @@ impl<'ctx> LEContext<'ctx> {
     pub fn double_type(&self) -> LEFloatType<'ctx> {
         self.compiler_context.symbols.double_type()
     }
+    pub fn str_type(&self) -> LEPointerType<'ctx> {
+        self.compiler_context.symbols.str_type()
+    }
 
     pub fn get_variable(&self, name: &str) -> Result<LEPointerValue<'ctx>> {
         self.compiler_context.get_variable(name)
     }
 
-    pub fn get_generic_type(&self, type_declarator: &TypeDeclarator) -> Result<LEBasicTypeEnum<'ctx>> {
-        self.compiler_context.get_type(type_declarator)
+    /// 获取类型声明对应的类型，会先查找当前的泛型参数绑定，遇到泛型结构体时生成对应的实例
+    pub fn get_generic_type(&mut self, type_declarator: &TypeDeclarator) -> Result<LEBasicTypeEnum<'ctx>> {
+        match type_declarator {
+            TypeDeclarator::TypeIdentifier(identifier) => {
+                if let Some(ty) = self.compiler_context.get_generic_binding(&identifier.name) {
+                    return Ok(ty);
+                }
+                if let Some(structure) = self.compiler_context.generic_structures.get(&identifier.name) {
+                    return Err(CompileError::GenericArgumentNumberMismatched {
+                        identifier: identifier.name.clone(),
+                        expect: structure.generic_params.len(),
+                        found: 0,
+                    });
+                }
+                self.compiler_context.get_type(type_declarator)
+            }
+            TypeDeclarator::Array(array) => {
+                let element_type = self.get_generic_type(&array.element_type)?;
+                Ok(LEBasicType::get_array_type(&element_type, array.len).to_le_type_enum())
+            }
+            TypeDeclarator::Slice(slice) => {
+                let element_type = self.get_generic_type(&slice.element_type)?;
+                Ok(self.get_slice_type(element_type))
+            }
+            TypeDeclarator::Vector(vector) => {
+                let element_type = self.get_generic_type(&vector.element_type)?;
+                get_vector_type(element_type, vector.len)
+            }
+            TypeDeclarator::Reference(reference) => {
+                let point_type = self.get_generic_type(reference)?;
+                Ok(LEBasicType::get_pointer_type(&point_type).to_le_type_enum())
+            }
+            TypeDeclarator::Generic(generic) => {
+                let type_arguments = generic.type_arguments
+                    .iter()
+                    .map(|argument| self.get_generic_type(argument))
+                    .collect::<Result<Vec<_>>>()?;
+                self.instantiate_generic_structure(&generic.identifier.name, type_arguments)
+            }
+        }
+    }
+
+    /// 切片`[T]`是由指向第一个元素的引用`data`和元素个数`len`组成的结构体，相同元素类型的切片只会生成一次
+    pub fn get_slice_type(&mut self, element_type: LEBasicTypeEnum<'ctx>) -> LEBasicTypeEnum<'ctx> {
+        let name = format!("[{}]", element_type);
+        if let Some(ty) = self.compiler_context.structure_instances.get(&name) {
+            return ty.clone();
+        }
+        let member_types = [LEBasicType::get_pointer_type(&element_type).to_le_type_enum(), self.u64_type().to_le_type_enum()];
+        let slice_type = LEStructType::with_mangled_name(self, &name, &mangle::mangle_slice(&element_type), &["data", "len"], &member_types).to_le_type_enum();
+        self.compiler_context.structure_instances.insert(name, slice_type.clone());
+        slice_type
+    }
+
+    /// 生成泛型结构体的实例，相同类型实参的实例只会生成一次
+    pub fn instantiate_generic_structure(&mut self, name: &str, type_arguments: Vec<LEBasicTypeEnum<'ctx>>) -> Result<LEBasicTypeEnum<'ctx>> {
+        let structure = self.compiler_context.generic_structures.get(name).cloned()
+            .ok_or_else(|| CompileError::UnknownIdentifier { identifier: name.into() })?;
+        if structure.generic_params.len() != type_arguments.len() {
+            return Err(CompileError::GenericArgumentNumberMismatched {
+                identifier: name.into(),
+                expect: structure.generic_params.len(),
+                found: type_arguments.len(),
+            });
+        }
+        self.compiler_context.check_trait_bounds(&structure.generic_params, &type_arguments)?;
+        let instance_name = mangle::generic_instance_name(name, &type_arguments);
+        if let Some(ty) = self.compiler_context.structure_instances.get(&instance_name) {
+            return Ok(ty.clone());
+        }
+        //在泛型参数绑定下求成员的类型
+        let bindings = structure.generic_params.iter().map(|param| param.identifier.name.clone()).zip(type_arguments.iter().cloned()).collect();
+        let outer_bindings = std::mem::replace(&mut self.compiler_context.generic_bindings, bindings);
+        let member_types = structure.members
+            .iter()
+            .map(|(_, ty)| self.get_generic_type(ty))
+            .collect::<Result<Vec<_>>>();
+        self.compiler_context.generic_bindings = outer_bindings;
+        let member_types = member_types?;
+        let member_names = structure.members.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>();
+        let structure_type = LEStructType::with_mangled_name(self, &instance_name, &mangle::mangle_generic_structure(name, &type_arguments), &member_names, &member_types).to_le_type_enum();
+        self.compiler_context.structure_instances.insert(instance_name.clone(), structure_type.clone());
+        self.compiler_context.structure_instance_arguments.insert(instance_name, (name.to_string(), type_arguments));
+        Ok(structure_type)
+    }
+
+    /// 用实际类型匹配含泛型参数的类型声明，推导出泛型参数的类型
+    pub fn infer_generic_arguments(
+        &self,
+        generic_params: &[GenericParam],
+        declarator: &TypeDeclarator,
+        actual: &LEBasicTypeEnum<'ctx>,
+        bindings: &mut HashMap<String, LEBasicTypeEnum<'ctx>>,
+    ) -> Result<()> {
+        match (declarator, actual) {
+            (TypeDeclarator::TypeIdentifier(identifier), _) => {
+                if generic_params.iter().any(|param| param.identifier.name == identifier.name) {
+                    if let Some(bound) = bindings.get(&identifier.name) {
+                        if bound != actual {
+                            return Err(CompileError::TypeMismatched { expect: bound.to_string(), found: actual.to_string() });
+                        }
+                    } else {
+                        bindings.insert(identifier.name.clone(), actual.clone());
+                    }
+                }
+                Ok(())
+            }
+            (TypeDeclarator::Array(array), LEBasicTypeEnum::Array(actual_array)) => {
+                self.infer_generic_arguments(generic_params, &array.element_type, &actual_array.get_element_type(), bindings)
+            }
+            (TypeDeclarator::Slice(slice), LEBasicTypeEnum::Struct(actual_struct)) if actual_struct.struct_name().starts_with('[') => {
+                match actual_struct.get_member_offset_and_type("data") {
+                    Some((_, LEBasicTypeEnum::Pointer(data))) => self.infer_generic_arguments(generic_params, &slice.element_type, &data.get_point_type(), bindings),
+                    _ => Ok(())
+                }
+            }
+            (TypeDeclarator::Vector(vector), LEBasicTypeEnum::Vector(actual_vector)) => {
+                self.infer_generic_arguments(generic_params, &vector.element_type, &actual_vector.get_element_type(), bindings)
+            }
+            (TypeDeclarator::Reference(reference), LEBasicTypeEnum::Pointer(actual_pointer)) => {
+                self.infer_generic_arguments(generic_params, reference, &actual_pointer.get_point_type(), bindings)
+            }
+            (TypeDeclarator::Generic(generic), LEBasicTypeEnum::Struct(actual_struct)) => {
+                if let Some((name, type_arguments)) = self.compiler_context.structure_instance_arguments.get(actual_struct.struct_name()) {
+                    if name == &generic.identifier.name {
+                        for (argument, actual_argument) in generic.type_arguments.iter().zip(type_arguments.iter()) {
+                            self.infer_generic_arguments(generic_params, argument, actual_argument, bindings)?;
+                        }
+                    }
+                }
+                Ok(())
+            }
+            _ => Ok(())
+        }
     }
 
 
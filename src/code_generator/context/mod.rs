@@ -1,4 +1,5 @@
 pub use le_context::LEContext;
+pub use symbol_table::get_vector_type;
 
 mod le_context;
 mod symbol_table;
@@ -1,9 +1,10 @@
 use std::collections::HashMap;
 
+use inkwell::AddressSpace;
 use inkwell::context::Context;
 
 use crate::ast::nodes::TypeDeclarator;
-use crate::code_generator::builder::{LEBasicType, LEBasicTypeEnum, LEBoolType, LEBoolValue, LEFloatType, LEFunctionValue, LEIntegerType, LEPointerValue};
+use crate::code_generator::builder::{LEBasicType, LEBasicTypeEnum, LEBoolType, LEBoolValue, LEFloatType, LEFunctionValue, LEIntegerType, LEPointerType, LEPointerValue, LEVectorType};
 use crate::code_generator::Result;
 use crate::error::CompileError;
 use crate::lexer::Position;
@@ -47,6 +48,14 @@ impl<'ctx> Symbol<'ctx> {
             Symbol::Function(v) => { v.meta.is_built_in }
         }
     }
+
+    pub fn defined_pos(&self) -> Position {
+        match self {
+            Symbol::Type(v) => { v.meta.defined_pos.clone() }
+            Symbol::Variable(v) => { v.meta.defined_pos.clone() }
+            Symbol::Function(v) => { v.meta.defined_pos.clone() }
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -62,6 +71,8 @@ struct BuiltinTypes<'ctx> {
     u64_type: LEIntegerType<'ctx>,
     f32_type: LEFloatType<'ctx>,
     f64_type: LEFloatType<'ctx>,
+    ///字符串类型，以0结尾的u8序列的指针，和C的字符串兼容
+    str_type: LEPointerType<'ctx>,
 }
 
 
@@ -89,6 +100,7 @@ impl<'ctx> BuiltinTypes<'ctx> {
 
         let f32_type = LEFloatType::from_llvm_type(llvm_context.f32_type(), false);
         let f64_type = LEFloatType::from_llvm_type(llvm_context.f64_type(), true);
+        let str_type = LEPointerType::str_type(llvm_context.i8_type().ptr_type(AddressSpace::Generic), u8_type.to_le_type_enum());
         Self {
             bool_type,
             i8_type,
@@ -101,6 +113,7 @@ impl<'ctx> BuiltinTypes<'ctx> {
             u64_type,
             f32_type,
             f64_type,
+            str_type,
         }
     }
 }
@@ -114,67 +127,73 @@ impl<'ctx> SymbolTable<'ctx> {
             ("bool".into(), Symbol::Type(
                 Type {
                     inner: LEBasicTypeEnum::Bool(builtin_types.bool_type.clone()),
-                    meta: MetaData { defined_pos: Position { range: 0..0 }, is_built_in: true },
+                    meta: MetaData { defined_pos: Position::new(0..0, 0), is_built_in: true },
                 }
             )),
             ("i8".into(), Symbol::Type(
                 Type {
                     inner: LEBasicTypeEnum::Integer(builtin_types.i8_type.clone()),
-                    meta: MetaData { defined_pos: Position { range: 0..0 }, is_built_in: true },
+                    meta: MetaData { defined_pos: Position::new(0..0, 0), is_built_in: true },
                 }
             )),
             ("i16".into(), Symbol::Type(
                 Type {
                     inner: LEBasicTypeEnum::Integer(builtin_types.i16_type.clone()),
-                    meta: MetaData { defined_pos: Position { range: 0..0 }, is_built_in: true },
+                    meta: MetaData { defined_pos: Position::new(0..0, 0), is_built_in: true },
                 }
             )),
             ("i32".into(), Symbol::Type(
                 Type {
                     inner: LEBasicTypeEnum::Integer(builtin_types.i32_type.clone()),
-                    meta: MetaData { defined_pos: Position { range: 0..0 }, is_built_in: true },
+                    meta: MetaData { defined_pos: Position::new(0..0, 0), is_built_in: true },
                 }
             )),
             ("i64".into(), Symbol::Type(
                 Type {
                     inner: LEBasicTypeEnum::Integer(builtin_types.i64_type.clone()),
-                    meta: MetaData { defined_pos: Position { range: 0..0 }, is_built_in: true },
+                    meta: MetaData { defined_pos: Position::new(0..0, 0), is_built_in: true },
                 }
             )),
             ("u8".into(), Symbol::Type(
                 Type {
                     inner: LEBasicTypeEnum::Integer(builtin_types.u8_type.clone()),
-                    meta: MetaData { defined_pos: Position { range: 0..0 }, is_built_in: true },
+                    meta: MetaData { defined_pos: Position::new(0..0, 0), is_built_in: true },
                 }
             )),
             ("u16".into(), Symbol::Type(
                 Type {
                     inner: LEBasicTypeEnum::Integer(builtin_types.u16_type.clone()),
-                    meta: MetaData { defined_pos: Position { range: 0..0 }, is_built_in: true },
+                    meta: MetaData { defined_pos: Position::new(0..0, 0), is_built_in: true },
                 }
             )),
             ("u32".into(), Symbol::Type(
                 Type {
                     inner: LEBasicTypeEnum::Integer(builtin_types.u32_type.clone()),
-                    meta: MetaData { defined_pos: Position { range: 0..0 }, is_built_in: true },
+                    meta: MetaData { defined_pos: Position::new(0..0, 0), is_built_in: true },
                 }
             )),
             ("u64".into(), Symbol::Type(
                 Type {
                     inner: LEBasicTypeEnum::Integer(builtin_types.u64_type.clone()),
-                    meta: MetaData { defined_pos: Position { range: 0..0 }, is_built_in: true },
+                    meta: MetaData { defined_pos: Position::new(0..0, 0), is_built_in: true },
                 }
             )),
             ("f32".into(), Symbol::Type(
                 Type {
                     inner: LEBasicTypeEnum::Float(builtin_types.f32_type.clone()),
-                    meta: MetaData { defined_pos: Position { range: 0..0 }, is_built_in: true },
+                    meta: MetaData { defined_pos: Position::new(0..0, 0), is_built_in: true },
                 }
             )),
             ("f64".into(), Symbol::Type(
                 Type {
                     inner: LEBasicTypeEnum::Float(builtin_types.f64_type.clone()),
-                    meta: MetaData { defined_pos: Position { range: 0..0 }, is_built_in: true },
+                    meta: MetaData { defined_pos: Position::new(0..0, 0), is_built_in: true },
+                }
+            )),
+            ("str".into(), Symbol::Type(
+                Type {
+                    inner: LEBasicTypeEnum::Pointer(builtin_types.str_type.clone()),
+                    meta: MetaData { defined_pos: Position::new(0..0, 0), is_built_in: true },
                 }
             )),
         ];
@@ -201,11 +220,23 @@ impl<'ctx> SymbolTable<'ctx> {
                 let array_type = LEBasicType::get_array_type(&element_type, array.len);
                 Ok(array_type.to_le_type_enum())
             }
+            TypeDeclarator::Vector(vector) => {
+                let element_type = self.get_type(&vector.element_type)?;
+                get_vector_type(element_type, vector.len)
+            }
             TypeDeclarator::Reference(reference) => {
                 let point_type = self.get_type(reference)?;
                 let pointer_type = LEBasicType::get_pointer_type(&point_type);
                 Ok(pointer_type.to_le_type_enum())
             }
+            TypeDeclarator::Generic(generic) => {
+                //泛型实例由LEContext负责生成，符号表中只有具体类型
+                Err(CompileError::IdentifierIsNotType { identifier: generic.identifier.name.clone() })
+            }
+            TypeDeclarator::Slice(_) => {
+                //切片类型同样由LEContext负责生成
+                Err(CompileError::IdentifierIsNotType { identifier: "slice".into() })
+            }
         }
     }
 
@@ -300,6 +331,16 @@ impl<'ctx> SymbolTable<'ctx> {
     pub fn push_block_table(&mut self) {
         self.table.push(HashMap::default());
     }
+
+    ///取出全局表以外的所有块级符号表，用于在其他函数体中间生成泛型实例
+    pub fn take_local_tables(&mut self) -> Vec<HashMap<String, Symbol<'ctx>>> {
+        self.table.split_off(1)
+    }
+
+    pub fn restore_local_tables(&mut self, tables: Vec<HashMap<String, Symbol<'ctx>>>) {
+        self.table.truncate(1);
+        self.table.extend(tables);
+    }
     pub fn pop_block_table(&mut self) {
         self.table.pop();
     }
@@ -337,7 +378,17 @@ impl<'ctx> SymbolTable<'ctx> {
     pub fn double_type(&self) -> LEFloatType<'ctx> {
         self.builtin_types.f64_type.clone()
     }
+    pub fn str_type(&self) -> LEPointerType<'ctx> {
+        self.builtin_types.str_type.clone()
+    }
 }
 
-
-
+///向量类型的通道数不能为0，元素只能是整数、浮点数或布尔类型
+pub fn get_vector_type<'ctx>(element_type: LEBasicTypeEnum<'ctx>, len: u32) -> Result<LEBasicTypeEnum<'ctx>> {
+    if len == 0 {
+        return Err(CompileError::NotAllowZeroLengthArray);
+    }
+    LEVectorType::new(element_type.clone(), len)
+        .map(|vector_type| vector_type.to_le_type_enum())
+        .ok_or_else(|| CompileError::InvalidVectorElementType { ty: element_type.to_string() })
+}
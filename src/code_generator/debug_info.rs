@@ -0,0 +1,288 @@
+use std::collections::HashMap;
+
+use inkwell::AddressSpace;
+use inkwell::basic_block::BasicBlock;
+use inkwell::builder::Builder;
+use inkwell::context::Context;
+use inkwell::debug_info::{AsDIScope, debug_metadata_version, DebugInfoBuilder, DICompileUnit, DIFile, DIFlags, DIFlagsConstants, DILocation, DIScope, DIType, DWARFEmissionKind, DWARFSourceLanguage};
+use inkwell::module::{FlagBehavior, Linkage, Module};
+use inkwell::targets::TargetData;
+use inkwell::values::{FunctionValue, PointerValue};
+use llvm_sys::debuginfo::{LLVMCreateDIBuilderDisallowUnresolved, LLVMDIBuilderCreateVectorType, LLVMDIBuilderGetOrCreateSubrange, LLVMDisposeDIBuilder};
+use llvm_sys::prelude::{LLVMMetadataRef, LLVMModuleRef};
+
+use crate::code_generator::builder::{LEBasicType, LEBasicTypeEnum, LEType};
+use crate::lexer::{Position, SourceMap};
+
+///DWARF中基本类型的编码
+const DW_ATE_BOOLEAN: u32 = 0x02;
+const DW_ATE_FLOAT: u32 = 0x04;
+const DW_ATE_SIGNED: u32 = 0x05;
+const DW_ATE_SIGNED_CHAR: u32 = 0x06;
+const DW_ATE_UNSIGNED: u32 = 0x07;
+const DW_ATE_UNSIGNED_CHAR: u32 = 0x08;
+
+/// `-g`时生成的DWARF调试信息：每个函数对应DISubprogram，每个代码块对应DILexicalBlock，
+/// 参数和局部变量对应DILocalVariable，每条语句开始时设置DILocation
+pub struct DebugInfo<'ctx> {
+    builder: DebugInfoBuilder<'ctx>,
+    ///inkwell没有提供的调试信息通过LLVM的C接口在这个模块中创建
+    llvm_module: LLVMModuleRef,
+    compile_unit: DICompileUnit<'ctx>,
+    target_data: TargetData,
+    is_optimized: bool,
+    ///源文件的序号到它的DIFile和每一行起始的字节偏移
+    files: HashMap<usize, (DIFile<'ctx>, Vec<usize>)>,
+    ///当前所在的作用域，最内层的在最后
+    scopes: Vec<DIScope<'ctx>>,
+    ///进入泛型实例等嵌套生成的函数前的位置，退出时恢复
+    saved_locations: Vec<Option<DILocation<'ctx>>>,
+    current_location: Option<DILocation<'ctx>>,
+    types: HashMap<String, DIType<'ctx>>,
+}
+
+impl<'ctx> DebugInfo<'ctx> {
+    /// 为模块创建编译单元，`file_id`是模块对应的源文件，`source_map`中的所有文件都可以出现在调试信息中
+    pub fn new(module: &Module<'ctx>, source_map: &SourceMap, file_id: usize, target_data: TargetData, is_optimized: bool) -> Self {
+        let debug_version = module.get_context().i32_type().const_int(debug_metadata_version() as u64, false);
+        module.add_basic_value_flag("Debug Info Version", FlagBehavior::Warning, debug_version);
+        let (file_name, directory) = split_path(source_map, file_id);
+        let (builder, compile_unit) = module.create_debug_info_builder(
+            true,
+            DWARFSourceLanguage::C,
+            &file_name,
+            &directory,
+            "lelang",
+            is_optimized,
+            "",
+            0,
+            "",
+            DWARFEmissionKind::Full,
+            0,
+            false,
+            false,
+            "",
+            "",
+        );
+        let files = source_map.files().enumerate().map(|(file_id, file)| {
+            let (file_name, directory) = split_path(source_map, file_id);
+            let line_starts = std::iter::once(0).chain(file.content.match_indices('\n').map(|(index, _)| index + 1)).collect();
+            (file_id, (builder.create_file(&file_name, &directory), line_starts))
+        }).collect();
+        Self {
+            builder,
+            llvm_module: module.as_mut_ptr(),
+            compile_unit,
+            target_data,
+            is_optimized,
+            files,
+            scopes: vec![],
+            saved_locations: vec![],
+            current_location: None,
+            types: HashMap::new(),
+        }
+    }
+
+    /// 把字节范围的起点转换为从1开始的行号和列号
+    fn line_column(&self, pos: &Position) -> (u32, u32) {
+        match self.files.get(&pos.file_id) {
+            Some((_, line_starts)) => {
+                let line = line_starts.partition_point(|start| *start <= pos.range.start);
+                let column = pos.range.start - line_starts[line - 1] + 1;
+                (line as u32, column as u32)
+            }
+            None => (0, 0)
+        }
+    }
+
+    fn file(&self, pos: &Position) -> DIFile<'ctx> {
+        self.files.get(&pos.file_id).map(|(file, _)| *file).unwrap_or_else(|| self.compile_unit.get_file())
+    }
+
+    /// 进入函数：生成DISubprogram并关联到函数，之后的作用域都在这个函数中
+    pub fn enter_function(
+        &mut self,
+        function: FunctionValue<'ctx>,
+        name: &str,
+        pos: &Position,
+        param_types: &[LEBasicTypeEnum<'ctx>],
+        return_type: Option<LEBasicTypeEnum<'ctx>>,
+    ) {
+        let file = self.file(pos);
+        let (line, _) = self.line_column(pos);
+        let return_type = return_type.map(|ty| self.get_type(&ty));
+        let param_types = param_types.iter().map(|ty| self.get_type(ty)).collect::<Vec<_>>();
+        let subroutine_type = self.builder.create_subroutine_type(file, return_type, &param_types, DIFlags::PUBLIC);
+        let linkage_name = function.get_name().to_string_lossy().to_string();
+        let subprogram = self.builder.create_function(
+            file.as_debug_info_scope(),
+            name,
+            Some(&linkage_name),
+            file,
+            line,
+            subroutine_type,
+            function.get_linkage() != Linkage::External,
+            true,
+            line,
+            DIFlags::PUBLIC,
+            self.is_optimized,
+        );
+        function.set_subprogram(subprogram);
+        self.scopes.push(subprogram.as_debug_info_scope());
+        self.saved_locations.push(self.current_location.take());
+    }
+
+    /// 退出函数，恢复进入函数前的作用域和位置
+    pub fn exit_function(&mut self, context: &'ctx Context, builder: &Builder<'ctx>) {
+        self.scopes.pop();
+        self.current_location = self.saved_locations.pop().flatten();
+        match self.current_location {
+            Some(location) => builder.set_current_debug_location(context, location),
+            None => builder.unset_current_debug_location(),
+        }
+    }
+
+    pub fn enter_block(&mut self, pos: &Position) {
+        if let Some(scope) = self.scopes.last().copied() {
+            let (line, column) = self.line_column(pos);
+            let block = self.builder.create_lexical_block(scope, self.file(pos), line, column);
+            self.scopes.push(block.as_debug_info_scope());
+        }
+    }
+
+    pub fn exit_block(&mut self) {
+        self.scopes.pop();
+    }
+
+    /// 把之后生成的指令的位置设为`pos`
+    pub fn set_location(&mut self, context: &'ctx Context, builder: &Builder<'ctx>, pos: &Position) {
+        if let Some(scope) = self.scopes.last().copied() {
+            let (line, column) = self.line_column(pos);
+            let location = self.builder.create_debug_location(context, line, column, scope, None);
+            builder.set_current_debug_location(context, location);
+            self.current_location = Some(location);
+        }
+    }
+
+    /// 为参数或局部变量生成DILocalVariable，`arg_no`是从1开始的参数序号，局部变量为None，
+    /// 变量的声明插入到`block`的末尾
+    pub fn declare_variable(
+        &mut self,
+        context: &'ctx Context,
+        name: &str,
+        arg_no: Option<u32>,
+        ty: &LEBasicTypeEnum<'ctx>,
+        storage: PointerValue<'ctx>,
+        pos: &Position,
+        block: BasicBlock<'ctx>,
+    ) {
+        let scope = match self.scopes.last().copied() {
+            Some(scope) => scope,
+            None => return,
+        };
+        let file = self.file(pos);
+        let (line, column) = self.line_column(pos);
+        let di_type = self.get_type(ty);
+        let variable = match arg_no {
+            Some(arg_no) => self.builder.create_parameter_variable(scope, name, arg_no, file, line, di_type, true, DIFlags::ZERO),
+            None => self.builder.create_auto_variable(scope, name, file, line, di_type, true, DIFlags::ZERO, 0),
+        };
+        let location = self.builder.create_debug_location(context, line, column, scope, None);
+        self.builder.insert_declare_at_end(storage, Some(variable), None, location, block);
+    }
+
+    /// lelang类型对应的调试信息类型，结构体按名字缓存
+    fn get_type(&mut self, ty: &LEBasicTypeEnum<'ctx>) -> DIType<'ctx> {
+        let name = ty.to_string();
+        if let Some(di_type) = self.types.get(&name) {
+            return *di_type;
+        }
+        let llvm_type = ty.get_llvm_basic_type();
+        let size_in_bits = self.target_data.get_store_size(&llvm_type) * 8;
+        let align_in_bits = self.target_data.get_abi_alignment(&llvm_type) * 8;
+        let di_type = match ty {
+            LEBasicTypeEnum::Integer(integer) => {
+                let encoding = match (integer.signed(), size_in_bits) {
+                    (true, 8) => DW_ATE_SIGNED_CHAR,
+                    (false, 8) => DW_ATE_UNSIGNED_CHAR,
+                    (true, _) => DW_ATE_SIGNED,
+                    (false, _) => DW_ATE_UNSIGNED,
+                };
+                self.builder.create_basic_type(&name, size_in_bits, encoding, DIFlags::PUBLIC).unwrap().as_type()
+            }
+            LEBasicTypeEnum::Float(_) => {
+                self.builder.create_basic_type(&name, size_in_bits, DW_ATE_FLOAT, DIFlags::PUBLIC).unwrap().as_type()
+            }
+            LEBasicTypeEnum::Bool(_) => {
+                self.builder.create_basic_type(&name, size_in_bits, DW_ATE_BOOLEAN, DIFlags::PUBLIC).unwrap().as_type()
+            }
+            LEBasicTypeEnum::Pointer(pointer) => {
+                let pointee = self.get_type(&pointer.get_point_type());
+                self.builder.create_pointer_type(&name, pointee, size_in_bits, align_in_bits, AddressSpace::Generic).as_type()
+            }
+            LEBasicTypeEnum::Array(array) => {
+                let element = self.get_type(&array.get_element_type());
+                self.builder.create_array_type(element, size_in_bits, align_in_bits, &[0..array.len() as i64]).as_type()
+            }
+            LEBasicTypeEnum::Vector(vector) => {
+                let element = self.get_type(&vector.get_element_type());
+                self.create_vector_type(element, size_in_bits, align_in_bits, vector.len())
+            }
+            LEBasicTypeEnum::Struct(structure) => {
+                let scope = self.compile_unit.as_debug_info_scope();
+                let file = self.compile_unit.get_file();
+                let llvm_struct_type = structure.get_llvm_type();
+                let mut members = vec![];
+                for (index, (member_name, member_type)) in structure.get_member_names().into_iter().zip(structure.get_member_types()).enumerate() {
+                    let member = self.get_type(&member_type);
+                    let llvm_member_type = member_type.get_llvm_basic_type();
+                    let offset_in_bits = self.target_data.offset_of_element(&llvm_struct_type, index as u32).unwrap_or(0) * 8;
+                    members.push(self.builder.create_member_type(
+                        scope,
+                        &member_name,
+                        file,
+                        0,
+                        self.target_data.get_store_size(&llvm_member_type) * 8,
+                        self.target_data.get_abi_alignment(&llvm_member_type) * 8,
+                        offset_in_bits,
+                        DIFlags::PUBLIC,
+                        member,
+                    ).as_type());
+                }
+                self.builder.create_struct_type(scope, &name, file, 0, size_in_bits, align_in_bits, DIFlags::PUBLIC, None, &members, 0, None, &name).as_type()
+            }
+        };
+        self.types.insert(name, di_type);
+        di_type
+    }
+
+    /// 向量是带有DIFlagVector的数组类型，调试器按向量显示；inkwell只能创建普通的数组类型，
+    /// 这里用一个临时的DIBuilder创建，向量类型不是前向引用，不需要在finalize时解析
+    fn create_vector_type(&self, element: DIType<'ctx>, size_in_bits: u64, align_in_bits: u32, len: u32) -> DIType<'ctx> {
+        unsafe {
+            let builder = LLVMCreateDIBuilderDisallowUnresolved(self.llvm_module);
+            let mut subscripts = [LLVMDIBuilderGetOrCreateSubrange(builder, 0, len as i64)];
+            let vector = LLVMDIBuilderCreateVectorType(builder, size_in_bits, align_in_bits, element.as_mut_ptr(), subscripts.as_mut_ptr(), 1);
+            LLVMDisposeDIBuilder(builder);
+            //DIType只包含元数据的指针
+            std::mem::transmute::<LLVMMetadataRef, DIType<'ctx>>(vector)
+        }
+    }
+
+    /// 生成完所有函数后调用，解析调试信息中的前向引用
+    pub fn finalize(&self) {
+        self.builder.finalize();
+    }
+}
+
+/// 源文件的文件名和所在目录
+fn split_path(source_map: &SourceMap, file_id: usize) -> (String, String) {
+    match source_map.get_file(file_id) {
+        Some(file) => {
+            let file_name = file.path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+            let directory = file.path.parent().map(|parent| parent.display().to_string()).unwrap_or_default();
+            (file_name, directory)
+        }
+        None => ("<unknown>".into(), String::new())
+    }
+}
@@ -0,0 +1,71 @@
+/// 格式字符串中的一段：原样输出的文本或一个`{}`占位符
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormatPiece {
+    Text(String),
+    Placeholder,
+}
+
+/// 解析`print`和`println`的格式字符串，`{{`和`}}`输出花括号本身，格式错误时返回原因
+pub fn parse_format_string(format: &str) -> Result<Vec<FormatPiece>, String> {
+    let mut pieces = vec![];
+    let mut text = String::new();
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            }
+            '{' => {
+                if chars.next() != Some('}') {
+                    return Err("`{` must be followed by `}`, use `{{` to print `{`".into());
+                }
+                if !text.is_empty() {
+                    pieces.push(FormatPiece::Text(std::mem::take(&mut text)));
+                }
+                pieces.push(FormatPiece::Placeholder);
+            }
+            '}' => return Err("unmatched `}`, use `}}` to print `}`".into()),
+            c => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        pieces.push(FormatPiece::Text(text));
+    }
+    Ok(pieces)
+}
+
+/// 格式字符串需要的实参个数
+pub fn count_placeholders(pieces: &[FormatPiece]) -> usize {
+    pieces.iter().filter(|piece| **piece == FormatPiece::Placeholder).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escaped_braces_are_text() {
+        let pieces = parse_format_string("{{x}} = {}").unwrap();
+        assert_eq!(pieces, vec![FormatPiece::Text("{x} = ".into()), FormatPiece::Placeholder]);
+        assert_eq!(parse_format_string("{{}}").unwrap(), vec![FormatPiece::Text("{}".into())]);
+    }
+
+    #[test]
+    fn unterminated_placeholder_is_error() {
+        assert!(parse_format_string("x = {").is_err());
+        assert!(parse_format_string("x = {y}").is_err());
+        assert!(parse_format_string("x = }").is_err());
+    }
+
+    #[test]
+    fn placeholder_count_mismatch() {
+        let pieces = parse_format_string("x = {}, y = {}").unwrap();
+        assert_eq!(count_placeholders(&pieces), 2);
+        assert_eq!(count_placeholders(&parse_format_string("{{}}").unwrap()), 0);
+    }
+}
@@ -1,9 +1,15 @@
-use inkwell::AddressSpace;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use inkwell::{AddressSpace, IntPredicate};
+use inkwell::attributes::{Attribute, AttributeLoc};
 use inkwell::basic_block::BasicBlock;
+use inkwell::builder::Builder;
 use inkwell::context::Context;
 use inkwell::module::{Linkage, Module};
-use inkwell::types::BasicMetadataTypeEnum;
-use inkwell::values::BasicValueEnum;
+use inkwell::targets::TargetData;
+use inkwell::types::{BasicMetadataTypeEnum, BasicType, BasicTypeEnum, IntType, StructType};
+use inkwell::values::{BasicMetadataValueEnum, BasicValue, BasicValueEnum, FunctionValue, IntValue, PointerValue};
 
 use crate::ast::nodes::*;
 use crate::code_generator;
@@ -11,8 +17,67 @@ use crate::code_generator::builder::*;
 use crate::code_generator::builder::binary_operator_builder::{CompareBinaryOperator, LogicBinaryOperator};
 use crate::code_generator::builder::expression::Expression;
 use crate::code_generator::context::LEContext;
-use crate::error::{CompileError, LEError, Result};
-use crate::lexer::{Number, Operator, Position};
+use crate::code_generator::{generic_bound, mangle};
+use crate::code_generator::abi::{CAbi, CAbiLowering, FunctionAbi, PassMode};
+use crate::code_generator::c_header::{ExportedFunction, unsupported_c_type};
+use crate::code_generator::debug_info::DebugInfo;
+use crate::code_generator::format_string::{count_placeholders, FormatPiece, parse_format_string};
+use crate::error::{CompileError, LEError, Result, SyntaxError};
+use crate::lexer::{Number, Operator, Position, SourceMap};
+
+///范围分支包含的值少于这个数量时展开成switch的case，否则生成比较链
+const MATCH_SWITCH_DENSE_LIMIT: i128 = 64;
+
+///程序入口，用户定义的`main`改名后由生成的C入口`main(argc, argv)`调用
+const MAIN_FUNCTION_NAME: &str = "main";
+
+///编译到WebAssembly时，`decl`声明的函数从这个模块导入
+const WASM_IMPORT_MODULE: &str = "env";
+
+///运行时库中`print`和`println`使用的输出函数的前缀，保留的符号名不会和程序中的同名函数冲突
+const RUNTIME_WRITE_PREFIX: &str = "__lelang_write_";
+
+///可以用`llvm_`前缀调用的LLVM内建函数：名字、参数个数和参数的类别
+const INTRINSICS: [(&str, usize, IntrinsicKind); 16] = [
+    ("sqrt", 1, IntrinsicKind::FloatMath),
+    ("fabs", 1, IntrinsicKind::FloatMath),
+    ("pow", 2, IntrinsicKind::FloatMath),
+    ("floor", 1, IntrinsicKind::FloatMath),
+    ("ceil", 1, IntrinsicKind::FloatMath),
+    ("trunc", 1, IntrinsicKind::FloatMath),
+    ("round", 1, IntrinsicKind::FloatMath),
+    ("sin", 1, IntrinsicKind::FloatMath),
+    ("cos", 1, IntrinsicKind::FloatMath),
+    ("exp", 1, IntrinsicKind::FloatMath),
+    ("log", 1, IntrinsicKind::FloatMath),
+    ("minnum", 2, IntrinsicKind::FloatMath),
+    ("maxnum", 2, IntrinsicKind::FloatMath),
+    ("memcpy", 4, IntrinsicKind::MemoryTransfer),
+    ("memmove", 4, IntrinsicKind::MemoryTransfer),
+    ("memset", 4, IntrinsicKind::MemorySet),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IntrinsicKind {
+    ///参数都是同一种浮点数或浮点向量类型，返回值也是这个类型
+    FloatMath,
+    ///`(目标, 源, 字节数, 是否volatile)`
+    MemoryTransfer,
+    ///`(目标, 字节值, 字节数, 是否volatile)`
+    MemorySet,
+}
+
+impl IntrinsicKind {
+    fn describe_param(&self, index: usize) -> &'static str {
+        match (self, index) {
+            (IntrinsicKind::FloatMath, _) => "float or float vector of the same type",
+            (_, 0) | (IntrinsicKind::MemoryTransfer, 1) => "ref u8",
+            (IntrinsicKind::MemorySet, 1) => "u8",
+            (_, 2) => "integer",
+            _ => "bool",
+        }
+    }
+}
 
 macro_rules! le_error {
     ($expr:expr,$pos:expr) => {
@@ -24,6 +89,17 @@ macro_rules! le_error {
 pub struct CodeGenerator<'ctx> {
     pub context: LEContext<'ctx>,
     pub builder: LEBuilder<'ctx>,
+    pub module: Module<'ctx>,
+    ///使用`-g`编译时生成调试信息
+    debug_info: Option<DebugInfo<'ctx>>,
+    ///编译到WebAssembly时，`pub`函数作为wasm模块的导出，`decl`声明的函数作为导入
+    wasm_interface: bool,
+    ///入口文件中`pub`函数和C入口`main`在模块中的名字，输出函数库时只有它们被导出
+    exported_functions: HashSet<String>,
+    ///导出函数的原型，用于生成C头文件
+    exported_prototypes: Vec<ExportedFunction<'ctx>>,
+    ///目标支持时，`decl`声明的函数和导出函数中的结构体和数组按C ABI传递
+    c_abi: Option<CAbiLowering>,
 }
 
 impl<'ctx> CodeGenerator<'ctx> {
@@ -37,31 +113,74 @@ impl<'ctx> CodeGenerator<'ctx> {
             Expr::Identifier(n) => { self.build_identifier_expression(n) }
             Expr::ArrayInitializer(n) => { self.build_array_initializer(n) }
             Expr::StructureInitializer(n) => { self.build_structure_initializer(n) }
-            _ => { unimplemented!() }
-            // Expr::StringLiteral(n) => { Ok(Some(self.build_string_literal(n)?)) }
+            Expr::StringLiteral(n) => { self.build_string_literal(n) }
         }
     }
 
+    /// 字符串字面量保存为以0结尾的全局常量，表达式的值是指向它的str
+    fn build_string_literal(&mut self, value: &StringLiteral) -> Result<Expression<'ctx>> {
+        let i8_type = self.context.llvm_context.i8_type();
+        let bytes = value.content
+            .bytes()
+            .chain(std::iter::once(0))
+            .map(|byte| i8_type.const_int(byte as u64, false))
+            .collect::<Vec<_>>();
+        let content = i8_type.const_array(&bytes);
+        let global = self.module.add_global(content.get_type(), None, "");
+        global.set_initializer(&content);
+        global.set_constant(true);
+        global.set_linkage(Linkage::Private);
+        global.set_unnamed_addr(true);
+        let str_type = self.context.str_type();
+        let pointer = global.as_pointer_value().const_cast(str_type.get_llvm_type());
+        Ok(Expression::Right(LEPointerValue { ty: str_type, llvm_value: pointer }.to_le_value_enum()))
+    }
+
 
     fn build_structure_initializer(&mut self, expr: &StructureInitializer) -> Result<Expression<'ctx>> {
+        //先对成员初始值求值，泛型结构体需要用它们推导类型实参
+        let mut member_values = vec![];
+        for (index, (name, initial_value)) in expr.member_initial_values.iter().enumerate() {
+            //同一个成员不能初始化两次
+            if let Some((_, defined)) = expr.member_initial_values[..index].iter().find(|(defined_name, _)| defined_name == name) {
+                return Err(CompileError::IdentifierAlreadyDefined { identifier: name.clone(), defined_position: defined.pos() }.to_leerror(initial_value.pos()));
+            }
+            let value = self.build_expression(initial_value.as_ref())?;
+            let value = le_error!(self.builder.read_expression(&self.context,value),initial_value.pos())?;
+            member_values.push((name, value, initial_value.pos()));
+        }
         //获取结构体类型
-        let struct_type = self.context.get_generic_type(&TypeDeclarator::TypeIdentifier(expr.structure_name.clone()))
-            .map_err(|e| LEError::new_compile_error(e, expr.structure_name.pos.clone()))?;
+        let struct_type = if self.context.compiler_context.is_generic_structure(&expr.structure_name.name) {
+            self.get_generic_structure_initializer_type(expr, &member_values)?
+        } else {
+            let declarator = if expr.type_arguments.is_empty() {
+                TypeDeclarator::TypeIdentifier(expr.structure_name.clone())
+            } else {
+                TypeDeclarator::Generic(Box::new(GenericDeclarator {
+                    identifier: expr.structure_name.clone(),
+                    type_arguments: expr.type_arguments.clone(),
+                    pos: expr.structure_name.pos(),
+                }))
+            };
+            self.context.get_generic_type(&declarator)
+                .map_err(|e| LEError::new_compile_error(e, expr.structure_name.pos.clone()))?
+        };
         //检查类型是否一致
         if let LEBasicTypeEnum::Struct(struct_type) = struct_type {
-            let initializer_member_num = expr.member_initial_values.len();
+            let initializer_member_num = member_values.len();
             if struct_type.get_llvm_type().get_field_types().len() != initializer_member_num {
                 return Err(CompileError::TypeMismatched { expect: struct_type.to_string(), found: expr.structure_name.name.clone() }.to_leerror(expr.pos()));
             }
             let mut value_array = vec![];
-            for (name, initial_value) in expr.member_initial_values.iter() {
-                let value = self.build_expression(initial_value.as_ref())?;
-                value_array.push((struct_type.get_member_offset(name).unwrap(), value));
+            for (name, value, _) in member_values.into_iter() {
+                let offset = struct_type.get_member_offset(name)
+                    .ok_or_else(|| CompileError::NoSuchMember { member_name: name.clone() }.to_leerror(expr.pos()))?;
+                value_array.push((offset, value));
             }
             value_array.sort_unstable_by(|x, y| x.0.cmp(&y.0));
             let struct_llvm_value = &value_array
                 .into_iter()
-                .map(|x| self.builder.read_expression(&self.context, x.1).unwrap().to_llvm_basic_value_enum()).collect::<Vec<_>>();
+                .map(|x| x.1.to_llvm_basic_value_enum()).collect::<Vec<_>>();
             //创建结构体value
             let struct_value = struct_type.get_llvm_type().const_named_struct(struct_llvm_value);
             Ok(Expression::Right(LEStructValue { ty: struct_type, llvm_value: struct_value }.to_le_value_enum()))
@@ -70,6 +189,30 @@ impl<'ctx> CodeGenerator<'ctx> {
         }
     }
 
+    /// 获取泛型结构体初始化表达式的实例类型，未给出类型实参时按成员的值推导
+    fn get_generic_structure_initializer_type(&mut self, expr: &StructureInitializer, member_values: &[(&String, LEBasicValueEnum<'ctx>, Position)]) -> Result<LEBasicTypeEnum<'ctx>> {
+        let structure = self.context.compiler_context.generic_structures[&expr.structure_name.name].clone();
+        let type_arguments = if expr.type_arguments.is_empty() {
+            let mut bindings = HashMap::new();
+            for (name, value, pos) in member_values.iter() {
+                if let Some((_, declarator)) = structure.members.iter().find(|(member_name, _)| member_name == *name) {
+                    le_error!(self.context.infer_generic_arguments(&structure.generic_params,declarator,&LEBasicValue::get_le_type(value),&mut bindings),pos.clone())?;
+                }
+            }
+            structure.generic_params
+                .iter()
+                .map(|param| bindings.remove(&param.identifier.name)
+                    .ok_or_else(|| CompileError::CannotInferGenericArgument { identifier: param.identifier.name.clone() }.to_leerror(expr.pos())))
+                .collect::<Result<Vec<_>>>()?
+        } else {
+            expr.type_arguments
+                .iter()
+                .map(|argument| le_error!(self.context.get_generic_type(argument),argument.pos()))
+                .collect::<Result<Vec<_>>>()?
+        };
+        le_error!(self.context.instantiate_generic_structure(&expr.structure_name.name,type_arguments),expr.structure_name.pos())
+    }
+
     fn build_unary_operator_expression(&mut self, expr: &UnaryOpExpression) -> Result<Expression<'ctx>> {
         let value = self.build_expression(expr.expr.as_ref())?;
         match expr.op {
@@ -146,107 +289,130 @@ impl<'ctx> CodeGenerator<'ctx> {
     }
 
     fn build_binary_operator_expression(&mut self, value: &BinaryOpExpression) -> Result<Expression<'ctx>> {
+        //成员访问、方法调用和类型转换的右侧不是值，单独生成
+        match value.op {
+            Operator::Dot => {
+                let left = self.build_expression(value.left.as_ref())?;
+                return if let Expr::Identifier(identifier) = value.right.as_ref() {
+                    Ok(Expression::Left(le_error!(self.builder.build_dot(&self.context,left, &identifier.name),value.pos())?))
+                } else if let Expr::CallExpression(method_call) = value.right.as_ref() {
+                    self.build_method_call_expression(left, value.left.pos(), method_call)
+                } else {
+                    Err(CompileError::NoSuitableBinaryOperator {
+                        op: Operator::Dot,
+                        left_type: "".to_string(),
+                        right_type: "".to_string(),
+                    }.to_leerror(value.pos.clone()))
+                };
+            }
+            Operator::Cast => {
+                let left = self.build_expression(value.left.as_ref())?;
+                return if let Expr::Identifier(type_identifier) = value.right.as_ref() {
+                    let ty = le_error!(self.context.get_generic_type(&TypeDeclarator::TypeIdentifier(type_identifier.clone())),type_identifier.pos())?;
+                    Ok(Expression::Right(le_error!(self.builder.build_cast(&self.context,left,ty),value.right.pos())?))
+                } else {
+                    Err(CompileError::ExpressionIsNotType { pos: value.right.pos() }.to_leerror(value.right.pos()))
+                };
+            }
+            _ => {}
+        }
+        let left = self.build_expression(value.left.as_ref())?;
+        let right = self.build_expression(value.right.as_ref())?;
+        //结构体优先调用用户定义的运算符函数
+        if let Some(result) = self.build_operator_function_call(value, &left, &right)? {
+            return Ok(result);
+        }
         //针对不同运算符调用不同的生成函数
         match value.op {
             Operator::Plus => {
-                let left = self.build_expression(value.left.as_ref())?;
-                let right = self.build_expression(value.right.as_ref())?;
                 Ok(Expression::Right(le_error!(self.builder.build_add(&self.context,left, right),value.pos())?))
             }
             Operator::Sub => {
-                let left = self.build_expression(value.left.as_ref())?;
-                let right = self.build_expression(value.right.as_ref())?;
                 Ok(Expression::Right(le_error!(self.builder.build_sub(&self.context,left, right),value.pos())?))
             }
             Operator::Mul => {
-                let left = self.build_expression(value.left.as_ref())?;
-                let right = self.build_expression(value.right.as_ref())?;
                 Ok(Expression::Right(le_error!(self.builder.build_mul(&self.context,left, right),value.pos())?))
             }
             Operator::Div => {
-                let left = self.build_expression(value.left.as_ref())?;
-                let right = self.build_expression(value.right.as_ref())?;
                 Ok(Expression::Right(le_error!(self.builder.build_div(&self.context,left, right),value.pos())?))
             }
+            Operator::Mod => {
+                Ok(Expression::Right(le_error!(self.builder.build_mod(&self.context,left, right),value.pos())?.to_le_value_enum()))
+            }
             Operator::Assign => {
-                let left = self.build_expression(value.left.as_ref())?;
-                let right = self.build_expression(value.right.as_ref())?;
                 Ok(Expression::Left(le_error!(self.builder.build_assign(&self.context,left, right),value.pos())?))
             }
             Operator::Equal => {
-                let left = self.build_expression(value.left.as_ref())?;
-                let right = self.build_expression(value.right.as_ref())?;
                 Ok(Expression::Right(le_error!(self.builder.build_compare(&self.context,left, right, CompareBinaryOperator::Equal),value.pos())?.to_le_value_enum()))
             }
             Operator::NotEqual => {
-                let left = self.build_expression(value.left.as_ref())?;
-                let right = self.build_expression(value.right.as_ref())?;
                 Ok(Expression::Right(le_error!(self.builder.build_compare(&self.context,left, right, CompareBinaryOperator::NotEqual), value.pos())?.to_le_value_enum()))
             }
             Operator::GreaterThan => {
-                let left = self.build_expression(value.left.as_ref())?;
-                let right = self.build_expression(value.right.as_ref())?;
                 Ok(Expression::Right(le_error!(self.builder.build_compare(&self.context,left, right, CompareBinaryOperator::GreaterThan), value.pos())?.to_le_value_enum()))
             }
             Operator::LessThan => {
-                let left = self.build_expression(value.left.as_ref())?;
-                let right = self.build_expression(value.right.as_ref())?;
                 Ok(Expression::Right(le_error!(self.builder.build_compare(&self.context,left, right, CompareBinaryOperator::LessThan), value.pos())?.to_le_value_enum()))
             }
             Operator::GreaterOrEqualThan => {
-                let left = self.build_expression(value.left.as_ref())?;
-                let right = self.build_expression(value.right.as_ref())?;
                 Ok(Expression::Right(le_error!(self.builder.build_compare(&self.context,left, right, CompareBinaryOperator::GreaterOrEqualThan),value.pos())?.to_le_value_enum()))
             }
             Operator::LessOrEqualThan => {
-                let left = self.build_expression(value.left.as_ref())?;
-                let right = self.build_expression(value.right.as_ref())?;
                 Ok(Expression::Right(le_error!(self.builder.build_compare(&self.context,left, right, CompareBinaryOperator::LessOrEqualThan),value.pos())?.to_le_value_enum()))
             }
-            Operator::Dot => {
-                let left = self.build_expression(value.left.as_ref())?;
-                if let Expr::Identifier(identifier) = value.right.as_ref() {
-                    Ok(Expression::Left(le_error!(self.builder.build_dot(&self.context,left, &identifier.name),value.pos())?))
-                } else {
-                    Err(CompileError::NoSuitableBinaryOperator {
-                        op: Operator::Dot,
-                        left_type: "".to_string(),
-                        right_type: "".to_string(),
-                    }.to_leerror(value.pos.clone()))
-                }
-            }
             Operator::And => {
-                let left = self.build_expression(value.left.as_ref())?;
-                let right = self.build_expression(value.right.as_ref())?;
                 Ok(Expression::Right(le_error!(self.builder.build_binary_logic(&self.context,left, right, LogicBinaryOperator::And),value.pos())?.to_le_value_enum()))
             }
             Operator::Or => {
-                let left = self.build_expression(value.left.as_ref())?;
-                let right = self.build_expression(value.right.as_ref())?;
                 Ok(Expression::Right(le_error!(self.builder.build_binary_logic(&self.context,left, right, LogicBinaryOperator::Or),value.pos())?.to_le_value_enum()))
             }
             Operator::Xor => {
-                let left = self.build_expression(value.left.as_ref())?;
-                let right = self.build_expression(value.right.as_ref())?;
                 Ok(Expression::Right(le_error!(self.builder.build_binary_logic(&self.context,left, right, LogicBinaryOperator::Xor),value.pos())?.to_le_value_enum()))
             }
+            _ => { unimplemented!() }
+        }
+    }
 
-            Operator::Mod => {
-                let left = self.build_expression(value.left.as_ref())?;
-                let right = self.build_expression(value.right.as_ref())?;
-                Ok(Expression::Right(le_error!(self.builder.build_mod(&self.context,left, right),value.pos())?.to_le_value_enum()))
-            }
-            Operator::Cast => {
-                let left = self.build_expression(value.left.as_ref())?;
-                if let Expr::Identifier(type_identifier) = value.right.as_ref() {
-                    let ty = le_error!(self.context.get_generic_type(&TypeDeclarator::TypeIdentifier(type_identifier.clone())),type_identifier.pos())?;
-                    Ok(Expression::Right(le_error!(self.builder.build_cast(&self.context,left,ty),value.right.pos())?))
-                } else {
-                    Err(CompileError::ExpressionIsNotType { pos: value.right.pos() }.to_leerror(value.right.pos()))
-                }
+    /// 结构体的运算符优先调用用户定义的运算符函数，如`+`对应`__add`
+    /// 运算符函数可以定义在结构体的impl块或trait实现中，也可以是参数类型匹配的全局函数
+    fn build_operator_function_call(&mut self, value: &BinaryOpExpression, left: &Expression<'ctx>, right: &Expression<'ctx>) -> Result<Option<Expression<'ctx>>> {
+        let left_type = match left {
+            Expression::Left(pointer) => pointer.ty.get_point_type(),
+            Expression::Right(value) => LEBasicValue::get_le_type(value),
+            Expression::Unit => return Ok(None),
+        };
+        if !matches!(left_type, LEBasicTypeEnum::Struct(_)) {
+            return Ok(None);
+        }
+        let function_name = match mangle::operator_function_name(&value.op) {
+            Some(function_name) => function_name,
+            None => return Ok(None),
+        };
+        let params = [left.clone(), right.clone()];
+        if let Some(function) = self.find_operator_function(&left_type, function_name) {
+            return self.builder.build_call(&self.context, function, &params).map(Some).map_err(|e| e.to_leerror(value.pos()));
+        }
+        //没有定义`__ne`时对`__eq`的结果取反
+        if let (Operator::NotEqual, Some(function)) = (&value.op, self.find_operator_function(&left_type, "__eq")) {
+            let equal = le_error!(self.builder.build_call(&self.context,function,&params),value.pos())?;
+            let equal = le_error!(self.builder.read_expression(&self.context,equal),value.pos())?;
+            if let LEBasicValueEnum::Bool(equal) = equal {
+                let not_equal = self.builder.llvm_builder.build_not(equal.llvm_value, "");
+                return Ok(Some(Expression::Right(LEBoolValue { ty: equal.ty, llvm_value: not_equal }.to_le_value_enum())));
             }
-            _ => { unimplemented!() }
+            return Err(CompileError::TypeMismatched { expect: "bool".into(), found: LEBasicValue::get_le_type(&equal).to_string() }.to_leerror(value.pos()));
         }
+        Ok(None)
+    }
+
+    fn find_operator_function(&self, left_type: &LEBasicTypeEnum<'ctx>, function_name: &str) -> Option<LEFunctionValue<'ctx>> {
+        let method_name = mangle::method_symbol_name(&left_type.to_string(), function_name);
+        if let Ok(function) = self.context.compiler_context.get_function(&method_name) {
+            return Some(function);
+        }
+        self.context.compiler_context.get_function(function_name)
+            .ok()
+            .filter(|function| function.ty.param_types().first() == Some(left_type))
     }
 
     fn build_identifier_expression(&mut self, value: &Identifier) -> Result<Expression<'ctx>> {
@@ -273,8 +439,46 @@ impl<'ctx> CodeGenerator<'ctx> {
     }
 
     fn build_call_expression(&mut self, value: &FunctionCall) -> Result<Expression<'ctx>> {
+        if self.context.compiler_context.is_generic_function(&value.function_name.name) {
+            return self.build_generic_call_expression(value);
+        }
+        //用户没有定义同名函数时，`ptr_`开头的函数和`size_of`是指针的内建操作，可以带类型实参
+        let is_user_function = self.context.compiler_context.get_function(&value.function_name.name).is_ok();
+        if !is_user_function {
+            if let Some(result) = self.build_pointer_builtin_call(value)? {
+                return Ok(result);
+            }
+        }
+        if !value.type_arguments.is_empty() {
+            return Err(CompileError::GenericArgumentNumberMismatched {
+                identifier: value.function_name.name.clone(),
+                expect: 0,
+                found: value.type_arguments.len(),
+            }.to_leerror(value.function_name.pos()));
+        }
+        //`vec_`开头的函数是向量的内建操作，`llvm_`开头的函数是LLVM内建函数，`print`和`println`是格式化输出
+        if !is_user_function {
+            if let Some(result) = self.build_vector_builtin_call(value)? {
+                return Ok(result);
+            }
+            if let Some(result) = self.build_intrinsic_call(value)? {
+                return Ok(result);
+            }
+            if let Some(result) = self.build_print_call(value)? {
+                return Ok(result);
+            }
+        }
         //从符号表查找函数
         let function = le_error!(self.context.compiler_context.get_function(&value.function_name.name),value.function_name.pos())?;
+        //可变参数函数的实参不能少于固定参数
+        let fixed_params = function.ty.param_types().len();
+        if function.ty.is_variadic() && value.params.len() < fixed_params {
+            return Err(CompileError::ArgumentNumberMismatched {
+                identifier: value.function_name.name.clone(),
+                expect: fixed_params,
+                found: value.params.len(),
+            }.to_leerror(value.pos.clone()));
+        }
         let mut params = vec![];
         //对所有实参求值
         for param in value.params.iter() {
@@ -284,6 +488,562 @@ impl<'ctx> CodeGenerator<'ctx> {
         self.builder.build_call(&self.context, function, &params).map_err(|e| e.to_leerror(value.pos.clone()))
     }
 
+    /// 向量的内建操作：
+    /// `vec_from(array)`、`vec_splat(value, lanes)`、`vec_extract(v, index)`、`vec_insert(v, index, value)`、
+    /// `vec_shuffle(a, b, [mask])`以及`vec_reduce_add/mul/min/max(v)`，不是内建操作时返回None
+    fn build_vector_builtin_call(&mut self, value: &FunctionCall) -> Result<Option<Expression<'ctx>>> {
+        let name = value.function_name.name.as_str();
+        let expect = match name {
+            "vec_from" | "vec_reduce_add" | "vec_reduce_mul" | "vec_reduce_min" | "vec_reduce_max" => 1,
+            "vec_splat" | "vec_extract" => 2,
+            "vec_insert" | "vec_shuffle" => 3,
+            _ => return Ok(None),
+        };
+        if value.params.len() != expect {
+            return Err(CompileError::ArgumentNumberMismatched {
+                identifier: name.into(),
+                expect,
+                found: value.params.len(),
+            }.to_leerror(value.pos.clone()));
+        }
+        let result = match name {
+            "vec_from" => {
+                let array = self.build_vector_builtin_argument(&value.params[0])?;
+                let array = array.clone().into_array_value().ok_or_else(|| CompileError::TypeMismatched {
+                    expect: "array".into(),
+                    found: LEBasicValue::get_le_type(&array).to_string(),
+                }.to_leerror(value.params[0].pos()))?;
+                le_error!(self.builder.build_vector_from_array(&self.context,array),value.params[0].pos())?.to_le_value_enum()
+            }
+            "vec_splat" => {
+                let element = self.build_vector_builtin_argument(&value.params[0])?;
+                let len = self.get_lane_literal(&value.params[1])?;
+                //与`vec<T;N>`相同，通道数在1到`u32::MAX`之间，不能截断
+                let lanes = match u32::try_from(len) {
+                    Ok(lanes) if lanes > 0 => lanes,
+                    _ => return Err(SyntaxError::InvalidVectorLength { len }.to_leerror(value.params[1].pos())),
+                };
+                le_error!(self.builder.build_vector_splat(&self.context,element,lanes),value.pos.clone())?.to_le_value_enum()
+            }
+            "vec_extract" => {
+                let vector = self.build_vector_argument(&value.params[0])?;
+                let index = self.build_lane_index_argument(&vector, &value.params[1])?;
+                le_error!(self.builder.build_vector_extract(&self.context,vector,index),value.pos.clone())?
+            }
+            "vec_insert" => {
+                let vector = self.build_vector_argument(&value.params[0])?;
+                let index = self.build_lane_index_argument(&vector, &value.params[1])?;
+                let element = self.build_vector_builtin_argument(&value.params[2])?;
+                le_error!(self.builder.build_vector_insert(&self.context,vector,index,element),value.params[2].pos())?.to_le_value_enum()
+            }
+            "vec_shuffle" => {
+                let left = self.build_vector_argument(&value.params[0])?;
+                let right = self.build_vector_argument(&value.params[1])?;
+                //掩码必须是整数字面量组成的数组
+                //掩码中的序号在对应的字面量处检查范围，超出u32的序号不能被截断
+                let lanes = left.ty.len().saturating_mul(2);
+                let mask = if let Expr::ArrayInitializer(mask) = &value.params[2] {
+                    mask.elements.iter().map(|element| {
+                        let index = self.get_lane_literal(element)?;
+                        u32::try_from(index).ok().filter(|index| *index < lanes)
+                            .ok_or_else(|| CompileError::VectorLaneOutOfRange { index, lanes }.to_leerror(element.pos()))
+                    }).collect::<Result<Vec<_>>>()?
+                } else {
+                    return Err(CompileError::TypeMismatched { expect: "array literal".into(), found: "expression".into() }.to_leerror(value.params[2].pos()));
+                };
+                le_error!(self.builder.build_vector_shuffle(&self.context,left,right,&mask),value.params[2].pos())?.to_le_value_enum()
+            }
+            _ => {
+                let op = match name {
+                    "vec_reduce_add" => VectorReduceOperator::Add,
+                    "vec_reduce_mul" => VectorReduceOperator::Mul,
+                    "vec_reduce_min" => VectorReduceOperator::Min,
+                    _ => VectorReduceOperator::Max,
+                };
+                let vector = self.build_vector_argument(&value.params[0])?;
+                le_error!(self.builder.build_vector_reduce(&self.context,&self.module,vector,op),value.params[0].pos())?
+            }
+        };
+        Ok(Some(Expression::Right(result)))
+    }
+
+    fn build_vector_builtin_argument(&mut self, param: &Expr) -> Result<LEBasicValueEnum<'ctx>> {
+        let expression = self.build_expression(param)?;
+        le_error!(self.builder.read_expression(&self.context,expression),param.pos())
+    }
+
+    fn build_vector_argument(&mut self, param: &Expr) -> Result<LEVectorValue<'ctx>> {
+        let vector = self.build_vector_builtin_argument(param)?;
+        vector.clone().into_vector_value().ok_or_else(|| CompileError::TypeMismatched {
+            expect: "vector".into(),
+            found: LEBasicValue::get_le_type(&vector).to_string(),
+        }.to_leerror(param.pos()))
+    }
+
+    /// 通道序号是字面量时在编译期检查范围
+    fn build_lane_index_argument(&mut self, vector: &LEVectorValue<'ctx>, param: &Expr) -> Result<LEIntegerValue<'ctx>> {
+        if let Expr::NumberLiteral(NumberLiteral { number: Number::Integer(index), pos }) = param {
+            if *index >= vector.ty.len() as u64 {
+                return Err(CompileError::VectorLaneOutOfRange { index: *index, lanes: vector.ty.len() }.to_leerror(pos.clone()));
+            }
+        }
+        let index = self.build_vector_builtin_argument(param)?;
+        index.clone().into_int_value().ok_or_else(|| CompileError::TypeMismatched {
+            expect: self.context.i32_type().to_string(),
+            found: LEBasicValue::get_le_type(&index).to_string(),
+        }.to_leerror(param.pos()))
+    }
+
+    fn get_lane_literal(&self, param: &Expr) -> Result<u64> {
+        if let Expr::NumberLiteral(NumberLiteral { number: Number::Integer(index), .. }) = param {
+            Ok(*index)
+        } else {
+            Err(CompileError::TypeMismatched { expect: "integer literal".into(), found: "expression".into() }.to_leerror(param.pos()))
+        }
+    }
+
+    /// 指针的内建操作：
+    /// `ptr_offset(p, n)`、`ptr_read(p)`、`ptr_write(p, value)`、`ptr_cast::<T>(p)`以及`size_of::<T>()`，不是内建操作时返回None
+    fn build_pointer_builtin_call(&mut self, value: &FunctionCall) -> Result<Option<Expression<'ctx>>> {
+        let name = value.function_name.name.as_str();
+        let (expect, expect_type_arguments) = match name {
+            "ptr_read" => (1, 0),
+            "ptr_offset" | "ptr_write" => (2, 0),
+            "ptr_cast" => (1, 1),
+            "size_of" => (0, 1),
+            _ => return Ok(None),
+        };
+        if value.params.len() != expect {
+            return Err(CompileError::ArgumentNumberMismatched {
+                identifier: name.into(),
+                expect,
+                found: value.params.len(),
+            }.to_leerror(value.pos.clone()));
+        }
+        if value.type_arguments.len() != expect_type_arguments {
+            return Err(CompileError::GenericArgumentNumberMismatched {
+                identifier: name.into(),
+                expect: expect_type_arguments,
+                found: value.type_arguments.len(),
+            }.to_leerror(value.function_name.pos()));
+        }
+        let type_argument = match value.type_arguments.first() {
+            Some(argument) => Some(le_error!(self.context.get_generic_type(argument),argument.pos())?),
+            None => None,
+        };
+        let result = match name {
+            "ptr_read" => {
+                let pointer = self.build_pointer_argument(&value.params[0])?;
+                Expression::Right(self.builder.build_load(&self.context, pointer))
+            }
+            "ptr_write" => {
+                let pointer = self.build_pointer_argument(&value.params[0])?;
+                let element = self.build_vector_builtin_argument(&value.params[1])?;
+                let element_type = LEBasicValue::get_le_type(&element);
+                if element_type != pointer.ty.get_point_type() {
+                    return Err(CompileError::TypeMismatched {
+                        expect: pointer.ty.get_point_type().to_string(),
+                        found: element_type.to_string(),
+                    }.to_leerror(value.params[1].pos()));
+                }
+                le_error!(self.builder.build_store(&self.context,pointer,element),value.pos.clone())?;
+                Expression::Unit
+            }
+            "ptr_offset" => {
+                let pointer = self.build_pointer_argument(&value.params[0])?;
+                let offset = self.build_vector_builtin_argument(&value.params[1])?;
+                let offset = offset.clone().into_int_value().ok_or_else(|| CompileError::TypeMismatched {
+                    expect: self.context.i64_type().to_string(),
+                    found: LEBasicValue::get_le_type(&offset).to_string(),
+                }.to_leerror(value.params[1].pos()))?;
+                Expression::Right(self.builder.build_pointer_offset(&self.context, pointer, offset).to_le_value_enum())
+            }
+            "ptr_cast" => {
+                let pointer = self.build_pointer_argument(&value.params[0])?;
+                Expression::Right(self.builder.build_pointer_cast(&self.context, pointer, type_argument.unwrap()).to_le_value_enum())
+            }
+            _ => {
+                let size = type_argument.unwrap().get_llvm_basic_type().size_of().unwrap();
+                Expression::Right(LEIntegerValue { ty: self.context.u64_type(), llvm_value: size }.to_le_value_enum())
+            }
+        };
+        Ok(Some(result))
+    }
+
+    fn build_pointer_argument(&mut self, param: &Expr) -> Result<LEPointerValue<'ctx>> {
+        let pointer = self.build_vector_builtin_argument(param)?;
+        pointer.clone().into_pointer_value().ok_or_else(|| CompileError::TypeMismatched {
+            expect: "reference".into(),
+            found: LEBasicValue::get_le_type(&pointer).to_string(),
+        }.to_leerror(param.pos()))
+    }
+
+    /// 以`llvm_`为前缀调用LLVM内建函数，如`llvm_sqrt(x)`调用`llvm.sqrt.f64`，不是支持的内建函数时返回None
+    fn build_intrinsic_call(&mut self, value: &FunctionCall) -> Result<Option<Expression<'ctx>>> {
+        let intrinsic = match value.function_name.name.strip_prefix("llvm_") {
+            Some(intrinsic) => intrinsic,
+            None => return Ok(None),
+        };
+        let (expect, kind) = match INTRINSICS.iter().find(|(name, _, _)| *name == intrinsic) {
+            Some((_, expect, kind)) => (*expect, *kind),
+            None => return Ok(None),
+        };
+        if value.params.len() != expect {
+            return Err(CompileError::ArgumentNumberMismatched {
+                identifier: value.function_name.name.clone(),
+                expect,
+                found: value.params.len(),
+            }.to_leerror(value.pos.clone()));
+        }
+        let args = value.params.iter().map(|param| self.build_vector_builtin_argument(param)).collect::<Result<Vec<_>>>()?;
+        let arg_types = args.iter().map(LEBasicValue::get_le_type).collect::<Vec<_>>();
+        //检查实参类型，得到参与名字修饰的类型和返回类型
+        let bool_type = self.context.bool_type().to_le_type_enum();
+        let is_byte_pointer = |ty: &LEBasicTypeEnum<'ctx>| matches!(ty, LEBasicTypeEnum::Pointer(pointer) if matches!(pointer.get_point_type(), LEBasicTypeEnum::Integer(element) if element.get_llvm_type().get_bit_width() == 8));
+        let is_integer = |ty: &LEBasicTypeEnum<'ctx>| matches!(ty, LEBasicTypeEnum::Integer(_));
+        let (expect_types, overloaded_types, return_type) = match kind {
+            IntrinsicKind::FloatMath => {
+                let is_float = match &arg_types[0] {
+                    LEBasicTypeEnum::Float(_) => true,
+                    LEBasicTypeEnum::Vector(vector) => matches!(vector.get_element_type(), LEBasicTypeEnum::Float(_)),
+                    _ => false
+                };
+                let checks = arg_types.iter().map(|ty| is_float && ty == &arg_types[0]).collect::<Vec<_>>();
+                (checks, vec![arg_types[0].clone()], Some(arg_types[0].clone()))
+            }
+            IntrinsicKind::MemoryTransfer => {
+                let checks = vec![is_byte_pointer(&arg_types[0]), is_byte_pointer(&arg_types[1]), is_integer(&arg_types[2]), arg_types[3] == bool_type];
+                (checks, vec![arg_types[0].clone(), arg_types[1].clone(), arg_types[2].clone()], None)
+            }
+            IntrinsicKind::MemorySet => {
+                let is_byte = matches!(&arg_types[1], LEBasicTypeEnum::Integer(t) if t.get_llvm_type().get_bit_width() == 8);
+                let checks = vec![is_byte_pointer(&arg_types[0]), is_byte, is_integer(&arg_types[2]), arg_types[3] == bool_type];
+                (checks, vec![arg_types[0].clone(), arg_types[2].clone()], None)
+            }
+        };
+        if let Some(index) = expect_types.iter().position(|is_valid| !is_valid) {
+            return Err(CompileError::TypeMismatched {
+                expect: kind.describe_param(index).into(),
+                found: arg_types[index].to_string(),
+            }.to_leerror(value.params[index].pos()));
+        }
+        let result = le_error!(self.builder.build_intrinsic_call(&self.module,intrinsic,&overloaded_types,&args,return_type),value.pos.clone())?;
+        Ok(Some(result.map(Expression::Right).unwrap_or(Expression::Unit)))
+    }
+
+    /// 内建的`print`和`println`：格式字符串在编译期解析，每个`{}`按对应实参的类型调用运行时库的输出函数，
+    /// 不是内建函数时返回None
+    fn build_print_call(&mut self, value: &FunctionCall) -> Result<Option<Expression<'ctx>>> {
+        let is_println = match value.function_name.name.as_str() {
+            "print" => false,
+            "println" => true,
+            _ => return Ok(None),
+        };
+        let format = match value.params.first() {
+            Some(Expr::StringLiteral(format)) => format,
+            Some(param) => {
+                return Err(CompileError::InvalidFormatString { reason: "format string is not a string literal".into() }.to_leerror(param.pos()));
+            }
+            None => {
+                return Err(CompileError::ArgumentNumberMismatched {
+                    identifier: value.function_name.name.clone(),
+                    expect: 1,
+                    found: 0,
+                }.to_leerror(value.pos.clone()));
+            }
+        };
+        let pieces = parse_format_string(&format.content)
+            .map_err(|reason| CompileError::InvalidFormatString { reason }.to_leerror(format.pos.clone()))?;
+        let params = &value.params[1..];
+        let expect = count_placeholders(&pieces);
+        if expect != params.len() {
+            //实参多了指向第一个多余的实参，少了指向格式字符串
+            let pos = params.get(expect).map(Expr::pos).unwrap_or_else(|| format.pos.clone());
+            return Err(CompileError::FormatArgumentNumberMismatched { expect, found: params.len() }.to_leerror(pos));
+        }
+        //先对所有实参求值再输出，实参中的输出不会插入到格式字符串中间
+        let args = params.iter().map(|param| self.build_vector_builtin_argument(param)).collect::<Result<Vec<_>>>()?;
+        let mut args = args.into_iter().zip(params.iter());
+        for piece in pieces.iter() {
+            match piece {
+                FormatPiece::Text(text) => self.build_write_text(text, &format.pos)?,
+                FormatPiece::Placeholder => {
+                    let (arg, param) = args.next().unwrap();
+                    self.build_write_value(arg, &param.pos())?;
+                }
+            }
+        }
+        if is_println {
+            self.build_write_text("\n", &format.pos)?;
+        }
+        Ok(Some(Expression::Unit))
+    }
+
+    fn build_write_text(&mut self, text: &str, pos: &Position) -> Result<()> {
+        let text = self.build_string_literal(&StringLiteral { content: text.into(), pos: pos.clone() })?;
+        let text = le_error!(self.builder.read_expression(&self.context,text),pos.clone())?;
+        self.build_write_call("str", text)
+    }
+
+    /// 调用运行时库的输出函数，如`kind`为`str`时调用`__lelang_write_str`。
+    /// 这些函数不经过符号表，直接按保留的符号名声明在模块中，程序中定义的同名函数不会被调用
+    fn build_write_call(&mut self, kind: &str, argument: LEBasicValueEnum<'ctx>) -> Result<()> {
+        let llvm_name = format!("{}{}", RUNTIME_WRITE_PREFIX, kind);
+        let argument = argument.to_llvm_basic_value_enum();
+        let function = match self.module.get_function(&llvm_name) {
+            Some(function) => function,
+            None => {
+                let function_type = self.context.llvm_context.void_type().fn_type(&[argument.get_type().into()], false);
+                let function = self.module.add_function(&llvm_name, function_type, Some(Linkage::External));
+                if self.wasm_interface {
+                    self.add_string_attribute(function, "wasm-import-module", WASM_IMPORT_MODULE);
+                    self.add_string_attribute(function, "wasm-import-name", &llvm_name);
+                }
+                function
+            }
+        };
+        self.builder.llvm_builder.build_call(function, &[argument.into()], "");
+        Ok(())
+    }
+
+    /// 按值的类型选择运行时库的输出函数，整数和浮点数扩展为64位输出，
+    /// 数组和向量在循环中逐个输出元素为`[a, b]`，结构体的成员类型各不相同，按成员输出为`Name { a: 1, b: 2 }`
+    fn build_write_value(&mut self, value: LEBasicValueEnum<'ctx>, pos: &Position) -> Result<()> {
+        let llvm_builder = &self.builder.llvm_builder;
+        match value {
+            LEBasicValueEnum::Integer(integer) => {
+                let (function_name, ty, llvm_value) = if integer.ty.signed() {
+                    let ty = self.context.i64_type();
+                    ("int64", ty.clone(), llvm_builder.build_int_s_extend_or_bit_cast(integer.llvm_value, ty.get_llvm_type(), ""))
+                } else {
+                    let ty = self.context.u64_type();
+                    ("uint64", ty.clone(), llvm_builder.build_int_z_extend_or_bit_cast(integer.llvm_value, ty.get_llvm_type(), ""))
+                };
+                self.build_write_call(function_name, LEIntegerValue { ty, llvm_value }.to_le_value_enum())
+            }
+            LEBasicValueEnum::Float(float) => {
+                let ty = self.context.double_type();
+                let llvm_value = llvm_builder.build_float_ext(float.llvm_value, ty.get_llvm_type(), "");
+                self.build_write_call("float64", LEFloatValue { ty, llvm_value }.to_le_value_enum())
+            }
+            LEBasicValueEnum::Bool(_) => self.build_write_call("bool", value),
+            LEBasicValueEnum::Pointer(pointer) => {
+                //str按字符串输出，其他引用(包括ref u8)输出地址
+                if pointer.ty.is_str() {
+                    self.build_write_call("str", pointer.to_le_value_enum())
+                } else {
+                    let pointer = self.builder.build_pointer_cast(&self.context, pointer, self.context.u8_type().to_le_type_enum());
+                    self.build_write_call("pointer", pointer.to_le_value_enum())
+                }
+            }
+            LEBasicValueEnum::Array(array) => {
+                //数组存入临时变量，循环中按下标读取元素
+                let pointer = build_entry_alloca(llvm_builder, array.ty.get_llvm_type());
+                llvm_builder.build_store(pointer, array.llvm_value);
+                self.build_write_elements(array.ty.get_element_type(), array.ty.len(), pos, |llvm_builder, index| {
+                    let indices = [index.get_type().const_zero(), index];
+                    let element = unsafe { llvm_builder.build_in_bounds_gep(pointer, &indices, "") };
+                    llvm_builder.build_load(element, "")
+                })
+            }
+            LEBasicValueEnum::Vector(vector) => {
+                let llvm_value = vector.llvm_value;
+                self.build_write_elements(vector.ty.get_element_type(), vector.ty.len(), pos, |llvm_builder, index| {
+                    llvm_builder.build_extract_element(llvm_value, index, "")
+                })
+            }
+            LEBasicValueEnum::Struct(structure) => {
+                let members = structure.ty.get_member_names().into_iter().zip(structure.ty.get_member_types().into_iter()).collect::<Vec<_>>();
+                self.build_write_text(&format!("{} {{ ", structure.ty), pos)?;
+                for (index, (name, member_type)) in members.into_iter().enumerate() {
+                    if index > 0 {
+                        self.build_write_text(", ", pos)?;
+                    }
+                    self.build_write_text(&format!("{}: ", name), pos)?;
+                    let member = self.builder.llvm_builder.build_extract_value(structure.llvm_value, index as u32, "").unwrap();
+                    let member = le_error!(LEBasicValueEnum::from_type_and_llvm_value(member_type,member),pos.clone())?;
+                    self.build_write_value(member, pos)?;
+                }
+                self.build_write_text(" }", pos)
+            }
+        }
+    }
+
+    /// 输出`[a, b]`，元素在循环中由`element`按下标读取后输出，不随元素个数展开
+    fn build_write_elements(
+        &mut self,
+        element_type: LEBasicTypeEnum<'ctx>,
+        len: u32,
+        pos: &Position,
+        element: impl Fn(&Builder<'ctx>, IntValue<'ctx>) -> BasicValueEnum<'ctx>,
+    ) -> Result<()> {
+        self.build_write_text("[", pos)?;
+        if len > 0 {
+            let llvm_context = self.context.llvm_context;
+            let index_type = llvm_context.i64_type();
+            let preheader = self.builder.llvm_builder.get_insert_block().unwrap();
+            let function = preheader.get_parent().unwrap();
+            let loop_block = llvm_context.append_basic_block(function, "write_elements");
+            let separator_block = llvm_context.append_basic_block(function, "write_separator");
+            let element_block = llvm_context.append_basic_block(function, "write_element");
+            let end_block = llvm_context.append_basic_block(function, "write_elements_end");
+            self.builder.llvm_builder.build_unconditional_branch(loop_block);
+
+            //第一个元素之前不输出分隔符
+            self.builder.llvm_builder.position_at_end(loop_block);
+            let index = self.builder.llvm_builder.build_phi(index_type, "");
+            let index_value = index.as_basic_value().into_int_value();
+            let is_first = self.builder.llvm_builder.build_int_compare(IntPredicate::EQ, index_value, index_type.const_zero(), "");
+            self.builder.llvm_builder.build_conditional_branch(is_first, element_block, separator_block);
+            self.builder.llvm_builder.position_at_end(separator_block);
+            self.build_write_text(", ", pos)?;
+            self.builder.llvm_builder.build_unconditional_branch(element_block);
+
+            self.builder.llvm_builder.position_at_end(element_block);
+            let value = element(&self.builder.llvm_builder, index_value);
+            let value = le_error!(LEBasicValueEnum::from_type_and_llvm_value(element_type,value),pos.clone())?;
+            self.build_write_value(value, pos)?;
+            //元素为数组时内层循环会新建基本块，回边从当前插入的基本块出发
+            let next = self.builder.llvm_builder.build_int_add(index_value, index_type.const_int(1, false), "");
+            let latch = self.builder.llvm_builder.get_insert_block().unwrap();
+            index.add_incoming(&[(&index_type.const_zero(), preheader), (&next, latch)]);
+            let finished = self.builder.llvm_builder.build_int_compare(IntPredicate::EQ, next, index_type.const_int(len as u64, false), "");
+            self.builder.llvm_builder.build_conditional_branch(finished, end_block, loop_block);
+            self.builder.llvm_builder.position_at_end(end_block);
+        }
+        self.build_write_text("]", pos)
+    }
+
+    /// 调用泛型函数，类型实参由`::<>`显式给出或从实参类型推导
+    fn build_generic_call_expression(&mut self, value: &FunctionCall) -> Result<Expression<'ctx>> {
+        let definition = self.context.compiler_context.generic_functions[&value.function_name.name].clone();
+        let generic_params = &definition.prototype.generic_params;
+        //对所有实参求值
+        let mut params = vec![];
+        let mut param_types = vec![];
+        for param in value.params.iter() {
+            let expression = self.build_expression(param)?;
+            let param_value = le_error!(self.builder.read_expression(&self.context,expression),param.pos())?;
+            param_types.push(LEBasicValue::get_le_type(&param_value));
+            params.push(Expression::Right(param_value));
+        }
+        let type_arguments = if value.type_arguments.is_empty() {
+            let mut bindings = HashMap::new();
+            for ((declarator, actual), param) in definition.prototype.param_types.iter().zip(param_types.iter()).zip(value.params.iter()) {
+                le_error!(self.context.infer_generic_arguments(generic_params,declarator,actual,&mut bindings),param.pos())?;
+            }
+            generic_params
+                .iter()
+                .map(|param| bindings.remove(&param.identifier.name)
+                    .ok_or_else(|| CompileError::CannotInferGenericArgument { identifier: param.identifier.name.clone() }.to_leerror(value.pos.clone())))
+                .collect::<Result<Vec<_>>>()?
+        } else {
+            if value.type_arguments.len() != generic_params.len() {
+                return Err(CompileError::GenericArgumentNumberMismatched {
+                    identifier: value.function_name.name.clone(),
+                    expect: generic_params.len(),
+                    found: value.type_arguments.len(),
+                }.to_leerror(value.function_name.pos()));
+            }
+            value.type_arguments
+                .iter()
+                .map(|argument| le_error!(self.context.get_generic_type(argument),argument.pos()))
+                .collect::<Result<Vec<_>>>()?
+        };
+        le_error!(self.context.compiler_context.check_trait_bounds(generic_params,&type_arguments),value.pos.clone())?;
+        let function = self.instantiate_generic_function(&definition, type_arguments)
+            .map_err(|e| CompileError::InGenericInstantiation {
+                instance: value.function_name.name.clone(),
+                inner: Box::new(e),
+            }.to_leerror(value.pos.clone()))?;
+        //生成函数调用
+        self.builder.build_call(&self.context, function, &params).map_err(|e| e.to_leerror(value.pos.clone()))
+    }
+
+    /// 生成泛型函数的实例，相同类型实参的实例只会生成一次
+    fn instantiate_generic_function(&mut self, definition: &FunctionDefinition, type_arguments: Vec<LEBasicTypeEnum<'ctx>>) -> Result<LEFunctionValue<'ctx>> {
+        let name = &definition.prototype.identifier.name;
+        let instance_name = mangle::generic_instance_name(name, &type_arguments);
+        if let Some(function) = self.context.compiler_context.function_instances.get(&instance_name) {
+            return Ok(function.clone());
+        }
+        let llvm_name = mangle::mangle_generic_instance(name, &type_arguments);
+        let bindings = definition.prototype.generic_params.iter().map(|param| param.identifier.name.clone()).zip(type_arguments.into_iter()).collect();
+        let outer_bindings = std::mem::replace(&mut self.context.compiler_context.generic_bindings, bindings);
+        //保存调用处的状态，实例的函数体在独立的上下文中生成
+        let insert_block = self.builder.llvm_builder.get_insert_block();
+        let current_function = self.context.compiler_context.current_function.take();
+        let return_variable = self.context.compiler_context.return_variable.take();
+        let return_block = self.context.compiler_context.return_block.take();
+        let local_tables = self.context.compiler_context.symbols.take_local_tables();
+        let result = self.build_named_function_prototype(&definition.prototype, instance_name.clone(), &llvm_name)
+            .and_then(|function| {
+                //多个源文件可能生成同一个实例，链接时只保留一份
+                function.llvm_value.set_linkage(Linkage::LinkOnceODR);
+                self.add_function_attributes(function.llvm_value, &definition.prototype);
+                //先放入缓存，函数体中的递归调用使用同一个实例
+                self.context.compiler_context.function_instances.insert(instance_name.clone(), function.clone());
+                self.build_function(function, definition)
+            });
+        self.context.compiler_context.symbols.restore_local_tables(local_tables);
+        self.context.compiler_context.current_function = current_function;
+        self.context.compiler_context.return_variable = return_variable;
+        self.context.compiler_context.return_block = return_block;
+        if let Some(block) = insert_block {
+            self.builder.llvm_builder.position_at_end(block);
+        }
+        self.context.compiler_context.generic_bindings = outer_bindings;
+        result
+    }
+
+    fn build_method_call_expression(&mut self, receiver: Expression<'ctx>, receiver_pos: Position, value: &FunctionCall) -> Result<Expression<'ctx>> {
+        //获取接收者的指针，引用类型的变量先解引用，右值先存入临时变量
+        let receiver_pointer = match receiver {
+            Expression::Left(pointer) => match pointer.ty.get_point_type() {
+                LEBasicTypeEnum::Pointer(_) => match self.builder.build_load(&self.context, pointer) {
+                    LEBasicValueEnum::Pointer(reference) => reference,
+                    other => {
+                        return Err(CompileError::TypeMismatched {
+                            expect: "reference".into(),
+                            found: LEBasicValue::get_le_type(&other).to_string(),
+                        }.to_leerror(receiver_pos));
+                    }
+                },
+                _ => pointer,
+            },
+            Expression::Right(value) => {
+                self.builder.build_alloca_with_initial_value(&self.context, value)
+            }
+            Expression::Unit => {
+                return Err(CompileError::ExpressionIsNotRightValueExpression.to_leerror(receiver_pos));
+            }
+        };
+        let receiver_type = receiver_pointer.ty.get_point_type();
+        //从符号表查找方法，结构体的方法和trait为任意类型实现的方法都以类型名为前缀
+        let method_name = mangle::method_symbol_name(&receiver_type.to_string(), &value.function_name.name);
+        let function = self.context.compiler_context.get_function(&method_name)
+            .map_err(|_| CompileError::NoSuchMember { member_name: value.function_name.name.clone() }.to_leerror(value.function_name.pos()))?;
+        //第一个参数为self，类型是结构体或结构体的引用
+        let self_argument = match function.ty.param_types().first() {
+            Some(LEBasicTypeEnum::Pointer(self_type)) if self_type.get_point_type() == receiver_type => {
+                Expression::Right(receiver_pointer.to_le_value_enum())
+            }
+            Some(self_type) if self_type == &receiver_type => {
+                Expression::Left(receiver_pointer)
+            }
+            _ => {
+                return Err(CompileError::IdentifierIsNotCallable { identifier: method_name }.to_leerror(value.function_name.pos()));
+            }
+        };
+        let mut params = vec![self_argument];
+        //对其余实参求值
+        for param in value.params.iter() {
+            params.push(self.build_expression(param)?)
+        }
+        //生成方法调用
+        self.builder.build_call(&self.context, function, &params).map_err(|e| e.to_leerror(value.pos.clone()))
+    }
+
     fn build_local_variable_definition(&mut self, variable: &Variable) -> Result<Expression<'ctx>> {
         //获取变量的类型，初始值
         let initial_value_expr = self.build_expression(variable.value.as_ref())?;
@@ -313,6 +1073,17 @@ impl<'ctx> CodeGenerator<'ctx> {
         self.builder.llvm_builder.position_at_end(current_insert_block);
         //初始化申请的内存
         le_error!(self.builder.build_store(&self.context, pointer.clone(),initial_value),variable.pos())?;
+        if let Some(debug_info) = &mut self.debug_info {
+            debug_info.declare_variable(
+                self.context.llvm_context,
+                &variable.prototype.identifier.name,
+                None,
+                &pointer.ty.get_point_type(),
+                pointer.llvm_value,
+                &variable.prototype.identifier.pos(),
+                current_insert_block,
+            );
+        }
         le_error!(self.context.insert_local_variable(
             variable.prototype.identifier.name.clone(),
             pointer,variable.prototype.identifier.pos()),
@@ -322,8 +1093,20 @@ impl<'ctx> CodeGenerator<'ctx> {
     }
 
     fn build_code_block(&mut self, code_block: &CodeBlock) -> Result<bool> {
+        if let Some(debug_info) = &mut self.debug_info {
+            debug_info.enter_block(&code_block.pos);
+        }
+        let is_return_block = self.build_statements(code_block)?;
+        if let Some(debug_info) = &mut self.debug_info {
+            debug_info.exit_block();
+        }
+        Ok(is_return_block)
+    }
+
+    fn build_statements(&mut self, code_block: &CodeBlock) -> Result<bool> {
         //对每一条语句调用生成函数
         for statement in code_block.statements.iter() {
+            self.set_debug_location(&statement.pos());
             match statement {
                 Statement::Expressions(expr) => {
                     self.build_expression(expr)?;
@@ -346,11 +1129,21 @@ impl<'ctx> CodeGenerator<'ctx> {
                 Statement::WhileLoop(while_loop) => {
                     self.build_while_loop(while_loop)?;
                 }
+                Statement::Match(match_statement) => {
+                    self.build_match_statement(match_statement)?;
+                }
             }
         }
         Ok(false)
     }
 
+    /// 使用`-g`编译时，把之后生成的指令的位置设为`pos`
+    fn set_debug_location(&mut self, pos: &Position) {
+        if let Some(debug_info) = &mut self.debug_info {
+            debug_info.set_location(self.context.llvm_context, &self.builder.llvm_builder, pos);
+        }
+    }
+
     fn build_return(&mut self, expr: Expression, position: Position) -> Result<()> {
         //拿到返回的basic block，将返回值存入返回变量
         let return_variable = &self.context.compiler_context.return_variable;
@@ -462,42 +1255,191 @@ impl<'ctx> CodeGenerator<'ctx> {
         Ok(())
     }
 
-    fn build_function_prototype(&mut self, module: &Module<'ctx>, prototype: &FunctionPrototype) -> Result<LEFunctionValue<'ctx>> {
-        let mut param_llvm_metadata_types = vec![];
-        let mut param_types = vec![];
+    fn build_match_statement(&mut self, statement: &MatchStatement) -> Result<()> {
+        let scrutinee = self.build_expression(statement.scrutinee.as_ref())?;
+        let scrutinee_value = le_error!(self.builder.read_expression(&self.context, scrutinee),statement.scrutinee.pos())?;
+        let (llvm_value, signed, is_bool) = match &scrutinee_value {
+            LEBasicValueEnum::Integer(i) => (i.llvm_value, i.ty.signed(), false),
+            LEBasicValueEnum::Bool(b) => (b.llvm_value, false, true),
+            _ => {
+                return Err(CompileError::TypeMismatched {
+                    expect: "integer or bool".into(),
+                    found: LEBasicValue::get_le_type(&scrutinee_value).to_string(),
+                }.to_leerror(statement.scrutinee.pos()));
+            }
+        };
+        let scrutinee_type = LEBasicValue::get_le_type(&scrutinee_value);
+        let int_type = llvm_value.get_type();
+
+        //每个分支一个basic block，最后是merge块
+        let current_block = self.builder.llvm_builder.get_insert_block().unwrap();
+        let mut last_block = current_block;
+        let mut arm_blocks = vec![];
+        for _ in statement.arms.iter() {
+            last_block = self.context.llvm_context.insert_basic_block_after(last_block, "");
+            arm_blocks.push(last_block);
+        }
+        let merge_block = self.context.llvm_context.insert_basic_block_after(last_block, "");
+
+        //在出现第一个需要比较链的范围之前，字面量和较小的范围都放进switch，之后的分支按顺序进入比较链
+        let mut cases = vec![];
+        let mut covered = HashSet::new();
+        let mut ranges = vec![];
+        let mut default_block = merge_block;
+        for (arm, arm_block) in statement.arms.iter().zip(arm_blocks.iter()) {
+            if default_block != merge_block {
+                //通配分支之后的分支不可达，不再参与分发
+                continue;
+            }
+            let (start, end) = match &arm.pattern {
+                MatchPattern::Wildcard { .. } => {
+                    default_block = *arm_block;
+                    continue;
+                }
+                MatchPattern::Bool { value, pos } => {
+                    if !is_bool {
+                        return Err(CompileError::TypeMismatched {
+                            expect: scrutinee_type.to_string(),
+                            found: "bool".into(),
+                        }.to_leerror(pos.clone()));
+                    }
+                    (*value as i128, *value as i128)
+                }
+                MatchPattern::Integer { value, pos } => {
+                    self.check_match_pattern_value(*value, &scrutinee_type, int_type, is_bool, pos.clone())?;
+                    (*value, *value)
+                }
+                MatchPattern::Range { start, end, inclusive, pos } => {
+                    let end = if *inclusive { *end } else { *end - 1 };
+                    self.check_match_pattern_value(*start, &scrutinee_type, int_type, is_bool, pos.clone())?;
+                    self.check_match_pattern_value(end, &scrutinee_type, int_type, is_bool, pos.clone())?;
+                    (*start, end)
+                }
+            };
+            if ranges.is_empty() && end - start < MATCH_SWITCH_DENSE_LIMIT {
+                for value in start..=end {
+                    if covered.insert(value) {
+                        //截断为64位后即为该值在被匹配类型中的位模式
+                        cases.push((int_type.const_int(value as u64, signed), *arm_block));
+                    }
+                }
+            } else {
+                ranges.push((start, end, *arm_block));
+            }
+        }
+
+        //生成范围分支的比较链
+        let mut chain_blocks = vec![];
+        let mut last_chain_block = current_block;
+        for _ in ranges.iter() {
+            last_chain_block = self.context.llvm_context.insert_basic_block_after(last_chain_block, "");
+            chain_blocks.push(last_chain_block);
+        }
+        let switch_default = chain_blocks.first().cloned().unwrap_or(default_block);
+        self.builder.llvm_builder.build_switch(llvm_value, switch_default, &cases);
+        for (index, (start, end, arm_block)) in ranges.iter().enumerate() {
+            let next_block = chain_blocks.get(index + 1).cloned().unwrap_or(default_block);
+            self.builder.llvm_builder.position_at_end(chain_blocks[index]);
+            let (ge, le) = if signed { (IntPredicate::SGE, IntPredicate::SLE) } else { (IntPredicate::UGE, IntPredicate::ULE) };
+            let lower = self.builder.llvm_builder.build_int_compare(ge, llvm_value, int_type.const_int(*start as u64, signed), "");
+            let upper = self.builder.llvm_builder.build_int_compare(le, llvm_value, int_type.const_int(*end as u64, signed), "");
+            let in_range = self.builder.llvm_builder.build_and(lower, upper, "");
+            self.builder.llvm_builder.build_conditional_branch(in_range, *arm_block, next_block);
+        }
+
+        //生成每个分支的代码
+        for (arm, arm_block) in statement.arms.iter().zip(arm_blocks.into_iter()) {
+            self.builder.llvm_builder.position_at_end(arm_block);
+            self.context.compiler_context.push_block_table();
+            let is_return_block = self.build_code_block(&arm.code_block)?;
+            if !is_return_block {
+                self.builder.llvm_builder.build_unconditional_branch(merge_block);
+            }
+            self.context.compiler_context.pop_block_table();
+        }
+        self.builder.llvm_builder.position_at_end(merge_block);
+        Ok(())
+    }
+
+    ///检查模式中的整数能否用被匹配值的类型表示
+    fn check_match_pattern_value(&self, value: i128, scrutinee_type: &LEBasicTypeEnum<'ctx>, int_type: IntType<'ctx>, is_bool: bool, pos: Position) -> Result<()> {
+        if is_bool {
+            return Err(CompileError::TypeMismatched {
+                expect: "bool".into(),
+                found: "integer".into(),
+            }.to_leerror(pos));
+        }
+        let bit_width = int_type.get_bit_width();
+        let signed = scrutinee_type.clone().into_int_type().map(|t| t.signed()).unwrap_or(false);
+        let (min, max) = if signed {
+            (-(1i128 << (bit_width - 1)), (1i128 << (bit_width - 1)) - 1)
+        } else {
+            (0, (1i128 << bit_width) - 1)
+        };
+        if value < min || value > max {
+            return Err(CompileError::TypeMismatched {
+                expect: scrutinee_type.to_string(),
+                found: format!("integer `{}`", value),
+            }.to_leerror(pos));
+        }
+        Ok(())
+    }
+
+    fn build_function_prototype(&mut self, prototype: &FunctionPrototype) -> Result<LEFunctionValue<'ctx>> {
+        let name = prototype.identifier.name.clone();
+        //模块中的函数名形如`math::sqrt`，在LLVM模块中使用路径的修饰名
+        if name.contains("::") {
+            let llvm_name = mangle::mangle_path(&name.split("::").collect::<Vec<_>>());
+            return self.build_named_function_prototype(prototype, name, &llvm_name);
+        }
+        self.build_named_function_prototype(prototype, name.clone(), &name)
+    }
 
+    /// 原型中的参数类型和返回类型
+    fn resolve_prototype_types(&mut self, prototype: &FunctionPrototype) -> Result<(Vec<LEBasicTypeEnum<'ctx>>, Option<LEBasicTypeEnum<'ctx>>)> {
+        let mut param_types = vec![];
         for param_type in prototype.param_types.iter() {
             let ty = self.context.get_generic_type(param_type).map_err(|e| e.to_leerror(param_type.pos()))?;
-            param_types.push(ty.clone());
-            param_llvm_metadata_types.push(BasicMetadataTypeEnum::from(ty.get_llvm_basic_type()))
+            param_types.push(ty);
         }
-        let return_type;
+        let return_type = match &prototype.return_type {
+            None => None,
+            Some(type_declarator) => Some(self.context.get_generic_type(type_declarator).map_err(|e| e.to_leerror(type_declarator.pos()))?),
+        };
+        Ok((param_types, return_type))
+    }
+
+    /// 生成函数原型，`symbol_name`是符号表中的名字，`llvm_name`是模块中的名字
+    fn build_named_function_prototype(&mut self, prototype: &FunctionPrototype, symbol_name: String, llvm_name: &str) -> Result<LEFunctionValue<'ctx>> {
+        let (param_types, return_type) = self.resolve_prototype_types(prototype)?;
+        let param_llvm_metadata_types = param_types
+            .iter()
+            .map(|ty| BasicMetadataTypeEnum::from(ty.get_llvm_basic_type()))
+            .collect::<Vec<_>>();
+        let is_variadic = prototype.is_variadic;
         //检查参数是否合法
-        let external_function = match &prototype.return_type {
+        let external_function = match return_type.clone() {
             None => {
-                return_type = None;
-                self.context.llvm_context.void_type().fn_type(&param_llvm_metadata_types, false)
+                self.context.llvm_context.void_type().fn_type(&param_llvm_metadata_types, is_variadic)
             }
-            Some(type_declarator) => {
-                let ty = self.context.get_generic_type(type_declarator).map_err(|e| e.to_leerror(type_declarator.pos()))?;
-                return_type = Some(ty.clone());
+            Some(ty) => {
                 match ty {
-                    LEBasicTypeEnum::Integer(i) => { i.get_llvm_type().fn_type(&param_llvm_metadata_types, false) }
-                    LEBasicTypeEnum::Bool(i) => { i.get_llvm_type().fn_type(&param_llvm_metadata_types, false) }
-                    LEBasicTypeEnum::Float(i) => { i.get_llvm_type().fn_type(&param_llvm_metadata_types, false) }
-                    LEBasicTypeEnum::Pointer(i) => { i.get_llvm_type().fn_type(&param_llvm_metadata_types, false) }
-                    LEBasicTypeEnum::Array(i) => { i.get_llvm_type().fn_type(&param_llvm_metadata_types, false) }
-                    LEBasicTypeEnum::Struct(i) => { i.get_llvm_type().fn_type(&param_llvm_metadata_types, false) }
-                    LEBasicTypeEnum::Vector(i) => { i.get_llvm_type().fn_type(&param_llvm_metadata_types, false) }
+                    LEBasicTypeEnum::Integer(i) => { i.get_llvm_type().fn_type(&param_llvm_metadata_types, is_variadic) }
+                    LEBasicTypeEnum::Bool(i) => { i.get_llvm_type().fn_type(&param_llvm_metadata_types, is_variadic) }
+                    LEBasicTypeEnum::Float(i) => { i.get_llvm_type().fn_type(&param_llvm_metadata_types, is_variadic) }
+                    LEBasicTypeEnum::Pointer(i) => { i.get_llvm_type().fn_type(&param_llvm_metadata_types, is_variadic) }
+                    LEBasicTypeEnum::Array(i) => { i.get_llvm_type().fn_type(&param_llvm_metadata_types, is_variadic) }
+                    LEBasicTypeEnum::Struct(i) => { i.get_llvm_type().fn_type(&param_llvm_metadata_types, is_variadic) }
+                    LEBasicTypeEnum::Vector(i) => { i.get_llvm_type().fn_type(&param_llvm_metadata_types, is_variadic) }
                 }
             }
         };
         //构造一个LLVM函数
-        let external_function_value = module.add_function(&prototype.identifier.name, external_function, Some(Linkage::External));
+        let external_function_value = self.module.add_function(llvm_name, external_function, Some(Linkage::External));
         let function_type = LEFunctionType::new(external_function, return_type, param_types);
         let le_function = LEFunctionValue { ty: function_type, llvm_value: external_function_value };
         le_error!(self.context.insert_global_function(
-            prototype.identifier.name.clone(),
+            symbol_name,
             le_function.clone(),
             prototype.identifier.pos()),prototype.identifier.pos())?;
         Ok(le_function)
@@ -524,11 +1466,20 @@ impl<'ctx> CodeGenerator<'ctx> {
         }
     }
 
-    fn build_function(&mut self, module: &Module<'ctx>, function_node: &FunctionDefinition) -> Result<LEFunctionValue<'ctx>> {
-        let function_value = self.build_function_prototype(module, &function_node.prototype)?;
+    fn build_function(&mut self, function_value: LEFunctionValue<'ctx>, function_node: &FunctionDefinition) -> Result<LEFunctionValue<'ctx>> {
         let entry = self.context.llvm_context.append_basic_block(function_value.llvm_value, "");
         let return_block = self.context.llvm_context.append_basic_block(function_value.llvm_value, "");
         let return_type = function_value.ty.return_type();
+        if let Some(debug_info) = &mut self.debug_info {
+            debug_info.enter_function(
+                function_value.llvm_value,
+                &function_node.prototype.identifier.name,
+                &function_node.prototype.identifier.pos(),
+                function_value.ty.param_types(),
+                return_type.clone(),
+            );
+        }
+        self.set_debug_location(&function_node.prototype.identifier.pos());
         //对返回值为空类型或其他类型做特殊处理
         if let Some(none_void_type) = return_type {
             self.builder.llvm_builder.position_at_end(entry);
@@ -552,15 +1503,33 @@ impl<'ctx> CodeGenerator<'ctx> {
             let param_pos = function_node.prototype.param_types[index].pos();
             let param_value = le_error!(LEBasicValueEnum::from_type_and_llvm_value(param_type.clone(), param),param_pos.clone())?;
             let param_pointer = self.builder.build_alloca_with_initial_value(&self.context, param_value);
+            if let Some(debug_info) = &mut self.debug_info {
+                debug_info.declare_variable(self.context.llvm_context, name, Some(index as u32 + 1), param_type, param_pointer.llvm_value, &param_pos, entry);
+            }
             le_error!(self.context.insert_local_variable(name.clone(),param_pointer,param_pos.clone()),param_pos)?;
         }
 
         let is_return_block = self.build_code_block(&function_node.code_block)?;
-        if !is_return_block {
-            self.builder.llvm_builder.build_unconditional_branch(return_block);
+        let noreturn = function_node.prototype.attributes.iter().find(|attribute| attribute.name.name == "noreturn");
+        match (noreturn, is_return_block) {
+            (Some(attribute), true) => return Err(CompileError::InvalidAttribute {
+                attribute: attribute.name.name.clone(),
+                reason: "function ends with `ret`".to_string(),
+            }.to_leerror(attribute.pos())),
+            //`#[noreturn]`函数的末尾不可达，函数体应以`panic`、`exit`或不退出的循环结束，否则行为未定义
+            (Some(_), false) => {
+                self.builder.llvm_builder.build_unreachable();
+            }
+            (None, false) => {
+                self.builder.llvm_builder.build_unconditional_branch(return_block);
+            }
+            (None, true) => {}
         }
         //删除一个块级符号表
         self.context.compiler_context.pop_block_table();
+        if let Some(debug_info) = &mut self.debug_info {
+            debug_info.exit_function(self.context.llvm_context, &self.builder.llvm_builder);
+        }
         Ok(function_value)
     }
 
@@ -570,64 +1539,584 @@ impl<'ctx> CodeGenerator<'ctx> {
     }
 
 
-    fn generate_all_functions(&mut self, module: &Module<'ctx>, ast: &Ast) -> Result<()> {
+    fn generate_all_functions(&mut self, ast: &Ast) -> Result<()> {
+        //记录所有泛型函数的定义
+        for function_node in ast.function_definitions.iter().filter(|function_node| !function_node.prototype.generic_params.is_empty()) {
+            self.register_generic_function(function_node, ast)?;
+        }
         for function_prototype in ast.extern_functions.iter() {
-            let name = function_prototype.identifier.clone();
+            self.check_function_attributes(function_prototype, true)?;
+            let symbol = attribute_symbol_name(function_prototype).unwrap_or_else(|| function_prototype.identifier.name.clone());
             //生成所有的函数原型
-            self.build_function_prototype(module, function_prototype)?;
+            let function_value = self.build_extern_function_prototype(function_prototype)?;
+            if self.wasm_interface {
+                self.add_string_attribute(function_value.llvm_value, "wasm-import-module", WASM_IMPORT_MODULE);
+                self.add_string_attribute(function_value.llvm_value, "wasm-import-name", &symbol);
+            }
+        }
+        //先生成所有函数和方法的原型，函数体中可以调用定义在后面的函数
+        let mut function_values = vec![];
+        let mut user_main = None;
+        //`#[link_name]`和`#[no_mangle]`指定的符号名，同名时LLVM会悄悄改名，需要报错
+        let mut symbols = HashSet::new();
+        for function_node in ast.function_definitions.iter().filter(|function_node| function_node.prototype.generic_params.is_empty()) {
+            self.check_function_attributes(&function_node.prototype, false)?;
+            if function_node.prototype.identifier.name == MAIN_FUNCTION_NAME {
+                let main_value = self.build_main_prototype(&function_node.prototype)?;
+                self.add_function_attributes(main_value.llvm_value, &function_node.prototype);
+                user_main = Some(main_value.clone());
+                function_values.push(main_value);
+                continue;
+            }
+            let name = function_node.prototype.identifier.name.clone();
+            //`#[link_name]`和`#[no_mangle]`指定的符号名，同时也是导出的名字
+            let symbol_override = attribute_symbol_name(&function_node.prototype);
+            if let Some(symbol) = &symbol_override {
+                claim_symbol(&mut symbols, &function_node.prototype, symbol)?;
+            }
+            let symbol = symbol_override.clone().unwrap_or_else(|| name.clone());
+            let is_exported = function_node.prototype.is_public && !name.contains("::");
+            if is_exported {
+                self.check_exported_types(&function_node.prototype)?;
+            }
+            let function_abi = if is_exported { self.function_abi(&function_node.prototype)? } else { None };
+            //导出到wasm的是按C ABI降级后的包装函数
+            let mut export_value = None;
+            let function_value = match (&function_abi, &symbol_override) {
+                //需要按C ABI降级的导出函数在模块中使用修饰名，由同名的包装函数转换参数后调用
+                (Some(function_abi), _) => {
+                    let function_value = self.build_named_function_prototype(&function_node.prototype, name.clone(), &mangle::mangle_path(&[&name]))?;
+                    let wrapper = self.module.add_function(&symbol, function_abi.llvm_type, Some(Linkage::External));
+                    function_abi.add_attributes(self.context.llvm_context, wrapper);
+                    self.build_c_abi_export_wrapper(function_value.llvm_value, wrapper, function_abi);
+                    export_value = Some(wrapper);
+                    function_value
+                }
+                (None, Some(symbol)) => self.build_named_function_prototype(&function_node.prototype, name.clone(), symbol)?,
+                (None, None) => self.build_function_prototype(&function_node.prototype)?,
+            };
+            self.add_function_attributes(function_value.llvm_value, &function_node.prototype);
+            //导入的模块会在每个导入它的源文件中生成一份，链接时只保留一份
+            if function_node.prototype.identifier.name.contains("::") {
+                function_value.llvm_value.set_linkage(Linkage::LinkOnceODR);
+            }
+            if function_node.prototype.is_public {
+                if self.wasm_interface {
+                    self.add_string_attribute(export_value.unwrap_or(function_value.llvm_value), "wasm-export-name", &symbol);
+                }
+                //模块中的函数使用修饰名，不作为C ABI导出
+                if !function_node.prototype.identifier.name.contains("::") {
+                    self.exported_functions.insert(symbol.clone());
+                    self.exported_prototypes.push(ExportedFunction {
+                        name: symbol,
+                        param_names: function_node.param_names.clone(),
+                        ty: function_value.ty.clone(),
+                    });
+                }
+            }
+            function_values.push(function_value);
         }
-        for function_node in ast.function_definitions.iter() {
-            let name = function_node.prototype.identifier.clone();
-            //生成所有的函数实现
-            self.build_function(module, function_node)?;
+        let mut method_values = vec![];
+        let mut impl_targets = vec![];
+        for impl_block in ast.impls.iter() {
+            let target_type = self.get_impl_target_type(impl_block)?;
+            let target_name = target_type.to_string();
+            //方法中的`Self`指代实现的目标类型
+            let outer_bindings = self.bind_self_type(target_type.clone());
+            let mut values = vec![];
+            for function_node in impl_block.functions.iter() {
+                let method_name = &function_node.prototype.identifier.name;
+                let symbol_name = mangle::method_symbol_name(&target_name, method_name);
+                self.check_function_attributes(&function_node.prototype, false)?;
+                let llvm_name = match (attribute_symbol_name(&function_node.prototype), &impl_block.trait_name) {
+                    (Some(symbol), _) => {
+                        claim_symbol(&mut symbols, &function_node.prototype, &symbol)?;
+                        symbol
+                    }
+                    (None, trait_name) => mangle::mangle_method(&target_type, trait_name.as_ref().map(|name| name.name.as_str()), method_name),
+                };
+                let method_value = self.build_named_function_prototype(&function_node.prototype, symbol_name, &llvm_name)?;
+                self.add_function_attributes(method_value.llvm_value, &function_node.prototype);
+                //模块中的类型或trait的方法同样会在每个导入模块的源文件中生成
+                let is_module_item = target_name.contains("::") || impl_block.trait_name.as_ref().map(|name| name.name.contains("::")).unwrap_or(false);
+                if is_module_item {
+                    method_value.llvm_value.set_linkage(Linkage::LinkOnceODR);
+                }
+                values.push(method_value);
+            }
+            if let Some(trait_name) = &impl_block.trait_name {
+                self.check_trait_impl(impl_block, trait_name, &values)?;
+                self.context.compiler_context.trait_impls.insert((target_name, trait_name.name.clone()));
+            }
+            self.context.compiler_context.generic_bindings = outer_bindings;
+            method_values.extend(values);
+            impl_targets.push(target_type);
+        }
+        //生成所有的函数实现，泛型函数在调用时才实例化
+        let function_nodes = ast.function_definitions.iter().filter(|function_node| function_node.prototype.generic_params.is_empty());
+        for (function_node, function_value) in function_nodes.zip(function_values.into_iter()) {
+            self.build_function(function_value, function_node)?;
+        }
+        let mut method_values = method_values.into_iter();
+        for (impl_block, target_type) in ast.impls.iter().zip(impl_targets.into_iter()) {
+            let outer_bindings = self.bind_self_type(target_type);
+            for (function_node, function_value) in impl_block.functions.iter().zip(method_values.by_ref()) {
+                self.build_function(function_value, function_node)?;
+            }
+            self.context.compiler_context.generic_bindings = outer_bindings;
+        }
+        if let Some(user_main) = user_main {
+            self.build_main_wrapper(user_main);
         }
         Ok(())
     }
 
-    fn generate_all_global_variables(&mut self, module: &Module<'ctx>, ast: &Ast) -> Result<()> {
+    /// `decl`声明的外部函数，参数或返回值中有结构体或数组时按C ABI降级：模块中声明降级后的C函数，
+    /// lelang中调用的是按原签名生成的内部函数，由它转换参数后调用C函数
+    fn build_extern_function_prototype(&mut self, prototype: &FunctionPrototype) -> Result<LEFunctionValue<'ctx>> {
+        let name = prototype.identifier.name.clone();
+        let symbol_override = attribute_symbol_name(prototype);
+        let function_abi = match self.function_abi(prototype)? {
+            Some(function_abi) => function_abi,
+            None => {
+                let function = match &symbol_override {
+                    Some(symbol) => self.build_named_function_prototype(prototype, name, symbol)?,
+                    None => self.build_function_prototype(prototype)?,
+                };
+                self.add_function_attributes(function.llvm_value, prototype);
+                return Ok(function);
+            }
+        };
+        let thunk = self.build_named_function_prototype(prototype, name.clone(), &mangle::mangle_path(&["c", &name]))?;
+        let c_function = self.module.add_function(symbol_override.as_deref().unwrap_or(&name), function_abi.llvm_type, Some(Linkage::External));
+        function_abi.add_attributes(self.context.llvm_context, c_function);
+        self.add_function_attributes(c_function, prototype);
+        self.build_c_abi_import_thunk(thunk.llvm_value, c_function, &function_abi);
+        Ok(thunk)
+    }
+
+    /// 导出函数的参数和返回值必须能用C表示，与生成的头文件和C ABI降级一致
+    fn check_exported_types(&mut self, prototype: &FunctionPrototype) -> Result<()> {
+        let (param_types, return_type) = self.resolve_prototype_types(prototype)?;
+        let declarators = prototype.param_types.iter().chain(prototype.return_type.iter());
+        for (ty, declarator) in param_types.iter().chain(return_type.iter()).zip(declarators) {
+            if let Some((unsupported, reason)) = unsupported_c_type(ty) {
+                return Err(CompileError::UnsupportedExportType {
+                    function: prototype.identifier.name.clone(),
+                    ty: unsupported.to_string(),
+                    reason,
+                }.to_leerror(declarator.pos()));
+            }
+        }
+        Ok(())
+    }
+
+    /// 目标支持C ABI降级且签名中有结构体或数组时，返回降级后的签名
+    fn function_abi(&mut self, prototype: &FunctionPrototype) -> Result<Option<FunctionAbi<'ctx>>> {
+        if self.c_abi.is_none() && !prototype.is_variadic {
+            return Ok(None);
+        }
+        let (param_types, return_type) = self.resolve_prototype_types(prototype)?;
+        //可变参数函数无法通过内部函数转发实参，只有标量和指针的签名可以按LLVM的默认方式传递
+        if prototype.is_variadic {
+            let declarators = prototype.param_types.iter().chain(prototype.return_type.iter());
+            for (ty, declarator) in param_types.iter().chain(return_type.iter()).zip(declarators) {
+                if matches!(ty, LEBasicTypeEnum::Struct(_) | LEBasicTypeEnum::Array(_)) {
+                    return Err(CompileError::VariadicAggregate {
+                        function: prototype.identifier.name.clone(),
+                        ty: ty.to_string(),
+                    }.to_leerror(declarator.pos()));
+                }
+            }
+            return Ok(None);
+        }
+        let param_types = param_types.iter().map(|ty| ty.get_llvm_basic_type()).collect::<Vec<_>>();
+        let return_type = return_type.map(|ty| ty.get_llvm_basic_type());
+        Ok(self.c_abi.as_ref().unwrap().function_abi(self.context.llvm_context, &param_types, return_type))
+    }
+
+    /// 在入口块中分配可以同时容纳`value_type`和按位转换后的`cast_type`的内存，返回两种类型的指针
+    fn build_cast_slot(&self, value_type: BasicTypeEnum<'ctx>, cast_type: StructType<'ctx>) -> (PointerValue<'ctx>, PointerValue<'ctx>) {
+        let builder = &self.builder.llvm_builder;
+        let target_data = self.c_abi.as_ref().unwrap().target_data();
+        let slot = if target_data.get_abi_size(&cast_type) > target_data.get_abi_size(&value_type) {
+            builder.build_alloca(cast_type, "")
+        } else {
+            builder.build_alloca(value_type, "")
+        };
+        let value_pointer = builder.build_pointer_cast(slot, value_type.ptr_type(AddressSpace::Generic), "");
+        let cast_pointer = builder.build_pointer_cast(slot, cast_type.ptr_type(AddressSpace::Generic), "");
+        (value_pointer, cast_pointer)
+    }
+
+    /// 生成按lelang约定调用、按C ABI调用`c_function`的内部函数，调用处内联后没有额外开销
+    fn build_c_abi_import_thunk(&mut self, thunk: FunctionValue<'ctx>, c_function: FunctionValue<'ctx>, function_abi: &FunctionAbi<'ctx>) {
+        let context = self.context.llvm_context;
+        let builder = &self.builder.llvm_builder;
+        builder.unset_current_debug_location();
+        let entry = context.append_basic_block(thunk, "");
+        builder.position_at_end(entry);
+        let mut args: Vec<BasicMetadataValueEnum> = vec![];
+        let return_slot = match (&function_abi.return_mode, function_abi.return_type()) {
+            (Some(PassMode::Indirect), Some(return_type)) => {
+                let slot = builder.build_alloca(return_type, "");
+                args.push(slot.into());
+                Some(slot)
+            }
+            _ => None,
+        };
+        for ((mode, ty), param) in function_abi.params.iter().zip(function_abi.param_types()).zip(thunk.get_param_iter()) {
+            match mode {
+                PassMode::Direct => args.push(param.into()),
+                PassMode::Cast(types) => {
+                    let (value_pointer, cast_pointer) = self.build_cast_slot(*ty, FunctionAbi::cast_struct_type(context, types));
+                    builder.build_store(value_pointer, param);
+                    for index in 0..types.len() {
+                        let pointer = builder.build_struct_gep(cast_pointer, index as u32, "").unwrap();
+                        args.push(builder.build_load(pointer, "").into());
+                    }
+                }
+                PassMode::Indirect => {
+                    let copy = builder.build_alloca(*ty, "");
+                    builder.build_store(copy, param);
+                    args.push(copy.into());
+                }
+            }
+        }
+        let result = builder.build_call(c_function, &args, "").try_as_basic_value().left();
+        match (&function_abi.return_mode, function_abi.return_type(), result) {
+            (Some(PassMode::Direct), _, Some(result)) => {
+                builder.build_return(Some(&result));
+            }
+            (Some(PassMode::Cast(types)), Some(return_type), Some(result)) => {
+                let (value_pointer, cast_pointer) = self.build_cast_slot(return_type, FunctionAbi::cast_struct_type(context, types));
+                if types.len() == 1 {
+                    builder.build_store(builder.build_struct_gep(cast_pointer, 0, "").unwrap(), result);
+                } else {
+                    builder.build_store(cast_pointer, result);
+                }
+                builder.build_return(Some(&builder.build_load(value_pointer, "")));
+            }
+            (Some(PassMode::Indirect), _, _) => {
+                builder.build_return(Some(&builder.build_load(return_slot.unwrap(), "")));
+            }
+            _ => {
+                builder.build_return(None);
+            }
+        }
+        thunk.set_linkage(Linkage::Internal);
+        self.add_enum_attribute(thunk, "alwaysinline");
+    }
+
+    /// 生成按C ABI导出的包装函数`wrapper`，转换参数后调用按lelang约定生成的`implementation`
+    fn build_c_abi_export_wrapper(&mut self, implementation: FunctionValue<'ctx>, wrapper: FunctionValue<'ctx>, function_abi: &FunctionAbi<'ctx>) {
+        let context = self.context.llvm_context;
+        let builder = &self.builder.llvm_builder;
+        builder.unset_current_debug_location();
+        let entry = context.append_basic_block(wrapper, "");
+        builder.position_at_end(entry);
+        let params = wrapper.get_params();
+        let mut index = 0;
+        let return_pointer = match function_abi.return_mode {
+            Some(PassMode::Indirect) => {
+                index = 1;
+                Some(params[0].into_pointer_value())
+            }
+            _ => None,
+        };
+        let mut args: Vec<BasicMetadataValueEnum> = vec![];
+        for (mode, ty) in function_abi.params.iter().zip(function_abi.param_types()) {
+            match mode {
+                PassMode::Direct => {
+                    args.push(params[index].into());
+                    index += 1;
+                }
+                PassMode::Cast(types) => {
+                    let (value_pointer, cast_pointer) = self.build_cast_slot(*ty, FunctionAbi::cast_struct_type(context, types));
+                    for field in 0..types.len() {
+                        let pointer = builder.build_struct_gep(cast_pointer, field as u32, "").unwrap();
+                        builder.build_store(pointer, params[index + field]);
+                    }
+                    index += types.len();
+                    args.push(builder.build_load(value_pointer, "").into());
+                }
+                PassMode::Indirect => {
+                    args.push(builder.build_load(params[index].into_pointer_value(), "").into());
+                    index += 1;
+                }
+            }
+        }
+        let result = builder.build_call(implementation, &args, "").try_as_basic_value().left();
+        match (&function_abi.return_mode, function_abi.return_type(), result) {
+            (Some(PassMode::Direct), _, Some(result)) => {
+                builder.build_return(Some(&result));
+            }
+            (Some(PassMode::Cast(types)), Some(return_type), Some(result)) => {
+                let (value_pointer, cast_pointer) = self.build_cast_slot(return_type, FunctionAbi::cast_struct_type(context, types));
+                builder.build_store(value_pointer, result);
+                let value = if types.len() == 1 {
+                    builder.build_load(builder.build_struct_gep(cast_pointer, 0, "").unwrap(), "")
+                } else {
+                    builder.build_load(cast_pointer, "")
+                };
+                builder.build_return(Some(&value));
+            }
+            (Some(PassMode::Indirect), _, Some(result)) => {
+                builder.build_store(return_pointer.unwrap(), result);
+                builder.build_return(None);
+            }
+            _ => {
+                builder.build_return(None);
+            }
+        }
+    }
+
+    /// 用户定义的`main`只能是`le main()`或`le main(args:[str])`，返回值为空或`i32`，
+    /// 它在模块中改名为`_LN4mainE`，C入口`main`由`build_main_wrapper`生成
+    fn build_main_prototype(&mut self, prototype: &FunctionPrototype) -> Result<LEFunctionValue<'ctx>> {
+        let llvm_name = mangle::mangle_path(&[MAIN_FUNCTION_NAME]);
+        let main_value = self.build_named_function_prototype(prototype, MAIN_FUNCTION_NAME.into(), &llvm_name)?;
+        let str_type = self.context.str_type().to_le_type_enum();
+        let args_type = self.context.get_slice_type(str_type);
+        let valid_params = match main_value.ty.param_types() {
+            [] => true,
+            [param_type] => param_type == &args_type,
+            _ => false,
+        };
+        let valid_return = match main_value.ty.return_type() {
+            None => true,
+            Some(return_type) => return_type == self.context.i32_type().to_le_type_enum(),
+        };
+        if !valid_params || !valid_return {
+            let params = main_value.ty.param_types().iter().map(|ty| ty.to_string()).collect::<Vec<_>>();
+            let found = match main_value.ty.return_type() {
+                Some(return_type) => format!("main({})->{}", params.join(","), return_type),
+                None => format!("main({})", params.join(",")),
+            };
+            return Err(CompileError::InvalidMainSignature { found }.to_leerror(prototype.identifier.pos()));
+        }
+        Ok(main_value)
+    }
+
+    /// 生成C入口`i32 main(i32 argc, i8** argv)`：用argv构造`[str]`切片传给用户的`main`，
+    /// 用户的`main`没有返回值时进程的返回值为0
+    fn build_main_wrapper(&mut self, user_main: LEFunctionValue<'ctx>) {
+        let i32_type = self.context.i32_type().get_llvm_type();
+        let argv_type = LEBasicType::get_pointer_type(&self.context.str_type()).get_llvm_type();
+        let main_type = i32_type.fn_type(&[i32_type.into(), argv_type.into()], false);
+        let main_function = self.module.add_function(MAIN_FUNCTION_NAME, main_type, Some(Linkage::External));
+        if self.wasm_interface {
+            self.add_string_attribute(main_function, "wasm-export-name", MAIN_FUNCTION_NAME);
+        }
+        self.exported_functions.insert(MAIN_FUNCTION_NAME.into());
+        let entry = self.context.llvm_context.append_basic_block(main_function, "");
+        self.builder.llvm_builder.position_at_end(entry);
+        let mut args = vec![];
+        if let Some(LEBasicTypeEnum::Struct(slice_type)) = user_main.ty.param_types().first() {
+            let argc = main_function.get_nth_param(0).unwrap().into_int_value();
+            let argv = main_function.get_nth_param(1).unwrap().into_pointer_value();
+            let len = self.builder.llvm_builder.build_int_z_extend(argc, self.context.u64_type().get_llvm_type(), "");
+            let slice = slice_type.get_llvm_type().get_undef();
+            let slice = self.builder.llvm_builder.build_insert_value(slice, argv, 0, "").unwrap();
+            let slice = self.builder.llvm_builder.build_insert_value(slice, len, 1, "").unwrap();
+            args.push(slice.into_struct_value().into());
+        }
+        let result = self.builder.llvm_builder.build_call(user_main.llvm_value, &args, "");
+        let exit_code = result.try_as_basic_value().left().unwrap_or_else(|| i32_type.const_zero().into());
+        self.builder.llvm_builder.build_return(Some(&exit_code));
+    }
+
+    /// 检查`#[...]`属性：名字必须是支持的属性，同一属性只能出现一次，只有`link_name`带值，`inline`和`noinline`、
+    /// `link_name`和`no_mangle`不能同时使用；泛型函数的实例和`main`的符号名由编译器决定，
+    /// `decl`声明的函数本来就不修饰，这些函数不能指定符号名
+    fn check_function_attributes(&self, prototype: &FunctionPrototype, is_extern: bool) -> Result<()> {
+        let has = |name: &str| prototype.attributes.iter().any(|attribute| attribute.name.name == name);
+        for (index, attribute) in prototype.attributes.iter().enumerate() {
+            let name = attribute.name.name.as_str();
+            let invalid = |reason: &str| Err(CompileError::InvalidAttribute {
+                attribute: name.to_string(),
+                reason: reason.to_string(),
+            }.to_leerror(attribute.pos()));
+            if prototype.attributes[..index].iter().any(|previous| previous.name.name == name) {
+                return invalid("duplicate attribute");
+            }
+            match (name, &attribute.value) {
+                ("inline" | "noinline" | "cold" | "noreturn" | "no_mangle", Some(_)) => return invalid("this attribute takes no value"),
+                ("link_name", None) => return invalid("expect a symbol name like `#[link_name = \"foo\"]`"),
+                ("link_name", Some(value)) if value.content.is_empty() => return invalid("symbol name can not be empty"),
+                ("inline", None) if has("noinline") => return invalid("conflicts with `noinline`"),
+                ("link_name", _) if has("no_mangle") => return invalid("conflicts with `no_mangle`"),
+                ("no_mangle", _) if is_extern => return invalid("`decl` functions are never mangled"),
+                ("link_name" | "no_mangle", _) if !prototype.generic_params.is_empty() => return invalid("generic functions are instantiated with mangled names"),
+                ("link_name" | "no_mangle", _) if !is_extern && prototype.identifier.name == MAIN_FUNCTION_NAME => return invalid("`main` is always exported as the C entry `main`"),
+                ("inline" | "noinline" | "cold" | "noreturn" | "no_mangle" | "link_name", _) => {}
+                _ => return invalid("unknown attribute"),
+            }
+        }
+        Ok(())
+    }
+
+    /// 把`inline`、`noinline`、`cold`和`noreturn`属性加到LLVM函数上，`inline`只是内联提示
+    fn add_function_attributes(&self, function: FunctionValue<'ctx>, prototype: &FunctionPrototype) {
+        for attribute in prototype.attributes.iter() {
+            match attribute.name.name.as_str() {
+                "inline" => self.add_enum_attribute(function, "inlinehint"),
+                "noinline" => self.add_enum_attribute(function, "noinline"),
+                "cold" => self.add_enum_attribute(function, "cold"),
+                "noreturn" => self.add_enum_attribute(function, "noreturn"),
+                _ => {}
+            }
+        }
+    }
+
+    fn add_enum_attribute(&self, function: FunctionValue<'ctx>, name: &str) {
+        let attribute = self.context.llvm_context.create_enum_attribute(Attribute::get_named_enum_kind_id(name), 0);
+        function.add_attribute(AttributeLoc::Function, attribute);
+    }
+
+    fn add_string_attribute(&self, function: FunctionValue<'ctx>, key: &str, value: &str) {
+        let attribute = self.context.llvm_context.create_string_attribute(key, value);
+        function.add_attribute(AttributeLoc::Function, attribute);
+    }
+
+    /// 把`Self`绑定到实现的目标类型，返回原来的泛型参数绑定
+    fn bind_self_type(&mut self, target_type: LEBasicTypeEnum<'ctx>) -> HashMap<String, LEBasicTypeEnum<'ctx>> {
+        let bindings = HashMap::from([("Self".to_string(), target_type)]);
+        std::mem::replace(&mut self.context.compiler_context.generic_bindings, bindings)
+    }
+
+    /// 检查trait实现块是否恰好实现了trait中声明的所有方法，且签名一致
+    fn check_trait_impl(&mut self, impl_block: &ImplBlock, trait_name: &Identifier, method_values: &[LEFunctionValue<'ctx>]) -> Result<()> {
+        let trait_definition = self.context.compiler_context.traits.get(&trait_name.name).cloned()
+            .ok_or_else(|| CompileError::UnknownTrait { identifier: trait_name.name.clone() }.to_leerror(trait_name.pos()))?;
+        for function_node in impl_block.functions.iter() {
+            let method = &function_node.prototype.identifier;
+            if !trait_definition.functions.iter().any(|prototype| prototype.identifier.name == method.name) {
+                return Err(CompileError::MethodNotInTrait { trait_name: trait_name.name.clone(), method: method.name.clone() }.to_leerror(method.pos()));
+            }
+        }
+        for prototype in trait_definition.functions.iter() {
+            let implemented = impl_block.functions.iter().zip(method_values.iter())
+                .find(|(function_node, _)| function_node.prototype.identifier.name == prototype.identifier.name);
+            let (function_node, function_value) = implemented
+                .ok_or_else(|| CompileError::TraitMethodNotImplemented {
+                    trait_name: trait_name.name.clone(),
+                    method: prototype.identifier.name.clone(),
+                }.to_leerror(impl_block.target.pos()))?;
+            //在`Self`绑定下求trait中声明的签名，和实现的签名比较
+            let param_types = prototype.param_types
+                .iter()
+                .map(|ty| le_error!(self.context.get_generic_type(ty),ty.pos()))
+                .collect::<Result<Vec<_>>>()?;
+            let return_type = match &prototype.return_type {
+                Some(ty) => Some(le_error!(self.context.get_generic_type(ty),ty.pos())?),
+                None => None,
+            };
+            if param_types != function_value.ty.param_types() || return_type != function_value.ty.return_type() {
+                return Err(CompileError::TypeMismatched {
+                    expect: format_signature(&param_types, &return_type),
+                    found: format_signature(function_value.ty.param_types(), &function_value.ty.return_type()),
+                }.to_leerror(function_node.prototype.pos()));
+            }
+        }
+        Ok(())
+    }
+
+    fn generate_all_traits(&mut self, ast: &Ast) -> Result<()> {
+        for trait_definition in ast.traits.iter() {
+            let identifier = &trait_definition.identifier;
+            if let Some(defined) = self.context.compiler_context.traits.get(&identifier.name) {
+                return Err(CompileError::IdentifierAlreadyDefined { identifier: identifier.name.clone(), defined_position: defined.identifier.pos() }.to_leerror(identifier.pos()));
+            }
+            self.context.compiler_context.traits.insert(identifier.name.clone(), trait_definition.clone());
+        }
+        Ok(())
+    }
+
+    fn register_generic_function(&mut self, function_node: &FunctionDefinition, ast: &Ast) -> Result<()> {
+        let identifier = &function_node.prototype.identifier;
+        if let Some(symbol) = self.context.compiler_context.get_symbol(&identifier.name) {
+            return Err(CompileError::IdentifierAlreadyDefined { identifier: identifier.name.clone(), defined_position: symbol.defined_pos() }.to_leerror(identifier.pos()));
+        }
+        if let Some(defined) = self.context.compiler_context.generic_functions.get(&identifier.name) {
+            return Err(CompileError::IdentifierAlreadyDefined { identifier: identifier.name.clone(), defined_position: defined.prototype.identifier.pos() }.to_leerror(identifier.pos()));
+        }
+        generic_bound::check_generic_function_bounds(function_node, ast, &self.context.compiler_context.traits)?;
+        self.check_function_attributes(&function_node.prototype, false)?;
+        self.context.compiler_context.generic_functions.insert(identifier.name.clone(), function_node.clone());
+        Ok(())
+    }
+
+    /// impl块的目标必须是已定义的结构体，trait可以为任意类型实现
+    fn get_impl_target_type(&mut self, impl_block: &ImplBlock) -> Result<LEBasicTypeEnum<'ctx>> {
+        let target_type = le_error!(self.context.get_generic_type(&TypeDeclarator::TypeIdentifier(impl_block.target.clone())),impl_block.target.pos())?;
+        if impl_block.trait_name.is_some() || matches!(target_type, LEBasicTypeEnum::Struct(_)) {
+            Ok(target_type)
+        } else {
+            Err(CompileError::TypeMismatched {
+                expect: "Struct".into(),
+                found: target_type.to_string(),
+            }.to_leerror(impl_block.target.pos()))
+        }
+    }
+
+    fn generate_all_global_variables(&mut self, ast: &Ast) -> Result<()> {
         for variable in ast.globals_variables.iter() {
             let expr_value = self.build_expression(variable.value.as_ref())?;
             let initial = le_error!(self.builder.read_expression(&self.context,expr_value),variable.value.pos())?;
-            let initial_type = LEBasicValue::get_le_type(&initial);
+            //全局变量的初始值必须是常量，常量之间的运算会被折叠为常量
+            if initial.to_llvm_basic_value_enum().as_instruction_value().is_some() {
+                return Err(CompileError::TypeMismatched { expect: "constant expression".into(), found: "expression".into() }.to_leerror(variable.value.pos()));
+            }
             if let Some(exact_type) = &variable.prototype.type_declarator {
                 let target_type = le_error!(self.context.get_generic_type(exact_type),exact_type.pos())?;
-                if target_type == initial_type {
-                    self.create_global_variable(
-                        variable.prototype.identifier.name.clone(),
-                        initial,
-                        module,
-                        variable.prototype.identifier.pos(),
-                    )?;
-                } else {
+                let initial_type = LEBasicValue::get_le_type(&initial);
+                if target_type != initial_type {
                     return Err(CompileError::TypeMismatched { expect: target_type.to_string(), found: initial_type.to_string() }.to_leerror(variable.pos()));
                 }
-            } else {
-                self.create_global_variable(
-                    variable.prototype.identifier.name.clone(),
-                    initial,
-                    module,
-                    variable.prototype.identifier.pos(),
-                )?;
             }
+            self.create_global_variable(variable, initial)?;
         }
         Ok(())
     }
 
-    pub fn create_global_variable(&mut self, name: String, initial_value: LEBasicValueEnum<'ctx>, module: &Module<'ctx>, position: Position) -> Result<LEPointerValue<'ctx>> {
-        let pointer = self.builder.build_global_alloca_with_initial_value(initial_value, module, Some(1.into()));
-        le_error!(self.context.insert_global_variable(name, pointer.clone(), position.clone()),position)?;
-        Ok(pointer)
+    /// 模块中的全局变量会在每个导入模块的源文件中生成，和模块中的函数一样使用修饰名，链接时只保留一份，
+    /// 所有源文件读写的是同一个变量；入口文件中`pub`的全局变量以原名导出，其余的只在本文件中可见
+    pub fn create_global_variable(&mut self, variable: &Variable, initial_value: LEBasicValueEnum<'ctx>) -> Result<LEPointerValue<'ctx>> {
+        let name = &variable.prototype.identifier.name;
+        let (symbol, linkage) = if name.contains("::") {
+            (mangle::mangle_path(&[name.as_str()]), Linkage::LinkOnceODR)
+        } else if variable.is_public {
+            (name.clone(), Linkage::External)
+        } else {
+            (name.clone(), Linkage::Internal)
+        };
+        let pointer = self.builder.build_global_alloca_with_initial_value(initial_value, &self.module, &symbol, linkage);
+        let position = variable.prototype.identifier.pos();
+        le_error!(self.context.insert_global_variable(name.clone(), pointer.clone(), position.clone()),position)
     }
 
-    fn generate_all_global_structures(&mut self, module: &Module, ast: &Ast) -> Result<()> {
+    fn generate_all_global_structures(&mut self, ast: &Ast) -> Result<()> {
         for structure in ast.globals_structures.iter() {
+            check_structure_members(structure)?;
+        }
+        //泛型结构体在使用时才实例化，这里只记录定义
+        for structure in ast.globals_structures.iter().filter(|structure| !structure.generic_params.is_empty()) {
+            let identifier = &structure.identifier;
+            if let Some(defined) = self.context.compiler_context.generic_structures.get(&identifier.name) {
+                return Err(CompileError::IdentifierAlreadyDefined { identifier: identifier.name.clone(), defined_position: defined.identifier.pos() }.to_leerror(identifier.pos()));
+            }
+            generic_bound::check_bounds_defined(&structure.generic_params, &self.context.compiler_context.traits)?;
+            self.context.compiler_context.generic_structures.insert(identifier.name.clone(), structure.clone());
+        }
+        for structure in ast.globals_structures.iter().filter(|structure| structure.generic_params.is_empty()) {
             let mut names = vec![];
             let mut types = vec![];
             for (name, ty) in structure.members.iter() {
                 names.push(name.as_str());
                 types.push(le_error!(self.context.get_generic_type(ty),ty.pos())?);
             }
-            let structure_type = LEStructType::from_llvm_type(&self.context, &names, &types);
+            let structure_type = LEStructType::from_llvm_type(&self.context, &structure.identifier.name, &names, &types);
             le_error!(self.context.insert_global_type(
                 structure.identifier.name.clone(),
                 structure_type.to_le_type_enum(),
@@ -638,23 +2127,99 @@ impl<'ctx> CodeGenerator<'ctx> {
     }
 
 
-    pub fn compile(&mut self, module: &Module<'ctx>, ast: &Ast) -> Result<()> {
+    pub fn compile(&mut self, ast: &Ast) -> Result<()> {
         //生成所有全局变量相关的代码
-        self.generate_all_global_variables(module, ast)?;
+        self.generate_all_global_variables(ast)?;
+        //记录所有trait的定义
+        self.generate_all_traits(ast)?;
         //生成所有全局结构体类型的代码
-        self.generate_all_global_structures(module, ast)?;
+        self.generate_all_global_structures(ast)?;
         //生成所有全局函数的代码
-        self.generate_all_functions(module, ast)?;
+        self.generate_all_functions(ast)?;
+        if let Some(debug_info) = &self.debug_info {
+            debug_info.finalize();
+        }
         Ok(())
     }
 
-    pub fn create(context: &'ctx Context) -> Self {
+    /// 为之后生成的函数、变量和语句生成调试信息，`input_path`是模块对应的源文件
+    pub fn enable_debug_info(&mut self, source_map: &SourceMap, input_path: &Path, target_data: TargetData, is_optimized: bool) {
+        let file_id = source_map.find_file(input_path).unwrap_or_default();
+        self.debug_info = Some(DebugInfo::new(&self.module, source_map, file_id, target_data, is_optimized));
+    }
+
+    /// 入口文件中用`pub`标记的函数以及C入口`main`，它们以源代码中的名字作为C ABI符号导出
+    pub fn exported_functions(&self) -> &HashSet<String> {
+        &self.exported_functions
+    }
+
+    /// 入口文件中用`pub`标记的函数的原型，按定义的顺序排列
+    pub fn exported_prototypes(&self) -> &[ExportedFunction<'ctx>] {
+        &self.exported_prototypes
+    }
+
+    /// 按目标的C调用约定传递`decl`声明的函数和导出函数的结构体和数组参数，需要在`compile`之前调用
+    pub fn enable_c_abi(&mut self, abi: CAbi, target_data: TargetData) {
+        self.c_abi = Some(CAbiLowering::new(abi, target_data));
+    }
+
+    /// 编译到WebAssembly时调用，为之后生成的`pub`函数和C入口`main`加上wasm导出名，
+    /// 为`decl`声明的函数加上`env`模块中的导入名
+    pub fn enable_wasm_interface(&mut self) {
+        self.wasm_interface = true;
+    }
+
+    pub fn create(context: &'ctx Context, module_name: &str) -> Self {
         let llvm_builder = context.create_builder();
         Self {
             builder: LEBuilder::new(llvm_builder),
             context: LEContext::new(context),
+            module: context.create_module(module_name),
+            debug_info: None,
+            wasm_interface: false,
+            exported_functions: HashSet::new(),
+            exported_prototypes: vec![],
+            c_abi: None,
         }
     }
 }
 
+/// `#[link_name = "foo"]`指定的符号名，`#[no_mangle]`时为不带模块路径的名字，没有指定时返回None
+fn attribute_symbol_name(prototype: &FunctionPrototype) -> Option<String> {
+    prototype.attributes.iter().find_map(|attribute| match (attribute.name.name.as_str(), &attribute.value) {
+        ("link_name", Some(value)) => Some(value.content.clone()),
+        ("no_mangle", None) => prototype.identifier.name.rsplit("::").next().map(|name| name.to_string()),
+        _ => None,
+    })
+}
+
+/// 记录属性指定的符号名，已被其他函数使用时报告在指定符号名的属性上
+fn claim_symbol(symbols: &mut HashSet<String>, prototype: &FunctionPrototype, symbol: &str) -> Result<()> {
+    if symbols.insert(symbol.to_string()) {
+        return Ok(());
+    }
+    let attribute = prototype.attributes.iter().find(|attribute| matches!(attribute.name.name.as_str(), "link_name" | "no_mangle")).unwrap();
+    Err(CompileError::InvalidAttribute {
+        attribute: attribute.name.name.clone(),
+        reason: format!("symbol `{}` is already used by another function", symbol),
+    }.to_leerror(attribute.pos()))
+}
 
+/// 函数签名的文字形式，用于报告trait方法签名不一致
+fn format_signature(param_types: &[LEBasicTypeEnum], return_type: &Option<LEBasicTypeEnum>) -> String {
+    let params = param_types.iter().map(|ty| ty.to_string()).collect::<Vec<_>>().join(",");
+    match return_type {
+        Some(ty) => format!("le ({})->{}", params, ty),
+        None => format!("le ({})", params),
+    }
+}
+
+/// 结构体的成员名不能重复，成员名没有单独记录位置，报告在成员的类型上
+fn check_structure_members(structure: &Structure) -> Result<()> {
+    for (index, (name, ty)) in structure.members.iter().enumerate() {
+        if let Some((_, defined)) = structure.members[..index].iter().find(|(defined_name, _)| defined_name == name) {
+            return Err(CompileError::IdentifierAlreadyDefined { identifier: name.clone(), defined_position: defined.pos() }.to_leerror(ty.pos()));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,320 @@
+use std::collections::HashMap;
+
+use crate::ast::nodes::*;
+use crate::error::{CompileError, Result};
+use crate::lexer::Operator;
+
+/// 在泛型函数定义处检查trait约束：约束必须是已定义的trait，
+/// 对泛型参数类型的值调用的方法必须在它的某个约束中声明，接收者可以是任意表达式，
+/// 如`foo().method()`、`s.field.method()`，`ast`用于查找结构体成员和函数的返回类型
+pub fn check_generic_function_bounds(function: &FunctionDefinition, ast: &Ast, traits: &HashMap<String, TraitDefinition>) -> Result<()> {
+    let generic_params = &function.prototype.generic_params;
+    check_bounds_defined(generic_params, traits)?;
+    let mut checker = BoundChecker { generic_params, ast, traits, scopes: vec![HashMap::new()] };
+    for (name, ty) in function.param_names.iter().zip(function.prototype.param_types.iter()) {
+        checker.scopes[0].insert(name.clone(), Some(ty.clone()));
+    }
+    checker.check_code_block(&function.code_block)
+}
+
+/// 检查泛型参数的约束是否都是已定义的trait
+pub fn check_bounds_defined(generic_params: &[GenericParam], traits: &HashMap<String, TraitDefinition>) -> Result<()> {
+    for bound in generic_params.iter().flat_map(|param| param.bounds.iter()) {
+        if !traits.contains_key(&bound.name) {
+            return Err(CompileError::UnknownTrait { identifier: bound.name.clone() }.to_leerror(bound.pos()));
+        }
+    }
+    Ok(())
+}
+
+struct BoundChecker<'a> {
+    generic_params: &'a [GenericParam],
+    ast: &'a Ast,
+    traits: &'a HashMap<String, TraitDefinition>,
+    ///变量名到它声明或推导出的类型，推导不出时为None，内层变量会遮蔽外层的同名变量
+    scopes: Vec<HashMap<String, Option<TypeDeclarator>>>,
+}
+
+impl<'a> BoundChecker<'a> {
+    ///类型是泛型参数或泛型参数的引用时，返回对应的泛型参数
+    fn generic_param_of(&self, ty: &TypeDeclarator) -> Option<&'a GenericParam> {
+        match ty {
+            TypeDeclarator::TypeIdentifier(identifier) => {
+                self.generic_params.iter().find(|param| param.identifier.name == identifier.name)
+            }
+            TypeDeclarator::Reference(reference) => self.generic_param_of(reference),
+            _ => None
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<TypeDeclarator> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name)).cloned().flatten()
+    }
+
+    fn insert(&mut self, name: String, ty: Option<TypeDeclarator>) {
+        self.scopes.last_mut().unwrap().insert(name, ty);
+    }
+
+    fn check_code_block(&mut self, code_block: &CodeBlock) -> Result<()> {
+        self.scopes.push(HashMap::new());
+        let result = code_block.statements.iter().try_for_each(|statement| self.check_statement(statement));
+        self.scopes.pop();
+        result
+    }
+
+    fn check_statement(&mut self, statement: &Statement) -> Result<()> {
+        match statement {
+            Statement::Expressions(expr) | Statement::Return(expr) => self.check_expression(expr),
+            Statement::VariableDefinition(variable) => {
+                self.check_expression(&variable.value)?;
+                let ty = match &variable.prototype.type_declarator {
+                    Some(ty) => Some(ty.clone()),
+                    None => self.type_of(&variable.value),
+                };
+                self.insert(variable.prototype.identifier.name.clone(), ty);
+                Ok(())
+            }
+            Statement::If(statement) => {
+                self.check_expression(&statement.cond)?;
+                self.check_code_block(&statement.then_block)?;
+                if let Some(else_block) = &statement.else_block {
+                    self.check_code_block(else_block)?;
+                }
+                Ok(())
+            }
+            Statement::ForLoop(for_loop) => {
+                self.scopes.push(HashMap::new());
+                let result = self.check_statement(&for_loop.init_statement)
+                    .and_then(|_| self.check_statement(&for_loop.condition))
+                    .and_then(|_| self.check_statement(&for_loop.iterate))
+                    .and_then(|_| self.check_code_block(&for_loop.code_block));
+                self.scopes.pop();
+                result
+            }
+            Statement::WhileLoop(while_loop) => {
+                self.check_expression(&while_loop.condition)?;
+                self.check_code_block(&while_loop.code_block)
+            }
+            Statement::Match(statement) => {
+                self.check_expression(&statement.scrutinee)?;
+                statement.arms.iter().try_for_each(|arm| self.check_code_block(&arm.code_block))
+            }
+            Statement::Void(_) => Ok(())
+        }
+    }
+
+    fn check_expression(&mut self, expr: &Expr) -> Result<()> {
+        match expr {
+            Expr::BinaryOperator(binary) => {
+                if let (Operator::Dot, Expr::CallExpression(method_call)) = (&binary.op, binary.right.as_ref()) {
+                    if let Some(param) = self.type_of(&binary.left).and_then(|ty| self.generic_param_of(&ty)) {
+                        self.check_method_in_bounds(param, &method_call.function_name)?;
+                    }
+                    self.check_expression(&binary.left)?;
+                    method_call.params.iter().try_for_each(|param| self.check_expression(param))
+                } else {
+                    self.check_expression(&binary.left)?;
+                    self.check_expression(&binary.right)
+                }
+            }
+            Expr::UnaryOperator(unary) => self.check_expression(&unary.expr),
+            Expr::CallExpression(call) => call.params.iter().try_for_each(|param| self.check_expression(param)),
+            Expr::ArrayInitializer(array) => array.elements.iter().try_for_each(|element| self.check_expression(element)),
+            Expr::StructureInitializer(structure) => {
+                structure.member_initial_values.iter().try_for_each(|(_, value)| self.check_expression(value))
+            }
+            Expr::NumberLiteral(_) | Expr::StringLiteral(_) | Expr::Identifier(_) => Ok(())
+        }
+    }
+
+    fn check_method_in_bounds(&self, param: &GenericParam, method: &Identifier) -> Result<()> {
+        if self.bound_method(param, &method.name).is_some() {
+            Ok(())
+        } else {
+            Err(CompileError::MethodNotInBounds {
+                generic_param: param.identifier.name.clone(),
+                method: method.name.clone(),
+            }.to_leerror(method.pos()))
+        }
+    }
+
+    ///在泛型参数的约束中查找方法的声明
+    fn bound_method(&self, param: &GenericParam, method: &str) -> Option<&'a FunctionPrototype> {
+        param.bounds
+            .iter()
+            .filter_map(|bound| self.traits.get(&bound.name))
+            .find_map(|trait_definition| trait_definition.functions.iter().find(|function| function.identifier.name == method))
+    }
+
+    ///推导表达式的类型声明，只推导检查方法调用需要的部分：变量、成员、函数和方法的返回值以及不改变类型的运算，
+    ///推导不出时返回None，此时不检查该接收者，实例化时仍会检查
+    fn type_of(&self, expr: &Expr) -> Option<TypeDeclarator> {
+        match expr {
+            Expr::Identifier(identifier) => self.lookup(&identifier.name),
+            Expr::UnaryOperator(unary) => self.type_of(&unary.expr),
+            Expr::BinaryOperator(binary) => match (&binary.op, binary.right.as_ref()) {
+                (Operator::Dot, Expr::Identifier(member)) => self.member_type(&self.type_of(&binary.left)?, &member.name),
+                (Operator::Dot, Expr::CallExpression(method_call)) => self.method_return_type(&self.type_of(&binary.left)?, method_call),
+                (Operator::Cast, Expr::Identifier(ty)) => Some(TypeDeclarator::TypeIdentifier(ty.clone())),
+                (Operator::Plus | Operator::Sub | Operator::Mul | Operator::Div | Operator::Mod, _) => self.type_of(&binary.left),
+                _ => None
+            },
+            Expr::CallExpression(call) => self.call_return_type(call),
+            Expr::StructureInitializer(structure) => self.structure_initializer_type(structure),
+            Expr::NumberLiteral(_) | Expr::StringLiteral(_) | Expr::ArrayInitializer(_) => None
+        }
+    }
+
+    ///结构体成员的类型，泛型结构体的成员类型用类型实参替换
+    fn member_type(&self, receiver: &TypeDeclarator, member: &str) -> Option<TypeDeclarator> {
+        let (name, type_arguments) = match strip_reference(receiver) {
+            TypeDeclarator::TypeIdentifier(identifier) => (&identifier.name, &[][..]),
+            TypeDeclarator::Generic(generic) => (&generic.identifier.name, generic.type_arguments.as_slice()),
+            _ => return None
+        };
+        let structure = self.ast.globals_structures.iter().find(|structure| &structure.identifier.name == name)?;
+        let (_, ty) = structure.members.iter().find(|(member_name, _)| member_name == member)?;
+        Some(substitute(ty, &bind(&structure.generic_params, type_arguments)))
+    }
+
+    ///方法的返回类型：接收者是泛型参数时在它的约束中查找，否则在目标类型的impl块中查找，`Self`替换为接收者的类型
+    fn method_return_type(&self, receiver: &TypeDeclarator, method_call: &FunctionCall) -> Option<TypeDeclarator> {
+        let receiver = strip_reference(receiver);
+        let prototype = match self.generic_param_of(receiver) {
+            Some(param) => self.bound_method(param, &method_call.function_name.name)?,
+            None => self.impl_method(receiver_name(receiver)?, &method_call.function_name.name)?,
+        };
+        let bindings = HashMap::from([("Self".to_string(), receiver.clone())]);
+        Some(substitute(prototype.return_type.as_ref()?, &bindings))
+    }
+
+    ///函数调用的返回类型，`S::new(...)`在S的impl块中查找，泛型函数的类型参数按显式类型实参或实参的类型推导
+    fn call_return_type(&self, call: &FunctionCall) -> Option<TypeDeclarator> {
+        let name = &call.function_name.name;
+        let mut bindings = HashMap::new();
+        let prototype = match self.ast.function_definitions.iter().map(|function| &function.prototype)
+            .chain(self.ast.extern_functions.iter())
+            .find(|prototype| &prototype.identifier.name == name) {
+            Some(prototype) => prototype,
+            None => {
+                let (target, method) = name.rsplit_once("::")?;
+                let identifier = Identifier { name: target.to_string(), pos: call.function_name.pos() };
+                bindings.insert("Self".to_string(), TypeDeclarator::TypeIdentifier(identifier));
+                self.impl_method(target, method)?
+            }
+        };
+        if call.type_arguments.is_empty() {
+            for (param_type, argument) in prototype.param_types.iter().zip(call.params.iter()) {
+                if let Some(actual) = self.type_of(argument) {
+                    infer(param_type, &actual, &prototype.generic_params, &mut bindings);
+                }
+            }
+        } else {
+            bindings.extend(bind(&prototype.generic_params, &call.type_arguments));
+        }
+        //未推导出的类型参数可能与当前函数的泛型参数同名，不能原样返回
+        if !prototype.generic_params.iter().all(|param| bindings.contains_key(&param.identifier.name)) {
+            return None;
+        }
+        Some(substitute(prototype.return_type.as_ref()?, &bindings))
+    }
+
+    ///结构体初始化表达式的类型，泛型结构体的类型实参按成员的值推导
+    fn structure_initializer_type(&self, initializer: &StructureInitializer) -> Option<TypeDeclarator> {
+        let identifier = initializer.structure_name.clone();
+        let structure = self.ast.globals_structures.iter().find(|structure| structure.identifier.name == identifier.name)?;
+        if structure.generic_params.is_empty() {
+            return Some(TypeDeclarator::TypeIdentifier(identifier));
+        }
+        let type_arguments = if initializer.type_arguments.is_empty() {
+            let mut bindings = HashMap::new();
+            for (name, value) in initializer.member_initial_values.iter() {
+                let member = structure.members.iter().find(|(member_name, _)| member_name == name);
+                if let (Some((_, declarator)), Some(actual)) = (member, self.type_of(value)) {
+                    infer(declarator, &actual, &structure.generic_params, &mut bindings);
+                }
+            }
+            structure.generic_params
+                .iter()
+                .map(|param| bindings.remove(&param.identifier.name))
+                .collect::<Option<Vec<_>>>()?
+        } else {
+            initializer.type_arguments.clone()
+        };
+        let pos = identifier.pos();
+        Some(TypeDeclarator::Generic(Box::new(GenericDeclarator { identifier, type_arguments, pos })))
+    }
+
+    fn impl_method(&self, target: &str, method: &str) -> Option<&'a FunctionPrototype> {
+        self.ast.impls
+            .iter()
+            .filter(|impl_block| impl_block.target.name == target)
+            .flat_map(|impl_block| impl_block.functions.iter())
+            .map(|function| &function.prototype)
+            .find(|prototype| prototype.identifier.name == method)
+    }
+}
+
+fn strip_reference(ty: &TypeDeclarator) -> &TypeDeclarator {
+    match ty {
+        TypeDeclarator::Reference(reference) => strip_reference(reference),
+        _ => ty
+    }
+}
+
+fn receiver_name(ty: &TypeDeclarator) -> Option<&str> {
+    match ty {
+        TypeDeclarator::TypeIdentifier(identifier) => Some(&identifier.name),
+        _ => None
+    }
+}
+
+///把泛型参数按顺序绑定到类型实参
+fn bind(generic_params: &[GenericParam], type_arguments: &[TypeDeclarator]) -> HashMap<String, TypeDeclarator> {
+    generic_params.iter().map(|param| param.identifier.name.clone()).zip(type_arguments.iter().cloned()).collect()
+}
+
+///用实际类型匹配含泛型参数的类型声明，推导出泛型参数对应的类型
+fn infer(declarator: &TypeDeclarator, actual: &TypeDeclarator, generic_params: &[GenericParam], bindings: &mut HashMap<String, TypeDeclarator>) {
+    match (declarator, actual) {
+        (TypeDeclarator::TypeIdentifier(identifier), _) => {
+            if generic_params.iter().any(|param| param.identifier.name == identifier.name) {
+                bindings.entry(identifier.name.clone()).or_insert_with(|| actual.clone());
+            }
+        }
+        (TypeDeclarator::Reference(declarator), TypeDeclarator::Reference(actual)) => infer(declarator, actual, generic_params, bindings),
+        (TypeDeclarator::Generic(declarator), TypeDeclarator::Generic(actual)) if declarator.identifier.name == actual.identifier.name => {
+            for (declarator, actual) in declarator.type_arguments.iter().zip(actual.type_arguments.iter()) {
+                infer(declarator, actual, generic_params, bindings);
+            }
+        }
+        _ => {}
+    }
+}
+
+///把类型声明中的类型参数替换为绑定的类型
+fn substitute(ty: &TypeDeclarator, bindings: &HashMap<String, TypeDeclarator>) -> TypeDeclarator {
+    match ty {
+        TypeDeclarator::TypeIdentifier(identifier) => bindings.get(&identifier.name).cloned().unwrap_or_else(|| ty.clone()),
+        TypeDeclarator::Reference(reference) => TypeDeclarator::Reference(Box::new(substitute(reference, bindings))),
+        TypeDeclarator::Generic(generic) => TypeDeclarator::Generic(Box::new(GenericDeclarator {
+            identifier: generic.identifier.clone(),
+            type_arguments: generic.type_arguments.iter().map(|argument| substitute(argument, bindings)).collect(),
+            pos: generic.pos.clone(),
+        })),
+        TypeDeclarator::Array(array) => TypeDeclarator::Array(Box::new(ArrayDeclarator {
+            element_type: substitute(&array.element_type, bindings),
+            len: array.len,
+            pos: array.pos.clone(),
+        })),
+        TypeDeclarator::Slice(slice) => TypeDeclarator::Slice(Box::new(SliceDeclarator {
+            element_type: substitute(&slice.element_type, bindings),
+            pos: slice.pos.clone(),
+        })),
+        TypeDeclarator::Vector(vector) => TypeDeclarator::Vector(Box::new(VectorDeclarator {
+            element_type: substitute(&vector.element_type, bindings),
+            len: vector.len,
+            pos: vector.pos.clone(),
+        })),
+    }
+}
@@ -0,0 +1,161 @@
+use crate::code_generator::builder::LEBasicTypeEnum;
+use crate::lexer::Operator;
+
+/// 生成路径形式符号的LLVM名字，避免不同结构体的同名方法在模块中冲突
+/// 格式参考Itanium ABI的嵌套名字：`_LN` + 每段的`长度+名字` + `E`
+/// ```
+/// use lelang::code_generator::mangle::mangle_path;
+/// assert_eq!(mangle_path(&["MyStruct", "area"]), "_LN8MyStruct4areaE");
+/// ```
+pub fn mangle_path(segments: &[&str]) -> String {
+    let mut mangled = String::from("_LN");
+    for segment in segments {
+        push_segments(&mut mangled, segment);
+    }
+    mangled.push('E');
+    mangled
+}
+
+/// 把名字按`::`拆开，每段写成`长度+名字`
+fn push_segments(mangled: &mut String, name: &str) {
+    for segment in name.split("::") {
+        mangled.push_str(&segment.len().to_string());
+        mangled.push_str(segment);
+    }
+}
+
+/// 具名类型在修饰名中的编码：单段名字为`长度+名字`，模块路径为`N` + 每段 + `E`
+pub fn mangle_type_name(name: &str) -> String {
+    let mut mangled = String::new();
+    push_segments(&mut mangled, name);
+    if name.contains("::") {
+        format!("N{}E", mangled)
+    } else {
+        mangled
+    }
+}
+
+/// 类型在修饰名中的编码，只由字母、数字和`_`组成
+/// 标量和str为`长度+名字`，指针为`P` + 指向的类型，数组为`A长度_` + 元素类型，向量为`Dv长度_` + 元素类型，
+/// 结构体使用创建时记录的编码
+pub fn mangle_type(ty: &LEBasicTypeEnum) -> String {
+    match ty {
+        LEBasicTypeEnum::Pointer(t) if t.is_str() => mangle_type_name("str"),
+        LEBasicTypeEnum::Pointer(t) => format!("P{}", mangle_type(&t.get_point_type())),
+        LEBasicTypeEnum::Array(t) => format!("A{}_{}", t.len(), mangle_type(&t.get_element_type())),
+        LEBasicTypeEnum::Vector(t) => format!("Dv{}_{}", t.len(), mangle_type(&t.get_element_type())),
+        LEBasicTypeEnum::Struct(t) => t.mangled_name().to_string(),
+        scalar => mangle_type_name(&scalar.to_string()),
+    }
+}
+
+/// 泛型结构体实例的类型编码：名字的编码 + `I` + 每个类型实参的编码 + `E`
+pub fn mangle_generic_structure(name: &str, type_arguments: &[LEBasicTypeEnum]) -> String {
+    let arguments = type_arguments.iter().map(mangle_type).collect::<String>();
+    format!("{}I{}E", mangle_type_name(name), arguments)
+}
+
+/// 切片`[T]`的类型编码：`S_` + 元素类型的编码
+pub fn mangle_slice(element_type: &LEBasicTypeEnum) -> String {
+    format!("S_{}", mangle_type(element_type))
+}
+
+/// 方法在模块中的名字：`_LN` + 目标类型的编码 + trait路径的每段 + 方法名 + `E`
+/// 目标类型按结构编码，泛型结构体实例和非结构体类型的方法也只含合法的符号字符
+pub fn mangle_method(target_type: &LEBasicTypeEnum, trait_name: Option<&str>, method_name: &str) -> String {
+    let mut mangled = String::from("_LN");
+    mangled.push_str(&mangle_type(target_type));
+    if let Some(trait_name) = trait_name {
+        push_segments(&mut mangled, trait_name);
+    }
+    push_segments(&mut mangled, method_name);
+    mangled.push('E');
+    mangled
+}
+
+/// 结构体方法在符号表中的名字，也是`MyStruct::new(...)`调用时使用的名字
+pub fn method_symbol_name(struct_name: &str, method_name: &str) -> String {
+    format!("{}::{}", struct_name, method_name)
+}
+
+/// 泛型实例的名字，如`max<i32>`、`Pair<i32,f64>`，同时作为实例缓存的key
+pub fn generic_instance_name(name: &str, type_arguments: &[LEBasicTypeEnum]) -> String {
+    let arguments = type_arguments.iter().map(|ty| ty.to_string()).collect::<Vec<_>>();
+    format!("{}<{}>", name, arguments.join(","))
+}
+
+/// 泛型函数实例在模块中的名字：`_LN` + 名字每段的`长度+名字` + `I` + 每个类型实参的编码 + `EE`
+/// ```
+/// use lelang::code_generator::mangle::mangle_generic_instance;
+/// assert_eq!(mangle_generic_instance("max", &[]), "_LN3maxIEE");
+/// ```
+pub fn mangle_generic_instance(name: &str, type_arguments: &[LEBasicTypeEnum]) -> String {
+    let mut mangled = String::from("_LN");
+    push_segments(&mut mangled, name);
+    mangled.push('I');
+    for ty in type_arguments {
+        mangled.push_str(&mangle_type(ty));
+    }
+    mangled.push_str("EE");
+    mangled
+}
+
+/// 运算符对应的运算符函数名，结构体通过定义这些函数重载运算符
+pub fn operator_function_name(op: &Operator) -> Option<&'static str> {
+    match op {
+        Operator::Plus => Some("__add"),
+        Operator::Sub => Some("__sub"),
+        Operator::Mul => Some("__mul"),
+        Operator::Div => Some("__div"),
+        Operator::Mod => Some("__mod"),
+        Operator::Equal => Some("__eq"),
+        Operator::NotEqual => Some("__ne"),
+        Operator::LessThan => Some("__lt"),
+        Operator::GreaterThan => Some("__gt"),
+        Operator::LessOrEqualThan => Some("__le"),
+        Operator::GreaterOrEqualThan => Some("__ge"),
+        _ => None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn path_segments_are_length_prefixed() {
+        assert_eq!(mangle_path(&["MyStruct", "area"]), "_LN8MyStruct4areaE");
+        assert_eq!(mangle_path(&["math", "sqrt"]), "_LN4math4sqrtE");
+        //同名方法在不同结构体和trait实现中的名字不同
+        assert_ne!(mangle_path(&["A", "show"]), mangle_path(&["A", "Show", "show"]));
+        assert_ne!(mangle_path(&["ab", "c"]), mangle_path(&["a", "bc"]));
+        //模块路径按`::`拆成多段
+        assert_eq!(mangle_path(&["math::Point", "new"]), "_LN4math5Point3newE");
+    }
+
+    #[test]
+    fn type_names_are_length_prefixed() {
+        assert_eq!(mangle_type_name("i32"), "3i32");
+        assert_eq!(mangle_type_name("math::Point"), "N4math5PointE");
+        assert_ne!(mangle_type_name("a::b"), mangle_type_name("a_b"));
+    }
+
+    #[test]
+    fn generic_instance_names_contain_only_symbol_characters() {
+        let name = mangle_generic_instance("math::max", &[]);
+        assert_eq!(name, "_LN4math3maxIEE");
+        assert!(name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'));
+    }
+
+    #[test]
+    fn method_symbol_names() {
+        assert_eq!(method_symbol_name("Counter", "new"), "Counter::new");
+    }
+
+    #[test]
+    fn operator_function_names() {
+        assert_eq!(operator_function_name(&Operator::Plus), Some("__add"));
+        assert_eq!(operator_function_name(&Operator::GreaterOrEqualThan), Some("__ge"));
+        assert_eq!(operator_function_name(&Operator::And), None);
+    }
+}
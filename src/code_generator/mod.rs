@@ -1,5 +1,11 @@
 pub mod generator;
 pub mod builder;
 pub mod context;
+pub mod mangle;
+pub mod generic_bound;
+pub mod format_string;
+pub mod debug_info;
+pub mod c_header;
+pub mod abi;
 
 pub type Result<T> = std::result::Result<T, crate::error::CompileError>;
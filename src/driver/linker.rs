@@ -0,0 +1,327 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::arg_parser::Args;
+use crate::error::{LEError, Result};
+
+///输出静态库时按顺序查找的打包工具
+const ARCHIVER_CANDIDATES: [&str; 2] = ["ar", "llvm-ar"];
+
+///编译到wasm32时使用的链接器
+const WASM_LINKER: &str = "wasm-ld";
+
+/// 按顺序查找的链接器，前三个是C编译器驱动，会自动加上C运行时的启动文件
+const LINKER_CANDIDATES: [&str; 5] = ["cc", "clang", "gcc", "ld.lld", "ld"];
+
+///直接调用ld时，用来查询C运行时文件位置的C编译器驱动
+const C_COMPILER_CANDIDATES: [&str; 3] = ["cc", "clang", "gcc"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LinkerFlavor {
+    ///cc、clang、gcc等C编译器驱动
+    Cc,
+    ///直接调用ld、ld.lld等链接器
+    Ld,
+}
+
+impl LinkerFlavor {
+    fn from_path(path: &Path) -> Self {
+        let name = path.file_stem().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        if name == "ld" || name.starts_with("ld.") || name.starts_with("ld64") {
+            LinkerFlavor::Ld
+        } else {
+            LinkerFlavor::Cc
+        }
+    }
+}
+
+/// 把目标文件和运行时静态库链接为可执行文件
+pub fn link_executable(config: &Args, object_paths: &[PathBuf], output_path: &Path) -> Result<()> {
+    let linker = find_linker(config)?;
+    let runtime = find_runtime_library()?;
+    let flavor = LinkerFlavor::from_path(&linker);
+
+    let c_runtime = match flavor {
+        LinkerFlavor::Ld => Some(CRuntime::query()?),
+        LinkerFlavor::Cc => None,
+    };
+
+    let mut cmd = Command::new(&linker);
+    cmd.arg("-o").arg(output_path);
+    if let Some(c_runtime) = &c_runtime {
+        cmd.args(&c_runtime.begin_objects);
+        if let Some(dynamic_linker) = &c_runtime.dynamic_linker {
+            cmd.arg("-dynamic-linker").arg(dynamic_linker);
+        }
+    }
+    cmd.args(object_paths);
+    cmd.arg(&runtime);
+    for path in config.library_paths.iter() {
+        cmd.arg("-L").arg(path);
+    }
+    for library in config.libraries.iter() {
+        cmd.arg(format!("-l{}", library));
+    }
+    if let Some(c_runtime) = &c_runtime {
+        cmd.args(c_runtime.library_dirs.iter().map(|dir| format!("-L{}", dir.display())));
+        cmd.arg("-lc");
+        cmd.args(&c_runtime.end_objects);
+    }
+
+    run_linker(&linker, cmd)
+}
+
+/// 把wasm32目标文件链接为`.wasm`模块：没有入口函数，带有wasm导出名的函数被导出，
+/// 带有导入名的`decl`函数保留为导入，线性内存默认导出为`memory`，数组和结构体位于其中的栈上。
+/// 运行时库是为本机编译的，不会被链接，用到的运行时函数同样作为导入由宿主提供
+pub fn link_wasm_module(config: &Args, object_paths: &[PathBuf], output_path: &Path) -> Result<()> {
+    let linker = match &config.linker {
+        Some(linker) => linker.clone(),
+        None => find_in_path(WASM_LINKER).ok_or_else(|| LEError::LinkError {
+            message: format!("cannot find `{}` for wasm32 targets, use `--linker` to specify one", WASM_LINKER)
+        })?,
+    };
+    let mut cmd = Command::new(&linker);
+    cmd.arg("-o").arg(output_path);
+    cmd.args(["--no-entry", "--allow-undefined"]);
+    cmd.args(object_paths);
+    for path in config.library_paths.iter() {
+        cmd.arg("-L").arg(path);
+    }
+    for library in config.libraries.iter() {
+        cmd.arg(format!("-l{}", library));
+    }
+    run_linker(&linker, cmd)
+}
+
+/// 把目标文件链接为动态库，动态库中用到的运行时库函数由加载它的程序提供
+pub fn link_shared_library(config: &Args, object_path: &Path, output_path: &Path) -> Result<()> {
+    let linker = find_linker(config)?;
+    let mut cmd = Command::new(&linker);
+    cmd.arg("-shared").arg("-o").arg(output_path);
+    cmd.arg(object_path);
+    for path in config.library_paths.iter() {
+        cmd.arg("-L").arg(path);
+    }
+    for library in config.libraries.iter() {
+        cmd.arg(format!("-l{}", library));
+    }
+    run_linker(&linker, cmd)
+}
+
+/// 用ar把目标文件打包为静态库，使用者链接时需要同时链接运行时库
+pub fn create_static_library(object_path: &Path, output_path: &Path) -> Result<()> {
+    let archiver = ARCHIVER_CANDIDATES
+        .iter()
+        .find_map(|name| find_in_path(name))
+        .ok_or_else(|| LEError::LinkError {
+            message: format!("cannot find an archiver, tried {}", ARCHIVER_CANDIDATES.join(", "))
+        })?;
+    //ar会向已有的静态库中追加成员，先删除旧的输出
+    let _ = std::fs::remove_file(output_path);
+    let mut cmd = Command::new(&archiver);
+    cmd.arg("rcs").arg(output_path).arg(object_path);
+    run_linker(&archiver, cmd)
+}
+
+fn run_linker(linker: &Path, mut cmd: Command) -> Result<()> {
+    let output = cmd.output().map_err(|e| LEError::LinkError {
+        message: format!("failed to run linker `{}`: {}", linker.display(), e)
+    })?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(LEError::LinkError {
+            message: format!(
+                "linker `{}` failed with {}\n{}",
+                linker.display(),
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim_end()
+            )
+        })
+    }
+}
+
+/// `--linker`指定的链接器优先，否则在PATH中依次查找
+fn find_linker(config: &Args) -> Result<PathBuf> {
+    if let Some(linker) = &config.linker {
+        return Ok(linker.clone());
+    }
+    LINKER_CANDIDATES
+        .iter()
+        .find_map(|name| find_in_path(name))
+        .ok_or_else(|| LEError::LinkError {
+            message: format!("cannot find a linker, tried {}, use `--linker` to specify one", LINKER_CANDIDATES.join(", "))
+        })
+}
+
+/// 在PATH中查找可执行文件，名字不含平台的可执行文件后缀
+pub fn find_in_path(name: &str) -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths)
+        .map(|dir| dir.join(format!("{}{}", name, std::env::consts::EXE_SUFFIX)))
+        .find(|path| path.is_file())
+}
+
+fn runtime_library_name() -> &'static str {
+    if cfg!(windows) { "lelang_runtime.lib" } else { "liblelang_runtime.a" }
+}
+
+/// 依次查找环境变量`LELANG_RUNTIME_DIR`、编译器所在目录及其`lib`子目录，最后是构建时生成运行时库的目录，
+/// 链接结果不依赖当前工作目录
+fn find_runtime_library() -> Result<PathBuf> {
+    find_runtime_library_in(&runtime_library_dirs())
+}
+
+fn runtime_library_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![];
+    if let Some(dir) = std::env::var_os("LELANG_RUNTIME_DIR") {
+        dirs.push(PathBuf::from(dir));
+    }
+    if let Some(exe_dir) = std::env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf)) {
+        dirs.push(exe_dir.join("lib"));
+        dirs.push(exe_dir);
+    }
+    dirs.push(PathBuf::from(env!("LELANG_RUNTIME_DIR")));
+    dirs
+}
+
+fn find_runtime_library_in(dirs: &[PathBuf]) -> Result<PathBuf> {
+    dirs.iter()
+        .map(|dir| dir.join(runtime_library_name()))
+        .find(|path| path.is_file())
+        .ok_or_else(|| LEError::LinkError {
+            message: format!("cannot find runtime library `{}`, set `LELANG_RUNTIME_DIR` to the directory containing it", runtime_library_name())
+        })
+}
+
+const CRT_BEGIN_OBJECTS: [&str; 2] = ["crt1.o", "crti.o"];
+const CRT_END_OBJECTS: [&str; 1] = ["crtn.o"];
+
+///ELF程序头中记录动态链接器路径的类型
+const PT_INTERP: u32 = 3;
+
+/// 直接调用ld时需要自己加上的C运行时：启动文件、libc所在目录和动态链接器。
+/// 这些路径因libc(glibc、musl)、架构和发行版而不同，向C编译器驱动查询而不是写死
+struct CRuntime {
+    begin_objects: Vec<PathBuf>,
+    end_objects: Vec<PathBuf>,
+    library_dirs: Vec<PathBuf>,
+    dynamic_linker: Option<PathBuf>,
+}
+
+impl CRuntime {
+    fn query() -> Result<Self> {
+        let compiler = C_COMPILER_CANDIDATES
+            .iter()
+            .find_map(|name| find_in_path(name))
+            .ok_or_else(|| LEError::LinkError {
+                message: format!("linking with `ld` directly needs a C compiler to locate the C runtime, tried {}", C_COMPILER_CANDIDATES.join(", "))
+            })?;
+        let find_objects = |names: &[&str]| -> Result<Vec<PathBuf>> {
+            names.iter().map(|name| print_file_name(&compiler, name).ok_or_else(|| LEError::LinkError {
+                message: format!("`{} -print-file-name={}` did not find the C runtime file", compiler.display(), name)
+            })).collect()
+        };
+        let begin_objects = find_objects(&CRT_BEGIN_OBJECTS)?;
+        let end_objects = find_objects(&CRT_END_OBJECTS)?;
+        let library_dirs = ["libc.so", "libc.a"]
+            .iter()
+            .filter_map(|name| print_file_name(&compiler, name))
+            .filter_map(|path| path.parent().map(Path::to_path_buf))
+            .take(1)
+            .collect();
+        //C编译器本身链接到同一个libc，它的动态链接器就是可执行文件需要的
+        let dynamic_linker = elf_interpreter(&compiler);
+        Ok(Self { begin_objects, end_objects, library_dirs, dynamic_linker })
+    }
+}
+
+/// `cc -print-file-name=name`给出的绝对路径，找不到时C编译器原样输出名字
+fn print_file_name(compiler: &Path, name: &str) -> Option<PathBuf> {
+    let output = Command::new(compiler).arg(format!("-print-file-name={}", name)).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let path = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    if path.is_absolute() && path.is_file() { Some(path) } else { None }
+}
+
+/// 读取64位小端ELF可执行文件的PT_INTERP程序头，即它使用的动态链接器
+fn elf_interpreter(path: &Path) -> Option<PathBuf> {
+    let data = std::fs::read(path).ok()?;
+    let read_u16 = |offset: usize| data.get(offset..offset + 2).map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]));
+    let read_u32 = |offset: usize| data.get(offset..offset + 4).map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()));
+    let read_u64 = |offset: usize| data.get(offset..offset + 8).map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()) as usize);
+    //魔数、64位、小端
+    if data.get(0..6)? != b"\x7fELF\x02\x01" {
+        return None;
+    }
+    let program_header_offset = read_u64(0x20)?;
+    let program_header_size = read_u16(0x36)? as usize;
+    let program_header_count = read_u16(0x38)? as usize;
+    (0..program_header_count)
+        .map(|index| program_header_offset + index * program_header_size)
+        .find(|header| read_u32(*header) == Some(PT_INTERP))
+        .and_then(|header| {
+            let offset = read_u64(header + 0x08)?;
+            let size = read_u64(header + 0x20)?;
+            let interpreter = data.get(offset..offset + size)?;
+            let interpreter = interpreter.strip_suffix(&[0]).unwrap_or(interpreter);
+            Some(PathBuf::from(String::from_utf8_lossy(interpreter).to_string()))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    #[test]
+    fn linker_flavor_from_name() {
+        for linker in ["ld", "/usr/bin/ld", "ld.lld", "ld.gold", "ld64.lld"] {
+            assert_eq!(LinkerFlavor::from_path(Path::new(linker)), LinkerFlavor::Ld, "{}", linker);
+        }
+        for linker in ["cc", "/usr/bin/clang-14", "gcc", "x86_64-linux-gnu-gcc", "lld-wrapper"] {
+            assert_eq!(LinkerFlavor::from_path(Path::new(linker)), LinkerFlavor::Cc, "{}", linker);
+        }
+    }
+
+    #[test]
+    fn linker_option_takes_precedence() {
+        let mut config = Args::parse_from(["lelang", "-i", "main.le"]);
+        config.linker = Some(PathBuf::from("/opt/toolchain/bin/my-ld"));
+        assert_eq!(find_linker(&config).unwrap(), PathBuf::from("/opt/toolchain/bin/my-ld"));
+    }
+
+    #[test]
+    fn runtime_library_is_searched_in_order() {
+        let root = std::env::temp_dir().join(format!("lelang_runtime_search_{}", std::process::id()));
+        let (first, second) = (root.join("first"), root.join("second"));
+        std::fs::create_dir_all(&first).unwrap();
+        std::fs::create_dir_all(&second).unwrap();
+        std::fs::write(second.join(runtime_library_name()), b"").unwrap();
+        assert_eq!(find_runtime_library_in(&[first.clone(), second.clone()]).unwrap(), second.join(runtime_library_name()));
+        std::fs::write(first.join(runtime_library_name()), b"").unwrap();
+        assert_eq!(find_runtime_library_in(&[first.clone(), second]).unwrap(), first.join(runtime_library_name()));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn missing_runtime_library_is_link_error() {
+        let dir = std::env::temp_dir().join(format!("lelang_runtime_missing_{}", std::process::id()));
+        match find_runtime_library_in(&[dir]) {
+            Err(LEError::LinkError { message }) => {
+                assert!(message.contains("cannot find runtime library"), "{}", message);
+                assert!(message.contains("LELANG_RUNTIME_DIR"), "{}", message);
+            }
+            other => panic!("expect a link error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn runtime_library_built_with_compiler_is_found() {
+        assert!(find_runtime_library().is_ok());
+    }
+}
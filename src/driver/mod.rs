@@ -1,130 +1,315 @@
+use std::collections::HashSet;
 use std::env::args;
 use std::fs::File;
 use std::io::Read;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::time::Instant;
 
 use ariadne::{Report, Source};
 use inkwell::context::Context;
 use inkwell::data_layout::DataLayout;
 use inkwell::memory_buffer::MemoryBuffer;
-use inkwell::targets::FileType;
+use inkwell::module::{Linkage, Module};
+use inkwell::targets::{FileType, TargetMachine};
 use inkwell::OptimizationLevel;
 use nom::error::context;
 use nom::Parser;
 
-use crate::arg_parser::{Args, OutputFormatEnum};
+use crate::arg_parser::{Args, OptimizeLevel, OutputFormatEnum};
 use crate::ast::Ast;
+use crate::code_generator::abi::CAbi;
+use crate::code_generator::c_header::{ExportedFunction, generate_c_header, header_guard};
 use crate::code_generator::generator::CodeGenerator;
 // use crate::code_generator::generator::CodeGenerator;
-use crate::driver::target::{initialize_target_machine, optimize_number_to_level};
+use crate::driver::target::{initialize_target_machine, is_host_target, is_wasm_target, optimize_number_to_level};
 use crate::error::{LEError, Result};
+use crate::driver::module_loader::load_program;
+use crate::driver::timer::PassTimer;
 use crate::lexer;
+use crate::lexer::SourceMap;
 use crate::optimizer::Optimizer;
 
 // use crate::optimizer::Optimizer;
 
 mod target;
+mod module_loader;
+mod linker;
+mod timer;
 
-pub fn compile_with_config(config: &Args, source: &str) -> Result<()> {
+pub use linker::find_in_path;
+
+pub fn compile_with_config(config: &Args, source_map: &mut SourceMap) -> Result<()> {
     let output_path = &config.output_path;
+    //token、ast和C头文件不经过优化，`--lto`没有意义
+    if config.lto && matches!(config.output_format, OutputFormatEnum::TOKENS | OutputFormatEnum::AST | OutputFormatEnum::HEADER) {
+        return Err(LEError::ArgumentError { message: format!("`--lto` can not be used with `-S {:?}`", config.output_format) });
+    }
+    //如果只需要打印token或ast，可以直接跳过后续阶段
+    match config.output_format {
+        OutputFormatEnum::TOKENS => return write_tokens(config, source_map),
+        OutputFormatEnum::AST => return write_ast(config, source_map),
+        _ => {}
+    }
+
+    //后端优化与目标代码生成的设置，调试信息中类型的大小和布局由目标决定
+    let target_machine = initialize_target_machine(config)?;
+    let is_wasm = is_wasm_target(&target_machine);
+    //运行时库和查询C运行时用的C编译器都是本机的，交叉编译只能输出目标文件等，由目标平台的工具链链接；wasm32使用wasm-ld链接
+    if !is_wasm && matches!(config.output_format, OutputFormatEnum::EXE | OutputFormatEnum::SHARED) && !is_host_target(&target_machine) {
+        return Err(LEError::TargetError {
+            message: format!(
+                "can not link `-S {:?}` for `{}` on host `{}`, use `-S OBJ` or `-S STATIC` and link with the toolchain of the target",
+                config.output_format,
+                target_machine.get_triple(),
+                TargetMachine::get_default_triple(),
+            ),
+        });
+    }
 
+    let mut timer = PassTimer::new(config.time_passes);
     let context = Context::create();
-    let module = context.create_module("main");
-    //词法分析
-    let lexer = lexer::LELexer::new(source);
-    if let Some(lexer) = lexer {
-        //如果只需要打印token，可以直接跳过后续阶段
-        if let OutputFormatEnum::TOKENS = config.output_format {
-            let tokens = lexer.map(|s| format!("{:?}\n", s)).collect::<Vec<_>>();
-            std::fs::write(
-                output_path,
-                tokens.iter().flat_map(|s| s.chars()).collect::<String>(),
-            )
-            .map_err(|e| LEError::IOError { other: Box::new(e) })?;
-        } else {
-            //语法分析
-            let ast = Ast::from_lexer(lexer)?;
-            //如果只需要打印ast，可以直接跳过后续阶段
-            if let OutputFormatEnum::AST = config.output_format {
-                let mut output_file = File::create(output_path).unwrap();
-                return {
-                    ast.print_with_root_name(
-                        output_file,
-                        config.input_path.to_str().unwrap().to_string(),
-                    )
-                    .map_err(|e| LEError::IOError { other: Box::new(e) })?;
-                    Ok(())
-                };
-            }
-            //类型检查和LLVM IR生成
-            let mut code_generator = CodeGenerator::create(&context);
-            code_generator.compile(&module, &ast)?;
+    //每个源文件单独做类型检查和LLVM IR生成，得到各自的LLVM模块，跨文件的调用通过decl声明的函数原型完成
+    let mut modules = vec![];
+    let mut exported_functions = HashSet::new();
+    let mut exported_prototypes = vec![];
+    let (ir_paths, source_paths): (Vec<&PathBuf>, Vec<&PathBuf>) = config.input_paths.iter().partition(|path| is_llvm_ir_path(path));
+    for input_path in source_paths {
+        let ast = load_program(input_path, &config.include_paths, source_map, &mut timer)?;
+        let mut code_generator = CodeGenerator::create(&context, &module_name(input_path));
+        if config.debug_info {
+            code_generator.enable_debug_info(source_map, input_path, target_machine.get_target_data(), config.optimization != OptimizeLevel::O0);
+        }
+        if is_wasm {
+            code_generator.enable_wasm_interface();
+        }
+        if let Some(abi) = CAbi::from_triple(&target_machine.get_triple().as_str().to_string_lossy()) {
+            code_generator.enable_c_abi(abi, target_machine.get_target_data());
+        }
+        timer.time("code generation", || code_generator.compile(&ast))?;
+        exported_functions.extend(code_generator.exported_functions().iter().cloned());
+        exported_prototypes.extend(code_generator.exported_prototypes().iter().cloned());
+        modules.push(code_generator.module);
+    }
 
-            //前端优化
-            let optimizer = Optimizer::new(&module, optimize_number_to_level(config.optimization));
-            optimizer.run_on_module(&module);
+    //`-S HEADER`只输出C头文件，`--emit-header`在其他输出之外额外输出C头文件
+    if config.output_format == OutputFormatEnum::HEADER || config.emit_header {
+        write_header(output_path, &exported_prototypes)?;
+        if config.output_format == OutputFormatEnum::HEADER {
+            return Ok(());
+        }
+    }
 
-            //后端优化与目标代码生成的设置
-            let target_machine = initialize_target_machine(config);
-            module.set_triple(&target_machine.get_triple());
+    //`.ll`和`.bc`输入与lelang的模块链接为一个模块，优化时可以跨语言内联
+    if !ir_paths.is_empty() {
+        for ir_path in ir_paths {
+            modules.push(load_ir_module(&context, ir_path)?);
+        }
+        modules = vec![link_modules(modules)?];
+    }
 
-            //运行LLVM后端并输出编译结果
-            match config.output_format {
-                OutputFormatEnum::IR => {
-                    module
-                        .print_to_file(output_path.with_extension("ll").as_path())
-                        .unwrap();
-                }
-                OutputFormatEnum::ASM => {
-                    target_machine
-                        .write_to_file(
-                            &module,
-                            FileType::Assembly,
-                            config.output_path.with_extension("S").as_path(),
-                        )
-                        .unwrap();
+    //函数库只导出pub函数，先链接为一个模块，其余函数改为内部链接，优化时可以内联或删除
+    let is_library = matches!(config.output_format, OutputFormatEnum::SHARED | OutputFormatEnum::STATIC);
+    if is_library {
+        let module = link_modules(modules)?;
+        internalize_functions(&module, &exported_functions);
+        modules = vec![module];
+    }
+
+    //优化前设置目标三元组和数据布局，交叉编译时类型的大小和对齐按目标计算
+    for module in modules.iter() {
+        module.set_triple(&target_machine.get_triple());
+        module.set_data_layout(&target_machine.get_target_data().get_data_layout());
+    }
+
+    //`--lto`时所有源文件先链接为一个模块，跨文件的调用也可以内联，和函数库一样只保留导出的函数，
+    //可执行文件、目标文件、汇编、IR和bitcode都输出链接时优化后的模块
+    let is_lto = config.lto;
+    if is_lto {
+        let module = link_modules(modules)?;
+        internalize_functions(&module, &exported_functions);
+        modules = vec![module];
+    }
+
+    //前端优化，函数库已经链接为一个模块，同样做链接时优化；`--passes`指定的pass代替优化级别的预设
+    let level = optimize_number_to_level(config.optimization.speed_level());
+    if let Some(dir) = &config.print_after_all {
+        std::fs::create_dir_all(dir).map_err(|e| LEError::IOError { other: Box::new(e) })?;
+    }
+    for module in modules.iter() {
+        let optimizer = if config.passes.is_empty() {
+            let mut optimizer = Optimizer::new(module, level, config.optimization.size_level());
+            if config.optimization != OptimizeLevel::O0 && (is_lto || is_library) {
+                optimizer.enable_lto(level);
+            }
+            optimizer
+        } else {
+            Optimizer::with_passes(&config.passes)
+        };
+        //`--print-after-all`时先输出优化前的IR，之后每个阶段结束后输出一次，文件名带有序号和阶段的名字，
+        //`--passes`指定的每个pass是一个阶段
+        let module_name = module.get_name().to_string_lossy().to_string();
+        let mut stage_index = 0;
+        let mut dump_result = match &config.print_after_all {
+            Some(dir) => module.print_to_file(dir.join(format!("{}-000-input.ll", module_name))),
+            None => Ok(()),
+        };
+        optimizer.run_on_module_with(module, |stage, duration, module| {
+            timer.record(&format!("optimization: {}", stage), duration);
+            stage_index += 1;
+            if let Some(dir) = &config.print_after_all {
+                if dump_result.is_ok() {
+                    let file_name = format!("{}-{:03}-{}.ll", module_name, stage_index, stage.replace(' ', "-"));
+                    dump_result = module.print_to_file(dir.join(file_name));
                 }
-                OutputFormatEnum::OBJ => {
-                    target_machine
-                        .write_to_file(
-                            &module,
-                            FileType::Object,
-                            config.output_path.with_extension("o").as_path(),
-                        )
-                        .unwrap();
+            }
+        });
+        dump_result.map_err(|e| LEError::IOError { other: e.to_string().into() })?;
+    }
+
+    //运行LLVM后端并输出编译结果
+    match config.output_format {
+        OutputFormatEnum::EXE => {
+            //每个源文件生成各自的临时目标文件，再和运行时库一起链接为可执行文件
+            let mut object_paths = vec![];
+            for (index, (module, input_path)) in modules.iter().zip(config.input_paths.iter()).enumerate() {
+                let object_path = temporary_object_path(index, input_path);
+                timer.time("emission", || target_machine.write_to_file(module, FileType::Object, object_path.as_path()))
+                    .map_err(|e| LEError::IOError { other: Box::new(e) })?;
+                object_paths.push(object_path);
+            }
+            //wasm32目标输出`.wasm`模块
+            let result = timer.time("linking", || if is_wasm {
+                linker::link_wasm_module(config, &object_paths, output_path.with_extension("wasm").as_path())
+            } else {
+                linker::link_executable(config, &object_paths, output_path)
+            });
+            for object_path in object_paths.iter() {
+                let _ = std::fs::remove_file(object_path);
+            }
+            result?;
+        }
+        OutputFormatEnum::SHARED | OutputFormatEnum::STATIC => {
+            let object_path = temporary_object_path(0, &config.input_paths[0]);
+            timer.time("emission", || target_machine.write_to_file(&modules[0], FileType::Object, object_path.as_path()))
+                .map_err(|e| LEError::IOError { other: Box::new(e) })?;
+            let result = timer.time("linking", || if config.output_format == OutputFormatEnum::SHARED {
+                linker::link_shared_library(config, &object_path, output_path.with_extension(std::env::consts::DLL_EXTENSION).as_path())
+            } else {
+                linker::create_static_library(&object_path, output_path.with_extension("a").as_path())
+            });
+            let _ = std::fs::remove_file(&object_path);
+            result?;
+        }
+        output_format => {
+            //IR、汇编和目标文件只输出一个文件，多个源文件的模块先链接为一个模块
+            let module = link_modules(modules)?;
+            let start = Instant::now();
+            match output_format {
+                OutputFormatEnum::IR => {
+                    let ir_path = output_path.with_extension("ll");
+                    module.print_to_file(ir_path.as_path()).map_err(|e| LEError::IOError {
+                        other: format!("can not write LLVM IR to `{}`: {}", ir_path.display(), e).into()
+                    })?;
                 }
-                OutputFormatEnum::EXE => {
-                    target_machine
-                        .write_to_file(
-                            &module,
-                            FileType::Object,
-                            config.output_path.with_extension("out").as_path(),
-                        )
-                        .unwrap();
-
-                    //由于没有暂时没有考虑如何打包平台对应的链接工具链，所以目前直接使用llvm的工具链进行链接
-                    let mut cmd = Command::new("clang");
-                    let mut link_process = cmd
-                        .args([
-                            config.output_path.to_str().unwrap(),
-                            "print.c",
-                            "-o",
-                            config
-                                .output_path
-                                .as_path()
-                                .with_extension("out")
-                                .to_str()
-                                .unwrap(),
-                        ])
-                        .spawn()
-                        .unwrap();
-                    link_process.wait().unwrap();
+                OutputFormatEnum::BC => {
+                    let bitcode_path = output_path.with_extension("bc");
+                    if !module.write_bitcode_to_path(bitcode_path.as_path()) {
+                        return Err(LEError::IOError { other: format!("can not write bitcode to `{}`", bitcode_path.display()).into() });
+                    }
                 }
+                OutputFormatEnum::ASM => write_target_file(&target_machine, &module, FileType::Assembly, &output_path.with_extension("S"))?,
+                OutputFormatEnum::OBJ => write_target_file(&target_machine, &module, FileType::Object, &output_path.with_extension("o"))?,
                 _ => unreachable!(),
             }
+            timer.record("emission", start.elapsed());
+        }
+    }
+
+    timer.report();
+    Ok(())
+}
+
+/// 依次对每个源文件做词法分析，所有token输出到同一个文件
+fn write_tokens(config: &Args, source_map: &mut SourceMap) -> Result<()> {
+    let mut tokens = String::new();
+    for input_path in config.input_paths.iter().filter(|path| !is_llvm_ir_path(path)) {
+        let source = std::fs::read_to_string(input_path).map_err(|e| LEError::IOError { other: Box::new(e) })?;
+        let file_id = source_map.add_file(input_path.clone(), source);
+        if let Some(lexer) = lexer::LELexer::with_file_id(&source_map.get_file(file_id).unwrap().content, file_id) {
+            tokens.extend(lexer.map(|s| format!("{:?}\n", s)));
         }
     }
+    std::fs::write(&config.output_path, tokens).map_err(|e| LEError::IOError { other: Box::new(e) })
+}
 
+/// 依次输出每个源文件和它导入的模块合并后的ast
+fn write_ast(config: &Args, source_map: &mut SourceMap) -> Result<()> {
+    let mut output_file = File::create(&config.output_path).map_err(|e| LEError::IOError { other: Box::new(e) })?;
+    for input_path in config.input_paths.iter().filter(|path| !is_llvm_ir_path(path)) {
+        let ast = load_program(input_path, &config.include_paths, source_map, &mut PassTimer::new(false))?;
+        ast.print_with_root_name(&mut output_file, input_path.to_str().unwrap().to_string())
+            .map_err(|e| LEError::IOError { other: Box::new(e) })?;
+    }
     Ok(())
 }
+
+/// 输出汇编或目标文件，写入失败时报告输出路径
+fn write_target_file(target_machine: &TargetMachine, module: &Module, file_type: FileType, path: &Path) -> Result<()> {
+    target_machine.write_to_file(module, file_type, path).map_err(|e| LEError::IOError {
+        other: format!("can not write `{}`: {}", path.display(), e).into()
+    })
+}
+
+/// 为所有源文件中导出的函数生成一个C头文件
+fn write_header(output_path: &Path, exported_prototypes: &[ExportedFunction]) -> Result<()> {
+    let header_path = output_path.with_extension("h");
+    let file_name = header_path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    let header = generate_c_header(exported_prototypes, &header_guard(&file_name));
+    std::fs::write(&header_path, header).map_err(|e| LEError::IOError { other: Box::new(e) })
+}
+
+/// 以`.ll`或`.bc`结尾的输入是LLVM IR，不作为lelang源文件编译
+fn is_llvm_ir_path(path: &Path) -> bool {
+    matches!(path.extension().and_then(|extension| extension.to_str()), Some("ll" | "bc"))
+}
+
+/// 读取文本格式或bitcode格式的LLVM IR文件
+fn load_ir_module<'ctx>(context: &'ctx Context, path: &Path) -> Result<Module<'ctx>> {
+    //文件不存在或无法读取是IO错误，内容无法解析才是链接错误
+    let content = std::fs::read(path)
+        .map_err(|e| LEError::IOError { other: format!("can not read `{}`: {}", path.display(), e).into() })?;
+    let buffer = MemoryBuffer::create_from_memory_range_copy(&content, &path.display().to_string());
+    context.create_module_from_ir(buffer)
+        .map_err(|e| LEError::LinkError { message: format!("can not parse LLVM IR `{}`: {}", path.display(), e) })
+}
+
+/// 源文件对应的LLVM模块名
+fn module_name(input_path: &Path) -> String {
+    input_path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_else(|| "main".into())
+}
+
+/// 源文件对应的临时目标文件路径，链接完成后删除
+fn temporary_object_path(index: usize, input_path: &Path) -> PathBuf {
+    std::env::temp_dir().join(format!("lelang-{}-{}-{}.o", std::process::id(), index, module_name(input_path)))
+}
+
+/// 把模块中定义的、不在`exported_functions`中的函数改为内部链接，外部声明的函数不受影响
+fn internalize_functions(module: &Module, exported_functions: &HashSet<String>) {
+    for function in module.get_functions() {
+        let name = function.get_name().to_string_lossy();
+        if function.count_basic_blocks() > 0 && !exported_functions.contains(name.as_ref()) {
+            function.set_linkage(Linkage::Internal);
+        }
+    }
+}
+
+/// 把多个LLVM模块链接为一个模块，其余模块的内容都移入第一个模块
+fn link_modules(modules: Vec<Module>) -> Result<Module> {
+    let mut modules = modules.into_iter();
+    let linked = modules.next().unwrap();
+    for module in modules {
+        linked.link_in_module(module).map_err(|e| LEError::LinkError { message: e.to_string() })?;
+    }
+    Ok(linked)
+}
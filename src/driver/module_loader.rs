@@ -0,0 +1,196 @@
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::Instant;
+
+use crate::ast::Ast;
+use crate::ast::c_import::parse_c_header;
+use crate::ast::namespace::{ModuleInterface, qualify_module};
+use crate::ast::nodes::ASTNode;
+use crate::driver::timer::PassTimer;
+use crate::error::{CompileError, LEError, Result};
+use crate::lexer::{LELexer, Position, SourceMap};
+
+struct LoadedModule {
+    name: String,
+    ast: Ast,
+}
+
+/// 从入口文件开始递归加载`import`的模块，`import math;`先在导入它的文件所在目录查找`math.le`，
+/// 再依次查找`-I`指定的搜索路径，最后使用随编译器发布的标准库，每个模块只加载一次
+struct ModuleLoader<'a> {
+    search_paths: &'a [PathBuf],
+    source_map: &'a mut SourceMap,
+    ///按依赖顺序排列，被导入的模块排在导入它的模块前面
+    modules: Vec<LoadedModule>,
+    ///模块名到模块文件规范化后的路径和第一次导入它的位置
+    module_paths: HashMap<String, (PathBuf, Position)>,
+    ///入口文件规范化后的路径，入口文件不是模块，不能被导入
+    root_path: PathBuf,
+    timer: &'a mut PassTimer,
+}
+
+/// 加载入口文件和它导入的所有模块，把模块中的定义改名为`模块名::名字`后合并为一棵语法树
+pub fn load_program(input_path: &Path, search_paths: &[PathBuf], source_map: &mut SourceMap, timer: &mut PassTimer) -> Result<Ast> {
+    let root_path = input_path.canonicalize().unwrap_or_else(|_| input_path.to_path_buf());
+    let mut loader = ModuleLoader { search_paths, source_map, modules: vec![], module_paths: HashMap::new(), root_path, timer };
+    let root = loader.parse_file(input_path)?;
+    loader.load_imports(input_path, &root)?;
+    let mut program = loader.link(root)?;
+    inject_runtime_declarations(&mut program, source_map)?;
+    Ok(program)
+}
+
+///运行时库中函数的声明，随编译器一起发布
+const RUNTIME_DECLARATIONS: &str = include_str!("../../runtime/runtime.le");
+const RUNTIME_DECLARATIONS_NAME: &str = "<runtime>";
+
+/// 把运行时库的函数声明加入程序，程序中已经声明或定义的同名函数优先
+fn inject_runtime_declarations(program: &mut Ast, source_map: &mut SourceMap) -> Result<()> {
+    let file_id = match source_map.find_file(Path::new(RUNTIME_DECLARATIONS_NAME)) {
+        Some(file_id) => file_id,
+        None => source_map.add_file(PathBuf::from(RUNTIME_DECLARATIONS_NAME), RUNTIME_DECLARATIONS.into()),
+    };
+    let lexer = LELexer::with_file_id(&source_map.get_file(file_id).unwrap().content, file_id).unwrap();
+    let runtime = Ast::from_lexer(lexer)?;
+    for prototype in runtime.extern_functions {
+        let name = &prototype.identifier.name;
+        let defined = program.extern_functions.iter().any(|declared| &declared.identifier.name == name)
+            || program.function_definitions.iter().any(|function| &function.prototype.identifier.name == name)
+            || program.globals_variables.iter().any(|variable| &variable.prototype.identifier.name == name);
+        if !defined {
+            program.extern_functions.push(prototype);
+        }
+    }
+    Ok(())
+}
+
+///标准库模块（std目录）的源码，随编译器一起发布
+const STD_MODULES: [(&str, &str); 5] = [
+    ("math", include_str!("../../std/math.le")),
+    ("mem", include_str!("../../std/mem.le")),
+    ("str", include_str!("../../std/str.le")),
+    ("vector", include_str!("../../std/vector.le")),
+    ("hashmap", include_str!("../../std/hashmap.le")),
+];
+const STD_MODULES_DIR: &str = "<std>";
+
+fn std_module_source(path: &Path) -> Option<&'static str> {
+    let name = path.strip_prefix(STD_MODULES_DIR).ok()?.file_stem()?.to_str()?;
+    STD_MODULES.iter().find(|(module, _)| *module == name).map(|(_, source)| *source)
+}
+
+impl<'a> ModuleLoader<'a> {
+    fn parse_file(&mut self, path: &Path) -> Result<Ast> {
+        //标准库模块没有对应的文件，源码在编译器中
+        let content = match path.strip_prefix(STD_MODULES_DIR) {
+            Ok(_) => std_module_source(path).unwrap_or_default().to_string(),
+            Err(_) => std::fs::read_to_string(path).map_err(|e| LEError::IOError { other: Box::new(e) })?,
+        };
+        let file_id = self.source_map.add_file(path.to_path_buf(), content);
+        let content = &self.source_map.get_file(file_id).unwrap().content;
+        //语法分析时按需产生token，统计用时时由lexer累计产生token的用时，语法分析的用时中扣除这部分
+        let start = Instant::now();
+        let mut lexer = LELexer::with_file_id(content, file_id).unwrap();
+        let lexing = Rc::new(Cell::new(start.elapsed()));
+        if self.timer.is_enabled() {
+            lexer = lexer.record_lexing_time(lexing.clone());
+        }
+        let mut ast = Ast::from_lexer(lexer)?;
+        self.timer.record("lexing", lexing.get());
+        self.timer.record("parsing", start.elapsed().saturating_sub(lexing.get()));
+        self.load_c_headers(path, &mut ast)?;
+        Ok(ast)
+    }
+
+    /// 加载`decl c "header.h";`导入的C头文件，其中的声明放在导入它的文件的定义之前，
+    /// 头文件先在导入它的文件所在目录查找，再依次查找`-I`指定的搜索路径，同一个头文件只加载一次
+    fn load_c_headers(&mut self, importer: &Path, ast: &mut Ast) -> Result<()> {
+        if ast.c_headers.is_empty() {
+            return Ok(());
+        }
+        let mut loaded = HashSet::new();
+        let mut headers = Ast::new();
+        for header in ast.c_headers.iter() {
+            let importer_dir = importer.parent().map(Path::to_path_buf).unwrap_or_default();
+            let path = std::iter::once(importer_dir)
+                .chain(self.search_paths.iter().cloned())
+                .map(|dir| dir.join(&header.path.content))
+                .find(|path| path.is_file())
+                .ok_or_else(|| CompileError::UnknownCHeader { path: header.path.content.clone() }.to_leerror(header.path.pos()))?;
+            let canonical_path = path.canonicalize().unwrap_or_else(|_| path.clone());
+            if loaded.insert(canonical_path) {
+                let content = std::fs::read_to_string(&path).map_err(|e| LEError::IOError { other: Box::new(e) })?;
+                let file_id = self.source_map.add_file(path, content);
+                headers.merge(parse_c_header(&self.source_map.get_file(file_id).unwrap().content, file_id)?);
+            }
+        }
+        headers.merge(std::mem::replace(ast, Ast::new()));
+        *ast = headers;
+        Ok(())
+    }
+
+    fn resolve_module(&self, importer: &Path, name: &str) -> Option<PathBuf> {
+        let file_name = format!("{}.le", name);
+        let importer_dir = importer.parent().map(Path::to_path_buf).unwrap_or_default();
+        std::iter::once(importer_dir)
+            .chain(self.search_paths.iter().cloned())
+            .map(|dir| dir.join(&file_name))
+            .find(|path| path.is_file())
+            .or_else(|| {
+                let path = Path::new(STD_MODULES_DIR).join(&file_name);
+                std_module_source(&path).map(|_| path)
+            })
+    }
+
+    fn load_imports(&mut self, importer: &Path, ast: &Ast) -> Result<()> {
+        for import in ast.imports.iter() {
+            let name = &import.module.name;
+            let path = self.resolve_module(importer, name)
+                .ok_or_else(|| CompileError::UnknownModule { module: name.clone() }.to_leerror(import.module.pos()))?;
+            let canonical_path = path.canonicalize().unwrap_or_else(|_| path.clone());
+            //入口文件中的定义没有模块路径，再作为模块加载一次会重复定义
+            if canonical_path == self.root_path {
+                return Err(CompileError::ImportEntryFile { module: name.clone() }.to_leerror(import.module.pos()));
+            }
+            //同名模块只能对应同一个文件，循环导入时直接使用已经开始加载的模块
+            if let Some((loaded_path, defined_position)) = self.module_paths.get(name) {
+                if loaded_path != &canonical_path {
+                    return Err(CompileError::IdentifierAlreadyDefined {
+                        identifier: name.clone(),
+                        defined_position: defined_position.clone(),
+                    }.to_leerror(import.module.pos()));
+                }
+                continue;
+            }
+            self.module_paths.insert(name.clone(), (canonical_path, import.module.pos()));
+            let module_ast = self.parse_file(&path)?;
+            self.load_imports(&path, &module_ast)?;
+            self.modules.push(LoadedModule { name: name.clone(), ast: module_ast });
+        }
+        Ok(())
+    }
+
+    fn link(self, mut root: Ast) -> Result<Ast> {
+        let all_modules = self.modules.iter().map(|module| module.name.clone()).collect::<HashSet<_>>();
+        let interfaces = self.modules
+            .iter()
+            .map(|module| (module.name.clone(), ModuleInterface::from_ast(module.name.clone(), &module.ast)))
+            .collect::<HashMap<_, _>>();
+        let imports_of = |ast: &Ast| ast.imports
+            .iter()
+            .map(|import| (import.module.name.clone(), interfaces[&import.module.name].clone()))
+            .collect::<HashMap<_, _>>();
+        let mut program = Ast::new();
+        for mut module in self.modules.into_iter() {
+            let imports = imports_of(&module.ast);
+            qualify_module(&mut module.ast, Some(&interfaces[&module.name]), &imports, &all_modules)?;
+            program.merge(module.ast);
+        }
+        let imports = imports_of(&root);
+        qualify_module(&mut root, None, &imports, &all_modules)?;
+        program.merge(root);
+        Ok(program)
+    }
+}
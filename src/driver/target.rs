@@ -1,7 +1,8 @@
 use inkwell::OptimizationLevel;
-use inkwell::targets::{CodeModel, InitializationConfig, RelocMode, TargetMachine};
+use inkwell::targets::{CodeModel, InitializationConfig, RelocMode, Target, TargetMachine, TargetTriple};
 
-use crate::arg_parser::Args;
+use crate::arg_parser::{Args, CodeModelEnum, OutputFormatEnum, RelocationModelEnum};
+use crate::error::{LEError, Result};
 
 pub fn optimize_number_to_level(number: usize) -> OptimizationLevel {
     match number {
@@ -13,28 +14,71 @@ pub fn optimize_number_to_level(number: usize) -> OptimizationLevel {
     }
 }
 
-
-pub fn initialize_target_machine(config: &Args) -> TargetMachine {
-    inkwell::targets::Target::initialize_native(&InitializationConfig {
-        asm_parser: false,
-        asm_printer: true,
-        base: true,
-        disassembler: false,
-        info: false,
-        machine_code: true,
-    }).unwrap();
-    let target_triple = inkwell::targets::TargetMachine::get_default_triple();
-    let target = inkwell::targets::Target::get_first().unwrap();
-    let cpu = inkwell::targets::TargetMachine::get_host_cpu_name();
-    let cpu_features = inkwell::targets::TargetMachine::get_host_cpu_features();
-    let reloc = RelocMode::Default;
-    let model = CodeModel::Default;
+/// 按`--target`、`--cpu`、`--features`、`--relocation-model`和`--code-model`创建目标机器，
+/// 没有指定`--target`时编译到本机，默认使用本机的CPU和特性；交叉编译时默认使用目标的通用CPU
+pub fn initialize_target_machine(config: &Args) -> Result<TargetMachine> {
+    Target::initialize_all(&InitializationConfig::default());
+    let (target_triple, default_cpu, default_features) = match &config.target {
+        Some(triple) => (TargetTriple::create(triple), String::new(), String::new()),
+        None => (
+            TargetMachine::get_default_triple(),
+            TargetMachine::get_host_cpu_name().to_string(),
+            TargetMachine::get_host_cpu_features().to_string(),
+        ),
+    };
+    let target = Target::from_triple(&target_triple).map_err(|e| LEError::TargetError {
+        message: format!("{}, available targets: {}", e.to_string().trim_end(), available_targets().join(", ")),
+    })?;
+    let cpu = config.cpu.clone().unwrap_or(default_cpu);
+    let features = config.features.clone().unwrap_or(default_features);
+    let reloc = match config.relocation_model {
+        //动态库中的代码必须是位置无关的
+        RelocationModelEnum::Default if config.output_format == OutputFormatEnum::SHARED => RelocMode::PIC,
+        RelocationModelEnum::Default => RelocMode::Default,
+        RelocationModelEnum::Pic => RelocMode::PIC,
+        RelocationModelEnum::Static => RelocMode::Static,
+    };
+    let model = match config.code_model {
+        CodeModelEnum::Default => CodeModel::Default,
+        CodeModelEnum::Small => CodeModel::Small,
+        CodeModelEnum::Kernel => CodeModel::Kernel,
+        CodeModelEnum::Medium => CodeModel::Medium,
+        CodeModelEnum::Large => CodeModel::Large,
+    };
     target.create_target_machine(
         &target_triple,
-        cpu.to_str().unwrap(),
-        cpu_features.to_str().unwrap(),
-        optimize_number_to_level(config.optimization),
+        &cpu,
+        &features,
+        optimize_number_to_level(config.optimization.speed_level()),
         reloc,
         model,
-    ).unwrap()
-}
\ No newline at end of file
+    ).ok_or_else(|| LEError::TargetError {
+        message: format!("can not create target machine for `{}` with cpu `{}` and features `{}`", target_triple, cpu, features),
+    })
+}
+
+/// 目标是否为WebAssembly，如`wasm32-unknown-unknown`
+pub fn is_wasm_target(target_machine: &TargetMachine) -> bool {
+    target_machine.get_triple().as_str().to_string_lossy().starts_with("wasm")
+}
+
+/// 目标是否为本机：比较规范化后三元组的架构、操作系统和环境，厂商不同(如`pc`和`unknown`)时仍是本机
+pub fn is_host_target(target_machine: &TargetMachine) -> bool {
+    let components = |triple: &TargetTriple| {
+        let triple = TargetMachine::normalize_triple(triple).as_str().to_string_lossy().to_string();
+        let parts = triple.split('-').map(str::to_string).collect::<Vec<_>>();
+        (parts.get(0).cloned(), parts.get(2).cloned(), parts.get(3).cloned())
+    };
+    components(&target_machine.get_triple()) == components(&TargetMachine::get_default_triple())
+}
+
+/// 当前LLVM支持的所有目标的名字，如`x86-64`、`aarch64`、`riscv64`、`wasm32`
+fn available_targets() -> Vec<String> {
+    let mut names = vec![];
+    let mut target = Target::get_first();
+    while let Some(current) = target {
+        names.push(current.get_name().to_string_lossy().to_string());
+        target = current.get_next();
+    }
+    names
+}
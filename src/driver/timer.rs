@@ -0,0 +1,53 @@
+use std::time::{Duration, Instant};
+
+/// `--time-passes`时记录编译各阶段和每个优化pass的用时，编译结束后输出到标准错误，
+/// 同名的阶段（如多个源文件的词法分析）累加在一起
+pub struct PassTimer {
+    enabled: bool,
+    ///按第一次记录的顺序排列
+    records: Vec<(String, Duration)>,
+}
+
+impl PassTimer {
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled, records: vec![] }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// 运行`f`并记录它的用时
+    pub fn time<T>(&mut self, name: &str, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        self.record(name, start.elapsed());
+        result
+    }
+
+    pub fn record(&mut self, name: &str, duration: Duration) {
+        if !self.enabled {
+            return;
+        }
+        match self.records.iter_mut().find(|(recorded, _)| recorded == name) {
+            Some((_, total)) => *total += duration,
+            None => self.records.push((name.to_string(), duration)),
+        }
+    }
+
+    /// 按记录的顺序输出每个阶段的用时和占比
+    pub fn report(&self) {
+        if !self.enabled {
+            return;
+        }
+        let total = self.records.iter().map(|(_, duration)| *duration).sum::<Duration>();
+        let name_width = self.records.iter().map(|(name, _)| name.len()).max().unwrap_or(0).max("total".len());
+        eprintln!("===== lelang time report =====");
+        eprintln!("{:<width$}  {:>10}  {:>7}", "stage", "time(ms)", "percent", width = name_width);
+        for (name, duration) in self.records.iter() {
+            let percent = if total.is_zero() { 0.0 } else { duration.as_secs_f64() / total.as_secs_f64() * 100.0 };
+            eprintln!("{:<width$}  {:>10.3}  {:>6.1}%", name, duration.as_secs_f64() * 1000.0, percent, width = name_width);
+        }
+        eprintln!("{:<width$}  {:>10.3}  {:>6.1}%", "total", total.as_secs_f64() * 1000.0, 100.0, width = name_width);
+    }
+}
@@ -15,3 +15,28 @@ pub const TYPE_MISMATCHED: &str = "E0014";
 pub const NO_SUCH_MEMBER: &str = "E0015";
 pub const NOT_ALLOW_ZERO_LENGTH_ARRAY: &str = "E0016";
 pub const INVALID_TYPE_CAST: &str = "E0017";
+pub const INVALID_MATCH_PATTERN: &str = "E0018";
+pub const GENERIC_ARGUMENT_NUMBER_MISMATCHED: &str = "E0019";
+pub const CANNOT_INFER_GENERIC_ARGUMENT: &str = "E0020";
+pub const IN_GENERIC_INSTANTIATION: &str = "E0021";
+pub const UNKNOWN_TRAIT: &str = "E0022";
+pub const TRAIT_BOUND_NOT_SATISFIED: &str = "E0023";
+pub const TRAIT_METHOD_NOT_IMPLEMENTED: &str = "E0024";
+pub const METHOD_NOT_IN_TRAIT: &str = "E0025";
+pub const METHOD_NOT_IN_BOUNDS: &str = "E0026";
+pub const INVALID_VECTOR_ELEMENT_TYPE: &str = "E0027";
+pub const ARGUMENT_NUMBER_MISMATCHED: &str = "E0028";
+pub const VECTOR_LANE_OUT_OF_RANGE: &str = "E0029";
+pub const UNKNOWN_MODULE: &str = "E0030";
+pub const PRIVATE_ITEM: &str = "E0031";
+pub const INVALID_FORMAT_STRING: &str = "E0032";
+pub const FORMAT_ARGUMENT_NUMBER_MISMATCHED: &str = "E0033";
+pub const INVALID_MAIN_SIGNATURE: &str = "E0034";
+pub const UNKNOWN_C_HEADER: &str = "E0035";
+pub const INVALID_ATTRIBUTE: &str = "E0036";
+pub const INVALID_INTRINSIC_ARGUMENT: &str = "E0037";
+pub const INVALID_VECTOR_LENGTH: &str = "E0038";
+pub const INVALID_C_ARRAY_LENGTH: &str = "E0039";
+pub const IMPORT_ENTRY_FILE: &str = "E0040";
+pub const UNSUPPORTED_EXPORT_TYPE: &str = "E0041";
+pub const VARIADIC_AGGREGATE: &str = "E0042";
@@ -9,7 +9,7 @@ use nom::combinator::map;
 use thiserror::Error;
 
 use crate::error::TokenType::Colon;
-use crate::lexer::{LELexer, LEToken, Operator, Position};
+use crate::lexer::{LELexer, LEToken, Operator, Position, SourceMap};
 
 use super::error_list;
 
@@ -34,9 +34,16 @@ pub enum TokenType {
     Comma,
     Operator,
     SingleArrow,
+    DoubleArrow,
+    Match,
+    Impl,
+    Trait,
+    Import,
+    Pub,
     Identifier,
     NumberLiteral,
     StringLiteral,
+    Sharp,
 }
 
 impl TokenType {
@@ -61,9 +68,16 @@ impl TokenType {
             TokenType::Comma => { "," }
             TokenType::Operator => { "Operator" }
             TokenType::SingleArrow => { "->" }
+            TokenType::DoubleArrow => { "=>" }
+            TokenType::Match => { "match" }
+            TokenType::Impl => { "impl" }
+            TokenType::Trait => { "trait" }
+            TokenType::Import => { "import" }
+            TokenType::Pub => { "pub" }
             TokenType::Identifier => { "Identifier" }
             TokenType::NumberLiteral => { "Number" }
             TokenType::StringLiteral => { "String" }
+            TokenType::Sharp => { "#" }
         }
     }
 }
@@ -108,6 +122,12 @@ pub enum SyntaxError {
     },
     #[error("array size must be an integer.")]
     ArraySizeMustBeInteger,
+    #[error("invalid match pattern.")]
+    InvalidMatchPattern,
+    #[error("vector lane count `{len}` is out of range.")]
+    InvalidVectorLength {
+        len: u64,
+    },
 }
 
 impl SyntaxError {
@@ -203,6 +223,138 @@ pub enum CompileError {
         from: String,
         to: String,
     },
+
+    #[error("generic `{identifier}` expect {expect} type arguments, but got {found}")]
+    GenericArgumentNumberMismatched {
+        identifier: String,
+        expect: usize,
+        found: usize,
+    },
+
+    #[error("can not infer type of generic parameter `{identifier}`")]
+    CannotInferGenericArgument {
+        identifier: String,
+    },
+
+    #[error("error in instantiation of `{instance}`: {inner}")]
+    InGenericInstantiation {
+        instance: String,
+        inner: Box<LEError>,
+    },
+
+    #[error("can not find trait `{identifier}` in this scope")]
+    UnknownTrait {
+        identifier: String,
+    },
+
+    #[error("the trait `{trait_name}` is not implemented for type `{ty}`")]
+    TraitBoundNotSatisfied {
+        ty: String,
+        trait_name: String,
+    },
+
+    #[error("not all trait methods implemented, missing `{method}` of trait `{trait_name}`")]
+    TraitMethodNotImplemented {
+        trait_name: String,
+        method: String,
+    },
+
+    #[error("method `{method}` is not a member of trait `{trait_name}`")]
+    MethodNotInTrait {
+        trait_name: String,
+        method: String,
+    },
+
+    #[error("no method `{method}` found in the bounds of generic parameter `{generic_param}`")]
+    MethodNotInBounds {
+        generic_param: String,
+        method: String,
+    },
+
+    #[error("vector element type must be integer, float or bool, but got `{ty}`")]
+    InvalidVectorElementType {
+        ty: String,
+    },
+
+    #[error("`{identifier}` expect {expect} arguments, but got {found}")]
+    ArgumentNumberMismatched {
+        identifier: String,
+        expect: usize,
+        found: usize,
+    },
+
+    #[error("lane index {index} out of range for vector with {lanes} lanes")]
+    VectorLaneOutOfRange {
+        index: u64,
+        lanes: u32,
+    },
+
+    #[error("can not find module `{module}`")]
+    UnknownModule {
+        module: String,
+    },
+
+    #[error("`{item}` is private in module `{module}`")]
+    PrivateItem {
+        module: String,
+        item: String,
+    },
+
+    #[error("invalid format string: {reason}")]
+    InvalidFormatString {
+        reason: String,
+    },
+
+    #[error("format string has {expect} placeholders, but got {found} arguments")]
+    FormatArgumentNumberMismatched {
+        expect: usize,
+        found: usize,
+    },
+
+    #[error("invalid signature of `main`: `{found}`")]
+    InvalidMainSignature {
+        found: String,
+    },
+
+    #[error("can not find C header `{path}`")]
+    UnknownCHeader {
+        path: String,
+    },
+
+    #[error("invalid attribute `{attribute}`: {reason}")]
+    InvalidAttribute {
+        attribute: String,
+        reason: String,
+    },
+
+    #[error("LLVM intrinsic `{intrinsic}` can not be called with an argument of type `{ty}`")]
+    InvalidIntrinsicArgument {
+        intrinsic: String,
+        ty: String,
+    },
+
+    #[error("array length `{len}` in C header is out of range")]
+    InvalidCArrayLength {
+        len: String,
+    },
+
+    #[error("module `{module}` is the entry file and can not be imported")]
+    ImportEntryFile {
+        module: String,
+    },
+
+    #[error("type `{ty}` in the signature of exported function `{function}` can not be represented in C")]
+    UnsupportedExportType {
+        function: String,
+        ty: String,
+        reason: String,
+    },
+
+    #[error("variadic function `{function}` can not pass or return `{ty}` by value")]
+    VariadicAggregate {
+        function: String,
+        ty: String,
+    },
 }
 
 impl CompileError {
@@ -226,7 +378,19 @@ pub enum LEError {
     #[error("error:{other}")]
     IOError {
         other: Box<dyn Error>
-    }
+    },
+    #[error("link error:{message}")]
+    LinkError {
+        message: String
+    },
+    #[error("target error:{message}")]
+    TargetError {
+        message: String
+    },
+    #[error("argument error:{message}")]
+    ArgumentError {
+        message: String
+    },
 }
 
 
@@ -238,7 +402,18 @@ impl LEError {
         Self::CompileError { compile_error: error, position }
     }
 
-    pub fn to_error_report_colored<'s>(&self, src: &'s str) -> ReportBuilder<(&'s str, Range<usize>)> {
+    ///错误所在的源文件序号，IO错误没有位置，使用入口文件
+    pub fn file_id(&self) -> usize {
+        match self {
+            LEError::SyntaxError { position, .. } | LEError::CompileError { position, .. } => position.file_id,
+            LEError::IOError { .. } | LEError::LinkError { .. } | LEError::TargetError { .. } | LEError::ArgumentError { .. } => 0,
+        }
+    }
+
+    pub fn to_error_report_colored(&self, sources: &SourceMap) -> ReportBuilder<(String, Range<usize>)> {
+        //标注的位置可能来自不同的源文件
+        let span = |position: &Position| (sources.name(position.file_id), position.range.clone());
+        let src = sources.name(self.file_id());
         let code_color = Color::White;
         let label_color = Color::Green;
         let help_color = Color::Green;
@@ -254,189 +429,213 @@ impl LEError {
             LEError::SyntaxError { syntax_error, position } => {
                 match syntax_error {
                     SyntaxError::UnexpectToken { expect, found } => {
-                        Report::build(ReportKind::Error, src, position.range.start())
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
                             .with_code(error_list::UNEXPECT_TOKEN)
                             .with_message(syntax_error.to_string().fg(code_color))
                             .with_label(
-                                Label::new((src, position.range.clone()))
+                                Label::new(span(position))
                                     .with_message(format!("Got a token `{}` here, but expect `{}`.", found.fg(loop_rainbow_color.next().unwrap()), expect.to_string().fg(loop_rainbow_color.next().unwrap())))
                                     .with_color(label_color)
                             )
                             .with_help(format!("Considering add `{}`", expect.fg(help_color)))
                     }
                     SyntaxError::MissingToken { expect } => {
-                        Report::build(ReportKind::Error, src, position.range.start())
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
                             .with_code(error_list::MISSING_TOKEN)
                             .with_message(syntax_error.to_string().fg(code_color))
                             .with_label(
-                                Label::new((src, position.range.clone()))
+                                Label::new(span(position))
                                     .with_message(format!("Missing a `{}` here", expect.to_string().fg(loop_rainbow_color.next().unwrap())))
                                     .with_color(label_color)
                             )
                             .with_help("Considering finish that")
                     }
                     SyntaxError::ArraySizeMustBeInteger {} => {
-                        Report::build(ReportKind::Error, src, position.range.start())
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
                             .with_code(error_list::ARRAY_SIZE_MUST_BE_INTEGER)
                             .with_message("Can only use signed integer as a array length".to_string())
                             .with_label(
-                                Label::new((src, position.range.clone()))
+                                Label::new(span(position))
                                     .with_color(label_color)
                             )
                             .with_help(format!("Considering change it to a `{}`", "sign integer".fg(Color::Green)))
                     }
+                    SyntaxError::InvalidMatchPattern => {
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
+                            .with_code(error_list::INVALID_MATCH_PATTERN)
+                            .with_message(syntax_error.to_string().fg(code_color))
+                            .with_label(
+                                Label::new(span(position))
+                                    .with_message("this pattern is not supported")
+                                    .with_color(label_color)
+                            )
+                            .with_help(format!("only `{}`, `{}`, `{}` and `{}` patterns are allowed",
+                                               "integer".fg(Color::Green), "bool".fg(Color::Green),
+                                               "start..=end".fg(Color::Green), "_".fg(Color::Green)))
+                    }
+                    SyntaxError::InvalidVectorLength { .. } => {
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
+                            .with_code(error_list::INVALID_VECTOR_LENGTH)
+                            .with_message(syntax_error.to_string().fg(code_color))
+                            .with_label(
+                                Label::new(span(position))
+                                    .with_message("lane count used here")
+                                    .with_color(label_color)
+                            )
+                            .with_help(format!("the lane count must be between `{}` and `{}`", 1.fg(Color::Green), u32::MAX.fg(Color::Green)))
+                    }
                 }
             }
             LEError::CompileError { compile_error, position } => {
                 match compile_error {
                     CompileError::UnknownIdentifier { identifier } => {
-                        Report::build(ReportKind::Error, src, position.range.start())
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
                             .with_code(error_list::UNKNOWN_IDENTIFIER)
                             .with_message(compile_error.to_string())
                             .with_label(
-                                Label::new((src, position.range.clone()))
+                                Label::new(span(position))
                                     .with_message(format!("can not find this identifier `{}`", identifier.fg(loop_rainbow_color.next().unwrap())))
                                     .with_color(label_color)
                             )
                     }
                     CompileError::IdentifierIsNotType { identifier } => {
-                        Report::build(ReportKind::Error, src, position.range.start())
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
                             .with_code(error_list::IDENTIFIER_IS_NOT_TYPE)
                             .with_message(compile_error.to_string())
                             .with_label(
-                                Label::new((src, position.range.clone()))
+                                Label::new(span(position))
                                     .with_message(format!("identifier `{}` is not a type", identifier.fg(loop_rainbow_color.next().unwrap())))
                                     .with_color(label_color)
                             )
                     }
                     CompileError::IdentifierIsNotCallable { identifier } => {
-                        Report::build(ReportKind::Error, src, position.range.start())
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
                             .with_code(error_list::IDENTIFIER_IS_NOT_CALLABLE)
                             .with_message(compile_error.to_string())
                             .with_label(
-                                Label::new((src, position.range.clone()))
+                                Label::new(span(position))
                                     .with_message(format!("identifier `{}` is not a function or any callable object", identifier.fg(loop_rainbow_color.next().unwrap())))
                                     .with_color(label_color)
                             )
                     }
                     CompileError::ExpressionIsNotLeftValueExpression => {
-                        Report::build(ReportKind::Error, src, position.range.start())
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
                             .with_code(error_list::EXPRESSION_IS_NOT_LEFT_VALUE_EXPRESSION)
                             .with_message(compile_error.to_string())
                             .with_label(
-                                Label::new((src, position.range.clone()))
+                                Label::new(span(position))
                                     .with_message("expression is not assignable".to_string())
                                     .with_color(label_color)
                             )
                     }
                     CompileError::ExpressionIsNotRightValueExpression => {
-                        Report::build(ReportKind::Error, src, position.range.start())
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
                             .with_code(error_list::EXPRESSION_IS_NOT_RIGHT_VALUE_EXPRESSION)
                             .with_message(compile_error.to_string())
                             .with_label(
-                                Label::new((src, position.range.clone()))
+                                Label::new(span(position))
                                     .with_message("expression have a void type, but access it".to_string())
                                     .with_color(label_color)
                             )
                     }
                     CompileError::IdentifierAlreadyDefined { identifier, defined_position } => {
-                        Report::build(ReportKind::Error, src, position.range.start())
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
                             .with_code(error_list::IDENTIFIER_ALREADY_DEFINED)
                             .with_message(compile_error.to_string())
                             .with_label(
-                                Label::new((src, position.range.clone()))
+                                Label::new(span(position))
                                     .with_message("identifier defined here".fg(Color::Blue))
                                     .with_color(label_color)
                             )
                             .with_label(
-                                Label::new((src, defined_position.range.clone()))
+                                Label::new(span(defined_position))
                                     .with_message(format!("but identifier `{}` already defined here", identifier.fg(loop_rainbow_color.next().unwrap())))
                                     .with_color(label_color)
                             )
                             .with_help("considering change the identifier")
                     }
                     CompileError::NoSuitableBinaryOperator { op, left_type, right_type } => {
-                        Report::build(ReportKind::Error, src, position.range.start())
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
                             .with_code(error_list::NO_SUITABLE_BINARY_OPERATOR)
                             .with_message(compile_error.to_string())
                             .with_label(
-                                Label::new((src, position.range.clone()))
+                                Label::new(span(position))
                                     .with_message(format!("operator `{}` not suitable for type `{}` and type `{}` here", op.fg(loop_rainbow_color.next().unwrap()), left_type.fg(loop_rainbow_color.next().unwrap()), right_type.fg(loop_rainbow_color.next().unwrap())))
                                     .with_color(label_color)
                             )
                             .with_help(format!("maybe you need a `{}` type cast here？", "as".fg(Color::Green)))
                     }
                     CompileError::NoSuitableUnaryOperator { op, target_type } => {
-                        Report::build(ReportKind::Error, src, position.range.start())
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
                             .with_code(error_list::NO_SUITABLE_UNARY_OPERATOR)
                             .with_message(compile_error.to_string())
                             .with_label(
-                                Label::new((src, position.range.clone()))
+                                Label::new(span(position))
                                     .with_message(format!("operator `{}` not suitable for type `{}` here", op.fg(loop_rainbow_color.next().unwrap()), target_type.fg(loop_rainbow_color.next().unwrap())))
                                     .with_color(label_color)
                             )
                     }
                     CompileError::TypeMismatched { expect, found } => {
-                        Report::build(ReportKind::Error, src, position.range.start())
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
                             .with_code(error_list::TYPE_MISMATCHED)
                             .with_message(compile_error.to_string())
                             .with_label(
-                                Label::new((src, position.range.clone()))
+                                Label::new(span(position))
                                     .with_message(format!("expect type `{}`, but found type `{}`", expect.fg(loop_rainbow_color.next().unwrap()), found.fg(loop_rainbow_color.next().unwrap())))
                                     .with_color(label_color)
                             )
                             .with_help(format!("maybe you need a type cast to type `{}` ?`", expect.fg(Color::Green)))
                     }
                     CompileError::NoSuchMember { member_name } => {
-                        Report::build(ReportKind::Error, src, position.range.start())
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
                             .with_code(error_list::NO_SUCH_MEMBER)
                             .with_message(compile_error.to_string())
                             .with_label(
-                                Label::new((src, position.range.clone()))
+                                Label::new(span(position))
                                     .with_message(format!("member `{}` access at here", member_name.fg(loop_rainbow_color.next().unwrap())))
                                     .with_color(label_color)
                             )
                             .with_help(format!("maybe you want to create a member named `{}` ?`", member_name.fg(Color::Green)))
                     }
                     CompileError::NotAllowZeroLengthArray => {
-                        Report::build(ReportKind::Error, src, position.range.start())
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
                             .with_code(error_list::NOT_ALLOW_ZERO_LENGTH_ARRAY)
                             .with_message(compile_error.to_string())
                             .with_label(
-                                Label::new((src, position.range.clone()))
+                                Label::new(span(position))
                                     .with_message("length defined here".to_string())
                                     .with_color(label_color)
                             )
                             .with_help("change the array length to integer")
                     }
                     CompileError::CanNotRedefineBuiltinTypes { identifier } => {
-                        Report::build(ReportKind::Error, src, position.range.start())
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
                             .with_code(error_list::CAN_NOT_REDEFINE_BUILTIN_TYPES)
                             .with_message(compile_error.to_string())
                             .with_label(
-                                Label::new((src, position.range.clone()))
+                                Label::new(span(position))
                                     .with_message(format!("identifier `{}` defined here", identifier.fg(loop_rainbow_color.next().unwrap())))
                                     .with_color(label_color)
                             )
                             .with_help("maybe you can change the identifier to another, which is not keyword or builtin identifier")
                     }
                     CompileError::ExpressionIsNotType { pos } => {
-                        Report::build(ReportKind::Error, src, position.range.start())
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
                             .with_code(error_list::EXPRESSION_IS_NOT_TYPE)
                             .with_message(compile_error.to_string())
                             .with_label(
-                                Label::new((src, pos.range.clone()))
+                                Label::new(span(pos))
                                     .with_message(format!("expression here is not a `{}`", "type".fg(loop_rainbow_color.next().unwrap())))
                                     .with_color(label_color)
                             )
                             .with_help("maybe you can change the identifier to no keyword or builtin identifier")
                     }
                     CompileError::InvalidTypeCast { from, to } => {
-                        Report::build(ReportKind::Error, src, position.range.start())
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
                             .with_code(error_list::INVALID_TYPE_CAST)
                             .with_message(compile_error.to_string())
                             .with_label(
-                                Label::new((src, position.range.clone()))
+                                Label::new(span(position))
                                     .with_message(format!("type `{}` value cannot cast to type `{}`",
                                                           from.fg(loop_rainbow_color.next().unwrap()),
                                                           to.fg(loop_rainbow_color.next().unwrap()))
@@ -444,12 +643,312 @@ impl LEError {
                                     .with_color(label_color)
                             )
                     }
+                    CompileError::GenericArgumentNumberMismatched { identifier, expect, found } => {
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
+                            .with_code(error_list::GENERIC_ARGUMENT_NUMBER_MISMATCHED)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new(span(position))
+                                    .with_message(format!("`{}` used with {} type arguments here",
+                                                          identifier.fg(loop_rainbow_color.next().unwrap()),
+                                                          found.fg(loop_rainbow_color.next().unwrap())))
+                                    .with_color(label_color)
+                            )
+                            .with_help(format!("give `{}` type arguments", expect.fg(Color::Green)))
+                    }
+                    CompileError::CannotInferGenericArgument { identifier } => {
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
+                            .with_code(error_list::CANNOT_INFER_GENERIC_ARGUMENT)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new(span(position))
+                                    .with_message(format!("type of `{}` can not be inferred from arguments", identifier.fg(loop_rainbow_color.next().unwrap())))
+                                    .with_color(label_color)
+                            )
+                            .with_help(format!("considering specify it with `{}`", "::<...>".fg(Color::Green)))
+                    }
+                    CompileError::UnknownTrait { identifier } => {
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
+                            .with_code(error_list::UNKNOWN_TRAIT)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new(span(position))
+                                    .with_message(format!("trait `{}` is not defined", identifier.fg(loop_rainbow_color.next().unwrap())))
+                                    .with_color(label_color)
+                            )
+                    }
+                    CompileError::TraitBoundNotSatisfied { ty, trait_name } => {
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
+                            .with_code(error_list::TRAIT_BOUND_NOT_SATISFIED)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new(span(position))
+                                    .with_message(format!("type `{}` does not implement `{}`",
+                                                          ty.fg(loop_rainbow_color.next().unwrap()),
+                                                          trait_name.fg(loop_rainbow_color.next().unwrap())))
+                                    .with_color(label_color)
+                            )
+                            .with_help(format!("considering add `{}`", format!("impl {} for {}", trait_name, ty).fg(Color::Green)))
+                    }
+                    CompileError::TraitMethodNotImplemented { trait_name, method } => {
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
+                            .with_code(error_list::TRAIT_METHOD_NOT_IMPLEMENTED)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new(span(position))
+                                    .with_message(format!("missing `{}` in this implementation of `{}`",
+                                                          method.fg(loop_rainbow_color.next().unwrap()),
+                                                          trait_name.fg(loop_rainbow_color.next().unwrap())))
+                                    .with_color(label_color)
+                            )
+                    }
+                    CompileError::MethodNotInTrait { trait_name, method } => {
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
+                            .with_code(error_list::METHOD_NOT_IN_TRAIT)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new(span(position))
+                                    .with_message(format!("`{}` is not declared in trait `{}`",
+                                                          method.fg(loop_rainbow_color.next().unwrap()),
+                                                          trait_name.fg(loop_rainbow_color.next().unwrap())))
+                                    .with_color(label_color)
+                            )
+                    }
+                    CompileError::MethodNotInBounds { generic_param, method } => {
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
+                            .with_code(error_list::METHOD_NOT_IN_BOUNDS)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new(span(position))
+                                    .with_message(format!("method `{}` called on a value of generic type `{}`",
+                                                          method.fg(loop_rainbow_color.next().unwrap()),
+                                                          generic_param.fg(loop_rainbow_color.next().unwrap())))
+                                    .with_color(label_color)
+                            )
+                            .with_help(format!("considering add a trait bound to `{}` which declares `{}`", generic_param, method))
+                    }
+                    CompileError::InvalidVectorElementType { ty } => {
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
+                            .with_code(error_list::INVALID_VECTOR_ELEMENT_TYPE)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new(span(position))
+                                    .with_message(format!("`{}` can not be a vector element", ty.fg(loop_rainbow_color.next().unwrap())))
+                                    .with_color(label_color)
+                            )
+                    }
+                    CompileError::ArgumentNumberMismatched { identifier, expect, found } => {
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
+                            .with_code(error_list::ARGUMENT_NUMBER_MISMATCHED)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new(span(position))
+                                    .with_message(format!("`{}` called with {} arguments here",
+                                                          identifier.fg(loop_rainbow_color.next().unwrap()),
+                                                          found.fg(loop_rainbow_color.next().unwrap())))
+                                    .with_color(label_color)
+                            )
+                            .with_help(format!("`{}` expect {} arguments", identifier, expect))
+                    }
+                    CompileError::VectorLaneOutOfRange { index, lanes } => {
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
+                            .with_code(error_list::VECTOR_LANE_OUT_OF_RANGE)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new(span(position))
+                                    .with_message(format!("lane {} used here", index.fg(loop_rainbow_color.next().unwrap())))
+                                    .with_color(label_color)
+                            )
+                            .with_help(format!("lane index must be less than {}", lanes))
+                    }
+                    CompileError::UnknownModule { module } => {
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
+                            .with_code(error_list::UNKNOWN_MODULE)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new(span(position))
+                                    .with_message(format!("module `{}` used here", module.fg(loop_rainbow_color.next().unwrap())))
+                                    .with_color(label_color)
+                            )
+                            .with_help(format!("considering add `import {};` or a search path with `-I`", module))
+                    }
+                    CompileError::PrivateItem { module, item } => {
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
+                            .with_code(error_list::PRIVATE_ITEM)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new(span(position))
+                                    .with_message(format!("`{}` accessed from outside of module `{}` here",
+                                                          item.fg(loop_rainbow_color.next().unwrap()),
+                                                          module.fg(loop_rainbow_color.next().unwrap())))
+                                    .with_color(label_color)
+                            )
+                            .with_help(format!("considering mark `{}` as `pub` in module `{}`", item, module))
+                    }
+                    CompileError::InvalidFormatString { reason } => {
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
+                            .with_code(error_list::INVALID_FORMAT_STRING)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new(span(position))
+                                    .with_message(reason.fg(loop_rainbow_color.next().unwrap()))
+                                    .with_color(label_color)
+                            )
+                            .with_help("format string must be a string literal, each `{}` is replaced by the next argument")
+                    }
+                    CompileError::FormatArgumentNumberMismatched { expect, found } => {
+                        let message = if found > expect {
+                            "this argument has no placeholder".to_string()
+                        } else {
+                            format!("{} placeholders but only {} arguments", expect.fg(loop_rainbow_color.next().unwrap()), found.fg(loop_rainbow_color.next().unwrap()))
+                        };
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
+                            .with_code(error_list::FORMAT_ARGUMENT_NUMBER_MISMATCHED)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new(span(position))
+                                    .with_message(message)
+                                    .with_color(label_color)
+                            )
+                            .with_help("each `{}` in the format string takes one argument, use `{{` and `}}` to print braces")
+                    }
+                    CompileError::InvalidMainSignature { found } => {
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
+                            .with_code(error_list::INVALID_MAIN_SIGNATURE)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new(span(position))
+                                    .with_message(format!("`{}` defined here", found.fg(loop_rainbow_color.next().unwrap())))
+                                    .with_color(label_color)
+                            )
+                            .with_help("`main` must be `le main()` or `le main(args:[str])`, and return nothing or `i32`")
+                    }
+                    CompileError::UnknownCHeader { path } => {
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
+                            .with_code(error_list::UNKNOWN_C_HEADER)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new(span(position))
+                                    .with_message(format!("header `{}` imported here", path.fg(loop_rainbow_color.next().unwrap())))
+                                    .with_color(label_color)
+                            )
+                            .with_help("headers are searched in the directory of the importing file and the search paths given by `-I`")
+                    }
+                    CompileError::InvalidAttribute { attribute, .. } => {
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
+                            .with_code(error_list::INVALID_ATTRIBUTE)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new(span(position))
+                                    .with_message(format!("`{}` used here", attribute.fg(loop_rainbow_color.next().unwrap())))
+                                    .with_color(label_color)
+                            )
+                            .with_help("supported attributes are `inline`, `noinline`, `cold`, `noreturn`, `no_mangle` and `link_name = \"symbol\"`")
+                    }
+                    CompileError::InvalidIntrinsicArgument { ty, .. } => {
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
+                            .with_code(error_list::INVALID_INTRINSIC_ARGUMENT)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new(span(position))
+                                    .with_message(format!("argument of type `{}` used here", ty.fg(loop_rainbow_color.next().unwrap())))
+                                    .with_color(label_color)
+                            )
+                            .with_help("LLVM intrinsics accept integers, floats, vectors and references to them")
+                    }
+                    CompileError::InvalidCArrayLength { len } => {
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
+                            .with_code(error_list::INVALID_C_ARRAY_LENGTH)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new(span(position))
+                                    .with_message(format!("array of length `{}` declared here", len.fg(loop_rainbow_color.next().unwrap())))
+                                    .with_color(label_color)
+                            )
+                            .with_help(format!("lelang array lengths must be between `{}` and `{}`", 0.fg(Color::Green), u32::MAX.fg(Color::Green)))
+                    }
+                    CompileError::ImportEntryFile { module } => {
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
+                            .with_code(error_list::IMPORT_ENTRY_FILE)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new(span(position))
+                                    .with_message(format!("module `{}` imported here", module.fg(loop_rainbow_color.next().unwrap())))
+                                    .with_color(label_color)
+                            )
+                            .with_help("considering move the shared definitions into another module and import it from both files")
+                    }
+                    CompileError::UnsupportedExportType { function: _, ty, reason } => {
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
+                            .with_code(error_list::UNSUPPORTED_EXPORT_TYPE)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new(span(position))
+                                    .with_message(format!("`{}` {}", ty.fg(loop_rainbow_color.next().unwrap()), reason))
+                                    .with_color(label_color)
+                            )
+                            .with_help("remove `pub` to keep the function internal, or use a vector with integer or float lanes and a power of two lane count")
+                    }
+                    CompileError::VariadicAggregate { function: _, ty } => {
+                        Report::build(ReportKind::Error, src.clone(), position.range.start())
+                            .with_code(error_list::VARIADIC_AGGREGATE)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new(span(position))
+                                    .with_message(format!("`{}` declared here", ty.fg(loop_rainbow_color.next().unwrap())))
+                                    .with_color(label_color)
+                            )
+                            .with_help("structs and arrays of variadic functions can not be lowered to the C ABI, pass a reference instead")
+                    }
+                    CompileError::InGenericInstantiation { instance, inner } => {
+                        let mut report = Report::build(ReportKind::Error, src.clone(), position.range.start())
+                            .with_code(error_list::IN_GENERIC_INSTANTIATION)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new(span(position))
+                                    .with_message(format!("`{}` instantiated here", instance.fg(loop_rainbow_color.next().unwrap())))
+                                    .with_color(label_color)
+                            );
+                        //沿着实例化链标出每一层的位置，最后标出真正出错的位置
+                        let mut current = inner.as_ref();
+                        while let LEError::CompileError { compile_error: CompileError::InGenericInstantiation { instance, inner }, position } = current {
+                            report = report.with_label(
+                                Label::new(span(position))
+                                    .with_message(format!("which instantiates `{}` here", instance.fg(loop_rainbow_color.next().unwrap())))
+                                    .with_color(label_color)
+                            );
+                            current = inner.as_ref();
+                        }
+                        match current {
+                            LEError::SyntaxError { syntax_error, position } => {
+                                report.with_label(Label::new(span(position)).with_message(syntax_error.to_string()).with_color(label_color))
+                            }
+                            LEError::CompileError { compile_error, position } => {
+                                report.with_label(Label::new(span(position)).with_message(compile_error.to_string()).with_color(label_color))
+                            }
+                            LEError::IOError { .. } | LEError::LinkError { .. } | LEError::TargetError { .. } | LEError::ArgumentError { .. } => {
+                                report.with_note(current.to_string())
+                            }
+                        }
+                    }
                 }
             }
             LEError::IOError { other } => {
-                Report::build(ReportKind::Error, src, 0)
+                Report::build(ReportKind::Error, src.clone(), 0)
                     .with_message(other.to_string())
             }
+            LEError::LinkError { message } => {
+                Report::build(ReportKind::Error, src.clone(), 0)
+                    .with_message(format!("link error: {}", message))
+            }
+            LEError::TargetError { message } => {
+                Report::build(ReportKind::Error, src.clone(), 0)
+                    .with_message(format!("target error: {}", message))
+            }
+            LEError::ArgumentError { message } => {
+                Report::build(ReportKind::Error, src.clone(), 0)
+                    .with_message(format!("argument error: {}", message))
+            }
         }
     }
 }
@@ -1,21 +1,83 @@
+use std::ffi::CString;
+use std::os::raw::c_char;
+
 use inkwell::execution_engine::ExecutionEngine;
 use inkwell::module::Module;
 use inkwell::OptimizationLevel;
+use inkwell::targets::{InitializationConfig, Target};
+
+use crate::error::{LEError, Result};
 
 pub struct JITCompiler<'source> {
     engine: ExecutionEngine<'source>,
 }
 
-type MainFunc = unsafe extern "C" fn() -> i32;
+///编译器为用户的`main`生成的C入口，和AOT编译出的可执行文件的入口相同
+type MainFunc = unsafe extern "C" fn(i32, *const *const c_char) -> i32;
 
 impl<'source> JITCompiler<'source> {
-    pub fn new(module: &'source Module) -> Self {
-        Self { engine: module.create_jit_execution_engine(OptimizationLevel::None).unwrap() }
+    pub fn new(module: &'source Module) -> Result<Self> {
+        Target::initialize_native(&InitializationConfig::default()).map_err(|message| LEError::TargetError { message })?;
+        let engine = module.create_jit_execution_engine(OptimizationLevel::None).map_err(|e| LEError::TargetError {
+            message: format!("failed to create JIT execution engine: {}", e.to_string().trim_end())
+        })?;
+        Ok(Self { engine })
     }
-    pub fn run_main(&self) -> i32 {
+
+    /// 以`args`作为命令行参数运行`main`，返回进程的返回值，`args`的第一个元素通常是程序名；
+    /// 参数中含有NUL字符时无法转换为C字符串，返回错误
+    pub fn run_main(&self, args: &[String]) -> Result<i32> {
+        let args = args
+            .iter()
+            .map(|arg| CString::new(arg.as_str()).map_err(|e| LEError::IOError { other: Box::new(e) }))
+            .collect::<Result<Vec<_>>>()?;
+        //和C的argv一样以空指针结尾
+        let argv = args.iter().map(|arg| arg.as_ptr()).chain(std::iter::once(std::ptr::null())).collect::<Vec<_>>();
         unsafe {
-            let main = self.engine.get_function::<MainFunc>("main").unwrap();
-            main.call()
+            let main = self.engine.get_function::<MainFunc>("main").map_err(|e| LEError::TargetError {
+                message: format!("cannot find the entry `main`: {}", e)
+            })?;
+            Ok(main.call(args.len() as i32, argv.as_ptr()))
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use inkwell::context::Context;
+
+    use crate::ast::Ast;
+    use crate::code_generator::generator::CodeGenerator;
+    use crate::lexer::LELexer;
+
+    use super::*;
+
+    fn run(source: &str, args: &[&str]) -> Result<i32> {
+        let context = Context::create();
+        let ast = Ast::from_lexer(LELexer::new(source).unwrap()).unwrap();
+        let mut code_generator = CodeGenerator::create(&context, "main");
+        code_generator.compile(&ast).unwrap();
+        let jit = JITCompiler::new(&code_generator.module)?;
+        jit.run_main(&args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn main_returns_exit_code() {
+        assert_eq!(run("le main()->i32{\n    ret 6*7;\n}\n", &["main"]).unwrap(), 42);
+        //没有返回值的main返回0
+        assert_eq!(run("le main(){\n}\n", &["main"]).unwrap(), 0);
+    }
+
+    #[test]
+    fn main_receives_arguments() {
+        let source = "le main(args:[str])->i32{\n    ret args.len as i32;\n}\n";
+        assert_eq!(run(source, &["main"]).unwrap(), 1);
+        assert_eq!(run(source, &["main", "a", "bc"]).unwrap(), 3);
+    }
+
+    #[test]
+    fn argument_with_nul_is_error() {
+        let source = "le main(args:[str])->i32{\n    ret args.len as i32;\n}\n";
+        assert!(matches!(run(source, &["main", "a\0b"]), Err(LEError::IOError { .. })));
+    }
+}
@@ -17,8 +17,10 @@
 //! }
 //! ```
 
+pub use source_map::*;
 pub use token_iterator::*;
 
+mod source_map;
 mod token_iterator;
 mod number_parser;
 mod string_literal_parser;
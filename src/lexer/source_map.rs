@@ -0,0 +1,46 @@
+use std::path::{Path, PathBuf};
+
+///一次编译中读入的所有源文件，`Position::file_id`是文件在其中的序号
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    pub path: PathBuf,
+    pub content: String,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///加入一个源文件，返回它的序号
+    pub fn add_file(&mut self, path: PathBuf, content: String) -> usize {
+        self.files.push(SourceFile { path, content });
+        self.files.len() - 1
+    }
+
+    ///按加入时的路径查找已经加入的源文件，路径需要完全相同，不做规范化
+    pub fn find_file(&self, path: &Path) -> Option<usize> {
+        self.files.iter().position(|file| file.path == path)
+    }
+
+    pub fn get_file(&self, file_id: usize) -> Option<&SourceFile> {
+        self.files.get(file_id)
+    }
+
+    ///诊断信息中显示的文件名
+    pub fn name(&self, file_id: usize) -> String {
+        self.files
+            .get(file_id)
+            .map(|file| file.path.display().to_string())
+            .unwrap_or_else(|| "<unknown>".into())
+    }
+
+    pub fn files(&self) -> impl Iterator<Item=&SourceFile> {
+        self.files.iter()
+    }
+}
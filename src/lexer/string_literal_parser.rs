@@ -1,3 +1,24 @@
-pub fn parse_string_literal(s: &str) -> String {
-    s.into()
-}
\ No newline at end of file
+/// 去掉字符串字面量两端的引号并处理转义字符，支持`\n`、`\t`、`\r`、`\0`、`\\`和`\"`，
+/// 其他转义字符不合法，返回None
+pub fn parse_string_literal(s: &str) -> Option<String> {
+    let content = s.strip_prefix('"')?.strip_suffix('"')?;
+    let mut result = String::with_capacity(content.len());
+    let mut chars = content.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        let escaped = match chars.next()? {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '0' => '\0',
+            '\\' => '\\',
+            '"' => '"',
+            _ => return None,
+        };
+        result.push(escaped);
+    }
+    Some(result)
+}
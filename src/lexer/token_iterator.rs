@@ -1,14 +1,17 @@
 #![allow(unused)]
 
 use std::fmt::{Debug, Display, Formatter, write};
+use std::cell::Cell;
 use std::ops::Range;
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 
 use logos::{Lexer, Logos};
 
 use crate::error::{LEError, Result, SyntaxError, TokenType};
 use crate::lexer::LEToken::Semicolon;
 use crate::lexer::number_parser::parse_number;
+use crate::lexer::string_literal_parser::parse_string_literal;
 
 fn record_span(lexer: &mut Lexer<LogosToken>) {
     let token_start = lexer.span().start;
@@ -18,18 +21,26 @@ fn record_span(lexer: &mut Lexer<LogosToken>) {
 }
 
 fn parse_string_literal_token(s: &str) -> Option<String> {
-    Some(s.into())
+    parse_string_literal(s)
 }
 
 #[derive(Debug, Clone)]
 pub struct Position {
     pub range: Range<usize>,
+    ///所在源文件在`SourceMap`中的序号
+    pub file_id: usize,
 }
 
 impl Position {
+    pub fn new(range: Range<usize>, file_id: usize) -> Self {
+        Self { range, file_id }
+    }
+
+    ///覆盖两个位置的范围，两个位置必须位于同一个源文件中
     pub fn sum(&self, other: &Self) -> Self {
         use std::cmp::{max, min};
-        Self { range: (min(self.range.start, other.range.start)..max(self.range.end, other.range.end)) }
+        debug_assert_eq!(self.file_id, other.file_id, "position spans across source files");
+        Self { range: (min(self.range.start, other.range.start)..max(self.range.end, other.range.end)), file_id: self.file_id }
     }
 }
 
@@ -77,6 +88,21 @@ pub enum LogosToken {
     #[token("ret", | lex | record_span(lex))]
     Return,
 
+    #[token("match", | lex | record_span(lex))]
+    Match,
+
+    #[token("impl", | lex | record_span(lex))]
+    Impl,
+
+    #[token("trait", | lex | record_span(lex))]
+    Trait,
+
+    #[token("import", | lex | record_span(lex))]
+    Import,
+
+    #[token("pub", | lex | record_span(lex))]
+    Pub,
+
     #[token("true", | lex | record_span(lex))]
     True,
 
@@ -92,9 +118,24 @@ pub enum LogosToken {
     #[token(":", | lex | record_span(lex))]
     Colon,
 
+    #[token("::", | lex | record_span(lex))]
+    DoubleColon,
+
     #[token(".", | lex | record_span(lex))]
     Dot,
 
+    #[token("..", | lex | record_span(lex))]
+    DoubleDot,
+
+    #[token("..=", | lex | record_span(lex))]
+    DoubleDotEqual,
+
+    #[token("...", | lex | record_span(lex))]
+    Ellipsis,
+
+    #[token("#", | lex | record_span(lex))]
+    Sharp,
+
     #[token(";", | lex | record_span(lex))]
     Semicolon,
 
@@ -185,7 +226,7 @@ pub enum LogosToken {
     #[regex("[a-zA-Z_]+[a-zA-Z_0-9]*", | lex | {record_span(lex); lex.slice().to_string()})]
     Identifier(String),
 
-    #[regex(r#""[^\n]*""#, | lex | {record_span(lex); parse_string_literal_token(lex.slice())})]
+    #[regex(r#""([^"\\\n]|\\.)*""#, | lex | {record_span(lex); parse_string_literal_token(lex.slice())})]
     StringLiteral(String),
 
     #[regex(r#"[0-9]*(\.[0-9]+)?"#, | lex | {record_span(lex); parse_number(lex)})]
@@ -216,6 +257,16 @@ pub enum KeyWord {
     StructureDeclare,
 
     Ref,
+
+    Match,
+
+    Impl,
+
+    Trait,
+
+    Import,
+
+    Pub,
 }
 
 impl Display for KeyWord {
@@ -231,6 +282,11 @@ impl Display for KeyWord {
             KeyWord::While => { "while" }
             KeyWord::StructureDeclare => { "struct" }
             KeyWord::Ref => { "ref" }
+            KeyWord::Match => { "match" }
+            KeyWord::Impl => { "impl" }
+            KeyWord::Trait => { "trait" }
+            KeyWord::Import => { "import" }
+            KeyWord::Pub => { "pub" }
         };
         f.write_str(str)
     }
@@ -336,6 +392,8 @@ pub enum LEToken {
 
     Colon,
 
+    DoubleColon,
+
     Comma,
 
     Semicolon,
@@ -355,6 +413,14 @@ pub enum LEToken {
     SingleArrow,
 
     DoubleArrow,
+
+    DoubleDot,
+
+    DoubleDotEqual,
+
+    Ellipsis,
+
+    Sharp,
 }
 
 impl Display for LEToken {
@@ -366,6 +432,7 @@ impl Display for LEToken {
             LEToken::StringLiteral(s) => { Display::fmt(s, f) }
             LEToken::Identifier(i) => { Display::fmt(i, f) }
             LEToken::Colon => { f.write_str(":") }
+            LEToken::DoubleColon => { f.write_str("::") }
             LEToken::Comma => { f.write_str(":") }
             LEToken::Semicolon => { f.write_str(";") }
             LEToken::LeftPar => { f.write_str("(") }
@@ -376,6 +443,10 @@ impl Display for LEToken {
             LEToken::LeftBrace => { f.write_str("}") }
             LEToken::SingleArrow => { f.write_str("->") }
             LEToken::DoubleArrow => { f.write_str("=>") }
+            LEToken::DoubleDot => { f.write_str("..") }
+            LEToken::DoubleDotEqual => { f.write_str("..=") }
+            LEToken::Ellipsis => { f.write_str("...") }
+            LEToken::Sharp => { f.write_str("#") }
         }
     }
 }
@@ -429,6 +500,16 @@ impl From<LogosToken> for LEToken {
             LogosToken::NotEqual => { Self::Operator(Operator::NotEqual) }
             LogosToken::DoubleArrow => { Self::DoubleArrow }
             LogosToken::Cast => { Self::Operator(Operator::Cast) }
+            LogosToken::Match => { Self::KeyWord(KeyWord::Match) }
+            LogosToken::DoubleDot => { Self::DoubleDot }
+            LogosToken::DoubleDotEqual => { Self::DoubleDotEqual }
+            LogosToken::Ellipsis => { Self::Ellipsis }
+            LogosToken::Sharp => { Self::Sharp }
+            LogosToken::Impl => { Self::KeyWord(KeyWord::Impl) }
+            LogosToken::Trait => { Self::KeyWord(KeyWord::Trait) }
+            LogosToken::Import => { Self::KeyWord(KeyWord::Import) }
+            LogosToken::Pub => { Self::KeyWord(KeyWord::Pub) }
+            LogosToken::DoubleColon => { Self::DoubleColon }
             _ => { unreachable!("unknown character handling not implement yet") }
         }
     }
@@ -456,6 +537,8 @@ impl From<LogosToken> for LEToken {
 pub struct LELexer<'s> {
     inner: Lexer<'s, LogosToken>,
     current: Option<LEToken>,
+    ///统计用时时累计产生token的用时
+    lexing_time: Option<Rc<Cell<Duration>>>,
 }
 
 
@@ -463,7 +546,12 @@ impl<'s> Iterator for LELexer<'s> {
     type Item = LEToken;
 
     fn next(&mut self) -> Option<Self::Item> {
-        match self.inner.next() {
+        let start = self.lexing_time.as_ref().map(|_| Instant::now());
+        let next = self.inner.next();
+        if let (Some(start), Some(lexing_time)) = (start, &self.lexing_time) {
+            lexing_time.set(lexing_time.get() + start.elapsed());
+        }
+        match next {
             None => { self.current.take() }
             Some(x) => { self.current.replace(x.into()) }
         }
@@ -472,13 +560,25 @@ impl<'s> Iterator for LELexer<'s> {
 
 impl<'s> LELexer<'s> {
     pub fn new(s: &'s str) -> Option<Self> {
+        Self::with_file_id(s, 0)
+    }
+
+    ///词法分析`SourceMap`中的某个源文件，产生的位置都带有该文件的序号
+    pub fn with_file_id(s: &'s str, file_id: usize) -> Option<Self> {
         let mut s = Self {
-            inner: LogosToken::lexer_with_extras(s, Extra { current_pos: Position { range: (0..0) }, last_pos: Position { range: (0..0) } }),
+            inner: LogosToken::lexer_with_extras(s, Extra { current_pos: Position::new(0..0, file_id), last_pos: Position::new(0..0, file_id) }),
             current: None,
+            lexing_time: None,
         };
         s.next();
         Some(s)
     }
+
+    ///之后产生token的用时累加到`lexing_time`中，语法分析时按需产生token，用它可以区分词法分析和语法分析的用时
+    pub fn record_lexing_time(mut self, lexing_time: Rc<Cell<Duration>>) -> Self {
+        self.lexing_time = Some(lexing_time);
+        self
+    }
     pub fn consume(&mut self) {
         self.next().unwrap();
     }
@@ -692,6 +792,20 @@ impl<'s> LELexer<'s> {
         }
     }
 
+    pub fn consume_double_arrow(&mut self) -> Result<()> {
+        let current_pos = self.last_pos();
+        let consume = self.next();
+        if let Some(consume) = consume {
+            if let LEToken::DoubleArrow = consume {
+                Ok(())
+            } else {
+                Err(LEError::new_syntax_error(SyntaxError::unexpect_token(vec![TokenType::DoubleArrow], consume), current_pos))
+            }
+        } else {
+            Err(LEError::new_syntax_error(SyntaxError::missing_token(vec![TokenType::DoubleArrow]), current_pos))
+        }
+    }
+
     pub fn consume_return_type_allow(&mut self) -> Result<()> {
         let current_pos = self.last_pos();
         let consume = self.next();
@@ -10,6 +10,7 @@ use atty::Stream;
 use clap::Parser;
 
 use crate::arg_parser::Args;
+use crate::lexer::SourceMap;
 
 mod lexer;
 mod code_generator;
@@ -22,20 +23,19 @@ mod arg_parser;
 
 fn read_args_and_compile() -> std::io::Result<()> {
     let args: Args = arg_parser::Args::parse();
-    let mut input = File::open(&args.input_path)?;
-    let mut buffer = String::new();
-    let src = args.input_path.to_str().unwrap();
-    input.read_to_string(&mut buffer)?;
+    let mut source_map = SourceMap::new();
     let config = ariadne::Config::default()
         .with_color(atty::is(Stream::Stderr));
 
-    match driver::compile_with_config(&args, &buffer) {
+    match driver::compile_with_config(&args, &mut source_map) {
         Ok(_) => {}
         Err(err) => {
-            err.to_error_report_colored(src)
+            //错误可能位于任意一个被导入的模块中，报告时需要提供所有源文件
+            let sources = source_map.files().map(|file| (file.path.display().to_string(), file.content.clone()));
+            err.to_error_report_colored(&source_map)
                 .with_config(config)
                 .finish()
-                .eprint((src, Source::from(buffer)))?;
+                .eprint(ariadne::sources(sources))?;
         }
     }
     Ok(())
@@ -1,29 +1,252 @@
+use std::fmt::{Debug, Formatter};
+use std::time::{Duration, Instant};
+
+use inkwell::attributes::{Attribute, AttributeLoc};
 use inkwell::module::Module;
 use inkwell::OptimizationLevel;
 use inkwell::passes::{PassManager, PassManagerBuilder};
 use inkwell::values::FunctionValue;
 
+///与clang相同的内联阈值：`-O3`、默认、`-Os`和`-Oz`
+const AGGRESSIVE_INLINE_THRESHOLD: u32 = 250;
+const DEFAULT_INLINE_THRESHOLD: u32 = 225;
+const OPT_SIZE_INLINE_THRESHOLD: u32 = 75;
+const OPT_MIN_SIZE_INLINE_THRESHOLD: u32 = 25;
+
+/// `--passes`可以使用的一个LLVM pass，名字与LLVM的`opt`相同
+#[derive(Clone, Copy)]
+pub struct Pass {
+    pub name: &'static str,
+    add: fn(&PassManager<Module>),
+}
+
+impl Debug for Pass {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name)
+    }
+}
+
+///所有可以使用的pass。`--passes`中的每个pass单独运行，分析结果不在pass之间共享，因此不提供只产生别名分析结果的pass
+pub const PASSES: [Pass; 46] = [
+    Pass { name: "mem2reg", add: |pass_manager| pass_manager.add_promote_memory_to_register_pass() },
+    Pass { name: "reg2mem", add: |pass_manager| pass_manager.add_demote_memory_to_register_pass() },
+    Pass { name: "sroa", add: |pass_manager| pass_manager.add_scalar_repl_aggregates_pass() },
+    Pass { name: "instcombine", add: |pass_manager| pass_manager.add_instruction_combining_pass() },
+    Pass { name: "aggressive-instcombine", add: |pass_manager| pass_manager.add_aggressive_inst_combiner_pass() },
+    Pass { name: "instsimplify", add: |pass_manager| pass_manager.add_instruction_simplify_pass() },
+    Pass { name: "reassociate", add: |pass_manager| pass_manager.add_reassociate_pass() },
+    Pass { name: "gvn", add: |pass_manager| pass_manager.add_gvn_pass() },
+    Pass { name: "newgvn", add: |pass_manager| pass_manager.add_new_gvn_pass() },
+    Pass { name: "sccp", add: |pass_manager| pass_manager.add_sccp_pass() },
+    Pass { name: "ipsccp", add: |pass_manager| pass_manager.add_ipsccp_pass() },
+    Pass { name: "simplifycfg", add: |pass_manager| pass_manager.add_cfg_simplification_pass() },
+    Pass { name: "adce", add: |pass_manager| pass_manager.add_aggressive_dce_pass() },
+    Pass { name: "bdce", add: |pass_manager| pass_manager.add_bit_tracking_dce_pass() },
+    Pass { name: "dse", add: |pass_manager| pass_manager.add_dead_store_elimination_pass() },
+    Pass { name: "early-cse", add: |pass_manager| pass_manager.add_early_cse_pass() },
+    Pass { name: "early-cse-memssa", add: |pass_manager| pass_manager.add_early_cse_mem_ssa_pass() },
+    Pass { name: "licm", add: |pass_manager| pass_manager.add_licm_pass() },
+    Pass { name: "loop-rotate", add: |pass_manager| pass_manager.add_loop_rotate_pass() },
+    Pass { name: "loop-unroll", add: |pass_manager| pass_manager.add_loop_unroll_pass() },
+    Pass { name: "loop-unswitch", add: |pass_manager| pass_manager.add_loop_unswitch_pass() },
+    Pass { name: "loop-deletion", add: |pass_manager| pass_manager.add_loop_deletion_pass() },
+    Pass { name: "loop-idiom", add: |pass_manager| pass_manager.add_loop_idiom_pass() },
+    Pass { name: "indvars", add: |pass_manager| pass_manager.add_ind_var_simplify_pass() },
+    Pass { name: "loop-vectorize", add: |pass_manager| pass_manager.add_loop_vectorize_pass() },
+    Pass { name: "slp-vectorizer", add: |pass_manager| pass_manager.add_slp_vectorize_pass() },
+    Pass { name: "alignment-from-assumptions", add: |pass_manager| pass_manager.add_alignment_from_assumptions_pass() },
+    Pass { name: "mldst-motion", add: |pass_manager| pass_manager.add_merged_load_store_motion_pass() },
+    Pass { name: "memcpyopt", add: |pass_manager| pass_manager.add_memcpy_optimize_pass() },
+    Pass { name: "tailcallelim", add: |pass_manager| pass_manager.add_tail_call_elimination_pass() },
+    Pass { name: "jump-threading", add: |pass_manager| pass_manager.add_jump_threading_pass() },
+    Pass { name: "correlated-propagation", add: |pass_manager| pass_manager.add_correlated_value_propagation_pass() },
+    Pass { name: "inline", add: |pass_manager| pass_manager.add_function_inlining_pass() },
+    Pass { name: "always-inline", add: |pass_manager| pass_manager.add_always_inliner_pass() },
+    Pass { name: "prune-eh", add: |pass_manager| pass_manager.add_prune_eh_pass() },
+    Pass { name: "globaldce", add: |pass_manager| pass_manager.add_global_dce_pass() },
+    Pass { name: "globalopt", add: |pass_manager| pass_manager.add_global_optimizer_pass() },
+    Pass { name: "constmerge", add: |pass_manager| pass_manager.add_constant_merge_pass() },
+    Pass { name: "mergefunc", add: |pass_manager| pass_manager.add_merge_functions_pass() },
+    Pass { name: "deadargelim", add: |pass_manager| pass_manager.add_dead_arg_elimination_pass() },
+    Pass { name: "argpromotion", add: |pass_manager| pass_manager.add_argument_promotion_pass() },
+    Pass { name: "function-attrs", add: |pass_manager| pass_manager.add_function_attrs_pass() },
+    Pass { name: "strip-dead-prototypes", add: |pass_manager| pass_manager.add_strip_dead_prototypes_pass() },
+    Pass { name: "lower-expect", add: |pass_manager| pass_manager.add_lower_expect_intrinsic_pass() },
+    Pass { name: "lowerswitch", add: |pass_manager| pass_manager.add_lower_switch_pass() },
+    Pass { name: "verify", add: |pass_manager| pass_manager.add_verifier_pass() },
+];
+
+/// 按名字查找pass
+pub fn find_pass(name: &str) -> Option<Pass> {
+    PASSES.iter().find(|pass| pass.name == name).copied()
+}
+
+/// 一组一起运行的pass，函数级的pass逐个函数运行
+enum PassStage<'s> {
+    Function(PassManager<FunctionValue<'s>>),
+    Module(PassManager<Module<'s>>),
+}
+
+/// 按优化级别运行函数级和模块级的优化：`-O2`以上在模块级做内联、过程间优化、全局死代码删除等，
+/// `-O0`和`-O1`只内联标记为`alwaysinline`的函数；也可以用`--passes`指定依次运行的pass
 pub struct Optimizer<'s> {
-    pass_manager: PassManager<FunctionValue<'s>>,
+    ///按顺序运行的阶段和它们的名字，优化级别的预设由LLVM整体运行，只能按阶段统计用时
+    stages: Vec<(&'static str, PassStage<'s>)>,
+    size_level: u32,
 }
 
 impl<'s> Optimizer<'s> {
-    pub fn new(module: &Module<'s>, level: OptimizationLevel) -> Self {
-        let pass_manager = PassManager::create(module);
-        let builder = PassManagerBuilder::create();
-        builder.set_optimization_level(level);
-        builder.populate_function_pass_manager(&pass_manager);
-        Self { pass_manager }
-    }
-    pub fn run_on_function(&self, function: &FunctionValue) -> bool {
-        self.pass_manager.run_on(function)
-    }
-
-    pub fn run_on_module(&self, module: &Module) {
-        let mut current_function = module.get_first_function();
-        while let Some(f) = current_function {
-            self.run_on_function(&f);
-            current_function = f.get_next_function();
+    /// `size_level`为1、2时分别对应`-Os`、`-Oz`
+    pub fn new(module: &Module<'s>, level: OptimizationLevel, size_level: u32) -> Self {
+        let function_pass_manager = PassManager::create(module);
+        let module_pass_manager = PassManager::create(());
+        let builder = create_builder(level, size_level);
+        builder.populate_function_pass_manager(&function_pass_manager);
+        builder.populate_module_pass_manager(&module_pass_manager);
+        if matches!(level, OptimizationLevel::None | OptimizationLevel::Less) {
+            module_pass_manager.add_always_inliner_pass();
         }
+        let stages = vec![
+            ("function passes", PassStage::Function(function_pass_manager)),
+            ("module passes", PassStage::Module(module_pass_manager)),
+        ];
+        Self { stages, size_level }
     }
-}
\ No newline at end of file
+
+    /// 按顺序运行`passes`中的pass，代替优化级别的预设，每个pass有单独的PassManager，可以分别统计用时
+    pub fn with_passes(passes: &[Pass]) -> Self {
+        let stages = passes.iter().map(|pass| {
+            let pass_manager = PassManager::create(());
+            (pass.add)(&pass_manager);
+            (pass.name, PassStage::Module(pass_manager))
+        }).collect();
+        Self { stages, size_level: 0 }
+    }
+
+    /// 在模块级优化之后运行链接时优化，模块应当已经链接了所有源文件，并且只有导出的函数是外部链接
+    pub fn enable_lto(&mut self, level: OptimizationLevel) {
+        let lto_pass_manager = PassManager::create(());
+        let builder = create_builder(level, self.size_level);
+        builder.populate_lto_pass_manager(&lto_pass_manager, false, true);
+        self.stages.push(("lto passes", PassStage::Module(lto_pass_manager)));
+    }
+
+    pub fn run_on_module(&self, module: &Module<'s>) {
+        self.run_on_module_with(module, |_, _, _| {});
+    }
+
+    /// 依次运行每个阶段，每个阶段结束后以阶段的名字、用时和优化后的模块调用`after_stage`
+    pub fn run_on_module_with(&self, module: &Module<'s>, mut after_stage: impl FnMut(&str, Duration, &Module<'s>)) {
+        self.add_size_attributes(module);
+        for (name, stage) in self.stages.iter() {
+            let start = Instant::now();
+            match stage {
+                PassStage::Function(pass_manager) => {
+                    pass_manager.initialize();
+                    let mut current_function = module.get_first_function();
+                    while let Some(f) = current_function {
+                        pass_manager.run_on(&f);
+                        current_function = f.get_next_function();
+                    }
+                    pass_manager.finalize();
+                }
+                PassStage::Module(pass_manager) => {
+                    pass_manager.run_on(module);
+                }
+            }
+            after_stage(name, start.elapsed(), module);
+        }
+    }
+
+    /// 和clang一样，`-Os`为函数加上`optsize`，`-Oz`再加上`minsize`，后端生成代码时也会优先减小体积
+    fn add_size_attributes(&self, module: &Module<'s>) {
+        let attribute_names: &[&str] = match self.size_level {
+            0 => return,
+            1 => &["optsize"],
+            _ => &["optsize", "minsize"],
+        };
+        let context = module.get_context();
+        for function in module.get_functions().filter(|function| function.count_basic_blocks() > 0) {
+            for name in attribute_names {
+                let attribute = context.create_enum_attribute(Attribute::get_named_enum_kind_id(name), 0);
+                function.add_attribute(AttributeLoc::Function, attribute);
+            }
+        }
+    }
+}
+
+/// 按优化级别设置的PassManagerBuilder，`-O2`以上使用与clang相同的内联阈值
+fn create_builder(level: OptimizationLevel, size_level: u32) -> PassManagerBuilder {
+    let builder = PassManagerBuilder::create();
+    builder.set_optimization_level(level);
+    builder.set_size_level(size_level);
+    if let Some(threshold) = inline_threshold(level, size_level) {
+        builder.set_inliner_with_threshold(threshold);
+    }
+    builder
+}
+
+///与clang相同的内联阈值，`-O0`和`-O1`只内联`alwaysinline`的函数
+fn inline_threshold(level: OptimizationLevel, size_level: u32) -> Option<u32> {
+    match (level, size_level) {
+        (OptimizationLevel::None | OptimizationLevel::Less, _) => None,
+        (_, 1) => Some(OPT_SIZE_INLINE_THRESHOLD),
+        (_, 2) => Some(OPT_MIN_SIZE_INLINE_THRESHOLD),
+        (OptimizationLevel::Aggressive, _) => Some(AGGRESSIVE_INLINE_THRESHOLD),
+        _ => Some(DEFAULT_INLINE_THRESHOLD),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use inkwell::context::Context;
+
+    use crate::ast::Ast;
+    use crate::code_generator::generator::CodeGenerator;
+    use crate::lexer::LELexer;
+
+    use super::*;
+
+    /// 优化`source`编译出的模块，返回依次运行的阶段名
+    fn run_stages(source: &str, optimizer: impl for<'ctx> FnOnce(&Module<'ctx>) -> Optimizer<'ctx>) -> Vec<String> {
+        let context = Context::create();
+        let ast = Ast::from_lexer(LELexer::new(source).unwrap()).unwrap();
+        let mut code_generator = CodeGenerator::create(&context, "main");
+        code_generator.compile(&ast).unwrap();
+        let module = code_generator.module;
+        let mut stages = vec![];
+        optimizer(&module).run_on_module_with(&module, |name, _, module| {
+            assert!(module.verify().is_ok(), "invalid module after `{}`", name);
+            stages.push(name.to_string());
+        });
+        stages
+    }
+
+    const SOURCE: &str = "le add(a:i32,b:i32)->i32{\n    ret a+b;\n}\n\nle main()->i32{\n    ret add(1,2);\n}\n";
+
+    #[test]
+    fn preset_stages() {
+        let stages = run_stages(SOURCE, |module| Optimizer::new(module, OptimizationLevel::Default, 0));
+        assert_eq!(stages, ["function passes", "module passes"]);
+        let stages = run_stages(SOURCE, |module| {
+            let mut optimizer = Optimizer::new(module, OptimizationLevel::Aggressive, 2);
+            optimizer.enable_lto(OptimizationLevel::Aggressive);
+            optimizer
+        });
+        assert_eq!(stages, ["function passes", "module passes", "lto passes"]);
+    }
+
+    #[test]
+    fn explicit_passes_run_in_order() {
+        let passes = ["mem2reg", "instcombine", "inline", "mem2reg"].map(|name| find_pass(name).unwrap());
+        let stages = run_stages(SOURCE, |_| Optimizer::with_passes(&passes));
+        assert_eq!(stages, ["mem2reg", "instcombine", "inline", "mem2reg"]);
+    }
+
+    #[test]
+    fn pass_names_are_unique() {
+        for (index, pass) in PASSES.iter().enumerate() {
+            assert!(PASSES[index + 1..].iter().all(|other| other.name != pass.name), "duplicate pass `{}`", pass.name);
+        }
+    }
+}
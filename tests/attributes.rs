@@ -0,0 +1,53 @@
+//! 函数属性的检查，错误应报告为`InvalidAttribute`
+
+mod common;
+
+use common::compile_source;
+use lelang::error::{CompileError, LEError};
+
+/// 编译失败时出错的属性名和原因
+fn attribute_error(test: &str, source: &str) -> (String, String) {
+    match compile_source(test, source) {
+        Err(LEError::CompileError { compile_error: CompileError::InvalidAttribute { attribute, reason }, .. }) => (attribute, reason),
+        other => panic!("expect an invalid attribute error, got {:?}", other),
+    }
+}
+
+#[test]
+fn inline_conflicts_with_noinline() {
+    let source = "#[inline] #[noinline] le twice(a:i32)->i32{ ret a * 2; }\nle main()->i32{ ret twice(1); }";
+    let (attribute, reason) = attribute_error("inline_conflicts_with_noinline", source);
+    assert_eq!(attribute, "inline");
+    assert!(reason.contains("noinline"), "{}", reason);
+}
+
+#[test]
+fn repeated_attribute() {
+    let source = "#[cold] #[cold] le rare()->i32{ ret 1; }\nle main()->i32{ ret rare(); }";
+    let (attribute, reason) = attribute_error("repeated_attribute", source);
+    assert_eq!(attribute, "cold");
+    assert_eq!(reason, "duplicate attribute");
+}
+
+#[test]
+fn duplicate_symbol_name() {
+    let source = "#[link_name = \"shared\"] le first()->i32{ ret 1; }\n\
+                  #[link_name = \"shared\"] le second()->i32{ ret 2; }\n\
+                  le main()->i32{ ret first() + second(); }";
+    let (attribute, reason) = attribute_error("duplicate_symbol_name", source);
+    assert_eq!(attribute, "link_name");
+    assert!(reason.contains("`shared`"), "{}", reason);
+}
+
+#[test]
+fn noreturn_function_can_not_return() {
+    let source = "#[noreturn] le fail()->i32{ ret 1; }\nle main()->i32{ ret fail(); }";
+    let (attribute, _) = attribute_error("noreturn_function_can_not_return", source);
+    assert_eq!(attribute, "noreturn");
+}
+
+#[test]
+fn noreturn_function_ending_in_panic() {
+    let source = "#[noreturn] le fail(){ panic(\"unreachable\"); }\nle main()->i32{ fail(); ret 0; }";
+    compile_source("noreturn_function_ending_in_panic", source).unwrap();
+}
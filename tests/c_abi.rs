@@ -0,0 +1,86 @@
+//! 用本机的cc编译C桩函数，与lelang互相按值传递结构体，检查按C ABI降级后的签名与C编译器一致
+
+mod common;
+
+use std::process::Command;
+
+use common::{compile, fixture, output_dir, require_tool, run};
+
+const EXPECTED_OUTPUT: &str = "\
+204
+385.000000
+12 2.000000
+506.000000
+11 22 33
+10
+[3.000000, 5.000000]
+30.000000
+207.500000
+";
+
+fn run_c_abi_fixture(test: &str, optimization: &str) {
+    let cc = match require_tool(test, &["cc", "clang", "gcc"]) {
+        Some(cc) => cc,
+        None => return,
+    };
+    let archiver = match require_tool(test, &["ar", "llvm-ar"]) {
+        Some(archiver) => archiver,
+        None => return,
+    };
+    let dir = output_dir(test);
+    let object = dir.join("c_abi_stubs.o");
+    run(Command::new(&cc).arg("-c").arg("-O2").arg(fixture("c_abi_stubs.c")).arg("-o").arg(&object));
+    let library = dir.join("libc_abi_stubs.a");
+    let _ = std::fs::remove_file(&library);
+    run(Command::new(&archiver).arg("rcs").arg(&library).arg(&object));
+
+    let executable = dir.join("c_abi");
+    compile(&[
+        "-i", fixture("c_abi.le").to_str().unwrap(),
+        "-o", executable.to_str().unwrap(),
+        optimization,
+        "-S", "EXE",
+        "-L", dir.to_str().unwrap(),
+        "-l", "c_abi_stubs",
+    ]).unwrap();
+    assert_eq!(run(&mut Command::new(&executable)), EXPECTED_OUTPUT);
+}
+
+#[test]
+#[cfg(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn structs_passed_by_value_match_c() {
+    run_c_abi_fixture("structs_passed_by_value_match_c", "-O0");
+}
+
+#[test]
+#[cfg(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn structs_passed_by_value_match_c_after_inlining() {
+    run_c_abi_fixture("structs_passed_by_value_match_c_after_inlining", "-O2");
+}
+
+/// 按`target`编译fixture为IR，不需要C编译器就能检查降级后的声明
+fn c_abi_ir(test: &str, target: &str) -> String {
+    let output = output_dir(test).join("c_abi");
+    compile(&[
+        "-i", fixture("c_abi.le").to_str().unwrap(),
+        "-o", output.to_str().unwrap(),
+        "--target", target,
+        "-S", "IR",
+    ]).unwrap();
+    std::fs::read_to_string(output.with_extension("ll")).unwrap()
+}
+
+#[test]
+fn vector_members_use_sse_registers_on_x86_64() {
+    let ir = c_abi_ir("vector_members_use_sse_registers_on_x86_64", "x86_64-unknown-linux-gnu");
+    //16字节的向量使用一个SSE寄存器，两个8字节的向量各使用一个SSE寄存器
+    assert!(ir.contains("declare <2 x double> @c_lanes_scale(<2 x double>, double)"), "{}", ir);
+    assert!(ir.contains("declare double @c_halves_sum(<2 x float>, <2 x float>)"), "{}", ir);
+}
+
+#[test]
+fn vector_members_are_homogeneous_aggregates_on_aarch64() {
+    let ir = c_abi_ir("vector_members_are_homogeneous_aggregates_on_aarch64", "aarch64-unknown-linux-gnu");
+    assert!(ir.contains("declare <2 x double> @c_lanes_scale(<2 x double>, double)"), "{}", ir);
+    assert!(ir.contains("declare double @c_halves_sum([2 x <2 x float>])"), "{}", ir);
+}
@@ -0,0 +1,141 @@
+//! `-S HEADER`输出的C头文件：导出函数的声明、用到的结构体和向量类型，以及C无法表示的导出类型
+
+mod common;
+
+use std::path::PathBuf;
+use std::process::Command;
+
+use common::{compile, output_dir, require_tool, run};
+use lelang::error::{CompileError, LEError};
+
+/// 把模块和入口文件main.le写入测试的输出目录，编译为C头文件，返回头文件的路径
+fn compile_header(test: &str, modules: &[(&str, &str)], source: &str) -> Result<PathBuf, LEError> {
+    let dir = output_dir(test);
+    for (name, module) in modules {
+        std::fs::write(dir.join(format!("{}.le", name)), module).unwrap();
+    }
+    let input = dir.join("main.le");
+    std::fs::write(&input, source).unwrap();
+    let output = dir.join("main");
+    compile(&["-i", input.to_str().unwrap(), "-o", output.to_str().unwrap(), "-S", "HEADER"])?;
+    Ok(output.with_extension("h"))
+}
+
+/// 有C编译器时检查头文件可以通过编译
+fn check_syntax(test: &str, header: &PathBuf) {
+    if let Some(cc) = require_tool(test, &["cc", "clang", "gcc"]) {
+        run(Command::new(cc).arg("-fsyntax-only").arg("-x").arg("c").arg(header));
+    }
+}
+
+const SHAPES: &str = "\
+pub struct Point{
+    x:f64,
+    y:f64
+}
+
+pub struct Polygon{
+    points:[Point;4],
+    len:u32
+}
+
+pub le area(polygon:ref Polygon)->f64{
+    ret 0.0;
+}
+
+pub le scale(v:vec<f32;4>, factor:f32)->vec<f32;4>{
+    ret v;
+}
+
+pub le visible(p:Point, flags:[u8;2])->bool{
+    ret true;
+}
+
+le helper()->i32{
+    ret 0;
+}
+";
+
+#[test]
+fn header_declares_exported_functions() {
+    let test = "header_declares_exported_functions";
+    let header_path = compile_header(test, &[], SHAPES).unwrap();
+    let header = std::fs::read_to_string(&header_path).unwrap();
+    assert!(header.contains("#ifndef MAIN_H\n#define MAIN_H"), "{}", header);
+    //Point在Polygon之前定义
+    let point = header.find("typedef struct Point {\n    double x;\n    double y;\n} Point;").expect(&header);
+    let polygon = header.find("typedef struct Polygon {\n    struct Point points[4];\n    uint32_t len;\n} Polygon;").expect(&header);
+    assert!(point < polygon, "{}", header);
+    assert!(header.contains("typedef float floatx4 __attribute__((vector_size(16)));"), "{}", header);
+    assert!(header.contains("double area(struct Polygon *polygon);"), "{}", header);
+    assert!(header.contains("floatx4 scale(floatx4 v, float factor);"), "{}", header);
+    assert!(header.contains("uint8_t visible(struct Point p, uint8_t flags[2]);"), "{}", header);
+    //没有pub的函数不导出
+    assert!(!header.contains("helper"), "{}", header);
+    check_syntax(test, &header_path);
+}
+
+#[test]
+fn colliding_struct_names_are_renamed() {
+    let test = "colliding_struct_names_are_renamed";
+    let module = "pub struct b{\n    x:i32\n}\n";
+    let source = "\
+import a;
+
+pub struct a_b{
+    y:f64
+}
+
+pub le pick(p:a::b, q:a_b)->i32{
+    ret 0;
+}
+";
+    let header_path = compile_header(test, &[("a", module)], source).unwrap();
+    let header = std::fs::read_to_string(&header_path).unwrap();
+    //`a::b`和`a_b`替换字符后相同，后出现的`a_b`加上后缀
+    assert!(header.contains("typedef struct a_b {\n    int32_t x;\n} a_b;"), "{}", header);
+    assert!(header.contains("typedef struct a_b_2 {\n    double y;\n} a_b_2;"), "{}", header);
+    assert!(header.contains("int32_t pick(struct a_b p, struct a_b_2 q);"), "{}", header);
+    check_syntax(test, &header_path);
+}
+
+#[test]
+fn struct_named_like_exported_function_is_renamed() {
+    let test = "struct_named_like_exported_function_is_renamed";
+    let source = "pub struct norm{\n    x:f64\n}\n\npub le norm(v:norm)->f64{\n    ret v.x;\n}\n";
+    let header_path = compile_header(test, &[], source).unwrap();
+    let header = std::fs::read_to_string(&header_path).unwrap();
+    assert!(header.contains("double norm(struct norm_2 v);"), "{}", header);
+    check_syntax(test, &header_path);
+}
+
+fn expect_unsupported_export_type(test: &str, source: &str, expect_type: &str, expect_declarator: &str) {
+    match compile_header(test, &[], source) {
+        Err(LEError::CompileError { compile_error: CompileError::UnsupportedExportType { function, ty, .. }, position }) => {
+            assert_eq!(function, "f");
+            assert_eq!(ty, expect_type);
+            assert_eq!(&source[position.range.clone()], expect_declarator);
+        }
+        other => panic!("expect an unsupported export type error, got {:?}", other),
+    }
+}
+
+#[test]
+fn bool_vector_can_not_be_exported() {
+    expect_unsupported_export_type("bool_vector_can_not_be_exported", "pub le f(mask:vec<bool;4>)->i32{\n    ret 0;\n}\n", "vec<bool;4>", "vec<bool;4>");
+}
+
+#[test]
+fn vector_lane_count_must_be_power_of_two() {
+    //指针指向的结构体成员中的向量同样需要检查，错误报告在参数的类型上
+    let source = "struct Color{\n    rgb:vec<f32;3>\n}\n\npub le f(count:i32, color:ref Color)->i32{\n    ret count;\n}\n";
+    expect_unsupported_export_type("vector_lane_count_must_be_power_of_two", source, "vec<f32;3>", "Color");
+}
+
+#[test]
+fn unsupported_vectors_in_internal_functions() {
+    //没有导出的函数不受限制
+    let source = "le f(mask:vec<bool;4>, rgb:vec<f32;3>)->i32{\n    ret 0;\n}\n\npub le g()->i32{\n    ret 0;\n}\n";
+    let header = std::fs::read_to_string(compile_header("unsupported_vectors_in_internal_functions", &[], source).unwrap()).unwrap();
+    assert!(header.contains("int32_t g(void);"), "{}", header);
+}
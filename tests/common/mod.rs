@@ -0,0 +1,75 @@
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use clap::Parser;
+
+use lelang::arg_parser::Args;
+pub use lelang::driver::find_in_path;
+use lelang::driver::compile_with_config;
+use lelang::error::LEError;
+use lelang::lexer::SourceMap;
+
+/// 测试用的源文件，位于tests/fixtures目录
+pub fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures").join(name)
+}
+
+/// 每个测试各自的输出目录
+pub fn output_dir(test: &str) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(test);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// 按命令行参数编译，参数中不包含程序名
+pub fn compile(args: &[&str]) -> Result<(), LEError> {
+    let args = Args::parse_from(std::iter::once("lelang").chain(args.iter().copied()));
+    compile_with_config(&args, &mut SourceMap::new())
+}
+
+/// 依赖的外部工具不存在时跳过测试，返回找到的第一个工具
+pub fn require_tool(test: &str, candidates: &[&str]) -> Option<PathBuf> {
+    let tool = candidates.iter().find_map(|name| find_in_path(name));
+    if tool.is_none() {
+        eprintln!("skipping `{}`: none of {} found in PATH", test, candidates.join(", "));
+    }
+    tool
+}
+
+/// 运行程序，返回它的标准输出，退出状态不为0时测试失败
+pub fn run(cmd: &mut Command) -> String {
+    let output = cmd.output().unwrap_or_else(|e| panic!("failed to run {:?}: {}", cmd, e));
+    assert!(output.status.success(), "{:?} failed with {}\n{}", cmd, output.status, String::from_utf8_lossy(&output.stderr));
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+/// 把源代码写入测试的输出目录，编译为LLVM IR
+pub fn compile_source(test: &str, source: &str) -> Result<(), LEError> {
+    let dir = output_dir(test);
+    let input = dir.join("main.le");
+    std::fs::write(&input, source).unwrap();
+    compile(&["-i", input.to_str().unwrap(), "-o", dir.join("main").to_str().unwrap(), "-S", "IR"])
+}
+
+/// 编译为LLVM IR，返回IR的文本，编译失败时测试失败
+pub fn compile_to_ir(test: &str, source: &str) -> String {
+    if let Err(e) = compile_source(test, source) {
+        panic!("failed to compile `{}`: {:?}", test, e);
+    }
+    std::fs::read_to_string(output_dir(test).join("main.ll")).unwrap()
+}
+
+/// 编译为可执行文件并运行，返回它的标准输出；链接需要C编译器，找不到时返回None，跳过测试
+pub fn compile_and_run(test: &str, source: &str) -> Option<String> {
+    require_tool(test, &["cc", "clang", "gcc"])?;
+    let dir = output_dir(test);
+    let input = dir.join("main.le");
+    std::fs::write(&input, source).unwrap();
+    let executable = dir.join("main");
+    if let Err(e) = compile(&["-i", input.to_str().unwrap(), "-o", executable.to_str().unwrap(), "-S", "EXE"]) {
+        panic!("failed to compile `{}`: {:?}", test, e);
+    }
+    Some(run(&mut Command::new(&executable)))
+}
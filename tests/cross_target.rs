@@ -0,0 +1,72 @@
+//! 交叉编译：`--target`指定的目标输出对应架构的目标文件，不需要目标平台的工具链；
+//! 不能为其他架构链接可执行文件和动态库，未知的三元组报告目标错误
+
+mod common;
+
+use std::path::PathBuf;
+
+use common::{compile, output_dir};
+use lelang::error::LEError;
+
+const SOURCE: &str = "pub le add(a:i32,b:i32)->i32{\n    ret a+b;\n}\n\nle main()->i32{\n    ret add(1,2);\n}\n";
+
+///ELF文件头中的机器类型
+const EM_AARCH64: u16 = 183;
+const EM_RISCV: u16 = 243;
+
+fn compile_for(test: &str, target: &str, format: &str) -> Result<PathBuf, LEError> {
+    let dir = output_dir(test);
+    let input = dir.join("main.le");
+    std::fs::write(&input, SOURCE).unwrap();
+    let output = dir.join("main");
+    compile(&["-i", input.to_str().unwrap(), "-o", output.to_str().unwrap(), "--target", target, "-S", format])?;
+    Ok(output)
+}
+
+/// 64位小端ELF目标文件的机器类型
+fn elf_machine(object: &[u8]) -> u16 {
+    assert_eq!(&object[0..6], b"\x7fELF\x02\x01", "not a 64-bit little-endian ELF file");
+    u16::from_le_bytes([object[0x12], object[0x13]])
+}
+
+#[test]
+fn aarch64_object() {
+    let output = compile_for("aarch64_object", "aarch64-unknown-linux-gnu", "OBJ").unwrap();
+    let object = std::fs::read(output.with_extension("o")).unwrap();
+    assert_eq!(elf_machine(&object), EM_AARCH64);
+}
+
+#[test]
+fn riscv64_object() {
+    let output = compile_for("riscv64_object", "riscv64-unknown-linux-gnu", "OBJ").unwrap();
+    let object = std::fs::read(output.with_extension("o")).unwrap();
+    assert_eq!(elf_machine(&object), EM_RISCV);
+}
+
+#[test]
+fn wasm32_object() {
+    let output = compile_for("wasm32_object", "wasm32-unknown-unknown", "OBJ").unwrap();
+    let object = std::fs::read(output.with_extension("o")).unwrap();
+    //wasm的魔数和版本1
+    assert_eq!(&object[0..8], b"\0asm\x01\0\0\0");
+}
+
+#[test]
+fn unknown_triple_is_target_error() {
+    match compile_for("unknown_triple_is_target_error", "nonexistent-unknown-nowhere", "OBJ") {
+        Err(LEError::TargetError { message }) => assert!(message.contains("available targets"), "{}", message),
+        other => panic!("expect a target error, got {:?}", other),
+    }
+}
+
+#[test]
+fn cross_executable_is_target_error() {
+    //本机是aarch64时换成riscv64，保证目标不是本机
+    let target = if cfg!(target_arch = "aarch64") { "riscv64-unknown-linux-gnu" } else { "aarch64-unknown-linux-gnu" };
+    for format in ["EXE", "SHARED"] {
+        match compile_for("cross_executable_is_target_error", target, format) {
+            Err(LEError::TargetError { message }) => assert!(message.contains("-S OBJ"), "{}", message),
+            other => panic!("expect a target error for `-S {}`, got {:?}", format, other),
+        }
+    }
+}
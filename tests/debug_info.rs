@@ -0,0 +1,61 @@
+//! `-g`生成的DWARF调试信息：函数、参数和局部变量的行号，语句的位置以及向量类型
+
+mod common;
+
+use common::{compile, fixture, output_dir};
+
+fn compile_with_debug_info(test: &str) -> String {
+    let output = output_dir(test).join("debug_lines");
+    compile(&["-i", fixture("debug_lines.le").to_str().unwrap(), "-o", output.to_str().unwrap(), "-S", "IR", "-g"])
+        .unwrap_or_else(|e| panic!("failed to compile `{}`: {:?}", test, e));
+    std::fs::read_to_string(output.with_extension("ll")).unwrap()
+}
+
+/// 包含`pattern`的元数据定义行
+fn metadata<'a>(ir: &'a str, pattern: &str) -> &'a str {
+    ir.lines()
+        .find(|line| line.starts_with('!') && line.contains(pattern))
+        .unwrap_or_else(|| panic!("no metadata contains `{}`:\n{}", pattern, ir))
+}
+
+#[test]
+fn subprograms_have_definition_lines() {
+    let ir = compile_with_debug_info("subprograms_have_definition_lines");
+    for (name, line) in [("double", 1), ("add", 6), ("main", 11)] {
+        let subprogram = metadata(&ir, &format!("!DISubprogram(name: \"{}\"", name));
+        assert!(subprogram.contains(&format!(" line: {},", line)), "{}", subprogram);
+        assert!(subprogram.contains("DISPFlagDefinition"), "{}", subprogram);
+    }
+    assert!(ir.contains("!llvm.dbg.cu"), "{}", ir);
+}
+
+#[test]
+fn parameters_and_locals_have_lines() {
+    let ir = compile_with_debug_info("parameters_and_locals_have_lines");
+    for (name, arg, line) in [("a", Some(1), 6), ("b", Some(2), 6), ("sum", None, 7), ("total", None, 12), ("v", Some(1), 1)] {
+        let pattern = match arg {
+            Some(arg) => format!("!DILocalVariable(name: \"{}\", arg: {},", name, arg),
+            None => format!("!DILocalVariable(name: \"{}\",", name),
+        };
+        let variable = metadata(&ir, &pattern);
+        assert!(variable.contains(&format!(" line: {},", line)), "{}", variable);
+    }
+    assert!(ir.contains("call void @llvm.dbg.declare("), "{}", ir);
+}
+
+#[test]
+fn statements_have_locations() {
+    let ir = compile_with_debug_info("statements_have_locations");
+    //每条语句所在的行都有DILocation
+    for line in [2, 3, 7, 8, 12, 13, 14, 15] {
+        assert!(ir.contains(&format!("!DILocation(line: {},", line)), "line {}:\n{}", line, ir);
+    }
+}
+
+#[test]
+fn vectors_have_vector_flag() {
+    let ir = compile_with_debug_info("vectors_have_vector_flag");
+    let vector = metadata(&ir, "DIFlagVector");
+    assert!(vector.contains("tag: DW_TAG_array_type"), "{}", vector);
+    assert!(vector.contains("size: 256"), "{}", vector);
+}
@@ -0,0 +1,65 @@
+/* C ABI的测试桩函数，与tests/fixtures/c_abi.le互相按值传递结构体，由tests/c_abi.rs用本机的cc编译 */
+#include <stdint.h>
+
+typedef struct { int64_t x, y; } Pair;
+typedef struct { double x, y; } Floats;
+typedef struct { int32_t a; double b; } Mixed;
+typedef struct { int64_t a, b, c; } Big;
+typedef double f64x2 __attribute__((vector_size(16)));
+typedef float f32x2 __attribute__((vector_size(8)));
+typedef struct { f64x2 v; } Lanes;
+typedef struct { f32x2 lo, hi; } Halves;
+
+/* lelang中导出的函数 */
+double le_mixed_sum(Mixed m);
+int64_t le_exhaust_integer(int64_t a, int64_t b, int64_t c, int64_t d, int64_t e, Pair p, int64_t f);
+
+/* 前5个参数占用5个通用寄存器，p需要2个，只能整体通过栈传递，f仍然使用r9 */
+int64_t c_exhaust_integer(int64_t a, int64_t b, int64_t c, int64_t d, int64_t e, Pair p, int64_t f) {
+    return a + 2 * b + 3 * c + 4 * d + 5 * e + 6 * p.x + 7 * p.y + 8 * f;
+}
+
+/* 前7个参数占用7个SSE寄存器，p需要2个，只能整体通过栈传递，h仍然使用xmm7 */
+double c_exhaust_sse(double a, double b, double c, double d, double e, double f, double g, Floats p, double h) {
+    return a + 2 * b + 3 * c + 4 * d + 5 * e + 6 * f + 7 * g + 8 * p.x + 9 * p.y + 10 * h;
+}
+
+/* 第一个8字节是INTEGER，第二个是SSE，参数和返回值各使用一个通用寄存器和一个SSE寄存器 */
+Mixed c_mixed_scale(Mixed m, int32_t k) {
+    Mixed result = { m.a * k, m.b * k };
+    return result;
+}
+
+/* SSE寄存器已经用尽，m虽然还有通用寄存器可用，也要整体通过栈传递，k使用rdi */
+double c_mixed_no_sse(double d1, double d2, double d3, double d4, double d5, double d6, double d7, double d8, Mixed m, int64_t k) {
+    return d1 + 2 * d2 + 3 * d3 + 4 * d4 + 5 * d5 + 6 * d6 + 7 * d7 + 8 * d8 + 9 * m.a + 10 * m.b + 11 * k;
+}
+
+/* 超过16字节，参数通过byval、返回值通过sret传递 */
+Big c_big_add(Big a, Big b) {
+    Big result = { a.a + b.a, a.b + b.b, a.c + b.c };
+    return result;
+}
+
+/* 数组形参就是指针 */
+int32_t c_array_sum(int32_t a[4]) {
+    return a[0] + a[1] + a[2] + a[3];
+}
+
+/* x86_64上16字节的向量是SSE和SSEUP，整体使用一个SSE寄存器；aarch64上是只有一个成员的同构短向量聚合 */
+Lanes c_lanes_scale(Lanes l, double k) {
+    Lanes result = { l.v * k };
+    return result;
+}
+
+/* x86_64上两个8字节都是SSE，使用两个SSE寄存器；aarch64上是有两个成员的同构短向量聚合 */
+double c_halves_sum(Halves h) {
+    return h.lo[0] + 2 * h.lo[1] + 3 * h.hi[0] + 4 * h.hi[1];
+}
+
+/* 从C按值传递结构体调用lelang导出的函数 */
+double c_call_lelang(void) {
+    Mixed m = { 3, 0.5 };
+    Pair p = { 6, 7 };
+    return le_mixed_sum(m) + (double)le_exhaust_integer(1, 2, 3, 4, 5, p, 8);
+}
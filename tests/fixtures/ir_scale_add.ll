@@ -0,0 +1,9 @@
+; called from tests/fixtures/ir_caller.le, tests/llvm_ir_input.rs links both into one program
+; the target triple and data layout are replaced by the compilation target
+
+define i64 @ir_scale_add(i64 %a, i64 %b) {
+entry:
+  %scaled = mul i64 %a, 10
+  %sum = add i64 %scaled, %b
+  ret i64 %sum
+}
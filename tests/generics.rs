@@ -0,0 +1,78 @@
+//! 泛型的单态化：每组类型实参生成一个实例，实例的符号按类型结构修饰
+
+mod common;
+
+use common::{compile_and_run, compile_to_ir};
+
+const GENERICS: &str = "\
+struct Pair<A,B>{
+    first:A,
+    second:B
+}
+
+le max<T>(a:T,b:T)->T{
+    if(a>b){
+        ret a;
+    }
+    ret b;
+}
+
+le first<A,B>(pair:Pair<A,B>)->A{
+    ret pair.first;
+}
+
+le main()->i32{
+    var i = max::<i32>(3,7);
+    var f = max::<f64>(2.5,1.5);
+    var inner = Pair{
+        first:i,
+        second:f,
+    };
+    var outer = Pair{
+        first:inner,
+        second:[1,2],
+    };
+    var nested = first(outer);
+    println(\"{} {} {} {}\", i, f, first(nested), nested.second);
+    ret 0;
+}
+";
+
+/// 符号中只允许字母、数字和`_`，不能出现`<`、`,`、`[`、`;`等类型的显示文本
+fn defined_functions(ir: &str) -> Vec<String> {
+    ir.lines()
+        .filter(|line| line.starts_with("define "))
+        .filter_map(|line| {
+            let start = line.find('@')? + 1;
+            let end = start + line[start..].find('(')?;
+            Some(line[start..end].trim_matches('"').to_string())
+        })
+        .collect()
+}
+
+#[test]
+fn each_instantiation_gets_its_own_symbol() {
+    let ir = compile_to_ir("each_instantiation_gets_its_own_symbol", GENERICS);
+    let functions = defined_functions(&ir);
+    assert!(functions.contains(&"_LN3maxI3i32EE".to_string()), "{:?}", functions);
+    assert!(functions.contains(&"_LN3maxI3f64EE".to_string()), "{:?}", functions);
+    for function in functions.iter() {
+        assert!(function.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'), "{}", function);
+    }
+}
+
+#[test]
+fn nested_generic_structures_are_mangled_structurally() {
+    let ir = compile_to_ir("nested_generic_structures_are_mangled_structurally", GENERICS);
+    let functions = defined_functions(&ir);
+    //first<Pair<i32,f64>,[i32;2]>和first<i32,f64>是两个不同的实例
+    assert!(functions.contains(&"_LN5firstI4PairI3i323f64EA2_3i32EE".to_string()), "{:?}", functions);
+    assert!(functions.contains(&"_LN5firstI3i323f64EE".to_string()), "{:?}", functions);
+}
+
+#[test]
+fn instances_run_with_their_own_types() {
+    if let Some(output) = compile_and_run("instances_run_with_their_own_types", GENERICS) {
+        assert_eq!(output, "7 2.500000 7 2.500000\n");
+    }
+}
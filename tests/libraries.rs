@@ -0,0 +1,85 @@
+//! 函数库输出：`-S SHARED`和`-S STATIC`只导出入口源文件中的pub函数，其余函数改为内部链接
+
+mod common;
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use common::{compile, output_dir, require_tool, run};
+
+const SOURCE: &str = "\
+le helper(x:i32)->i32{
+    ret x*2;
+}
+
+pub le twice_plus_one(x:i32)->i32{
+    ret helper(x)+1;
+}
+";
+
+fn compile_library(test: &str, format: &str, extra_args: &[&str]) -> PathBuf {
+    let dir = output_dir(test);
+    let input = dir.join("main.le");
+    std::fs::write(&input, SOURCE).unwrap();
+    let output = dir.join("libmain");
+    let mut args = vec!["-i", input.to_str().unwrap(), "-o", output.to_str().unwrap(), "-S", format, "-O0"];
+    args.extend_from_slice(extra_args);
+    compile(&args).unwrap_or_else(|e| panic!("failed to compile `{}`: {:?}", test, e));
+    output
+}
+
+/// 用nm列出函数库的符号，检查pub函数是外部符号，其余函数是内部符号或已被删除
+fn check_symbols(nm: &Path, library: &Path) {
+    let symbols = run(Command::new(nm).arg(library));
+    //nm的每一行是`地址 类型 名字`，类型为大写字母时是外部符号
+    let symbol_type = |name: &str| symbols
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>())
+        .find(|fields| fields.last() == Some(&name))
+        .map(|fields| fields[fields.len() - 2].to_string());
+    assert_eq!(symbol_type("twice_plus_one").as_deref(), Some("T"), "{}", symbols);
+    if let Some(ty) = symbol_type("helper") {
+        assert_eq!(ty, "t", "{}", symbols);
+    }
+}
+
+#[test]
+fn lto_internalizes_private_functions() {
+    //不需要外部工具，检查的是和函数库相同的内部化
+    let output = compile_library("lto_internalizes_private_functions", "IR", &["--lto"]);
+    let ir = std::fs::read_to_string(output.with_extension("ll")).unwrap();
+    assert!(ir.contains("define i32 @twice_plus_one("), "{}", ir);
+    assert!(!ir.contains("define i32 @helper("), "{}", ir);
+    //优化后helper可能已被内联删除，保留时是内部函数
+    if ir.contains("@helper(") {
+        assert!(ir.contains("define internal i32 @helper("), "{}", ir);
+    }
+}
+
+#[test]
+fn static_library_exports_pub_functions() {
+    let test = "static_library_exports_pub_functions";
+    if require_tool(test, &["ar", "llvm-ar"]).is_none() {
+        return;
+    }
+    let nm = match require_tool(test, &["nm", "llvm-nm"]) {
+        Some(nm) => nm,
+        None => return,
+    };
+    let output = compile_library(test, "STATIC", &[]);
+    check_symbols(&nm, &output.with_extension("a"));
+}
+
+#[test]
+fn shared_library_exports_pub_functions() {
+    let test = "shared_library_exports_pub_functions";
+    if require_tool(test, &["cc", "clang", "gcc"]).is_none() {
+        return;
+    }
+    let nm = match require_tool(test, &["nm", "llvm-nm"]) {
+        Some(nm) => nm,
+        None => return,
+    };
+    let output = compile_library(test, "SHARED", &[]);
+    check_symbols(&nm, &output.with_extension(std::env::consts::DLL_EXTENSION));
+}
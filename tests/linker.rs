@@ -0,0 +1,106 @@
+//! 链接可执行文件：`--linker`指定的链接器、`-l`和`-L`传给链接器的参数，以及找不到链接器时的错误
+
+mod common;
+
+use std::path::{Path, PathBuf};
+
+use common::{compile, output_dir};
+use lelang::error::LEError;
+
+const MAIN: &str = "le main()->i32{\n    println(\"{}\", 42);\n    ret 0;\n}\n";
+
+fn write_main(test: &str) -> (PathBuf, PathBuf) {
+    let dir = output_dir(test);
+    let input = dir.join("main.le");
+    std::fs::write(&input, MAIN).unwrap();
+    (input, dir.join("main"))
+}
+
+/// 生成一个记录命令行参数的假链接器，每行一个参数，`status`为它的退出状态
+#[cfg(unix)]
+fn fake_linker(dir: &Path, status: i32) -> (PathBuf, PathBuf) {
+    use std::os::unix::fs::PermissionsExt;
+
+    let linker = dir.join("fake-cc");
+    let record = dir.join("linker_args.txt");
+    let script = format!("#!/bin/sh\nfor arg in \"$@\"; do\n    printf '%s\\n' \"$arg\"\ndone > '{}'\necho 'fake linker failed' >&2\nexit {}\n", record.display(), status);
+    std::fs::write(&linker, script).unwrap();
+    std::fs::set_permissions(&linker, std::fs::Permissions::from_mode(0o755)).unwrap();
+    (linker, record)
+}
+
+#[cfg(unix)]
+#[test]
+fn linker_option_and_libraries() {
+    let (input, output) = write_main("linker_option_and_libraries");
+    let dir = output_dir("linker_option_and_libraries");
+    let (linker, record) = fake_linker(&dir, 0);
+    let library_dir = dir.join("libs");
+    compile(&[
+        "-i", input.to_str().unwrap(),
+        "-o", output.to_str().unwrap(),
+        "-S", "EXE",
+        "--linker", linker.to_str().unwrap(),
+        "-L", library_dir.to_str().unwrap(),
+        "-l", "m",
+        "-l", "pthread",
+    ]).unwrap();
+    let args = std::fs::read_to_string(&record).unwrap();
+    let args = args.lines().collect::<Vec<_>>();
+    assert_eq!(&args[..2], ["-o", output.to_str().unwrap()]);
+    //目标文件和运行时库在库的参数之前，库按命令行中的顺序传递
+    let runtime = args.iter().position(|arg| arg.ends_with("lelang_runtime.a") || arg.ends_with("lelang_runtime.lib")).unwrap();
+    let search_path = args.iter().position(|arg| *arg == "-L").unwrap();
+    assert_eq!(args[search_path + 1], library_dir.to_str().unwrap());
+    assert!(args[2..runtime].iter().all(|arg| arg.ends_with(".o")), "{:?}", args);
+    assert!(runtime < search_path);
+    assert_eq!(&args[search_path + 2..], ["-lm", "-lpthread"]);
+}
+
+#[cfg(unix)]
+#[test]
+fn linker_failure_is_link_error() {
+    let (input, output) = write_main("linker_failure_is_link_error");
+    let (linker, _) = fake_linker(&output_dir("linker_failure_is_link_error"), 1);
+    match compile(&["-i", input.to_str().unwrap(), "-o", output.to_str().unwrap(), "-S", "EXE", "--linker", linker.to_str().unwrap()]) {
+        Err(LEError::LinkError { message }) => {
+            assert!(message.contains("fake-cc"), "{}", message);
+            //链接器的错误输出包含在错误信息中
+            assert!(message.contains("fake linker failed"), "{}", message);
+        }
+        other => panic!("expect a link error, got {:?}", other),
+    }
+}
+
+#[test]
+fn missing_linker_option_is_link_error() {
+    let (input, output) = write_main("missing_linker_option_is_link_error");
+    let linker = output_dir("missing_linker_option_is_link_error").join("no-such-linker");
+    match compile(&["-i", input.to_str().unwrap(), "-o", output.to_str().unwrap(), "-S", "EXE", "--linker", linker.to_str().unwrap()]) {
+        Err(LEError::LinkError { message }) => assert!(message.contains("failed to run linker"), "{}", message),
+        other => panic!("expect a link error, got {:?}", other),
+    }
+}
+
+#[test]
+fn cannot_find_linker() {
+    //只有这个测试修改PATH，同一个测试程序中的其余测试都用`--linker`指定链接器，不受影响；
+    //在PATH中找到链接器的情况由其他测试中的compile_and_run覆盖
+    let (input, output) = write_main("cannot_find_linker");
+    let empty = output_dir("cannot_find_linker").join("empty_path");
+    std::fs::create_dir_all(&empty).unwrap();
+    let path = std::env::var_os("PATH");
+    std::env::set_var("PATH", &empty);
+    let result = compile(&["-i", input.to_str().unwrap(), "-o", output.to_str().unwrap(), "-S", "EXE"]);
+    match path {
+        Some(path) => std::env::set_var("PATH", path),
+        None => std::env::remove_var("PATH"),
+    }
+    match result {
+        Err(LEError::LinkError { message }) => {
+            assert!(message.contains("cannot find a linker"), "{}", message);
+            assert!(message.contains("--linker"), "{}", message);
+        }
+        other => panic!("expect a link error, got {:?}", other),
+    }
+}
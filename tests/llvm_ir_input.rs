@@ -0,0 +1,60 @@
+//! `.ll`和`.bc`输入与lelang的源文件链接为一个程序，无法解析的IR文件应报告链接错误，无法读取的报告IO错误
+
+mod common;
+
+use std::path::Path;
+use std::process::Command;
+
+use common::{compile, fixture, output_dir, require_tool, run};
+use lelang::error::LEError;
+
+#[test]
+#[cfg(unix)]
+fn ll_function_called_from_lelang() {
+    let test = "ll_function_called_from_lelang";
+    if require_tool(test, &["cc", "clang", "gcc"]).is_none() {
+        return;
+    }
+    for optimization in ["-O0", "-O2"] {
+        let executable = output_dir(test).join(format!("ir_caller{}", optimization));
+        compile(&[
+            "-i", fixture("ir_caller.le").to_str().unwrap(),
+            "-i", fixture("ir_scale_add.ll").to_str().unwrap(),
+            "-o", executable.to_str().unwrap(),
+            optimization,
+            "-S", "EXE",
+        ]).unwrap();
+        assert_eq!(run(&mut Command::new(&executable)), "42\n");
+    }
+}
+
+#[test]
+fn invalid_bitcode_is_link_error() {
+    let dir = output_dir("invalid_bitcode_is_link_error");
+    let bitcode = dir.join("broken.bc");
+    std::fs::write(&bitcode, b"BC\xc0\xde not really bitcode").unwrap();
+    match compile_with_ir("invalid_bitcode_is_link_error", &bitcode) {
+        Err(LEError::LinkError { message }) => assert!(message.contains(&bitcode.display().to_string()), "{}", message),
+        other => panic!("expect a link error for `{}`, got {:?}", bitcode.display(), other),
+    }
+}
+
+#[test]
+fn missing_bitcode_is_io_error() {
+    let missing = output_dir("missing_bitcode_is_io_error").join("missing.bc");
+    match compile_with_ir("missing_bitcode_is_io_error", &missing) {
+        Err(LEError::IOError { other }) => assert!(other.to_string().contains(&missing.display().to_string()), "{}", other),
+        other => panic!("expect an IO error for `{}`, got {:?}", missing.display(), other),
+    }
+}
+
+/// 把ir_caller.le和`input`一起编译为IR
+fn compile_with_ir(test: &str, input: &Path) -> Result<(), LEError> {
+    let output = output_dir(test).join("broken");
+    compile(&[
+        "-i", fixture("ir_caller.le").to_str().unwrap(),
+        "-i", input.to_str().unwrap(),
+        "-o", output.to_str().unwrap(),
+        "-S", "IR",
+    ])
+}
@@ -0,0 +1,37 @@
+//! 编译出的可执行文件的入口：C入口`main(argc, argv)`构造`args`后调用用户的`main`，用户`main`的返回值是进程的退出状态
+
+mod common;
+
+use std::process::Command;
+
+use common::{compile, output_dir, require_tool};
+
+/// 编译为可执行文件，以`args`为命令行参数运行，返回退出状态和标准输出，没有C编译器时返回None
+fn run_with_args(test: &str, source: &str, args: &[&str]) -> Option<(i32, String)> {
+    require_tool(test, &["cc", "clang", "gcc"])?;
+    let dir = output_dir(test);
+    let input = dir.join("main.le");
+    std::fs::write(&input, source).unwrap();
+    let executable = dir.join("main");
+    compile(&["-i", input.to_str().unwrap(), "-o", executable.to_str().unwrap(), "-S", "EXE"])
+        .unwrap_or_else(|e| panic!("failed to compile `{}`: {:?}", test, e));
+    let output = Command::new(&executable).args(args).output().unwrap();
+    Some((output.status.code().unwrap(), String::from_utf8_lossy(&output.stdout).to_string()))
+}
+
+#[test]
+fn args_len_is_exit_code() {
+    let source = "le main(args:[str])->i32{\n    println(\"{} {}\", args.len, ptr_read(ptr_offset(args.data,1 as u64)));\n    ret args.len as i32;\n}\n";
+    if let Some((code, output)) = run_with_args("args_len_is_exit_code", source, &["first", "second"]) {
+        assert_eq!(code, 3);
+        assert_eq!(output, "3 first\n");
+    }
+}
+
+#[test]
+fn main_without_return_value_exits_with_zero() {
+    if let Some((code, output)) = run_with_args("main_without_return_value_exits_with_zero", "le main(){\n    print(\"done\");\n}\n", &["ignored"]) {
+        assert_eq!(code, 0);
+        assert_eq!(output, "done");
+    }
+}
@@ -0,0 +1,85 @@
+//! match语句的代码生成：较密集的字面量和小范围生成switch，大范围生成比较链
+
+mod common;
+
+use common::{compile_and_run, compile_source, compile_to_ir};
+use lelang::error::{CompileError, LEError};
+
+#[test]
+fn dense_patterns_lower_to_switch() {
+    let source = "le classify(x:i32)->i32{\n    match x {\n        1 => ret 10;\n        2..=4 => ret 20;\n        _ => ret 30;\n    }\n}\nle main()->i32{ ret classify(3); }";
+    let ir = compile_to_ir("dense_patterns_lower_to_switch", source);
+    assert!(ir.contains("switch i32"), "{}", ir);
+    for value in 1..=4 {
+        assert!(ir.contains(&format!("i32 {}, label", value)), "{}", ir);
+    }
+    assert!(!ir.contains("icmp sge"), "{}", ir);
+}
+
+#[test]
+fn wide_ranges_lower_to_compare_chain() {
+    let source = "le classify(x:i32)->i32{\n    match x {\n        0 => ret 0;\n        1..=1000 => ret 1;\n        -1000..0 => ret 2;\n        _ => ret 3;\n    }\n}\nle main()->i32{ ret classify(3); }";
+    let ir = compile_to_ir("wide_ranges_lower_to_compare_chain", source);
+    //字面量仍然放进switch，两个范围各生成一组比较
+    assert!(ir.contains("i32 0, label"), "{}", ir);
+    assert_eq!(ir.matches("icmp sge i32").count(), 2, "{}", ir);
+    assert_eq!(ir.matches("icmp sle i32").count(), 2, "{}", ir);
+    assert!(!ir.contains("i32 500, label"), "{}", ir);
+}
+
+#[test]
+fn unsigned_patterns_beyond_i64() {
+    let source = "le classify(x:u64)->i32{\n    match x {\n        18446744073709551615 => ret 1;\n        9223372036854775808..=18446744073709551614 => ret 2;\n        _ => ret 3;\n    }\n}\nle main()->i32{ ret classify(0 as u64); }";
+    let ir = compile_to_ir("unsigned_patterns_beyond_i64", source);
+    //u64的最大值按位模式输出为-1，范围按无符号比较
+    assert!(ir.contains("i64 -1, label"), "{}", ir);
+    assert!(ir.contains("icmp uge i64"), "{}", ir);
+    assert!(ir.contains("icmp ule i64"), "{}", ir);
+}
+
+#[test]
+fn pattern_out_of_scrutinee_range() {
+    let source = "le classify(x:i8)->i32{\n    match x {\n        300 => ret 1;\n        _ => ret 0;\n    }\n}\nle main()->i32{ ret classify(1 as i8); }";
+    match compile_source("pattern_out_of_scrutinee_range", source) {
+        Err(LEError::CompileError { compile_error: CompileError::TypeMismatched { expect, found }, .. }) => {
+            assert_eq!(expect, "i8");
+            assert!(found.contains("300"), "{}", found);
+        }
+        other => panic!("expect a type mismatch, got {:?}", other),
+    }
+}
+
+#[test]
+fn first_matching_arm_wins() {
+    let source = "\
+le overlapping(x:i32)->i32{
+    match x {
+        1..=5 => ret 1;
+        3 => ret 2;
+        _ => ret 3;
+    }
+}
+le after_wildcard(x:i32)->i32{
+    match x {
+        _ => ret 0;
+        7 => ret 7;
+    }
+}
+le without_wildcard(x:i32)->i32{
+    var result = 0;
+    match x {
+        1 => { result = 1; }
+        2..=3 => { result = 2; }
+    }
+    ret result;
+}
+le main()->i32{
+    println(\"{} {} {}\", overlapping(3), overlapping(6), after_wildcard(7));
+    println(\"{} {} {}\", without_wildcard(1), without_wildcard(3), without_wildcard(9));
+    ret 0;
+}
+";
+    if let Some(output) = compile_and_run("first_matching_arm_wins", source) {
+        assert_eq!(output, "1 3 0\n1 2 0\n");
+    }
+}
@@ -0,0 +1,60 @@
+//! impl块中的方法和静态函数：按值或按引用传递self，`类型::函数`调用，方法名按路径修饰
+
+mod common;
+
+use common::{compile_and_run, compile_source, compile_to_ir};
+use lelang::error::{CompileError, LEError};
+
+const COUNTER: &str = "\
+struct Counter{
+    count:i64
+}
+
+impl Counter {
+    le new(start:i64)->Counter{
+        ret Counter{
+            count:start,
+        };
+    }
+
+    le bump(self:ref Counter){
+        self.count = self.count + (1 as i64);
+    }
+
+    le get(self:Counter)->i64{
+        ret self.count;
+    }
+
+    ## self is a reference here, calling methods on it loads the reference first
+    le bump_and_get(self:ref Counter)->i64{
+        self.bump();
+        ret self.get();
+    }
+}
+";
+
+#[test]
+fn methods_and_static_functions() {
+    let source = format!("{}\nle main()->i32{{\n    var counter = Counter::new(40 as i64);\n    counter.bump();\n    println(\"{{}} {{}}\", counter.get(), counter.bump_and_get());\n    println(\"{{}}\", Counter::new(7 as i64).get());\n    ret 0;\n}}\n", COUNTER);
+    if let Some(output) = compile_and_run("methods_and_static_functions", &source) {
+        assert_eq!(output, "41 42\n7\n");
+    }
+}
+
+#[test]
+fn method_symbols_are_mangled() {
+    let source = format!("{}\nle main()->i32{{\n    var counter = Counter::new(1 as i64);\n    counter.bump();\n    ret counter.get() as i32;\n}}\n", COUNTER);
+    let ir = compile_to_ir("method_symbols_are_mangled", &source);
+    for symbol in ["_LN7Counter3newE", "_LN7Counter4bumpE", "_LN7Counter3getE", "_LN7Counter12bump_and_getE"] {
+        assert!(ir.contains(&format!("@{}(", symbol)), "missing `{}`\n{}", symbol, ir);
+    }
+}
+
+#[test]
+fn unknown_method_is_error() {
+    let source = format!("{}\nle main()->i32{{\n    var counter = Counter::new(1 as i64);\n    counter.reset();\n    ret 0;\n}}\n", COUNTER);
+    match compile_source("unknown_method_is_error", &source) {
+        Err(LEError::CompileError { compile_error: CompileError::NoSuchMember { member_name }, .. }) => assert_eq!(member_name, "reset"),
+        other => panic!("expect an unknown member error, got {:?}", other),
+    }
+}
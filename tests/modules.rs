@@ -0,0 +1,132 @@
+//! 模块的加载：`import`的查找顺序、每个模块只加载一次、`pub`可见性以及`模块名::名字`的路径
+
+mod common;
+
+use std::path::PathBuf;
+
+use common::{compile, compile_and_run, compile_source, output_dir};
+use lelang::error::{CompileError, LEError};
+
+/// 把模块写入测试的输出目录，入口文件main.le由compile_source或compile_and_run写入
+fn write_modules(test: &str, modules: &[(&str, &str)]) -> PathBuf {
+    let dir = output_dir(test);
+    for (name, source) in modules {
+        std::fs::write(dir.join(format!("{}.le", name)), source).unwrap();
+    }
+    dir
+}
+
+const GEOMETRY: &str = "\
+pub struct Point{
+    x:i32,
+    y:i32
+}
+
+pub le origin()->Point{
+    ret shifted(0);
+}
+
+le shifted(offset:i32)->Point{
+    ret Point{
+        x:offset+1,
+        y:offset+2,
+    };
+}
+";
+
+#[test]
+fn qualified_paths_to_module_items() {
+    write_modules("qualified_paths_to_module_items", &[("geometry", GEOMETRY)]);
+    let source = "\
+import geometry;
+
+le sum(p:geometry::Point)->i32{
+    ret p.x+p.y;
+}
+
+le main()->i32{
+    var p = geometry::origin();
+    println(\"{} {}\", p, sum(p));
+    ret 0;
+}
+";
+    if let Some(output) = compile_and_run("qualified_paths_to_module_items", source) {
+        assert_eq!(output, "geometry::Point { x: 1, y: 2 } 3\n");
+    }
+}
+
+#[test]
+fn private_items_are_not_visible() {
+    write_modules("private_items_are_not_visible", &[("geometry", GEOMETRY)]);
+    let source = "import geometry;\n\nle main()->i32{\n    var p = geometry::shifted(1);\n    ret 0;\n}\n";
+    match compile_source("private_items_are_not_visible", source) {
+        Err(LEError::CompileError { compile_error: CompileError::PrivateItem { module, item }, .. }) => {
+            assert_eq!(module, "geometry");
+            assert_eq!(item, "shifted");
+        }
+        other => panic!("expect a private item error, got {:?}", other),
+    }
+}
+
+#[test]
+fn shared_module_is_loaded_once() {
+    //main和left、right都导入了base，base只加载一次，不会重复定义
+    write_modules("shared_module_is_loaded_once", &[
+        ("base", "pub le one()->i32{\n    ret 1;\n}\n"),
+        ("left", "import base;\n\npub le two()->i32{\n    ret base::one()+base::one();\n}\n"),
+        ("right", "import base;\n\npub le three()->i32{\n    ret base::one()+(2 as i32);\n}\n"),
+    ]);
+    let source = "import left;\nimport right;\nimport base;\n\nle main()->i32{\n    println(\"{} {} {}\", base::one(), left::two(), right::three());\n    ret 0;\n}\n";
+    if let Some(output) = compile_and_run("shared_module_is_loaded_once", source) {
+        assert_eq!(output, "1 2 3\n");
+    }
+}
+
+#[test]
+fn module_must_be_imported_by_the_using_file() {
+    write_modules("module_must_be_imported_by_the_using_file", &[
+        ("base", "pub le one()->i32{\n    ret 1;\n}\n"),
+        ("left", "import base;\n\npub le two()->i32{\n    ret base::one()+base::one();\n}\n"),
+    ]);
+    let source = "import left;\n\nle main()->i32{\n    ret base::one();\n}\n";
+    match compile_source("module_must_be_imported_by_the_using_file", source) {
+        Err(LEError::CompileError { compile_error: CompileError::UnknownModule { module }, .. }) => assert_eq!(module, "base"),
+        other => panic!("expect an unknown module error, got {:?}", other),
+    }
+}
+
+#[test]
+fn unknown_module_is_error() {
+    let source = "import missing_module;\n\nle main()->i32{\n    ret 0;\n}\n";
+    match compile_source("unknown_module_is_error", source) {
+        Err(LEError::CompileError { compile_error: CompileError::UnknownModule { module }, .. }) => assert_eq!(module, "missing_module"),
+        other => panic!("expect an unknown module error, got {:?}", other),
+    }
+}
+
+#[test]
+fn modules_in_search_paths() {
+    let dir = output_dir("modules_in_search_paths");
+    let include = dir.join("include");
+    std::fs::create_dir_all(&include).unwrap();
+    std::fs::write(include.join("found.le"), "pub le answer()->i32{\n    ret 42;\n}\n").unwrap();
+    let input = dir.join("main.le");
+    std::fs::write(&input, "import found;\n\nle main()->i32{\n    ret found::answer();\n}\n").unwrap();
+    let output = dir.join("main");
+    let args = ["-i", input.to_str().unwrap(), "-o", output.to_str().unwrap(), "-S", "IR"];
+    assert!(matches!(compile(&args), Err(LEError::CompileError { compile_error: CompileError::UnknownModule { .. }, .. })));
+    let mut args = args.to_vec();
+    args.extend(["-I", include.to_str().unwrap()]);
+    compile(&args).unwrap();
+}
+
+#[test]
+fn importing_the_entry_file_is_error() {
+    //main.le被util导入时不能再作为模块加载一次
+    write_modules("importing_the_entry_file_is_error", &[("util", "import main;\n\npub le one()->i32{\n    ret 1;\n}\n")]);
+    let source = "import util;\n\nle main()->i32{\n    ret util::one();\n}\n";
+    match compile_source("importing_the_entry_file_is_error", source) {
+        Err(LEError::CompileError { compile_error: CompileError::ImportEntryFile { module }, .. }) => assert_eq!(module, "main"),
+        other => panic!("expect an import entry file error, got {:?}", other),
+    }
+}
@@ -0,0 +1,91 @@
+//! 多个源文件分别编译后链接：跨文件通过decl声明调用，导入的模块在每个源文件中生成，链接后只保留一份
+
+mod common;
+
+use std::path::PathBuf;
+use std::process::Command;
+
+use common::{compile, output_dir, require_tool, run};
+
+const COUNTER: &str = "\
+pub var count = 0;
+
+pub le bump(){
+    count = count+1;
+}
+";
+
+const HELPER: &str = "\
+import counter;
+
+pub le bump_twice(){
+    counter::bump();
+    counter::count = counter::count+1;
+}
+
+pub le add_one(x:i32)->i32{
+    ret x+1;
+}
+";
+
+const MAIN: &str = "\
+import counter;
+
+decl le bump_twice();
+decl le add_one(i32)->i32;
+
+le main()->i32{
+    counter::bump();
+    bump_twice();
+    println(\"{} {}\", counter::count, add_one(41));
+    ret 0;
+}
+";
+
+/// 写入counter模块和两个源文件，返回两个源文件和输出文件的路径
+fn write_sources(test: &str) -> (PathBuf, PathBuf, PathBuf) {
+    let dir = output_dir(test);
+    std::fs::write(dir.join("counter.le"), COUNTER).unwrap();
+    std::fs::write(dir.join("helper.le"), HELPER).unwrap();
+    std::fs::write(dir.join("main.le"), MAIN).unwrap();
+    (dir.join("main.le"), dir.join("helper.le"), dir.join("main"))
+}
+
+fn compile_files(test: &str, format: &str) -> PathBuf {
+    let (main, helper, output) = write_sources(test);
+    compile(&["-i", main.to_str().unwrap(), "-i", helper.to_str().unwrap(), "-o", output.to_str().unwrap(), "-S", format])
+        .unwrap_or_else(|e| panic!("failed to compile `{}`: {:?}", test, e));
+    output
+}
+
+#[test]
+fn module_global_is_defined_once() {
+    let output = compile_files("module_global_is_defined_once", "IR");
+    let ir = std::fs::read_to_string(output.with_extension("ll")).unwrap();
+    //两个源文件都导入了counter，链接后只有一个有名字的定义
+    let definitions = ir.lines().filter(|line| line.starts_with("@_LN7counter5countE = ")).collect::<Vec<_>>();
+    assert_eq!(definitions.len(), 1, "{}", ir);
+    assert!(definitions[0].contains("linkonce_odr global i32 0"), "{}", definitions[0]);
+    //全局变量不再放在地址空间1中
+    assert!(!ir.contains("addrspace(1)"), "{}", ir);
+}
+
+#[test]
+fn cross_file_decl_calls() {
+    let output = compile_files("cross_file_decl_calls", "IR");
+    let ir = std::fs::read_to_string(output.with_extension("ll")).unwrap();
+    assert!(ir.contains("define void @bump_twice()"), "{}", ir);
+    assert!(ir.contains("call void @bump_twice()"), "{}", ir);
+    assert!(ir.contains("call i32 @add_one(i32 41)"), "{}", ir);
+}
+
+#[test]
+fn module_global_is_shared_by_executable_files() {
+    let test = "module_global_is_shared_by_executable_files";
+    if require_tool(test, &["cc", "clang", "gcc"]).is_none() {
+        return;
+    }
+    //每个源文件生成各自的目标文件，再链接为可执行文件，两个文件修改的是同一个count
+    let executable = compile_files(test, "EXE");
+    assert_eq!(run(&mut Command::new(&executable)), "3 42\n");
+}
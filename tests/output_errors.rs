@@ -0,0 +1,42 @@
+//! 不能组合的命令行参数，以及输出文件无法写入时报告的错误
+
+mod common;
+
+use common::{compile, output_dir};
+use lelang::error::LEError;
+
+const SOURCE: &str = "le main()->i32{\n    ret 0;\n}\n";
+
+fn compile_to(test: &str, output: &str, args: &[&str]) -> Result<(), LEError> {
+    let dir = output_dir(test);
+    let input = dir.join("main.le");
+    std::fs::write(&input, SOURCE).unwrap();
+    let output = dir.join(output);
+    let mut all_args = vec!["-i", input.to_str().unwrap(), "-o", output.to_str().unwrap()];
+    all_args.extend_from_slice(args);
+    compile(&all_args)
+}
+
+#[test]
+fn lto_with_unoptimized_output_is_argument_error() {
+    for format in ["TOKENS", "AST", "HEADER"] {
+        match compile_to("lto_with_unoptimized_output_is_argument_error", "main", &["-S", format, "--lto"]) {
+            Err(LEError::ArgumentError { message }) => assert!(message.contains(&format!("`-S {}`", format)), "{}", message),
+            other => panic!("expect an argument error for `-S {}`, got {:?}", format, other),
+        }
+    }
+}
+
+#[test]
+fn unwritable_output_is_io_error() {
+    //输出路径所在的目录不存在
+    for (format, extension) in [("IR", "ll"), ("ASM", "S"), ("OBJ", "o")] {
+        match compile_to("unwritable_output_is_io_error", "missing_dir/main", &["-S", format]) {
+            Err(LEError::IOError { other }) => {
+                let message = other.to_string();
+                assert!(message.contains(&format!("main.{}", extension)), "{}", message);
+            }
+            other => panic!("expect an IO error for `-S {}`, got {:?}", format, other),
+        }
+    }
+}
@@ -0,0 +1,81 @@
+//! 内建的`print`和`println`：格式字符串必须是字面量，占位符和实参个数一致，数组和向量在循环中输出
+
+mod common;
+
+use common::{compile_and_run, compile_source, compile_to_ir};
+use lelang::error::{CompileError, LEError};
+
+fn main_with(body: &str) -> String {
+    format!("le main()->i32{{\n{}\n    ret 0;\n}}\n", body)
+}
+
+fn expect_argument_number_mismatched(test: &str, body: &str, expect_count: usize, found_count: usize, expect_position: &str) {
+    let source = main_with(body);
+    match compile_source(test, &source) {
+        Err(LEError::CompileError { compile_error: CompileError::FormatArgumentNumberMismatched { expect, found }, position }) => {
+            assert_eq!(expect, expect_count);
+            assert_eq!(found, found_count);
+            assert_eq!(&source[position.range.clone()], expect_position);
+        }
+        other => panic!("expect a format argument number error, got {:?}", other),
+    }
+}
+
+#[test]
+fn too_few_format_arguments() {
+    //实参少了，错误报告在格式字符串上
+    expect_argument_number_mismatched("too_few_format_arguments", "    println(\"{} {}\", 1);", 2, 1, "\"{} {}\"");
+}
+
+#[test]
+fn too_many_format_arguments() {
+    //实参多了，错误报告在第一个多余的实参上
+    expect_argument_number_mismatched("too_many_format_arguments", "    print(\"{}\", 1, 22, 333);", 1, 3, "22");
+}
+
+#[test]
+fn format_string_must_be_literal() {
+    let source = main_with("    var format = \"{}\";\n    println(format, 1);");
+    match compile_source("format_string_must_be_literal", &source) {
+        Err(LEError::CompileError { compile_error: CompileError::InvalidFormatString { reason }, position }) => {
+            assert!(reason.contains("not a string literal"), "{}", reason);
+            assert_eq!(&source[position.range.clone()], "format");
+        }
+        other => panic!("expect an invalid format string error, got {:?}", other),
+    }
+}
+
+const NESTED: &str = "\
+struct Samples{
+    id:i32,
+    values:[i32;3]
+}
+
+le main()->i32{
+    var matrix = [[1,2],[3,4],[5,6]];
+    var samples = Samples{
+        id:7,
+        values:[10,20,30],
+    };
+    println(\"{} {} {}\", matrix, samples, vec_from([1.5,2.5]));
+    ret 0;
+}
+";
+
+#[test]
+fn arrays_are_printed_in_loops() {
+    let ir = compile_to_ir("arrays_are_printed_in_loops", NESTED);
+    assert!(ir.contains("write_elements:"), "{}", ir);
+    //二维数组的内外两层各是一个循环，整数元素只有一处输出调用，不随元素个数展开
+    let integer_writes = ir.matches("call void @__lelang_write_int64(").count();
+    //matrix的元素、samples的id和values的元素
+    assert_eq!(integer_writes, 3, "{}", ir);
+    assert_eq!(ir.matches("call void @__lelang_write_float64(").count(), 1, "{}", ir);
+}
+
+#[test]
+fn arrays_are_printed_in_order() {
+    if let Some(output) = compile_and_run("arrays_are_printed_in_order", NESTED) {
+        assert_eq!(output, "[[1, 2], [3, 4], [5, 6]] Samples { id: 7, values: [10, 20, 30] } [1.500000, 2.500000]\n");
+    }
+}
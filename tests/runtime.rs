@@ -0,0 +1,68 @@
+//! 运行时库：注入的声明指向带`__lelang_`前缀的符号，程序中的同名函数不和运行时库冲突；str和ref u8的输出
+
+mod common;
+
+use common::{compile_and_run, compile_to_ir};
+
+const SHADOWED: &str = "\
+le alloc(n:i64)->i64{
+    ret n*(2 as i64);
+}
+
+le time_seconds()->i64{
+    ret 7 as i64;
+}
+
+le main()->i32{
+    println(\"{} {}\", alloc(21 as i64), time_seconds());
+    ret 0;
+}
+";
+
+#[test]
+fn runtime_declarations_use_reserved_symbols() {
+    let source = "le main()->i32{\n    var p = alloc(8 as u64);\n    dealloc(p);\n    exit(0);\n    ret 0;\n}\n";
+    let ir = compile_to_ir("runtime_declarations_use_reserved_symbols", source);
+    for symbol in ["__lelang_alloc", "__lelang_dealloc", "__lelang_exit"] {
+        assert!(ir.contains(&format!(" @{}(", symbol)), "{}: {}", symbol, ir);
+    }
+    assert!(!ir.contains("@alloc("), "{}", ir);
+    assert!(!ir.contains("@exit("), "{}", ir);
+}
+
+#[test]
+fn program_functions_named_like_runtime_functions() {
+    //程序中的alloc和time_seconds代替注入的声明，链接时不和运行时库中的符号冲突
+    let ir = compile_to_ir("program_functions_named_like_runtime_functions", SHADOWED);
+    assert!(ir.contains("define i64 @alloc(i64"), "{}", ir);
+    assert!(!ir.contains("@__lelang_alloc"), "{}", ir);
+    if let Some(output) = compile_and_run("program_functions_named_like_runtime_functions_run", SHADOWED) {
+        assert_eq!(output, "42 7\n");
+    }
+}
+
+const STR_AND_BYTES: &str = "\
+le show(bytes:ref u8, text:str){
+    println(\"{} {}\", text, bytes);
+}
+
+le main()->i32{
+    var text = \"hi\";
+    var bytes = alloc(4 as u64);
+    show(bytes, text);
+    ret 0;
+}
+";
+
+#[test]
+fn ref_u8_is_printed_as_address() {
+    let ir = compile_to_ir("ref_u8_is_printed_as_address", STR_AND_BYTES);
+    assert!(ir.contains("call void @__lelang_write_str("), "{}", ir);
+    assert!(ir.contains("call void @__lelang_write_pointer("), "{}", ir);
+    if let Some(output) = compile_and_run("ref_u8_is_printed_as_address_run", STR_AND_BYTES) {
+        //alloc返回的ref u8不是字符串，输出它的地址而不是读取其中未初始化的字节
+        let (text, address) = output.trim_end().split_once(' ').unwrap();
+        assert_eq!(text, "hi");
+        assert!(!address.is_empty() && address != "hi", "{}", output);
+    }
+}
@@ -0,0 +1,73 @@
+//! SIMD向量的内建操作：`vec_splat`和`vec_shuffle`的代码生成以及通道数、通道序号的检查
+
+mod common;
+
+use common::{compile_and_run, compile_source, compile_to_ir};
+use lelang::error::{CompileError, LEError, SyntaxError};
+
+fn main_with(body: &str) -> String {
+    format!("le main()->i32{{\n{}\n    ret 0;\n}}\n", body)
+}
+
+#[test]
+fn splat_and_shuffle_codegen() {
+    let source = main_with("    var a = vec_splat(1.5, 4);\n    var b = vec_shuffle(a, a, [7,6,1,0,2]);");
+    let ir = compile_to_ir("splat_and_shuffle_codegen", &source);
+    assert!(ir.contains("shufflevector <4 x double>"), "{}", ir);
+    assert!(ir.contains("<4 x i32> zeroinitializer"), "{}", ir);
+    //掩码有5个序号，结果是5个通道的向量
+    assert!(ir.contains("<5 x i32> <i32 7, i32 6, i32 1, i32 0, i32 2>"), "{}", ir);
+}
+
+#[test]
+fn splat_and_shuffle_run() {
+    let source = main_with("    var a = vec_splat(2, 4);\n    var b = vec_from([1,2,3,4]);\n    println(\"{}\", vec_shuffle(a+b, b, [7,6,0,1]));");
+    if let Some(output) = compile_and_run("splat_and_shuffle_run", &source) {
+        assert_eq!(output, "[4, 3, 3, 4]\n");
+    }
+}
+
+fn expect_invalid_lane_count(test: &str, lanes: &str, expect_len: u64) {
+    let source = main_with(&format!("    var a = vec_splat(1.5, {});", lanes));
+    match compile_source(test, &source) {
+        Err(LEError::SyntaxError { syntax_error: SyntaxError::InvalidVectorLength { len }, position }) => {
+            assert_eq!(len, expect_len);
+            //错误报告在通道数的字面量上
+            assert_eq!(&source[position.range.clone()], lanes);
+        }
+        other => panic!("expect an invalid lane count error, got {:?}", other),
+    }
+}
+
+#[test]
+fn splat_lane_count_beyond_u32_is_error() {
+    expect_invalid_lane_count("splat_lane_count_beyond_u32_is_error", "4294967296", 4294967296);
+}
+
+#[test]
+fn splat_zero_lanes_is_error() {
+    expect_invalid_lane_count("splat_zero_lanes_is_error", "0", 0);
+}
+
+fn expect_lane_out_of_range(test: &str, mask: &str, expect_index: u64) {
+    let source = main_with(&format!("    var a = vec_splat(1.5, 4);\n    var b = vec_shuffle(a, a, [{}]);", mask));
+    match compile_source(test, &source) {
+        Err(LEError::CompileError { compile_error: CompileError::VectorLaneOutOfRange { index, lanes }, position }) => {
+            assert_eq!(index, expect_index);
+            assert_eq!(lanes, 8);
+            assert_eq!(&source[position.range.clone()], expect_index.to_string());
+        }
+        other => panic!("expect a lane out of range error, got {:?}", other),
+    }
+}
+
+#[test]
+fn shuffle_lane_out_of_range() {
+    expect_lane_out_of_range("shuffle_lane_out_of_range", "0,8", 8);
+}
+
+#[test]
+fn shuffle_lane_beyond_u32_is_not_truncated() {
+    //4294967296截断为u32后是0，不能当作合法的序号
+    expect_lane_out_of_range("shuffle_lane_beyond_u32_is_not_truncated", "1,4294967296", 4294967296);
+}
@@ -0,0 +1,127 @@
+//! 标准库模块：每个模块导入后使用它的公开接口
+
+mod common;
+
+use common::{compile_and_run, compile_to_ir};
+
+/// 先编译为IR，没有C编译器时也能检查标准库模块可以编译，再链接运行检查输出
+fn check_output(test: &str, source: &str, expect: &str) {
+    compile_to_ir(test, source);
+    if let Some(output) = compile_and_run(&format!("{}_run", test), source) {
+        assert_eq!(output, expect);
+    }
+}
+
+#[test]
+fn math_module() {
+    let source = "\
+import math;
+
+le main()->i32{
+    println(\"{} {} {} {} {}\", math::abs(0.0-2.5), math::sqrt(16.0), math::pow(2.0,10.0), math::floor(2.7), math::ceil(2.2));
+    println(\"{} {} {}\", math::abs_i64((0 as i64)-(7 as i64)), math::min(3,5), math::max(3,5));
+    println(\"{} {}\", math::gcd((0 as i64)-(12 as i64),18 as i64), math::gcd(0 as i64,5 as i64));
+    ret 0;
+}
+";
+    check_output("math_module", source, "2.500000 4.000000 1024.000000 2.000000 3.000000\n7 3 5\n6 5\n");
+}
+
+#[test]
+fn math_gcd_of_i64_minimum() {
+    //2^32 * 2^30 * 2 回绕为i64的最小值
+    let source = "\
+import math;
+
+le main()->i32{
+    var min = (65536 as i64)*(65536 as i64)*(1073741824 as i64)*(2 as i64);
+    println(\"{} {} {}\", math::gcd(min,6 as i64), math::gcd(min,(0 as i64)-(1024 as i64)), math::gcd(min,0 as i64));
+    ret 0;
+}
+";
+    check_output("math_gcd_of_i64_minimum", source, "2 1024 -9223372036854775808\n");
+}
+
+#[test]
+fn mem_module() {
+    let source = "\
+import mem;
+
+le main()->i32{
+    var source = alloc(8 as u64);
+    mem::fill(source,1 as u8,8 as u64);
+    ptr_write(source,5 as u8);
+    var target = alloc(8 as u64);
+    mem::copy(target,source,8 as u64);
+    mem::move(ptr_offset(target,1 as u64),target,3 as u64);
+    println(\"{} {} {} {}\", ptr_read(target), ptr_read(ptr_offset(target,1 as u64)), ptr_read(ptr_offset(target,2 as u64)), ptr_read(ptr_offset(target,7 as u64)));
+    dealloc(source);
+    dealloc(target);
+    ret 0;
+}
+";
+    check_output("mem_module", source, "5 5 1 1\n");
+}
+
+#[test]
+fn str_module() {
+    let source = "\
+import str;
+
+le main()->i32{
+    var joined = str::concat(\"foo\",\"bar\");
+    println(\"{} {} {}\", joined, str::length(joined), str::length(\"\"));
+    println(\"{} {} {}\", str::compare(\"abc\",\"abd\"), str::compare(\"b\",\"a\"), str::compare(joined,\"foobar\"));
+    println(\"{} {}\", str::equals(joined,\"foobar\"), str::equals(\"foo\",\"foobar\"));
+    dealloc(joined);
+    ret 0;
+}
+";
+    check_output("str_module", source, "foobar 6 0\n-1 1 0\ntrue false\n");
+}
+
+#[test]
+fn vector_module() {
+    let source = "\
+import vector;
+
+le main()->i32{
+    var v = vector::new::<i32>(1 as u64);
+    v = vector::push(v,1);
+    v = vector::push(v,2);
+    v = vector::push(v,3);
+    vector::set(v,0 as u64,10);
+    println(\"{} {} {}\", vector::len(v), vector::get(v,0 as u64), vector::last(v));
+    v = vector::pop(v);
+    v = vector::reserve(v,16 as u64);
+    println(\"{} {} {}\", vector::len(v), vector::get(v,1 as u64), vector::last(v));
+    vector::free(v);
+    ret 0;
+}
+";
+    check_output("vector_module", source, "3 10 3\n2 2 2\n");
+}
+
+#[test]
+fn hashmap_module() {
+    let source = "\
+import hashmap;
+
+le main()->i32{
+    var map = hashmap::new::<i64,f64>();
+    for(var key = 0 as i64;key<(20 as i64);key=key+(1 as i64);){
+        map = hashmap::insert(map,key,(key as f64)*0.5);
+    }
+    map = hashmap::insert(map,3 as i64,9.0);
+    println(\"{} {} {}\", hashmap::len(map), hashmap::get(map,3 as i64), hashmap::get(map,19 as i64));
+    println(\"{} {}\", hashmap::contains(map,0 as i64), hashmap::contains(map,20 as i64));
+    hashmap::free(map);
+    var small = hashmap::with_capacity::<u64,i32>(0 as u64);
+    small = hashmap::insert(small,42 as u64,1);
+    println(\"{} {}\", hashmap::len(small), hashmap::get(small,42 as u64));
+    hashmap::free(small);
+    ret 0;
+}
+";
+    check_output("hashmap_module", source, "20 9.000000 9.500000\ntrue false\n1 1\n");
+}
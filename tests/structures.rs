@@ -0,0 +1,125 @@
+//! 结构体和数组：成员顺序、重复成员、运算符函数以及数组的逐元素运算
+
+mod common;
+
+use common::{compile_and_run, compile_source, compile_to_ir};
+use lelang::error::{CompileError, LEError};
+
+fn expect_already_defined(test: &str, source: &str, expect_identifier: &str) {
+    match compile_source(test, source) {
+        Err(LEError::CompileError { compile_error: CompileError::IdentifierAlreadyDefined { identifier, .. }, .. }) => {
+            assert_eq!(identifier, expect_identifier);
+        }
+        other => panic!("expect an already defined error, got {:?}", other),
+    }
+}
+
+#[test]
+fn members_keep_definition_order() {
+    let source = "\
+struct Wide{
+    z:i32,
+    y:i32,
+    x:i32,
+    w:i32,
+    v:i32,
+    u:i32
+}
+
+le main()->i32{
+    var wide = Wide{
+        u:6,
+        v:5,
+        w:4,
+        x:3,
+        y:2,
+        z:1,
+    };
+    println(\"{}\", wide);
+    ret 0;
+}
+";
+    if let Some(output) = compile_and_run("members_keep_definition_order", source) {
+        assert_eq!(output, "Wide { z: 1, y: 2, x: 3, w: 4, v: 5, u: 6 }\n");
+    }
+}
+
+#[test]
+fn duplicate_member_is_error() {
+    let source = "struct Point{\n    x:i32,\n    x:f64\n}\n\nle main()->i32{\n    ret 0;\n}\n";
+    expect_already_defined("duplicate_member_is_error", source, "x");
+}
+
+#[test]
+fn duplicate_generic_member_is_error() {
+    let source = "struct Pair<A>{\n    first:A,\n    first:A\n}\n\nle main()->i32{\n    ret 0;\n}\n";
+    expect_already_defined("duplicate_generic_member_is_error", source, "first");
+}
+
+#[test]
+fn duplicate_member_initializer_is_error() {
+    let source = "struct Point{\n    x:i32,\n    y:i32\n}\n\nle main()->i32{\n    var p = Point{\n        x:1,\n        x:2,\n    };\n    ret 0;\n}\n";
+    expect_already_defined("duplicate_member_initializer_is_error", source, "x");
+}
+
+const OPERATORS: &str = "\
+struct Meters{
+    value:i64
+}
+
+impl Meters {
+    le __add(self:Meters,other:Meters)->Meters{
+        ret Meters{
+            value:self.value+other.value,
+        };
+    }
+    le __lt(self:Meters,other:Meters)->bool{
+        ret self.value<other.value;
+    }
+}
+
+le main()->i32{
+    var a = Meters{
+        value:3 as i64,
+    };
+    var b = Meters{
+        value:4 as i64,
+    };
+    var c = a+b;
+    println(\"{} {} {}\", c.value, a<b, b<a);
+    ret 0;
+}
+";
+
+#[test]
+fn operator_functions() {
+    if let Some(output) = compile_and_run("operator_functions", OPERATORS) {
+        assert_eq!(output, "7 true false\n");
+    }
+}
+
+const ARRAYS: &str = "\
+le main()->i32{
+    var a = [1,2,3];
+    var b = [10,20,30];
+    var m = [[1,2],[3,4]];
+    println(\"{} {} {}\", a+b, b-a, m*m);
+    ret 0;
+}
+";
+
+#[test]
+fn array_arithmetic_is_element_wise() {
+    if let Some(output) = compile_and_run("array_arithmetic_is_element_wise", ARRAYS) {
+        assert_eq!(output, "[11, 22, 33] [9, 18, 27] [[1, 4], [9, 16]]\n");
+    }
+}
+
+#[test]
+fn array_arithmetic_lowers_to_loop() {
+    let ir = compile_to_ir("array_arithmetic_lowers_to_loop", ARRAYS);
+    assert!(ir.contains("element_wise:"), "{}", ir);
+    assert!(ir.contains("phi i64"), "{}", ir);
+    //元素不再逐个展开为insertvalue
+    assert!(!ir.contains("insertvalue [3 x i32]"), "{}", ir);
+}
@@ -0,0 +1,103 @@
+//! trait和泛型约束：定义处检查对泛型值调用的方法是否在约束中声明，实例化时检查类型实参是否实现了约束
+
+mod common;
+
+use common::{compile_and_run, compile_source};
+use lelang::error::{CompileError, LEError};
+
+const SHOW: &str = "\
+trait Show{
+    le show(self:ref Self)->i64;
+    le twice(self:ref Self)->Self;
+}
+
+impl Show for i64{
+    le show(self:ref i64)->i64{
+        ret ptr_read(self);
+    }
+    le twice(self:ref i64)->i64{
+        ret ptr_read(self)*(2 as i64);
+    }
+}
+
+struct Holder<T>{
+    value:T
+}
+
+le identity<T>(value:T)->T{
+    ret value;
+}
+";
+
+fn expect_method_not_in_bounds(test: &str, body: &str, expect_method: &str) {
+    let source = format!("{}\nle check<T:Show>(value:T)->i64{{\n{}\n}}\n\nle main()->i32{{\n    ret 0;\n}}\n", SHOW, body);
+    match compile_source(test, &source) {
+        Err(LEError::CompileError { compile_error: CompileError::MethodNotInBounds { generic_param, method }, .. }) => {
+            assert_eq!(generic_param, "T");
+            assert_eq!(method, expect_method);
+        }
+        other => panic!("expect a method not in bounds error, got {:?}", other),
+    }
+}
+
+#[test]
+fn bound_methods_on_any_receiver() {
+    let source = format!("{}
+le check<T:Show>(value:T)->i64{{
+    var holder = Holder{{
+        value:value,
+    }};
+    ret value.show() + identity(value).show() + holder.value.show() + value.twice().show();
+}}
+
+le main()->i32{{
+    println(\"{{}}\", check(5 as i64));
+    ret 0;
+}}
+", SHOW);
+    if let Some(output) = compile_and_run("bound_methods_on_any_receiver", &source) {
+        assert_eq!(output, "25\n");
+    }
+}
+
+#[test]
+fn method_on_identifier_not_in_bounds() {
+    expect_method_not_in_bounds("method_on_identifier_not_in_bounds", "    ret value.hash();", "hash");
+}
+
+#[test]
+fn method_on_call_result_not_in_bounds() {
+    expect_method_not_in_bounds("method_on_call_result_not_in_bounds", "    ret identity(value).hash();", "hash");
+}
+
+#[test]
+fn method_on_member_not_in_bounds() {
+    let body = "    var holder = Holder{\n        value:value,\n    };\n    ret holder.value.hash();";
+    expect_method_not_in_bounds("method_on_member_not_in_bounds", body, "hash");
+}
+
+#[test]
+fn method_on_method_result_not_in_bounds() {
+    expect_method_not_in_bounds("method_on_method_result_not_in_bounds", "    ret value.twice().hash();", "hash");
+}
+
+#[test]
+fn unknown_trait_in_bounds() {
+    let source = "le check<T:Missing>(value:T)->T{\n    ret value;\n}\n\nle main()->i32{\n    ret 0;\n}\n";
+    match compile_source("unknown_trait_in_bounds", source) {
+        Err(LEError::CompileError { compile_error: CompileError::UnknownTrait { identifier }, .. }) => assert_eq!(identifier, "Missing"),
+        other => panic!("expect an unknown trait error, got {:?}", other),
+    }
+}
+
+#[test]
+fn type_argument_must_implement_bounds() {
+    let source = format!("{}\nle check<T:Show>(value:T)->i64{{\n    ret value.show();\n}}\n\nle main()->i32{{\n    ret check(1.5) as i32;\n}}\n", SHOW);
+    match compile_source("type_argument_must_implement_bounds", &source) {
+        Err(LEError::CompileError { compile_error: CompileError::TraitBoundNotSatisfied { ty, trait_name }, .. }) => {
+            assert_eq!(ty, "f64");
+            assert_eq!(trait_name, "Show");
+        }
+        other => panic!("expect an unsatisfied trait bound error, got {:?}", other),
+    }
+}
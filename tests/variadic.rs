@@ -0,0 +1,54 @@
+//! `decl`声明的可变参数函数：可变部分的实参按C的默认实参提升传递，固定部分不能按值传递结构体和数组
+
+mod common;
+
+use common::{compile_and_run, compile_source, compile_to_ir};
+use lelang::error::{CompileError, LEError};
+
+const PRINTF: &str = "\
+decl le printf(str, ...)->i32;
+
+le main()->i32{
+    var f = 1.5 as f32;
+    var c = (0-3) as i8;
+    var u = 200 as u8;
+    printf(\"%.2f %d %d %d\\n\", f, c, u, true);
+    ret 0;
+}
+";
+
+#[test]
+fn printf_arguments_are_promoted() {
+    let ir = compile_to_ir("printf_arguments_are_promoted", PRINTF);
+    //f32提升为f64，i8符号扩展、u8和bool零扩展为32位
+    assert!(ir.contains("fpext float"), "{}", ir);
+    assert!(ir.contains("sext i8"), "{}", ir);
+    assert!(ir.contains("zext i8"), "{}", ir);
+    assert!(ir.contains("zext i1"), "{}", ir);
+    assert!(ir.contains("call i32 (i8*, ...) @printf(i8*"), "{}", ir);
+    if let Some(output) = compile_and_run("printf_arguments_are_promoted_run", PRINTF) {
+        assert_eq!(output, "1.50 -3 200 1\n");
+    }
+}
+
+fn expect_variadic_aggregate(test: &str, declaration: &str, expect_type: &str) {
+    let source = format!("struct Pair{{\n    x:i64,\n    y:i64\n}}\n\n{}\n\nle main()->i32{{\n    ret 0;\n}}\n", declaration);
+    match compile_source(test, &source) {
+        Err(LEError::CompileError { compile_error: CompileError::VariadicAggregate { function, ty }, position }) => {
+            assert_eq!(function, "log_values");
+            assert_eq!(ty, expect_type);
+            assert!(source[position.range.clone()].starts_with(expect_type), "{:?}", position);
+        }
+        other => panic!("expect a variadic aggregate error, got {:?}", other),
+    }
+}
+
+#[test]
+fn variadic_struct_param_is_error() {
+    expect_variadic_aggregate("variadic_struct_param_is_error", "decl le log_values(i32, Pair, ...)->i32;", "Pair");
+}
+
+#[test]
+fn variadic_array_return_is_error() {
+    expect_variadic_aggregate("variadic_array_return_is_error", "decl le log_values(i32, ...)->[i32;2];", "[i32;2]");
+}
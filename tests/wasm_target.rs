@@ -0,0 +1,89 @@
+//! 编译到wasm32：检查导出函数按wasm32的C ABI传递结构体，再用本地的wasm运行时执行导出的函数，
+//! 没有`wasm-ld`或wasm运行时时只跳过运行的部分
+
+mod common;
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use common::{compile, fixture, output_dir, require_tool, run};
+
+const TARGET: &str = "wasm32-unknown-unknown";
+
+///用node执行wasm模块中导出的函数，`env`中的导入都返回0
+const NODE_RUNNER: &str = r#"
+const fs = require("fs");
+const [file, name, ...args] = process.argv.slice(1);
+const imports = { env: new Proxy({}, { get: () => () => 0 }) };
+WebAssembly.instantiate(fs.readFileSync(file), imports)
+    .then(({ instance }) => console.log(instance.exports[name](...args.map(Number))));
+"#;
+
+fn invoke(runtime: &Path, module: &Path, function: &str, args: &[&str]) -> String {
+    let mut cmd = Command::new(runtime);
+    if runtime.file_stem().unwrap() == "node" {
+        cmd.arg("-e").arg(NODE_RUNNER).arg(module).arg(function);
+    } else {
+        cmd.arg("run").arg("--invoke").arg(function).arg(module);
+    }
+    cmd.args(args);
+    run(&mut cmd).lines().last().unwrap_or_default().trim().to_string()
+}
+
+fn compile_fixture(test: &str, format: &str) -> PathBuf {
+    let output = output_dir(test).join("add");
+    compile(&[
+        "-i", fixture("wasm_add.le").to_str().unwrap(),
+        "-o", output.to_str().unwrap(),
+        "--target", TARGET,
+        "-S", format,
+    ]).unwrap();
+    output
+}
+
+/// IR中以`prefix`开头的函数定义
+fn definition<'a>(ir: &'a str, prefix: &str) -> &'a str {
+    ir.lines().find(|line| line.starts_with(prefix)).unwrap_or_else(|| panic!("no `{}` in\n{}", prefix, ir))
+}
+
+#[test]
+fn exported_structs_follow_wasm32_c_abi() {
+    let output = compile_fixture("exported_structs_follow_wasm32_c_abi", "IR");
+    let ir = std::fs::read_to_string(output.with_extension("ll")).unwrap();
+    //只有一个标量成员的结构体按这个标量传递
+    definition(&ir, "define i32 @twice(i32 ");
+    //其余的结构体参数传递指向副本的指针，返回值通过sret返回
+    assert!(definition(&ir, "define i32 @sum(").contains("byval("), "{}", ir);
+    assert!(definition(&ir, "define void @origin(").contains("sret("), "{}", ir);
+    //导出的是降级后的包装函数
+    assert!(ir.contains("\"wasm-export-name\"=\"sum\""), "{}", ir);
+}
+
+#[test]
+fn exported_structs_in_wasm32_object() {
+    let output = compile_fixture("exported_structs_in_wasm32_object", "OBJ");
+    let object = std::fs::read(output.with_extension("o")).unwrap();
+    assert_eq!(&object[0..8], b"\0asm\x01\0\0\0");
+    //目标文件的符号表中有导出的包装函数
+    for name in ["twice", "sum", "origin"] {
+        assert!(object.windows(name.len()).any(|window| window == name.as_bytes()), "no symbol `{}`", name);
+    }
+}
+
+#[test]
+fn exported_functions_run_under_wasm_runtime() {
+    let test = "exported_functions_run_under_wasm_runtime";
+    if require_tool(test, &["wasm-ld"]).is_none() {
+        return;
+    }
+    let runtime = match require_tool(test, &["wasmtime", "node"]) {
+        Some(runtime) => runtime,
+        None => return,
+    };
+    let output = compile_fixture(test, "EXE");
+    let module = output.with_extension("wasm");
+    assert!(module.is_file());
+    assert_eq!(invoke(&runtime, &module, "add", &["2", "3"]), "5");
+    assert_eq!(invoke(&runtime, &module, "scale", &["3", "4", "5"]), "35");
+    assert_eq!(invoke(&runtime, &module, "twice", &["21"]), "42");
+}